@@ -1,16 +1,74 @@
+use crate::utils::REDACTED;
+use crate::{ErrorKind, Result};
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Deserializer, Serialize};
 
-#[derive(Clone, Serialize)]
+/// 接口调用凭据
+///
+/// 保存到 redis、数据库等存储时使用 [`AccessToken::to_json`] 与 [`AccessToken::from_json`]，
+/// 格式带有版本号，SDK 升级后仍能读取旧版本写入的数据。
+#[derive(Clone, Serialize, Deserialize)]
 pub struct AccessToken {
     pub access_token: String,
     pub expired_at: DateTime<Utc>,
 }
 
+impl AccessToken {
+    /// 持久化格式的当前版本
+    pub const FORMAT_VERSION: u32 = 1;
+
+    /// 序列化为持久化格式，如
+    /// `{"version":1,"access_token":"...","expired_at":"2024-01-01T00:00:00Z"}`
+    pub fn to_json(&self) -> Result<String> {
+        let persisted = PersistedToken {
+            version: AccessToken::FORMAT_VERSION,
+            access_token: self.access_token.clone(),
+            expired_at: self.expired_at,
+        };
+        Ok(serde_json::to_string(&persisted)?)
+    }
+
+    /// 从持久化格式读取
+    ///
+    /// 没有 `version` 字段的数据按版本 1 读取；版本高于 [`AccessToken::FORMAT_VERSION`]
+    /// 时返回错误，通常是新版本 SDK 写入的数据。
+    pub fn from_json(json: &str) -> Result<AccessToken> {
+        let persisted: PersistedToken = serde_json::from_str(json)?;
+        if persisted.version > AccessToken::FORMAT_VERSION {
+            return Err(ErrorKind::InternalServer(format!(
+                "unsupported access token format version {}, expected at most {}",
+                persisted.version,
+                AccessToken::FORMAT_VERSION
+            ))
+            .into());
+        }
+
+        Ok(AccessToken {
+            access_token: persisted.access_token,
+            expired_at: persisted.expired_at,
+        })
+    }
+}
+
+/// 持久化格式
+#[derive(Serialize, Deserialize)]
+struct PersistedToken {
+    #[serde(default = "PersistedToken::legacy_version")]
+    version: u32,
+    access_token: String,
+    expired_at: DateTime<Utc>,
+}
+
+impl PersistedToken {
+    fn legacy_version() -> u32 {
+        1
+    }
+}
+
 impl std::fmt::Debug for AccessToken {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("AccessToken")
-            .field("access_token", &"********")
+            .field("access_token", &REDACTED)
             .field("expired_at", &self.expired_at)
             .finish()
     }
@@ -42,7 +100,7 @@ impl AccessTokenBuilder {
 impl std::fmt::Debug for AccessTokenBuilder {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("AccessTokenBuilder")
-            .field("access_token", &"********")
+            .field("access_token", &REDACTED)
             .field("expired_at", &self.expired_at)
             .finish()
     }
@@ -61,3 +119,33 @@ pub(crate) fn is_token_expired(token: &AccessToken) -> bool {
     let now = Utc::now();
     token.expired_at.signed_duration_since(now) < Duration::minutes(5)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_format() {
+        let token = AccessToken {
+            access_token: "token".to_string(),
+            expired_at: "2024-01-01T00:00:00Z".parse().unwrap(),
+        };
+        let json = token.to_json().unwrap();
+        assert_eq!(
+            json,
+            r#"{"version":1,"access_token":"token","expired_at":"2024-01-01T00:00:00Z"}"#
+        );
+
+        let parsed = AccessToken::from_json(&json).unwrap();
+        assert_eq!(parsed.access_token, "token");
+        assert_eq!(parsed.expired_at, token.expired_at);
+
+        // 旧版本写入的数据没有 version 字段
+        let legacy = r#"{"access_token":"token","expired_at":"2024-01-01T00:00:00Z"}"#;
+        assert_eq!(AccessToken::from_json(legacy).unwrap().access_token, "token");
+
+        let newer = r#"{"version":2,"access_token":"token","expired_at":"2024-01-01T00:00:00Z"}"#;
+        assert!(AccessToken::from_json(newer).is_err());
+        assert!(AccessToken::from_json("{}").is_err());
+    }
+}
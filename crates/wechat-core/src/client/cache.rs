@@ -0,0 +1,398 @@
+//! 响应缓存
+//!
+//! 数据分析类接口（如 `getDailySummary`）的数据一天才更新一次，重复调用只会浪费调用额度。
+//! [`CachedHttpClient`] 按 AppID、接口地址和参数计算缓存键，将成功的响应保存到可插拔的
+//! [`ResponseCache`] 中并设置 TTL。默认提供内存实现 [`MemoryResponseCache`]，
+//! 启用 `redis` feature 后可以使用 `RedisResponseCache` 在多实例之间共享。
+
+use super::HttpClient;
+use crate::Result;
+#[cfg(feature = "tokio")]
+use crate::utils::BackgroundTask;
+use crate::utils::{Endpoint, redact::is_sensitive_key};
+use async_trait::async_trait;
+use hex::encode;
+use http::{HeaderValue, Request, Response, header::CONTENT_TYPE};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+use url::Url;
+
+/// 默认缓存的接口路径前缀（数据分析类接口）
+pub const DEFAULT_CACHE_PATHS: &[&str] = &["/datacube/"];
+
+/// 定义响应缓存读取存储的行为
+#[async_trait]
+pub trait ResponseCache: Send + Sync {
+    /// 读取未过期的响应体
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+    /// 保存响应体，`ttl` 后过期
+    async fn set(&self, key: &str, body: Vec<u8>, ttl: Duration) -> Result<()>;
+}
+
+/// 缓存的响应体及过期时间
+type CacheEntry = (Vec<u8>, Instant);
+
+/// 内存存储方式的响应缓存
+#[derive(Debug, Default, Clone)]
+pub struct MemoryResponseCache {
+    entries: Arc<RwLock<HashMap<String, CacheEntry>>>,
+}
+
+impl MemoryResponseCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 清理已过期的缓存
+    pub async fn purge_expired(&self) {
+        let now = Instant::now();
+        self.entries
+            .write()
+            .await
+            .retain(|_, (_, expired_at)| *expired_at > now);
+    }
+
+    /// 启动每隔 `period` 清理一次过期缓存的后台任务，句柄被丢弃时任务终止
+    #[cfg(feature = "tokio")]
+    pub fn spawn_purge(&self, period: Duration) -> BackgroundTask {
+        let cache = self.clone();
+        BackgroundTask::spawn_interval(period, move || {
+            let cache = cache.clone();
+            async move { cache.purge_expired().await }
+        })
+    }
+}
+
+#[async_trait]
+impl ResponseCache for MemoryResponseCache {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let entries = self.entries.read().await;
+        Ok(entries
+            .get(key)
+            .filter(|(_, expired_at)| *expired_at > Instant::now())
+            .map(|(body, _)| body.clone()))
+    }
+
+    async fn set(&self, key: &str, body: Vec<u8>, ttl: Duration) -> Result<()> {
+        self.entries
+            .write()
+            .await
+            .insert(key.to_string(), (body, Instant::now() + ttl));
+        Ok(())
+    }
+}
+
+/// redis 响应缓存，需要启用 `redis` feature
+///
+/// 响应体以字符串保存，TTL 通过 `SET ... PX` 设置，过期后由 redis 删除。
+///
+/// ```no_run
+/// use wechat_core::client::RedisResponseCache;
+///
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = redis::Client::open("redis://127.0.0.1/")?;
+/// let connection = client.get_multiplexed_async_connection().await?;
+/// let cache = RedisResponseCache::new(connection).prefix("shop:");
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "redis")]
+#[derive(Clone)]
+pub struct RedisResponseCache {
+    connection: redis::aio::MultiplexedConnection,
+    prefix: String,
+}
+
+#[cfg(feature = "redis")]
+impl fmt::Debug for RedisResponseCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedisResponseCache")
+            .field("prefix", &self.prefix)
+            .finish()
+    }
+}
+
+#[cfg(feature = "redis")]
+impl RedisResponseCache {
+    /// 直接使用 [`CachedHttpClient`] 生成的缓存键，不加前缀
+    pub fn new(connection: redis::aio::MultiplexedConnection) -> Self {
+        RedisResponseCache {
+            connection,
+            prefix: String::new(),
+        }
+    }
+
+    /// key 前缀，多个服务共用一个 redis 时使用
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+}
+
+#[cfg(feature = "redis")]
+fn redis_error(error: redis::RedisError) -> crate::Error {
+    crate::ErrorKind::InternalServer(format!("redis: {}", error)).into()
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl ResponseCache for RedisResponseCache {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let mut connection = self.connection.clone();
+        redis::cmd("GET")
+            .arg(format!("{}{}", self.prefix, key))
+            .query_async(&mut connection)
+            .await
+            .map_err(redis_error)
+    }
+
+    async fn set(&self, key: &str, body: Vec<u8>, ttl: Duration) -> Result<()> {
+        let mut connection = self.connection.clone();
+        redis::cmd("SET")
+            .arg(format!("{}{}", self.prefix, key))
+            .arg(body)
+            .arg("PX")
+            .arg(ttl.as_millis().max(1) as u64)
+            .query_async::<()>(&mut connection)
+            .await
+            .map_err(redis_error)
+    }
+}
+
+/// 带响应缓存的 HTTP 客户端包装
+///
+/// 只缓存路径匹配 [`cache_path`](Self::cache_path) 前缀（默认为 [`DEFAULT_CACHE_PATHS`]）
+/// 且成功（HTTP 2xx、`errcode` 为 0 或不存在）的响应。缓存键为
+/// `wechat:response:{app_id}:{hash}`，hash 是请求方法、接口地址、去掉 access_token
+/// 等敏感参数后的 query 和请求体的 SHA-256。同一小程序不同 access_token 的调用共享缓存，
+/// 多个小程序共用一个 [`ResponseCache`] 时按 AppID 隔离。
+///
+/// # 示例
+///
+/// ```
+/// use std::{sync::Arc, time::Duration};
+/// use wechat_core::client::{CachedHttpClient, DryRunHttpClient, MemoryResponseCache};
+///
+/// let http_client = CachedHttpClient::new(
+///     "app_id",
+///     Arc::new(DryRunHttpClient::new()),
+///     Arc::new(MemoryResponseCache::new()),
+///     Duration::from_secs(3600),
+/// )
+/// .cache_path("/wxa/business/performance/");
+/// ```
+#[derive(Clone)]
+pub struct CachedHttpClient {
+    app_id: String,
+    inner: Arc<dyn HttpClient>,
+    cache: Arc<dyn ResponseCache>,
+    ttl: Duration,
+    paths: Vec<String>,
+}
+
+impl fmt::Debug for CachedHttpClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachedHttpClient")
+            .field("app_id", &self.app_id)
+            .field("inner", &"Arc<dyn HttpClient>")
+            .field("cache", &"Arc<dyn ResponseCache>")
+            .field("ttl", &self.ttl)
+            .field("paths", &self.paths)
+            .finish()
+    }
+}
+
+impl CachedHttpClient {
+    /// `app_id` 用于隔离缓存键，多个小程序共用同一个 [`ResponseCache`] 时互不影响
+    pub fn new(
+        app_id: impl Into<String>,
+        inner: Arc<dyn HttpClient>,
+        cache: Arc<dyn ResponseCache>,
+        ttl: Duration,
+    ) -> Self {
+        CachedHttpClient {
+            app_id: app_id.into(),
+            inner,
+            cache,
+            ttl,
+            paths: DEFAULT_CACHE_PATHS.iter().map(|path| path.to_string()).collect(),
+        }
+    }
+
+    /// 追加需要缓存的接口路径前缀，例如 `/datacube/`
+    pub fn cache_path(mut self, prefix: impl Into<String>) -> Self {
+        self.paths.push(prefix.into());
+        self
+    }
+
+    fn cache_key(&self, request: &Request<Vec<u8>>) -> Option<String> {
+        let url = Url::parse(&request.uri().to_string()).ok()?;
+        if !self.paths.iter().any(|path| url.path().starts_with(path)) {
+            return None;
+        }
+
+        let mut query: Vec<(String, String)> = url
+            .query_pairs()
+            .filter(|(key, _)| !is_sensitive_key(key))
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect();
+        query.sort();
+
+        let mut hasher = Sha256::new();
+        hasher.update(request.method().as_str());
+        hasher.update(b"\n");
+        hasher.update(Endpoint::from_url(&url).as_str());
+        for (key, value) in query {
+            hasher.update(format!("\n{}={}", key, value));
+        }
+        hasher.update(b"\n");
+        hasher.update(request.body());
+
+        Some(format!("wechat:response:{}:{}", self.app_id, encode(hasher.finalize())))
+    }
+}
+
+/// 成功且可以缓存的响应
+fn is_cacheable(response: &Response<Vec<u8>>) -> bool {
+    if !response.status().is_success() {
+        return false;
+    }
+
+    match serde_json::from_slice::<serde_json::Value>(response.body()) {
+        Ok(value) => value
+            .get("errcode")
+            .and_then(|code| code.as_i64())
+            .is_none_or(|code| code == 0),
+        Err(_) => false,
+    }
+}
+
+#[async_trait]
+impl HttpClient for CachedHttpClient {
+    async fn execute(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        let Some(key) = self.cache_key(&request) else {
+            return self.inner.execute(request).await;
+        };
+
+        match self.cache.get(&key).await {
+            Ok(Some(body)) => {
+                debug!("response cache hit: {}", key);
+                let url = Url::parse(&request.uri().to_string())?;
+                let mut response = Response::new(body);
+                response
+                    .headers_mut()
+                    .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                response.extensions_mut().insert(Endpoint::from_url(&url));
+                return Ok(response);
+            }
+            Ok(None) => {}
+            Err(error) => warn!("read response cache failed: {}", error),
+        }
+
+        let response = self.inner.execute(request).await?;
+        if is_cacheable(&response)
+            && let Err(error) = self.cache.set(&key, response.body().clone(), self.ttl).await
+        {
+            warn!("write response cache failed: {}", error);
+        }
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::RequestBuilder;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountClient {
+        calls: AtomicUsize,
+        body: &'static [u8],
+    }
+
+    #[async_trait]
+    impl HttpClient for CountClient {
+        async fn execute(&self, _request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(Response::new(self.body.to_vec()))
+        }
+    }
+
+    fn request(path: &str, token: &str, date: &str) -> Request<Vec<u8>> {
+        RequestBuilder::new(format!("https://api.weixin.qq.com{}", path))
+            .query(serde_json::json!({"access_token": token}))
+            .body(serde_json::json!({"begin_date": date, "end_date": date}))
+            .build()
+            .unwrap()
+    }
+
+    fn client(body: &'static [u8]) -> (CachedHttpClient, Arc<CountClient>) {
+        let inner = Arc::new(CountClient {
+            calls: AtomicUsize::new(0),
+            body,
+        });
+        let client = CachedHttpClient::new(
+            "wx123",
+            inner.clone(),
+            Arc::new(MemoryResponseCache::new()),
+            Duration::from_secs(60),
+        );
+        (client, inner)
+    }
+
+    #[tokio::test]
+    async fn test_cache_hit() {
+        let (client, inner) = client(br#"{"list":[]}"#);
+        let path = "/datacube/getweanalysisappiddailysummarytrend";
+
+        client.execute(request(path, "t1", "20240101")).await.unwrap();
+        let response = client.execute(request(path, "t2", "20240101")).await.unwrap();
+        assert_eq!(response.body(), br#"{"list":[]}"#);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+
+        client.execute(request(path, "t1", "20240102")).await.unwrap();
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+
+        client.execute(request("/wxa/getwxacode", "t1", "20240101")).await.unwrap();
+        client.execute(request("/wxa/getwxacode", "t1", "20240101")).await.unwrap();
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_shared_cache_per_app() {
+        let (client, inner) = client(br#"{"list":[]}"#);
+        let other = CachedHttpClient::new(
+            "wx456",
+            inner.clone(),
+            client.cache.clone(),
+            Duration::from_secs(60),
+        );
+        let path = "/datacube/getweanalysisappiddailysummarytrend";
+
+        client.execute(request(path, "t1", "20240101")).await.unwrap();
+        other.execute(request(path, "t2", "20240101")).await.unwrap();
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+
+        let key = client.cache_key(&request(path, "t1", "20240101")).unwrap();
+        assert!(key.starts_with("wechat:response:wx123:"));
+    }
+
+    #[tokio::test]
+    async fn test_skip_error_response() {
+        let (client, inner) =
+            client(br#"{"errcode":45009,"errmsg":"reach max api daily quota limit"}"#);
+        let path = "/datacube/getweanalysisappiddailysummarytrend";
+
+        client.execute(request(path, "t1", "20240101")).await.unwrap();
+        client.execute(request(path, "t1", "20240101")).await.unwrap();
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,147 @@
+//! 并发度限制
+//!
+//! 批量任务（比如批量生成小程序码）同时发起大量请求时，容易瞬间打满连接或触发微信的频率限制。
+//! [`ConcurrencyLimitHttpClient`] 基于 [`Semaphore`] 包装任意 [`HttpClient`]，限制同时进行的请求数。
+
+use super::HttpClient;
+use crate::Result;
+use crate::utils::Multipart;
+use async_trait::async_trait;
+use http::{Request, Response};
+use std::{fmt, sync::Arc};
+use tokio::io::AsyncWrite;
+use tokio::sync::Semaphore;
+
+/// 限制最大并发请求数的 HTTP 客户端包装
+///
+/// 超出并发上限的请求会排队等待，直到有请求完成。克隆后的客户端共享同一个并发上限。
+///
+/// # 示例
+///
+/// ```
+/// use std::sync::Arc;
+/// use wechat_core::client::{ConcurrencyLimitHttpClient, DryRunHttpClient, HttpClient};
+///
+/// let http_client: Arc<dyn HttpClient> = Arc::new(ConcurrencyLimitHttpClient::new(
+///     Arc::new(DryRunHttpClient::new()),
+///     8,
+/// ));
+/// ```
+#[derive(Clone)]
+pub struct ConcurrencyLimitHttpClient {
+    inner: Arc<dyn HttpClient>,
+    semaphore: Arc<Semaphore>,
+    max_concurrency: usize,
+}
+
+impl fmt::Debug for ConcurrencyLimitHttpClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConcurrencyLimitHttpClient")
+            .field("inner", &"Arc<dyn HttpClient>")
+            .field("max_concurrency", &self.max_concurrency)
+            .field("available", &self.semaphore.available_permits())
+            .finish()
+    }
+}
+
+impl ConcurrencyLimitHttpClient {
+    /// 包装 `inner`，最多同时进行 `max_concurrency` 个请求
+    ///
+    /// `max_concurrency` 为 0 时按 1 处理。
+    pub fn new(inner: Arc<dyn HttpClient>, max_concurrency: usize) -> Self {
+        let max_concurrency = max_concurrency.max(1);
+
+        ConcurrencyLimitHttpClient {
+            inner,
+            semaphore: Arc::new(Semaphore::new(max_concurrency)),
+            max_concurrency,
+        }
+    }
+
+    /// 最大并发请求数
+    pub fn max_concurrency(&self) -> usize {
+        self.max_concurrency
+    }
+
+    /// 当前可以立即发起的请求数
+    pub fn available(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+}
+
+#[async_trait]
+impl HttpClient for ConcurrencyLimitHttpClient {
+    async fn execute(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        let _permit = self
+            .semaphore
+            .acquire()
+            .await
+            .expect("semaphore is never closed");
+        self.inner.execute(request).await
+    }
+
+    async fn execute_multipart(&self, request: Request<Multipart>) -> Result<Response<Vec<u8>>> {
+        let _permit = self
+            .semaphore
+            .acquire()
+            .await
+            .expect("semaphore is never closed");
+        self.inner.execute_multipart(request).await
+    }
+
+    async fn execute_streaming(
+        &self,
+        request: Request<Vec<u8>>,
+        writer: &mut (dyn AsyncWrite + Send + Unpin),
+    ) -> Result<Response<u64>> {
+        let _permit = self
+            .semaphore
+            .acquire()
+            .await
+            .expect("semaphore is never closed");
+        self.inner.execute_streaming(request, writer).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct SlowClient {
+        running: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl HttpClient for SlowClient {
+        async fn execute(&self, _request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(running, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(Response::new(Vec::new()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_limit() {
+        let inner = Arc::new(SlowClient::default());
+        let client = ConcurrencyLimitHttpClient::new(inner.clone(), 2);
+
+        let tasks: Vec<_> = (0..6)
+            .map(|_| {
+                let client = client.clone();
+                tokio::spawn(async move { client.execute(Request::new(Vec::new())).await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        assert_eq!(inner.peak.load(Ordering::SeqCst), 2);
+        assert_eq!(client.available(), 2);
+    }
+}
@@ -0,0 +1,235 @@
+//! curl 风格的请求日志
+//!
+//! 联调时把即将发送的请求输出为可以直接复制执行的 curl 命令，响应输出状态码、耗时和截断后的
+//! 响应体，方便与微信文档的示例对照。access_token、secret 等敏感参数和字段均已脱敏。
+
+use super::HttpClient;
+use crate::Result;
+use crate::utils::{Multipart, REDACTED, body_snippet, is_sensitive_key, redact_body, redact_url};
+use async_trait::async_trait;
+use http::{HeaderMap, Request, Response, header};
+use std::{fmt, sync::Arc, time::Instant};
+use tokio::io::AsyncWrite;
+use tracing::debug;
+
+/// 以 `debug` 级别输出 curl 命令和响应的 HTTP 客户端包装
+///
+/// 日志 target 为 `wechat_core::client::curl`，可以只为它开启 debug 级别。
+/// 与 [`DefaultHeadersHttpClient`](super::DefaultHeadersHttpClient) 等包装组合时放在最外层，
+/// 输出的就是最终发送的请求。
+///
+/// # 示例
+///
+/// ```
+/// use std::sync::Arc;
+/// use wechat_core::client::{
+///     CurlLogHttpClient, DryRunHttpClient, MemoryTokenStorage, StableToken, WechatCore,
+/// };
+///
+/// // RUST_LOG=wechat_core::client::curl=debug
+/// let http_client = Arc::new(CurlLogHttpClient::new(Arc::new(DryRunHttpClient::new())));
+/// let token_type = Arc::new(StableToken::new("app_id", "secret", false, http_client.clone()));
+/// let core = WechatCore::custom(http_client, Arc::new(MemoryTokenStorage::new(token_type)));
+/// ```
+#[derive(Clone)]
+pub struct CurlLogHttpClient {
+    inner: Arc<dyn HttpClient>,
+}
+
+impl fmt::Debug for CurlLogHttpClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CurlLogHttpClient")
+            .field("inner", &"Arc<dyn HttpClient>")
+            .finish()
+    }
+}
+
+impl CurlLogHttpClient {
+    pub fn new(inner: Arc<dyn HttpClient>) -> Self {
+        CurlLogHttpClient { inner }
+    }
+}
+
+/// 把请求转换为 curl 命令，敏感参数、请求头和字段已脱敏
+///
+/// ```
+/// use wechat_core::client::to_curl;
+/// use wechat_core::utils::RequestBuilder;
+///
+/// let request = RequestBuilder::new("https://api.weixin.qq.com/wxa/genwxashortlink")
+///     .query(serde_json::json!({"access_token": "TOKEN"}))
+///     .body(serde_json::json!({"page_url": "pages/index/index"}))
+///     .build()?;
+///
+/// let curl = to_curl(&request);
+/// assert!(curl.starts_with(
+///     "curl -X POST 'https://api.weixin.qq.com/wxa/genwxashortlink?access_token=********'"
+/// ));
+/// assert!(curl.ends_with(r#"--data-raw '{"page_url":"pages/index/index"}'"#));
+/// # Ok::<(), wechat_core::Error>(())
+/// ```
+pub fn to_curl(request: &Request<Vec<u8>>) -> String {
+    let mut curl = command(request);
+    if !request.body().is_empty() {
+        curl.push_str(" --data-raw ");
+        curl.push_str(&quote(&redact_body(request.body())));
+    }
+    curl
+}
+
+/// multipart 请求的 curl 命令，文件字段只输出文件名
+fn to_curl_multipart(request: &Request<Multipart>) -> String {
+    let mut curl = command(request);
+    for part in request.body().parts() {
+        let value = match part.file() {
+            Some(file_name) => format!("@{}", file_name),
+            None if part.is_text() && is_sensitive_key(part.name()) => REDACTED.to_string(),
+            None if part.is_text() => String::from_utf8_lossy(part.data()).into_owned(),
+            None => format!("@{}", part.name()),
+        };
+        curl.push_str(" -F ");
+        curl.push_str(&quote(&format!("{}={}", part.name(), value)));
+    }
+    curl
+}
+
+/// 请求方法、地址和请求头部分
+fn command<T>(request: &Request<T>) -> String {
+    let url = match url::Url::parse(&request.uri().to_string()) {
+        Ok(url) => redact_url(&url),
+        Err(_) => request.uri().to_string(),
+    };
+    let mut curl = format!("curl -X {} {}", request.method(), quote(&url));
+    append_headers(&mut curl, request.headers());
+    curl
+}
+
+fn append_headers(curl: &mut String, headers: &HeaderMap) {
+    for (name, value) in headers {
+        let value = if name == header::AUTHORIZATION
+            || name == header::COOKIE
+            || is_sensitive_key(name.as_str())
+        {
+            REDACTED
+        } else {
+            value.to_str().unwrap_or("<binary>")
+        };
+        curl.push_str(" -H ");
+        curl.push_str(&quote(&format!("{}: {}", name, value)));
+    }
+}
+
+/// shell 单引号转义
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+fn log_response(response: &Result<Response<Vec<u8>>>, started: Instant) {
+    match response {
+        Ok(response) => debug!(
+            "response status: {}, elapsed: {:?}, body: {}",
+            response.status(),
+            started.elapsed(),
+            body_snippet(response.body())
+        ),
+        Err(e) => debug!("request failed after {:?}: {}", started.elapsed(), e),
+    }
+}
+
+#[async_trait]
+impl HttpClient for CurlLogHttpClient {
+    async fn execute(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        debug!("{}", to_curl(&request));
+        let started = Instant::now();
+        let response = self.inner.execute(request).await;
+        log_response(&response, started);
+        response
+    }
+
+    async fn execute_multipart(&self, request: Request<Multipart>) -> Result<Response<Vec<u8>>> {
+        debug!("{}", to_curl_multipart(&request));
+        let started = Instant::now();
+        let response = self.inner.execute_multipart(request).await;
+        log_response(&response, started);
+        response
+    }
+
+    async fn execute_streaming(
+        &self,
+        request: Request<Vec<u8>>,
+        writer: &mut (dyn AsyncWrite + Send + Unpin),
+    ) -> Result<Response<u64>> {
+        debug!("{}", to_curl(&request));
+        let started = Instant::now();
+        let response = self.inner.execute_streaming(request, writer).await;
+        match &response {
+            Ok(response) => debug!(
+                "response status: {}, elapsed: {:?}, {} bytes written",
+                response.status(),
+                started.elapsed(),
+                response.body()
+            ),
+            Err(e) => debug!("request failed after {:?}: {}", started.elapsed(), e),
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::DryRunHttpClient;
+    use crate::utils::{Part, RequestBuilder};
+
+    #[test]
+    fn test_to_curl() {
+        let request = RequestBuilder::new("https://api.weixin.qq.com/sns/jscode2session")
+            .method(http::Method::GET)
+            .query(serde_json::json!({"appid": "wx1", "secret": "SECRET", "js_code": "CODE"}))
+            .headers(serde_json::json!({"authorization": "Bearer abc", "x-env": "it's"}))
+            .build()
+            .unwrap();
+        let curl = to_curl(&request);
+        assert!(curl.starts_with("curl -X GET 'https://api.weixin.qq.com/sns/jscode2session?"));
+        assert!(curl.contains("appid=wx1"));
+        assert!(!curl.contains("SECRET") && !curl.contains("CODE") && !curl.contains("abc"));
+        assert!(curl.contains(r"-H 'x-env: it'\''s'"));
+        assert!(!curl.contains("--data-raw"));
+
+        let request = RequestBuilder::new("https://api.weixin.qq.com/cgi-bin/stable_token")
+            .body(serde_json::json!({"appid": "wx1", "secret": "SECRET"}))
+            .build()
+            .unwrap();
+        let curl = to_curl(&request);
+        assert!(curl.ends_with(r#"--data-raw '{"appid":"wx1","secret":"********"}'"#));
+    }
+
+    #[test]
+    fn test_to_curl_multipart() {
+        let form = Multipart::new()
+            .text("type", "image")
+            .text("access_token", "TOKEN")
+            .part(Part::bytes("media", vec![0xff, 0xd8]).file_name("a.jpg"));
+        let request = Request::post("https://api.weixin.qq.com/cgi-bin/media/upload")
+            .body(form)
+            .unwrap();
+        assert_eq!(
+            to_curl_multipart(&request),
+            "curl -X POST 'https://api.weixin.qq.com/cgi-bin/media/upload' \
+             -F 'type=image' -F 'access_token=********' -F 'media=@a.jpg'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_passes_through() {
+        let inner = Arc::new(DryRunHttpClient::with_response(r#"{"errcode":0}"#));
+        let client = CurlLogHttpClient::new(inner.clone());
+        let request = RequestBuilder::new("https://api.weixin.qq.com/wxa/getwxacode")
+            .body(serde_json::json!({"path": "pages/index/index"}))
+            .build()
+            .unwrap();
+        let response = client.execute(request).await.unwrap();
+        assert_eq!(response.body(), br#"{"errcode":0}"#);
+        assert_eq!(inner.requests().len(), 1);
+    }
+}
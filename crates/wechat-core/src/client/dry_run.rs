@@ -0,0 +1,180 @@
+//! dry-run HTTP 客户端
+//!
+//! 完整构建请求并记录下来，但不实际发送，用于审计即将产生的调用和排查参数问题。
+
+use super::HttpClient;
+use crate::Result;
+use crate::utils::{Endpoint, redact_body, redact_url};
+use async_trait::async_trait;
+use http::{HeaderMap, HeaderValue, Method, Request, Response, header::CONTENT_TYPE};
+use std::sync::Mutex;
+use tracing::info;
+
+/// 默认的模拟响应，包含 access_token 字段，使获取接口调用凭据的请求也能“成功”
+const DEFAULT_RESPONSE: &str =
+    r#"{"errcode":0,"errmsg":"ok","access_token":"DRY_RUN_ACCESS_TOKEN","expires_in":7200}"#;
+
+/// 被记录的请求
+///
+/// `url` 中的 access_token 等敏感参数已脱敏；UTF-8 请求体（如 JSON）中的 secret 等敏感字段
+/// 也已脱敏，非 UTF-8 请求体（如 multipart 上传的文件）原样保留。
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    /// 以 UTF-8 文本返回请求体，非 UTF-8 内容（如 multipart 文件）会被替换
+    pub fn body_text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+/// 只记录不发送的 HTTP 客户端
+///
+/// 每个请求都会记录下来并输出 `info` 日志，然后返回固定的模拟响应
+/// （默认为 `{"errcode":0,"errmsg":"ok",...}`）。返回类型包含必填字段的接口会因模拟响应
+/// 缺少字段而解析失败，可以通过 [`DryRunHttpClient::with_response`] 指定响应体。
+///
+/// # 示例
+///
+/// ```
+/// use std::sync::Arc;
+/// use wechat_core::client::{DryRunHttpClient, MemoryTokenStorage, StableToken, WechatCore};
+///
+/// let http_client = Arc::new(DryRunHttpClient::new());
+/// let token_type = Arc::new(StableToken::new("app_id", "secret", false, http_client.clone()));
+/// let core = WechatCore::custom(http_client.clone(), Arc::new(MemoryTokenStorage::new(token_type)));
+///
+/// // 使用 core 调用接口后，通过 http_client.requests() 查看将要发送的请求
+/// assert!(http_client.requests().is_empty());
+/// ```
+#[derive(Debug)]
+pub struct DryRunHttpClient {
+    response: Vec<u8>,
+    requests: Mutex<Vec<RecordedRequest>>,
+}
+
+impl Default for DryRunHttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DryRunHttpClient {
+    pub fn new() -> Self {
+        Self::with_response(DEFAULT_RESPONSE)
+    }
+
+    /// 指定所有请求返回的模拟响应体（JSON）
+    pub fn with_response(body: impl Into<Vec<u8>>) -> Self {
+        DryRunHttpClient {
+            response: body.into(),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// 已记录的请求
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// 取出并清空已记录的请求
+    pub fn take_requests(&self) -> Vec<RecordedRequest> {
+        std::mem::take(
+            &mut *self
+                .requests
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        )
+    }
+}
+
+#[async_trait]
+impl HttpClient for DryRunHttpClient {
+    async fn execute(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        let (parts, body) = request.into_parts();
+        let parsed = url::Url::parse(&parts.uri.to_string()).ok();
+        let url = match &parsed {
+            Some(url) => redact_url(url),
+            None => parts.uri.to_string(),
+        };
+
+        // stable_token 等请求体带有 AppSecret，记录与日志都只保留脱敏后的内容
+        let body = match std::str::from_utf8(&body) {
+            Ok(text) => redact_body(text.as_bytes()).into_bytes(),
+            Err(_) => body,
+        };
+        let recorded = RecordedRequest {
+            method: parts.method,
+            url,
+            headers: parts.headers,
+            body,
+        };
+
+        info!(
+            "dry-run {} {} body: {}",
+            recorded.method,
+            recorded.url,
+            redact_body(&recorded.body)
+        );
+
+        self.requests
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(recorded);
+
+        let mut response = Response::new(self.response.clone());
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        if let Some(url) = parsed {
+            response.extensions_mut().insert(Endpoint::from_url(&url));
+        }
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{MemoryTokenStorage, StableToken, WechatCore};
+    use crate::utils::{REDACTED, RequestBuilder, ResponseExt};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_dry_run() {
+        let http_client = Arc::new(DryRunHttpClient::new());
+        let token_type = Arc::new(StableToken::new("wx123", "secret", false, http_client.clone()));
+        let core = WechatCore::custom(
+            http_client.clone(),
+            Arc::new(MemoryTokenStorage::new(token_type)),
+        );
+
+        let request = RequestBuilder::new("https://api.weixin.qq.com/wxa/getwxacode")
+            .query(serde_json::json!({"access_token": core.token().await.unwrap()}))
+            .body(serde_json::json!({"path": "pages/index/index"}))
+            .build()
+            .unwrap();
+        let response = core.client.execute(request).await.unwrap();
+        response.to_json::<serde_json::Value>().unwrap();
+
+        let requests = http_client.take_requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].url.contains("/cgi-bin/stable_token"));
+        assert!(requests[0].body_text().contains(&format!(r#""secret":"{}""#, REDACTED)));
+        assert!(requests[0].body_text().contains(r#""appid":"wx123""#));
+        assert_eq!(requests[1].method, Method::POST);
+        assert!(requests[1].url.contains(&format!("access_token={}", REDACTED)));
+        assert!(!requests[1].url.contains("DRY_RUN_ACCESS_TOKEN"));
+        assert_eq!(requests[1].body_text(), r#"{"path":"pages/index/index"}"#);
+        assert!(http_client.requests().is_empty());
+    }
+}
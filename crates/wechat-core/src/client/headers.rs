@@ -0,0 +1,142 @@
+//! 自定义 User-Agent 与默认请求头
+//!
+//! 请求默认带有 [`HTTP_CLIENT_USER_AGENT`](crate::constants::HTTP_CLIENT_USER_AGENT)。
+//! [`DefaultHeadersHttpClient`] 包装任意 [`HttpClient`]，统一替换 User-Agent 并附加默认请求头
+//! （例如内部网关要求的 header）。
+
+use super::HttpClient;
+use crate::Result;
+use crate::utils::Multipart;
+use async_trait::async_trait;
+use http::{HeaderMap, HeaderName, HeaderValue, Request, Response, header::USER_AGENT};
+use std::{fmt, sync::Arc};
+use tokio::io::AsyncWrite;
+
+/// 附加默认请求头的 HTTP 客户端包装
+///
+/// User-Agent 总是被替换；其他默认请求头只在请求本身没有设置时附加。
+/// 将同一个包装后的客户端同时交给 `TokenType` 和 [`WechatCore::custom`](super::WechatCore::custom)，
+/// 获取 access_token 的请求也会带上这些请求头。
+///
+/// # 示例
+///
+/// ```
+/// use std::sync::Arc;
+/// use wechat_core::client::{
+///     DefaultHeadersHttpClient, DryRunHttpClient, MemoryTokenStorage, StableToken, WechatCore,
+/// };
+///
+/// let http_client = Arc::new(
+///     DefaultHeadersHttpClient::new(Arc::new(DryRunHttpClient::new()))
+///         .user_agent("my-service/1.0")?
+///         .header("x-gateway-token", "abc")?,
+/// );
+/// let token_type = Arc::new(StableToken::new("app_id", "secret", false, http_client.clone()));
+/// let core = WechatCore::custom(http_client, Arc::new(MemoryTokenStorage::new(token_type)));
+/// # Ok::<(), wechat_core::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct DefaultHeadersHttpClient {
+    inner: Arc<dyn HttpClient>,
+    headers: HeaderMap,
+}
+
+impl fmt::Debug for DefaultHeadersHttpClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DefaultHeadersHttpClient")
+            .field("inner", &"Arc<dyn HttpClient>")
+            .field("headers", &self.headers)
+            .finish()
+    }
+}
+
+impl DefaultHeadersHttpClient {
+    pub fn new(inner: Arc<dyn HttpClient>) -> Self {
+        DefaultHeadersHttpClient {
+            inner,
+            headers: HeaderMap::new(),
+        }
+    }
+
+    /// 设置 User-Agent
+    pub fn user_agent(self, user_agent: &str) -> Result<Self> {
+        self.header(USER_AGENT.as_str(), user_agent)
+    }
+
+    /// 添加默认请求头，名称或值不合法时返回错误
+    pub fn header(mut self, name: &str, value: &str) -> Result<Self> {
+        let name = HeaderName::from_bytes(name.as_bytes()).map_err(http::Error::from)?;
+        let value = HeaderValue::from_str(value).map_err(http::Error::from)?;
+        self.headers.insert(name, value);
+        Ok(self)
+    }
+
+    /// 合并一组默认请求头
+    pub fn headers(mut self, headers: HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    fn apply<T>(&self, mut request: Request<T>) -> Request<T> {
+        let headers = request.headers_mut();
+        for (name, value) in &self.headers {
+            if name == USER_AGENT || !headers.contains_key(name) {
+                headers.insert(name.clone(), value.clone());
+            }
+        }
+        request
+    }
+}
+
+#[async_trait]
+impl HttpClient for DefaultHeadersHttpClient {
+    async fn execute(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        self.inner.execute(self.apply(request)).await
+    }
+
+    async fn execute_multipart(&self, request: Request<Multipart>) -> Result<Response<Vec<u8>>> {
+        self.inner.execute_multipart(self.apply(request)).await
+    }
+
+    async fn execute_streaming(
+        &self,
+        request: Request<Vec<u8>>,
+        writer: &mut (dyn AsyncWrite + Send + Unpin),
+    ) -> Result<Response<u64>> {
+        self.inner
+            .execute_streaming(self.apply(request), writer)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::DryRunHttpClient;
+    use crate::utils::RequestBuilder;
+
+    #[tokio::test]
+    async fn test_default_headers() {
+        let inner = Arc::new(DryRunHttpClient::new());
+        let client = DefaultHeadersHttpClient::new(inner.clone())
+            .user_agent("my-service/1.0")
+            .unwrap()
+            .header("x-gateway-token", "abc")
+            .unwrap()
+            .header("x-env", "prod")
+            .unwrap();
+
+        let request = RequestBuilder::new("https://api.weixin.qq.com/wxa/getwxacode")
+            .headers(serde_json::json!({"x-env": "test"}))
+            .build()
+            .unwrap();
+        client.execute(request).await.unwrap();
+
+        let headers = &inner.requests()[0].headers;
+        assert_eq!(headers[USER_AGENT], "my-service/1.0");
+        assert_eq!(headers["x-gateway-token"], "abc");
+        assert_eq!(headers["x-env"], "test");
+
+        assert!(DefaultHeadersHttpClient::new(inner).header("bad header", "v").is_err());
+    }
+}
@@ -0,0 +1,194 @@
+//! 基于通用键值存储的接口调用凭据存储
+//!
+//! 接入 etcd、Consul、DynamoDB 等存储时，只需实现 [`KeyValueBackend`] 的读写，
+//! [`KvTokenStorage`] 负责序列化和过期时间，刷新交给 [`RefreshCoordinator`]。
+
+use super::access_token::AccessToken;
+use super::refresh::{RefreshCoordinator, RefreshStats, TokenStore};
+use super::token_storage::TokenStorage;
+use super::token_type::TokenType;
+use crate::{ErrorKind, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+/// 键值存储后端
+#[async_trait]
+pub trait KeyValueBackend: Send + Sync {
+    /// 读取 key 对应的值，不存在或已过期时返回 `None`
+    async fn get(&self, key: &str) -> Result<Option<String>>;
+    /// 写入 key，`ttl` 后过期；不支持过期时间的存储可以忽略 `ttl`
+    async fn set(&self, key: &str, value: &str, ttl: Duration) -> Result<()>;
+}
+
+/// 基于 [`KeyValueBackend`] 的接口调用凭据存储
+///
+/// access_token 以 [`AccessToken::to_json`] 的格式保存，过期时间同时作为 key 的 TTL。本地缓存、并发刷新保护
+/// 与多实例共享见 [`RefreshCoordinator`]。
+///
+/// ```
+/// use async_trait::async_trait;
+/// use std::collections::HashMap;
+/// use std::sync::{Arc, Mutex};
+/// use std::time::Duration;
+/// use wechat_core::client::{KeyValueBackend, KvTokenStorage, ReqwestHttpClient, StableToken};
+///
+/// #[derive(Default)]
+/// struct MapBackend(Mutex<HashMap<String, String>>);
+///
+/// #[async_trait]
+/// impl KeyValueBackend for MapBackend {
+///     async fn get(&self, key: &str) -> wechat_core::Result<Option<String>> {
+///         Ok(self.0.lock().unwrap().get(key).cloned())
+///     }
+///
+///     async fn set(&self, key: &str, value: &str, _ttl: Duration) -> wechat_core::Result<()> {
+///         self.0.lock().unwrap().insert(key.to_string(), value.to_string());
+///         Ok(())
+///     }
+/// }
+///
+/// let http_client = Arc::new(ReqwestHttpClient::new());
+/// let token_type = Arc::new(StableToken::new("app_id", "secret", false, http_client));
+/// let storage = KvTokenStorage::new(MapBackend::default(), token_type).key("shop:token");
+/// ```
+pub struct KvTokenStorage<K> {
+    coordinator: RefreshCoordinator<KvStore<K>>,
+}
+
+/// 以 JSON 格式读写后端中的单个 key
+struct KvStore<K> {
+    backend: K,
+    key: String,
+}
+
+impl<K: KeyValueBackend> KvTokenStorage<K> {
+    /// 使用默认的 key `wechat:access_token:{app_id}` 创建
+    pub fn new(backend: K, token_type: Arc<dyn TokenType>) -> Self {
+        let key = format!("wechat:access_token:{}", token_type.app_config().app_id);
+        KvTokenStorage {
+            coordinator: RefreshCoordinator::new(KvStore { backend, key }, token_type),
+        }
+    }
+
+    /// 存储 access_token 使用的 key
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.coordinator.store.key = key.into();
+        self
+    }
+
+    /// 后端存储
+    pub fn backend(&self) -> &K {
+        &self.coordinator.store().backend
+    }
+}
+
+#[async_trait]
+impl<K: KeyValueBackend> TokenStore for KvStore<K> {
+    async fn load(&self) -> Result<Option<AccessToken>> {
+        let Some(value) = self.backend.get(&self.key).await? else {
+            return Ok(None);
+        };
+        match AccessToken::from_json(&value) {
+            Ok(token) => Ok(Some(token)),
+            Err(e) => {
+                warn!("ignore malformed access token in {}: {}", self.key, e);
+                Ok(None)
+            }
+        }
+    }
+
+    async fn save(&self, token: &AccessToken) -> Result<()> {
+        let value = token.to_json()?;
+        let ttl = (token.expired_at - Utc::now())
+            .to_std()
+            .map_err(|_| ErrorKind::InternalServer("access token already expired".to_string()))?;
+        self.backend.set(&self.key, &value, ttl).await
+    }
+}
+
+#[async_trait]
+impl<K: KeyValueBackend> TokenStorage for KvTokenStorage<K> {
+    async fn token(&self) -> Result<String> {
+        self.coordinator.token().await
+    }
+
+    async fn refresh_access_token(&self) -> Result<String> {
+        self.coordinator.refresh_access_token().await
+    }
+
+    fn token_type(&self) -> Arc<dyn TokenType> {
+        self.coordinator.token_type()
+    }
+
+    async fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.coordinator.expires_at().await
+    }
+
+    fn refresh_stats(&self) -> Option<RefreshStats> {
+        self.coordinator.refresh_stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::refresh::tests::CountToken;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct MapBackend {
+        values: std::sync::Mutex<HashMap<String, String>>,
+        gets: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl KeyValueBackend for Arc<MapBackend> {
+        async fn get(&self, key: &str) -> Result<Option<String>> {
+            self.gets.fetch_add(1, Ordering::SeqCst);
+            Ok(self.values.lock().unwrap().get(key).cloned())
+        }
+
+        async fn set(&self, key: &str, value: &str, ttl: Duration) -> Result<()> {
+            assert!(ttl > Duration::from_secs(3600));
+            self.values.lock().unwrap().insert(key.to_string(), value.to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_local_cache() {
+        let backend = Arc::new(MapBackend::default());
+        let token_type = Arc::new(CountToken::default());
+        let storage = KvTokenStorage::new(backend.clone(), token_type.clone());
+
+        assert_eq!(storage.token().await.unwrap(), "token1");
+        assert_eq!(token_type.calls.load(Ordering::SeqCst), 1);
+        assert!(backend.values.lock().unwrap().contains_key("wechat:access_token:wx123"));
+
+        // 命中本地缓存，不再读取后端
+        let gets = backend.gets.load(Ordering::SeqCst);
+        assert_eq!(storage.token().await.unwrap(), "token1");
+        assert_eq!(backend.gets.load(Ordering::SeqCst), gets);
+    }
+
+    #[tokio::test]
+    async fn test_shared_backend() {
+        let backend = Arc::new(MapBackend::default());
+        let token_type = Arc::new(CountToken::default());
+        let first = KvTokenStorage::new(backend.clone(), token_type.clone()).key("token");
+        let second = KvTokenStorage::new(backend.clone(), token_type.clone()).key("token");
+
+        assert_eq!(first.token().await.unwrap(), "token1");
+        assert_eq!(second.token().await.unwrap(), "token1");
+        assert_eq!(token_type.calls.load(Ordering::SeqCst), 1);
+
+        // 后端中的数据损坏时重新刷新
+        backend.values.lock().unwrap().insert("token".to_string(), "{".to_string());
+        let third = KvTokenStorage::new(backend.clone(), token_type.clone()).key("token");
+        assert_eq!(third.token().await.unwrap(), "token2");
+    }
+}
@@ -4,26 +4,101 @@
 //! 支持稳定版接口调用凭据和普通版接口调用凭据。
 
 mod access_token;
+mod cache;
+mod concurrency;
+mod curl;
+mod dry_run;
+mod headers;
+mod kv_storage;
+mod refresh;
+#[cfg(feature = "tokio")]
+mod retry;
 mod token_storage;
 pub mod token_type;
 
 pub use access_token::AccessToken;
-pub use token_storage::{MemoryTokenStorage, TokenStorage};
+pub use cache::{CachedHttpClient, DEFAULT_CACHE_PATHS, MemoryResponseCache, ResponseCache};
+#[cfg(feature = "redis")]
+pub use cache::RedisResponseCache;
+pub use concurrency::ConcurrencyLimitHttpClient;
+pub use curl::{CurlLogHttpClient, to_curl};
+pub use dry_run::{DryRunHttpClient, RecordedRequest};
+pub use headers::DefaultHeadersHttpClient;
+pub use kv_storage::{KeyValueBackend, KvTokenStorage};
+pub use refresh::{RefreshCoordinator, RefreshStats, TokenStore};
+#[cfg(feature = "tokio")]
+pub use retry::{RetryHttpClient, RetryOn, RetryPolicy};
+pub use token_storage::{MemoryTokenStorage, StaticTokenStorage, TokenStorage};
 pub use token_type::{NonStableToken, StableToken, TokenType};
 
-use crate::Result;
+#[cfg(feature = "tokio")]
+use crate::utils::BackgroundTask;
+use crate::utils::{
+    Multipart, REDACTED, RequestBuilder, ResponseExt, check_download, should_check_download,
+};
+use crate::{ErrorKind, Result, constants, localized};
+#[cfg(feature = "reqwest")]
+use crate::{Error, utils::Endpoint};
 use async_trait::async_trait;
-use http::{Request, Response};
+use http::{HeaderValue, Method, Request, Response, header::CONTENT_TYPE};
+#[cfg(feature = "reqwest")]
 use reqwest::Request as ReqwestRequest;
+use serde_json::{Map, Value};
+#[cfg(feature = "tokio")]
+use std::time::Duration;
 use std::{fmt, sync::Arc};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tracing::debug;
+#[cfg(feature = "tokio")]
+use tracing::warn;
 
 /// 微信小程序的 App ID 和 Secret 配置。
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct AppConfig {
     pub app_id: String,
     pub secret: String,
 }
 
+impl fmt::Debug for AppConfig {
+    // 为了安全，不打印 secret
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AppConfig")
+            .field("app_id", &self.app_id)
+            .field("secret", &REDACTED)
+            .finish()
+    }
+}
+
+impl AppConfig {
+    /// 校验 App ID 与 Secret 的格式
+    ///
+    /// App ID 以 `wx` 开头，共 18 位字母或数字；Secret 不能为空，也不能包含空白字符。
+    /// 只检查格式，是否正确仍以微信接口的返回为准。
+    pub fn validate(&self) -> Result<()> {
+        let app_id = &self.app_id;
+        if !app_id.starts_with("wx")
+            || app_id.len() != 18
+            || !app_id.bytes().all(|b| b.is_ascii_alphanumeric())
+        {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "App ID 格式不正确，应为 wx 开头的 18 位字母或数字：{}",
+                "invalid app_id, expected 18 alphanumeric characters starting with 'wx': {}",
+                app_id
+            ))
+            .into());
+        }
+
+        if self.secret.is_empty() || self.secret.chars().any(char::is_whitespace) {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "Secret 不能为空，也不能包含空白字符",
+                "secret must not be empty or contain whitespace"
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
 /// 微信 SDK 核心客户端结构。
 ///
 /// 封装了 `HttpClient` 和 `TokenStorage`，提供发送请求和获取 Access Token 的方法。
@@ -52,6 +127,9 @@ impl Clone for WechatCore {
 
 impl WechatCore {
     /// 使用默认配置（`ReqwestHttpClient` 和 `MemoryTokenStorage` 与 `StableToken`）创建客户端。
+    ///
+    /// 需要启用 `reqwest` feature。
+    #[cfg(feature = "reqwest")]
     pub fn new(app_id: &str, secret: &str) -> Self {
         let http_client = Arc::new(ReqwestHttpClient::new());
         let token_type = Arc::new(StableToken::new(app_id, secret, false, http_client.clone()));
@@ -63,6 +141,20 @@ impl WechatCore {
         }
     }
 
+    /// 校验 App ID 与 Secret 的格式后，使用默认配置创建客户端。
+    ///
+    /// [`WechatCore::new`] 不做校验，配置错误要到首次调用接口时才会报错；
+    /// 配置来自环境变量等外部输入时建议使用本方法，校验规则见 [`AppConfig::validate`]。
+    #[cfg(feature = "reqwest")]
+    pub fn try_new(app_id: &str, secret: &str) -> Result<Self> {
+        AppConfig {
+            app_id: app_id.to_string(),
+            secret: secret.to_string(),
+        }
+        .validate()?;
+        Ok(WechatCore::new(app_id, secret))
+    }
+
     /// 使用自定义的 `HttpClient` 和 `TokenStorage` 创建客户端。
     pub fn custom(http_client: Arc<dyn HttpClient>, token_storage: Arc<dyn TokenStorage>) -> Self {
         WechatCore {
@@ -71,29 +163,205 @@ impl WechatCore {
         }
     }
 
+    /// 返回使用指定 access_token 的客户端，不再从 `TokenStorage` 取值。
+    ///
+    /// 适用于 access_token 由其他系统统一下发的场景，HTTP 客户端与原客户端共用。
+    pub fn with_token(&self, access_token: impl Into<String>) -> Self {
+        WechatCore {
+            client: self.client.clone(),
+            token_storage: Arc::new(StaticTokenStorage::new(
+                access_token,
+                self.token_storage.token_type(),
+            )),
+        }
+    }
+
+    /// 返回限制最大并发请求数的客户端，令牌存储与原客户端共用。
+    ///
+    /// 详见 [`ConcurrencyLimitHttpClient`]。
+    pub fn with_concurrency_limit(&self, max_concurrency: usize) -> Self {
+        WechatCore {
+            client: Arc::new(ConcurrencyLimitHttpClient::new(
+                self.client.clone(),
+                max_concurrency,
+            )),
+            token_storage: self.token_storage.clone(),
+        }
+    }
+
+    /// 返回请求失败时按 `policy` 重试的客户端，令牌存储与原客户端共用。
+    ///
+    /// 原客户端不受影响，可以只在需要重试的调用点使用返回的客户端。详见 [`RetryHttpClient`]。
+    #[cfg(feature = "tokio")]
+    pub fn with_retry(&self, policy: RetryPolicy) -> Self {
+        WechatCore {
+            client: Arc::new(RetryHttpClient::new(self.client.clone(), policy)),
+            token_storage: self.token_storage.clone(),
+        }
+    }
+
     /// 获取接口调用凭据（Access Token）。
     pub async fn token(&self) -> Result<String> {
         self.token_storage.token().await
     }
 
+    /// 启动每隔 `period` 检查一次 access_token 的后台任务，即将过期时提前刷新，
+    /// 避免业务请求等待刷新。句柄被丢弃时任务终止。
+    #[cfg(feature = "tokio")]
+    pub fn spawn_token_refresher(&self, period: Duration) -> BackgroundTask {
+        let token_storage = self.token_storage.clone();
+        BackgroundTask::spawn_interval(period, move || {
+            let token_storage = token_storage.clone();
+            async move {
+                if let Err(error) = token_storage.token().await {
+                    warn!("background token refresh failed: {}", error);
+                }
+            }
+        })
+    }
+
     /// 获取当前客户端的 App ID 和 Secret 配置。
     pub fn app_config(&self) -> AppConfig {
         self.token_storage.token_type().app_config()
     }
+
+    /// 调用 SDK 尚未封装的接口，返回原始 JSON。
+    ///
+    /// `path` 可以是 `/wxa/xxx` 形式的路径（拼接到 `https://api.weixin.qq.com`），
+    /// 也可以是该域名下完整的 https URL；其他地址返回 `InvalidParameter`，避免 access_token 被发往别处。
+    /// query 中自动注入 access_token，请求经过客户端配置的重试、并发限制等 `HttpClient` 包装，
+    /// errcode 非 0 时与其他接口一样转换为对应的 [`Error`](crate::Error)。
+    /// `query`、`body` 必须是 JSON 对象。下载类接口返回的不是 JSON，不能使用本方法。
+    ///
+    /// ```no_run
+    /// use http::Method;
+    /// use serde_json::json;
+    /// use wechat_core::client::WechatCore;
+    ///
+    /// # async fn run(client: WechatCore) -> wechat_core::Result<()> {
+    /// let value = client
+    ///     .request_raw(Method::POST, "/wxa/getpluginopenpid", None, Some(json!({"code": "xxx"})))
+    ///     .await?;
+    /// println!("{}", value["openpid"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn request_raw(
+        &self,
+        method: Method,
+        path: &str,
+        query: Option<Value>,
+        body: Option<Value>,
+    ) -> Result<Value> {
+        let url = raw_url(path)?;
+
+        let mut query = match query {
+            Some(Value::Object(query)) => query,
+            None => Map::new(),
+            Some(_) => {
+                return Err(ErrorKind::InvalidParameter(localized!(
+                    "query 必须是 JSON 对象",
+                    "query must be a JSON object"
+                ))
+                .into());
+            }
+        };
+        if body.as_ref().is_some_and(|body| !body.is_object()) {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "body 必须是 JSON 对象",
+                "body must be a JSON object"
+            ))
+            .into());
+        }
+        query.insert("access_token".to_string(), Value::String(self.token().await?));
+
+        let mut builder = RequestBuilder::new(url)
+            .method(method)
+            .query(Value::Object(query));
+        if let Some(body) = body {
+            builder = builder.body(body);
+        }
+
+        let response = self.client.execute(builder.build()?).await?;
+        debug!("raw request response status: {}", response.status());
+
+        response.to_json::<Value>()
+    }
+}
+
+/// [`WechatCore::request_raw`] 的请求地址，完整 URL 必须与微信接口域名同源（https、默认端口）
+fn raw_url(path: &str) -> Result<String> {
+    if !path.contains("://") {
+        return Ok(format!(
+            "{}/{}",
+            constants::END_POINT_BASE_URL,
+            path.trim_start_matches('/')
+        ));
+    }
+
+    let base = url::Url::parse(constants::END_POINT_BASE_URL)?;
+    if url::Url::parse(path).is_ok_and(|url| url.origin() == base.origin()) {
+        return Ok(path.to_string());
+    }
+    Err(ErrorKind::InvalidParameter(localized!(
+        "只能调用 {} 下的接口：{}",
+        "only URLs under {} are allowed: {}",
+        constants::END_POINT_BASE_URL,
+        path
+    ))
+    .into())
 }
 
 /// 定义 HTTP 客户端行为的 Trait。
 #[async_trait]
 pub trait HttpClient: Send + Sync {
     async fn execute(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>>;
+
+    /// 发送 multipart/form-data 请求
+    ///
+    /// 默认实现将表单编码后设置 `Content-Type` 并交给 [`execute`](Self::execute)，
+    /// 需要流式上传等能力时可以覆盖。
+    async fn execute_multipart(&self, request: Request<Multipart>) -> Result<Response<Vec<u8>>> {
+        let (mut parts, form) = request.into_parts();
+        let content_type = HeaderValue::from_str(&form.content_type()).map_err(http::Error::from)?;
+        parts.headers.insert(CONTENT_TYPE, content_type);
+
+        self.execute(Request::from_parts(parts, form.into_bytes()))
+            .await
+    }
+
+    /// 发送请求并将响应体直接写入 `writer`，返回的响应体为写入的字节数
+    ///
+    /// 用于小程序码、媒体文件等下载类接口。微信返回 JSON 错误时不会写入 `writer`，
+    /// 而是返回对应的错误。默认实现会先读取完整响应，可以覆盖为真正的流式读取。
+    async fn execute_streaming(
+        &self,
+        request: Request<Vec<u8>>,
+        writer: &mut (dyn AsyncWrite + Send + Unpin),
+    ) -> Result<Response<u64>> {
+        let response = self.execute(request).await?;
+        let response = if should_check_download(&response) {
+            check_download(response)?
+        } else {
+            response
+        };
+
+        let (parts, body) = response.into_parts();
+        writer.write_all(&body).await?;
+        writer.flush().await?;
+
+        Ok(Response::from_parts(parts, body.len() as u64))
+    }
 }
 
 /// 基于 `reqwest` 库的默认 HTTP 客户端实现。
+#[cfg(feature = "reqwest")]
 #[derive(Default, Clone)]
 pub struct ReqwestHttpClient {
     pub client: Arc<reqwest::Client>,
 }
 
+#[cfg(feature = "reqwest")]
 impl ReqwestHttpClient {
     pub fn new() -> Self {
         ReqwestHttpClient {
@@ -102,21 +370,34 @@ impl ReqwestHttpClient {
     }
 }
 
+#[cfg(feature = "reqwest")]
 #[async_trait]
 impl HttpClient for ReqwestHttpClient {
     async fn execute(&self, req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
         let reqwest_req: ReqwestRequest = req.try_into()?;
 
         #[cfg(test)]
-        eprintln!("reqwest url: {:?}", reqwest_req.url());
+        eprintln!(
+            "reqwest url: {}",
+            crate::utils::redact_url(reqwest_req.url())
+        );
 
-        let reqwest_res = self.client.execute(reqwest_req).await?;
+        let endpoint = Endpoint::from_url(reqwest_req.url());
+        let reqwest_res = self
+            .client
+            .execute(reqwest_req)
+            .await
+            .map_err(|e| transport_error(e, &endpoint))?;
 
         let status = reqwest_res.status();
         let version = reqwest_res.version();
         let headers = reqwest_res.headers().clone();
 
-        let body = reqwest_res.bytes().await?.to_vec();
+        let body = reqwest_res
+            .bytes()
+            .await
+            .map_err(|e| transport_error(e, &endpoint))?
+            .to_vec();
 
         let mut http_res_builder = Response::builder().status(status).version(version);
 
@@ -124,8 +405,224 @@ impl HttpClient for ReqwestHttpClient {
             headers_map.extend(headers);
         }
 
-        let http_res = http_res_builder.body(body)?;
+        let mut http_res = http_res_builder.body(body)?;
+        http_res.extensions_mut().insert(endpoint);
 
         Ok(http_res)
     }
+
+    async fn execute_streaming(
+        &self,
+        req: Request<Vec<u8>>,
+        writer: &mut (dyn AsyncWrite + Send + Unpin),
+    ) -> Result<Response<u64>> {
+        let reqwest_req: ReqwestRequest = req.try_into()?;
+        let endpoint = Endpoint::from_url(reqwest_req.url());
+        let mut reqwest_res = self
+            .client
+            .execute(reqwest_req)
+            .await
+            .map_err(|e| transport_error(e, &endpoint))?;
+
+        let mut http_res_builder = Response::builder()
+            .status(reqwest_res.status())
+            .version(reqwest_res.version())
+            .extension(endpoint.clone());
+
+        if let Some(headers_map) = http_res_builder.headers_mut() {
+            headers_map.extend(reqwest_res.headers().clone());
+        }
+
+        let head = http_res_builder.body(())?;
+        if should_check_download(&head) {
+            let body = reqwest_res
+                .bytes()
+                .await
+                .map_err(|e| transport_error(e, &endpoint))?
+                .to_vec();
+            let (parts, _) = head.into_parts();
+            let response = check_download(Response::from_parts(parts, body))?;
+            let (parts, body) = response.into_parts();
+            writer.write_all(&body).await?;
+            writer.flush().await?;
+            return Ok(Response::from_parts(parts, body.len() as u64));
+        }
+
+        let mut written = 0u64;
+        while let Some(chunk) = reqwest_res
+            .chunk()
+            .await
+            .map_err(|e| transport_error(e, &endpoint))?
+        {
+            writer.write_all(&chunk).await?;
+            written += chunk.len() as u64;
+        }
+        writer.flush().await?;
+
+        let (parts, _) = head.into_parts();
+        Ok(Response::from_parts(parts, written))
+    }
+}
+
+/// 去掉 reqwest 错误中带有 access_token 的完整 URL，改为附加接口地址
+#[cfg(feature = "reqwest")]
+fn transport_error(error: reqwest::Error, endpoint: &Endpoint) -> Error {
+    Error::from(error.without_url()).with_endpoint(endpoint.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct EchoClient;
+
+    #[async_trait]
+    impl HttpClient for EchoClient {
+        async fn execute(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+            let content_type = request.headers()[CONTENT_TYPE].as_bytes().to_vec();
+            Ok(Response::new([content_type, request.into_body()].concat()))
+        }
+    }
+
+    #[test]
+    fn test_app_config_validate() {
+        let config = |app_id: &str, secret: &str| AppConfig {
+            app_id: app_id.to_string(),
+            secret: secret.to_string(),
+        };
+        assert!(config("wx1234567890abcdef", "secret").validate().is_ok());
+
+        for (app_id, secret) in [
+            ("", "secret"),
+            ("wx123", "secret"),
+            ("ab1234567890abcdef", "secret"),
+            ("wx1234567890abcde-", "secret"),
+            ("wx1234567890abcdef", ""),
+            ("wx1234567890abcdef", "sec ret"),
+        ] {
+            let error = config(app_id, secret).validate().unwrap_err();
+            assert!(
+                matches!(error.kind(), ErrorKind::InvalidParameter(_)),
+                "{} {}",
+                app_id,
+                secret
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_multipart() {
+        let form = Multipart::new().file("media", "a.jpg", b"jpeg".to_vec());
+        let content_type = form.content_type();
+        let request = RequestBuilder::new("https://api.weixin.qq.com/cgi-bin/media/upload")
+            .query(serde_json::json!({"type": "image"}))
+            .build_multipart(form)
+            .unwrap();
+
+        let body = EchoClient.execute_multipart(request).await.unwrap().into_body();
+        let body = String::from_utf8(body).unwrap();
+        assert!(body.starts_with(&content_type));
+        assert!(body.contains("filename=\"a.jpg\""));
+    }
+
+    struct FixedClient(&'static str, &'static [u8]);
+
+    #[async_trait]
+    impl HttpClient for FixedClient {
+        async fn execute(&self, _request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+            Ok(Response::builder()
+                .header(CONTENT_TYPE, self.0)
+                .body(self.1.to_vec())
+                .unwrap())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_streaming() {
+        let request = || RequestBuilder::new("https://api.weixin.qq.com/wxa/getwxacode").build().unwrap();
+
+        let mut buffer = Vec::new();
+        let response = FixedClient("image/jpeg", b"jpeg")
+            .execute_streaming(request(), &mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(*response.body(), 4);
+        assert_eq!(buffer, b"jpeg");
+
+        let mut buffer = Vec::new();
+        let error = FixedClient("application/json", br#"{"errcode":40001,"errmsg":"invalid"}"#)
+            .execute_streaming(request(), &mut buffer)
+            .await
+            .unwrap_err();
+        assert!(matches!(error.kind(), crate::ErrorKind::InvalidCredential(_)));
+        assert!(buffer.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_request_raw() {
+        let http_client = Arc::new(DryRunHttpClient::with_response(
+            r#"{"errcode":0,"errmsg":"ok","openpid":"p1"}"#,
+        ));
+        let token_type = Arc::new(StableToken::new("wx123", "secret", false, http_client.clone()));
+        let token_storage = Arc::new(MemoryTokenStorage::new(token_type));
+        let client = WechatCore::custom(http_client.clone(), token_storage).with_token("TOKEN");
+
+        let value = client
+            .request_raw(
+                Method::POST,
+                "/wxa/getpluginopenpid",
+                Some(serde_json::json!({"lang": "zh_CN"})),
+                Some(serde_json::json!({"code": "c1"})),
+            )
+            .await
+            .unwrap();
+        assert_eq!(value["openpid"], "p1");
+
+        let requests = http_client.requests();
+        assert_eq!(requests[0].method, Method::POST);
+        assert!(requests[0].url.starts_with("https://api.weixin.qq.com/wxa/getpluginopenpid?"));
+        assert!(requests[0].url.contains("lang=zh_CN"));
+        assert!(requests[0].url.contains(&format!("access_token={}", REDACTED)));
+        let body: Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["code"], "c1");
+
+        let url = "https://api.weixin.qq.com/cgi-bin/openapi/quota/get";
+        client.request_raw(Method::GET, url, None, None).await.unwrap();
+        assert!(http_client.requests()[1].url.starts_with(url));
+
+        let error = client
+            .request_raw(Method::POST, "/wxa/x", Some(serde_json::json!([1])), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidParameter(_)));
+        assert_eq!(http_client.requests().len(), 2);
+
+        // access_token 不会发往微信接口以外的地址
+        for url in [
+            "https://example.com/wxa/x",
+            "http://api.weixin.qq.com/wxa/x",
+            "https://api.weixin.qq.com:8443/wxa/x",
+            "https://api.weixin.qq.com.example.com/wxa/x",
+            "https://api.weixin.qq.com@example.com/wxa/x",
+            "ftp://api.weixin.qq.com/wxa/x",
+        ] {
+            let error = client.request_raw(Method::GET, url, None, None).await.unwrap_err();
+            assert!(matches!(error.kind(), ErrorKind::InvalidParameter(_)), "{}", url);
+        }
+        assert_eq!(http_client.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_request_raw_error() {
+        let http_client = Arc::new(DryRunHttpClient::with_response(
+            r#"{"errcode":40001,"errmsg":"invalid credential"}"#,
+        ));
+        let token_type = Arc::new(StableToken::new("wx123", "secret", false, http_client.clone()));
+        let token_storage = Arc::new(MemoryTokenStorage::new(token_type));
+        let client = WechatCore::custom(http_client, token_storage).with_token("TOKEN");
+
+        let error = client.request_raw(Method::GET, "wxa/x", None, None).await.unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidCredential(_)));
+        assert_eq!(error.endpoint(), Some("https://api.weixin.qq.com/wxa/x"));
+    }
 }
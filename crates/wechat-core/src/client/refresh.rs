@@ -0,0 +1,317 @@
+//! 接口调用凭据的刷新编排
+//!
+//! 自定义存储只需要实现 [`TokenStore`] 的读写原语，[`RefreshCoordinator`] 负责过期判断、
+//! 本地缓存和并发刷新保护，redis、sql 等存储不再需要自己处理锁和等待。
+
+use super::access_token::{AccessToken, is_token_expired};
+use super::token_storage::TokenStorage;
+use super::token_type::TokenType;
+use crate::Result;
+use async_lock::{Mutex, RwLock};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{debug, warn};
+
+/// access_token 的读写原语
+///
+/// 只负责把 access_token 读出和写入存储，是否过期、何时刷新由 [`RefreshCoordinator`] 判断。
+/// `()` 表示不持久化，只使用进程内缓存。
+#[async_trait]
+pub trait TokenStore: Send + Sync {
+    /// 读取保存的 access_token，不存在时返回 `None`
+    async fn load(&self) -> Result<Option<AccessToken>>;
+    /// 保存刷新得到的 access_token
+    async fn save(&self, token: &AccessToken) -> Result<()>;
+}
+
+#[async_trait]
+impl TokenStore for () {
+    async fn load(&self) -> Result<Option<AccessToken>> {
+        Ok(None)
+    }
+
+    async fn save(&self, _token: &AccessToken) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// access_token 刷新计数
+///
+/// 用于发现刷新风暴：`refreshes` 短时间内持续增长，通常说明多个实例在互相刷新
+/// 或存储写入失败；`waiting` 长时间较大说明刷新请求耗时过长。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RefreshStats {
+    /// 发起刷新请求的次数
+    pub refreshes: u64,
+    /// 刷新请求失败的次数
+    pub failures: u64,
+    /// 拿到刷新权后发现已被其他任务或实例刷新、未发起请求的次数
+    pub coalesced: u64,
+    /// 当前正在等待刷新结果的任务数
+    pub waiting: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    refreshes: AtomicU64,
+    failures: AtomicU64,
+    coalesced: AtomicU64,
+    waiting: AtomicU64,
+}
+
+/// 等待刷新权期间计入 `waiting`，任务被取消时同样会减去
+struct Waiting<'a>(&'a AtomicU64);
+
+impl<'a> Waiting<'a> {
+    fn new(waiting: &'a AtomicU64) -> Self {
+        waiting.fetch_add(1, Ordering::Relaxed);
+        Waiting(waiting)
+    }
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// 基于 [`TokenStore`] 的刷新编排，实现 [`TokenStorage`]
+///
+/// - 优先读取本地缓存，过期后再读取存储，多个实例可以共享同一个 access_token；
+/// - 存储中也过期时，同一进程内只有一个任务发起刷新，其余任务等待后直接读取刷新结果；
+/// - 拿到刷新权后会再读取一次存储，其他实例已经刷新时直接使用；
+/// - 写入存储失败只记录日志，本次调用仍返回新的 access_token。
+///
+/// 多个实例同时刷新时，建议使用 [`StableToken`](super::StableToken)，
+/// 刷新不会使其他实例持有的 access_token 失效。
+///
+/// ```
+/// use async_trait::async_trait;
+/// use std::sync::{Arc, Mutex};
+/// use wechat_core::client::{
+///     AccessToken, RefreshCoordinator, ReqwestHttpClient, StableToken, TokenStore,
+/// };
+///
+/// /// 示例存储，实际项目中读写 redis、数据库等
+/// #[derive(Default)]
+/// struct SqlStore(Mutex<Option<AccessToken>>);
+///
+/// #[async_trait]
+/// impl TokenStore for SqlStore {
+///     async fn load(&self) -> wechat_core::Result<Option<AccessToken>> {
+///         Ok(self.0.lock().unwrap().clone())
+///     }
+///
+///     async fn save(&self, token: &AccessToken) -> wechat_core::Result<()> {
+///         *self.0.lock().unwrap() = Some(token.clone());
+///         Ok(())
+///     }
+/// }
+///
+/// let http_client = Arc::new(ReqwestHttpClient::new());
+/// let token_type = Arc::new(StableToken::new("app_id", "secret", false, http_client));
+/// let storage = RefreshCoordinator::new(SqlStore::default(), token_type);
+/// ```
+pub struct RefreshCoordinator<S> {
+    pub(crate) store: S,
+    cached: RwLock<Option<AccessToken>>,
+    refreshing: Mutex<()>,
+    token_type: Arc<dyn TokenType>,
+    counters: Counters,
+}
+
+impl<S: TokenStore> RefreshCoordinator<S> {
+    pub fn new(store: S, token_type: Arc<dyn TokenType>) -> Self {
+        RefreshCoordinator {
+            store,
+            cached: RwLock::new(None),
+            refreshing: Mutex::new(()),
+            token_type,
+            counters: Counters::default(),
+        }
+    }
+
+    /// 底层存储
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    async fn cached(&self) -> Option<String> {
+        let cached = self.cached.read().await;
+        cached
+            .as_ref()
+            .filter(|token| !is_token_expired(token))
+            .map(|token| token.access_token.clone())
+    }
+
+    /// 读取存储，存储中的 access_token 有效时同步到本地缓存
+    async fn load(&self) -> Result<Option<String>> {
+        let Some(token) = self.store.load().await? else {
+            return Ok(None);
+        };
+        if is_token_expired(&token) {
+            return Ok(None);
+        }
+
+        let access_token = token.access_token.clone();
+        *self.cached.write().await = Some(token);
+        Ok(Some(access_token))
+    }
+}
+
+#[async_trait]
+impl<S: TokenStore> TokenStorage for RefreshCoordinator<S> {
+    async fn token(&self) -> Result<String> {
+        if let Some(access_token) = self.cached().await {
+            return Ok(access_token);
+        }
+        if let Some(access_token) = self.load().await? {
+            return Ok(access_token);
+        }
+
+        self.refresh_access_token().await
+    }
+
+    async fn refresh_access_token(&self) -> Result<String> {
+        let waiting = Waiting::new(&self.counters.waiting);
+        let _refreshing = self.refreshing.lock().await;
+        drop(waiting);
+
+        // 等待期间可能已被其他任务或其他实例刷新
+        if let Some(access_token) = self.cached().await {
+            debug!("token already refreshed by another task");
+            self.counters.coalesced.fetch_add(1, Ordering::Relaxed);
+            return Ok(access_token);
+        }
+        if let Some(access_token) = self.load().await? {
+            debug!("token already refreshed by another instance");
+            self.counters.coalesced.fetch_add(1, Ordering::Relaxed);
+            return Ok(access_token);
+        }
+
+        debug!("performing network request to refresh token");
+        self.counters.refreshes.fetch_add(1, Ordering::Relaxed);
+        let token = self.token_type.token().await.inspect_err(|_| {
+            self.counters.failures.fetch_add(1, Ordering::Relaxed);
+        })?;
+        if let Err(e) = self.store.save(&token).await {
+            // 存储写入失败不影响本次调用，其他实例会自行刷新
+            warn!("save access token failed: {}", e);
+        }
+
+        debug!("fresh access token: {:#?}", token);
+        let access_token = token.access_token.clone();
+        *self.cached.write().await = Some(token);
+        Ok(access_token)
+    }
+
+    fn token_type(&self) -> Arc<dyn TokenType> {
+        self.token_type.clone()
+    }
+
+    async fn expires_at(&self) -> Option<DateTime<Utc>> {
+        let cached = self.cached.read().await;
+        cached.as_ref().map(|token| token.expired_at)
+    }
+
+    fn refresh_stats(&self) -> Option<RefreshStats> {
+        let counters = &self.counters;
+        Some(RefreshStats {
+            refreshes: counters.refreshes.load(Ordering::Relaxed),
+            failures: counters.failures.load(Ordering::Relaxed),
+            coalesced: counters.coalesced.load(Ordering::Relaxed),
+            waiting: counters.waiting.load(Ordering::Relaxed),
+        })
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use crate::client::AppConfig;
+    use crate::ErrorKind;
+    use chrono::Utc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// 记录刷新次数的 TokenType，第 n 次刷新返回 `token{n}`
+    ///
+    /// 每次刷新耗时 10ms，便于并发请求重叠。各个 TokenStorage 共用这个夹具。
+    #[derive(Default)]
+    pub(in crate::client) struct CountToken {
+        pub(in crate::client) calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl TokenType for CountToken {
+        async fn token(&self) -> Result<AccessToken> {
+            let calls = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Ok(AccessToken {
+                access_token: format!("token{}", calls),
+                expired_at: Utc::now() + chrono::Duration::hours(2),
+            })
+        }
+
+        fn app_config(&self) -> AppConfig {
+            AppConfig {
+                app_id: "wx123".to_string(),
+                secret: "secret".to_string(),
+            }
+        }
+    }
+
+    /// 第一次读取返回已过期的 token，写入总是失败
+    #[derive(Default)]
+    struct ExpiredStore {
+        loads: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl TokenStore for ExpiredStore {
+        async fn load(&self) -> Result<Option<AccessToken>> {
+            self.loads.fetch_add(1, Ordering::SeqCst);
+            Ok(Some(AccessToken {
+                access_token: "expired".to_string(),
+                expired_at: Utc::now(),
+            }))
+        }
+
+        async fn save(&self, _token: &AccessToken) -> Result<()> {
+            Err(ErrorKind::InternalServer("read only".to_string()).into())
+        }
+    }
+
+    /// MemoryTokenStorage、KvTokenStorage 的并发刷新都由 RefreshCoordinator 合并，只在这里测试
+    #[tokio::test]
+    async fn test_refresh_coordinator() {
+        let token_type = Arc::new(CountToken::default());
+        let store = ExpiredStore::default();
+        let storage = Arc::new(RefreshCoordinator::new(store, token_type.clone()));
+
+        assert!(storage.expires_at().await.is_none());
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let storage = storage.clone();
+                tokio::spawn(async move { storage.token().await })
+            })
+            .collect();
+        for task in tasks {
+            assert_eq!(task.await.unwrap().unwrap(), "token1");
+        }
+        assert_eq!(token_type.calls.load(Ordering::SeqCst), 1);
+        let stats = storage.refresh_stats().unwrap();
+        assert_eq!(stats.refreshes, 1);
+        assert_eq!(stats.failures, 0);
+        assert_eq!(stats.waiting, 0);
+        assert_eq!(stats.coalesced + stats.refreshes, 8);
+
+        // 写入失败时仍使用本地缓存，不再读取存储
+        let loads = storage.store().loads.load(Ordering::SeqCst);
+        assert_eq!(storage.token().await.unwrap(), "token1");
+        assert_eq!(storage.store().loads.load(Ordering::SeqCst), loads);
+        assert!(storage.expires_at().await.unwrap() > Utc::now());
+    }
+}
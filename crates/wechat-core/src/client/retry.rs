@@ -0,0 +1,515 @@
+//! 请求重试
+//!
+//! 不同接口的幂等性不同：查询数据可以放心重试，生成订单、发送消息重复提交则会造成副作用。
+//! [`RetryHttpClient`] 包装任意 [`HttpClient`]，按接口路径前缀选择 [`RetryPolicy`]，
+//! 可以分别配置重试条件、最大次数与总时间预算。
+//!
+//! 接口定义处通过 [`Idempotency`] 标注幂等性，非幂等接口默认只在请求确定没有被微信处理时
+//! 重试（连接失败、频率限制），避免重复发消息、重复生成链接。
+
+use super::HttpClient;
+use crate::utils::Idempotency;
+use crate::{Error, ErrorKind, Result};
+use async_trait::async_trait;
+use http::{Request, Response};
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::io::AsyncWrite;
+use tracing::warn;
+
+/// 触发重试的条件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryOn {
+    /// 仅网络错误（连接失败、超时、读写中断），请求可能尚未到达微信
+    NetworkOnly,
+    /// 网络错误，以及 HTTP 5xx 和微信系统繁忙（errcode -1）
+    NetworkAndServer,
+}
+
+/// 重试策略
+///
+/// 重试间隔从 `base_delay` 开始指数增长，不超过 `max_delay`；
+/// 从首次请求开始计算的总耗时超出 `budget` 时不再重试。
+///
+/// # 示例
+///
+/// ```
+/// use std::time::Duration;
+/// use wechat_core::client::{RetryOn, RetryPolicy};
+///
+/// let policy = RetryPolicy::default()
+///     .max_retries(3)
+///     .retry_on(RetryOn::NetworkAndServer)
+///     .budget(Duration::from_secs(5));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    retry_on: RetryOn,
+    base_delay: Duration,
+    max_delay: Duration,
+    budget: Option<Duration>,
+    max_rate_limit_wait: Option<Duration>,
+    retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    /// 仅网络错误重试，最多 2 次，总预算 10 秒
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 2,
+            retry_on: RetryOn::NetworkOnly,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(2),
+            budget: Some(Duration::from_secs(10)),
+            max_rate_limit_wait: None,
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 不重试，用于非幂等接口
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// 最大重试次数（不含首次请求）
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// 触发重试的条件
+    pub fn retry_on(mut self, retry_on: RetryOn) -> Self {
+        self.retry_on = retry_on;
+        self
+    }
+
+    /// 重试间隔的初始值与上限
+    pub fn backoff(mut self, base_delay: Duration, max_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self.max_delay = max_delay.max(base_delay);
+        self
+    }
+
+    /// 总时间预算，`None` 表示不限制
+    pub fn budget(mut self, budget: impl Into<Option<Duration>>) -> Self {
+        self.budget = budget.into();
+        self
+    }
+
+    /// 遇到频率限制（如 45011）时按 [`Error::retry_after`] 建议的时间等待后重试，
+    /// 建议时间超过 `max_wait` 时直接返回错误，交给上层排队
+    pub fn wait_on_rate_limit(mut self, max_wait: Duration) -> Self {
+        self.max_rate_limit_wait = Some(max_wait);
+        self
+    }
+
+    /// 非幂等接口也按 [`retry_on`](Self::retry_on) 的条件重试
+    ///
+    /// 默认关闭：非幂等接口只在连接失败、频率限制等请求确定没有被处理的情况下重试。
+    /// 读写超时时微信可能已经处理了请求，开启后可能重复发送消息，需要业务侧自行去重。
+    pub fn retry_non_idempotent(mut self, enabled: bool) -> Self {
+        self.retry_non_idempotent = enabled;
+        self
+    }
+
+    /// 第 `retry` 次重试（从 0 开始）前的等待时间
+    fn delay(&self, retry: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay)
+    }
+
+    /// 需要重试时返回等待时间
+    fn retry_delay(
+        &self,
+        result: &Result<Response<Vec<u8>>>,
+        retry: u32,
+        idempotency: Idempotency,
+    ) -> Option<Duration> {
+        let idempotent = idempotency.is_idempotent() || self.retry_non_idempotent;
+        match result {
+            Err(error) if is_connect_error(error) => Some(self.delay(retry)),
+            Err(error) if idempotent && is_network_error(error) => Some(self.delay(retry)),
+            Err(_) => None,
+            Ok(response) => {
+                if let Some(max_wait) = self.max_rate_limit_wait
+                    && let Some(retry_after) = rate_limit_retry_after(response)
+                {
+                    return (retry_after <= max_wait).then_some(retry_after);
+                }
+
+                (idempotent
+                    && self.retry_on == RetryOn::NetworkAndServer
+                    && is_server_error(response))
+                    .then(|| self.delay(retry))
+            }
+        }
+    }
+}
+
+/// 是否为网络层错误
+fn is_network_error(error: &Error) -> bool {
+    match error.kind() {
+        #[cfg(feature = "reqwest")]
+        ErrorKind::Reqwest(e) => e.is_connect() || e.is_timeout() || e.is_request() || e.is_body(),
+        ErrorKind::Io(_) | ErrorKind::Timeout(_) => true,
+        _ => false,
+    }
+}
+
+/// 是否为连接失败，此时请求一定没有到达微信
+fn is_connect_error(error: &Error) -> bool {
+    match error.kind() {
+        #[cfg(feature = "reqwest")]
+        ErrorKind::Reqwest(e) => e.is_connect(),
+        ErrorKind::Io(e) => e.kind() == std::io::ErrorKind::ConnectionRefused,
+        _ => false,
+    }
+}
+
+/// 是否为 HTTP 5xx 或微信系统繁忙
+fn is_server_error(response: &Response<Vec<u8>>) -> bool {
+    if response.status().is_server_error() {
+        return true;
+    }
+
+    serde_json::from_slice::<serde_json::Value>(response.body())
+        .ok()
+        .and_then(|value| value.get("errcode").and_then(|code| code.as_i64()))
+        == Some(-1)
+}
+
+/// 频率限制类错误建议的等待时间
+fn rate_limit_retry_after(response: &Response<Vec<u8>>) -> Option<Duration> {
+    let value = serde_json::from_slice::<serde_json::Value>(response.body()).ok()?;
+    let code = value.get("errcode")?.as_i64()?;
+    if code == 0 || code == -1 {
+        return None;
+    }
+
+    let message = value
+        .get("errmsg")
+        .and_then(|message| message.as_str())
+        .unwrap_or_default()
+        .to_string();
+    Error::from_code(i32::try_from(code).ok()?, message).retry_after()
+}
+
+/// 带重试的 HTTP 客户端包装
+///
+/// 按请求路径匹配 [`endpoint_policy`](Self::endpoint_policy) 配置的前缀（最长前缀优先），
+/// 未匹配时使用默认策略。非幂等请求的重试条件见
+/// [`RetryPolicy::retry_non_idempotent`]。流式下载可能已经写出部分数据，不做重试。
+///
+/// # 示例
+///
+/// ```
+/// use std::sync::Arc;
+/// use wechat_core::client::{DryRunHttpClient, RetryHttpClient, RetryOn, RetryPolicy};
+///
+/// let inner = Arc::new(DryRunHttpClient::new());
+/// let http_client = RetryHttpClient::new(inner, RetryPolicy::default())
+///     // 数据分析接口：网络错误和系统繁忙都重试
+///     .endpoint_policy(
+///         "/datacube/",
+///         RetryPolicy::default().max_retries(3).retry_on(RetryOn::NetworkAndServer),
+///     )
+///     // 发送订阅消息：不重试，避免重复发送
+///     .endpoint_policy("/cgi-bin/message/subscribe/send", RetryPolicy::none());
+/// ```
+#[derive(Clone)]
+pub struct RetryHttpClient {
+    inner: Arc<dyn HttpClient>,
+    default_policy: RetryPolicy,
+    endpoints: Vec<(String, RetryPolicy)>,
+}
+
+impl fmt::Debug for RetryHttpClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryHttpClient")
+            .field("inner", &"Arc<dyn HttpClient>")
+            .field("default_policy", &self.default_policy)
+            .field("endpoints", &self.endpoints)
+            .finish()
+    }
+}
+
+impl RetryHttpClient {
+    pub fn new(inner: Arc<dyn HttpClient>, default_policy: RetryPolicy) -> Self {
+        RetryHttpClient {
+            inner,
+            default_policy,
+            endpoints: Vec::new(),
+        }
+    }
+
+    /// 为路径以 `prefix` 开头的接口指定重试策略
+    pub fn endpoint_policy(mut self, prefix: impl Into<String>, policy: RetryPolicy) -> Self {
+        self.endpoints.push((prefix.into(), policy));
+        self
+    }
+
+    /// 请求对应的重试策略
+    pub fn policy_for(&self, path: &str) -> &RetryPolicy {
+        self.endpoints
+            .iter()
+            .filter(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, policy)| policy)
+            .unwrap_or(&self.default_policy)
+    }
+}
+
+fn clone_request(request: &Request<Vec<u8>>) -> Request<Vec<u8>> {
+    let mut cloned = Request::new(request.body().clone());
+    *cloned.method_mut() = request.method().clone();
+    *cloned.uri_mut() = request.uri().clone();
+    *cloned.version_mut() = request.version();
+    *cloned.headers_mut() = request.headers().clone();
+    if let Some(idempotency) = request.extensions().get::<Idempotency>() {
+        cloned.extensions_mut().insert(*idempotency);
+    }
+    cloned
+}
+
+#[async_trait]
+impl HttpClient for RetryHttpClient {
+    async fn execute(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        let policy = self.policy_for(request.uri().path());
+        if policy.max_retries == 0 {
+            return self.inner.execute(request).await;
+        }
+
+        let idempotency = Idempotency::of(&request);
+        let started = Instant::now();
+        let mut retry = 0;
+        loop {
+            let result = self.inner.execute(clone_request(&request)).await;
+            if retry >= policy.max_retries {
+                return result;
+            }
+
+            let Some(delay) = policy.retry_delay(&result, retry, idempotency) else {
+                return result;
+            };
+            if let Some(budget) = policy.budget
+                && started.elapsed() + delay > budget
+            {
+                return result;
+            }
+
+            warn!(
+                "retry {} {} after {:?} ({}/{})",
+                request.method(),
+                request.uri().path(),
+                delay,
+                retry + 1,
+                policy.max_retries
+            );
+            tokio::time::sleep(delay).await;
+            retry += 1;
+        }
+    }
+
+    async fn execute_streaming(
+        &self,
+        request: Request<Vec<u8>>,
+        writer: &mut (dyn AsyncWrite + Send + Unpin),
+    ) -> Result<Response<u64>> {
+        self.inner.execute_streaming(request, writer).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// 前 `failures` 次返回指定结果，之后成功
+    struct FlakyClient {
+        calls: AtomicUsize,
+        failures: usize,
+        fail: fn() -> Result<Response<Vec<u8>>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for FlakyClient {
+        async fn execute(&self, _request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                (self.fail)()
+            } else {
+                Ok(Response::new(br#"{"errcode":0}"#.to_vec()))
+            }
+        }
+    }
+
+    fn flaky(failures: usize, fail: fn() -> Result<Response<Vec<u8>>>) -> Arc<FlakyClient> {
+        Arc::new(FlakyClient {
+            calls: AtomicUsize::new(0),
+            failures,
+            fail,
+        })
+    }
+
+    fn network_error() -> Result<Response<Vec<u8>>> {
+        Err(ErrorKind::Io(std::io::ErrorKind::ConnectionReset.into()).into())
+    }
+
+    fn system_busy() -> Result<Response<Vec<u8>>> {
+        Ok(Response::new(br#"{"errcode":-1,"errmsg":"system error"}"#.to_vec()))
+    }
+
+    fn request(path: &str) -> Request<Vec<u8>> {
+        let mut request = non_idempotent_request(path);
+        request.extensions_mut().insert(Idempotency::Idempotent);
+        request
+    }
+
+    fn non_idempotent_request(path: &str) -> Request<Vec<u8>> {
+        Request::post(format!("https://api.weixin.qq.com{}", path))
+            .body(b"{}".to_vec())
+            .unwrap()
+    }
+
+    fn fast() -> RetryPolicy {
+        RetryPolicy::default().backoff(Duration::from_millis(1), Duration::from_millis(1))
+    }
+
+    #[tokio::test]
+    async fn test_retry_network_error() {
+        let inner = flaky(2, network_error);
+        let client = RetryHttpClient::new(inner.clone(), fast());
+
+        assert!(client.execute(request("/datacube/a")).await.is_ok());
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_policy() {
+        let inner = flaky(5, network_error);
+        let client = RetryHttpClient::new(inner.clone(), fast())
+            .endpoint_policy("/cgi-bin/message/", RetryPolicy::none());
+
+        assert!(client.execute(request("/cgi-bin/message/subscribe/send")).await.is_err());
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+
+        let inner = flaky(2, system_busy);
+        let client = RetryHttpClient::new(inner.clone(), fast()).endpoint_policy(
+            "/datacube/",
+            fast().retry_on(RetryOn::NetworkAndServer),
+        );
+        let response = client.execute(request("/wxa/getwxacode")).await.unwrap();
+        assert!(response.body().starts_with(br#"{"errcode":-1"#));
+
+        let response = client.execute(request("/datacube/a")).await.unwrap();
+        assert_eq!(response.body(), br#"{"errcode":0}"#);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_non_idempotent() {
+        let path = "/cgi-bin/message/subscribe/send";
+        let inner = flaky(1, network_error);
+        let client = RetryHttpClient::new(inner.clone(), fast());
+        assert!(client.execute(non_idempotent_request(path)).await.is_err());
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+
+        let inner = flaky(1, system_busy);
+        let policy = fast().retry_on(RetryOn::NetworkAndServer);
+        let client = RetryHttpClient::new(inner.clone(), policy);
+        let response = client.execute(non_idempotent_request(path)).await.unwrap();
+        assert!(response.body().starts_with(br#"{"errcode":-1"#));
+
+        // 连接失败时请求没有发出，非幂等接口也可以重试
+        fn refused() -> Result<Response<Vec<u8>>> {
+            Err(ErrorKind::Io(std::io::ErrorKind::ConnectionRefused.into()).into())
+        }
+        let inner = flaky(1, refused);
+        let client = RetryHttpClient::new(inner.clone(), fast());
+        assert!(client.execute(non_idempotent_request(path)).await.is_ok());
+
+        let inner = flaky(1, network_error);
+        let client = RetryHttpClient::new(inner.clone(), fast().retry_non_idempotent(true));
+        assert!(client.execute(non_idempotent_request(path)).await.is_ok());
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+
+        // GET 请求没有标注时视为幂等
+        let inner = flaky(1, network_error);
+        let client = RetryHttpClient::new(inner.clone(), fast());
+        let request = Request::get("https://api.weixin.qq.com/cgi-bin/a").body(Vec::new()).unwrap();
+        assert!(client.execute(request).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_budget() {
+        let inner = flaky(5, network_error);
+        let policy = RetryPolicy::default()
+            .max_retries(5)
+            .backoff(Duration::from_millis(20), Duration::from_millis(20))
+            .budget(Duration::from_millis(30));
+        let client = RetryHttpClient::new(inner.clone(), policy);
+
+        assert!(client.execute(request("/datacube/a")).await.is_err());
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_wait_on_rate_limit() {
+        fn rate_limited() -> Result<Response<Vec<u8>>> {
+            Ok(Response::new(br#"{"errcode":45011,"errmsg":"api freq out of limit"}"#.to_vec()))
+        }
+
+        let inner = flaky(1, rate_limited);
+        let policy = fast()
+            .wait_on_rate_limit(Duration::from_secs(120))
+            .budget(None);
+        let client = RetryHttpClient::new(inner.clone(), policy);
+        let started = tokio::time::Instant::now();
+        let response = client.execute(request("/wxa/getwxacode")).await.unwrap();
+        assert_eq!(response.body(), br#"{"errcode":0}"#);
+        assert!(started.elapsed() >= Duration::from_secs(60));
+
+        let inner = flaky(1, rate_limited);
+        let policy = fast().wait_on_rate_limit(Duration::from_secs(10));
+        let client = RetryHttpClient::new(inner.clone(), policy);
+        let response = client.execute(request("/wxa/getwxacode")).await.unwrap();
+        assert!(response.body().starts_with(br#"{"errcode":45011"#));
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_delay() {
+        let policy =
+            RetryPolicy::default().backoff(Duration::from_millis(100), Duration::from_millis(300));
+        assert_eq!(policy.delay(0), Duration::from_millis(100));
+        assert_eq!(policy.delay(1), Duration::from_millis(200));
+        assert_eq!(policy.delay(5), Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn test_with_retry() {
+        use crate::client::{MemoryTokenStorage, StableToken, WechatCore};
+
+        let inner = flaky(2, network_error);
+        let token_type = Arc::new(StableToken::new("wx123", "secret", false, inner.clone()));
+        let token_storage = Arc::new(MemoryTokenStorage::new(token_type));
+        let client = WechatCore::custom(inner.clone(), token_storage).with_token("TOKEN");
+
+        let method = http::Method::GET;
+        assert!(client.request_raw(method.clone(), "/datacube/a", None, None).await.is_err());
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+
+        let client = client.with_retry(fast());
+        assert!(client.request_raw(method, "/datacube/a", None, None).await.is_ok());
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -1,44 +1,50 @@
 //! 接口调用凭据存储读取模块
-//! 默认使用内存Arc结构,可参考实现读取保存方式，比如 redis、postgresql、mysql 等。
+//! 默认使用内存存储；接入 redis、postgresql、mysql 等存储时实现 [`TokenStore`] 的读写，
+//! 交给 [`RefreshCoordinator`] 处理刷新即可。
+//!
+//! 默认存储使用运行时无关的 `async-lock` 原语，tokio、smol、async-std 下均可使用。
 
-use super::access_token::{AccessToken, is_token_expired};
+use super::refresh::{RefreshCoordinator, RefreshStats};
+#[cfg(doc)]
+use super::refresh::TokenStore;
 use super::token_type::TokenType;
 use crate::Result;
 use async_trait::async_trait;
-use chrono::Utc;
-use std::sync::{
-    Arc,
-    atomic::{AtomicBool, Ordering},
-};
-use tokio::sync::{Notify, RwLock};
-use tracing::debug;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
 
 /// 定义接口调用凭据读取存储的行为
+///
+/// 一般不需要直接实现，自定义存储实现 [`TokenStore`] 后使用 [`RefreshCoordinator`] 包装。
 #[async_trait]
 pub trait TokenStorage: Send + Sync {
     async fn token(&self) -> Result<String>;
     async fn refresh_access_token(&self) -> Result<String>;
     fn token_type(&self) -> Arc<dyn TokenType>;
+
+    /// 当前缓存的 access_token 的过期时间，未知时返回 `None`
+    async fn expires_at(&self) -> Option<DateTime<Utc>> {
+        None
+    }
+
+    /// 刷新计数，不统计时返回 `None`
+    fn refresh_stats(&self) -> Option<RefreshStats> {
+        None
+    }
 }
 
 /// 接口调用凭据内存存储结构
+///
+/// 多个任务同时发现 access_token 过期时，只有第一个任务发起刷新请求，
+/// 其余任务等待后直接读取刷新结果。
 pub struct MemoryTokenStorage {
-    access_token: Arc<RwLock<AccessToken>>,
-    refreshing: Arc<AtomicBool>,
-    notify: Arc<Notify>,
-    token_type: Arc<dyn TokenType>,
+    coordinator: RefreshCoordinator<()>,
 }
 
 impl MemoryTokenStorage {
     pub fn new(token_type: Arc<dyn TokenType>) -> Self {
         MemoryTokenStorage {
-            access_token: Arc::new(RwLock::new(AccessToken {
-                access_token: String::new(),
-                expired_at: Utc::now(),
-            })),
-            refreshing: Arc::new(AtomicBool::new(false)),
-            notify: Arc::new(Notify::new()),
-            token_type,
+            coordinator: RefreshCoordinator::new((), token_type),
         }
     }
 }
@@ -47,58 +53,85 @@ impl MemoryTokenStorage {
 #[async_trait]
 impl TokenStorage for MemoryTokenStorage {
     async fn token(&self) -> Result<String> {
-        {
-            let guard = self.access_token.read().await;
-            if !is_token_expired(&guard) {
-                return Ok(guard.access_token.clone());
-            }
-        }
-
-        if self
-            .refreshing
-            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
-            .is_ok()
-        {
-            match self.refresh_access_token().await {
-                Ok(token) => {
-                    self.refreshing.store(false, Ordering::Release);
-                    self.notify.notify_waiters();
-                    Ok(token)
-                }
-                Err(e) => {
-                    self.refreshing.store(false, Ordering::Release);
-                    self.notify.notify_waiters();
-                    Err(e)
-                }
-            }
-        } else {
-            self.notify.notified().await;
-            let guard = self.access_token.read().await;
-            Ok(guard.access_token.clone())
-        }
+        self.coordinator.token().await
     }
 
     async fn refresh_access_token(&self) -> Result<String> {
-        let mut guard = self.access_token.write().await;
+        self.coordinator.refresh_access_token().await
+    }
 
-        if !is_token_expired(&guard) {
-            debug!("token already refreshed by another thread");
-            return Ok(guard.access_token.clone());
-        }
+    fn token_type(&self) -> Arc<dyn TokenType> {
+        self.coordinator.token_type()
+    }
 
-        debug!("performing network request to refresh token");
+    async fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.coordinator.expires_at().await
+    }
 
-        let builder = self.token_type.token().await?;
+    fn refresh_stats(&self) -> Option<RefreshStats> {
+        self.coordinator.refresh_stats()
+    }
+}
+
+/// 固定接口调用凭据的存储
+///
+/// access_token 由其他系统下发时使用，始终返回传入的 token，不会自行刷新。
+/// `token_type` 沿用原存储的配置，便于继续读取 App ID 等信息。
+pub struct StaticTokenStorage {
+    access_token: String,
+    token_type: Arc<dyn TokenType>,
+}
 
-        guard.access_token = builder.access_token.clone();
-        guard.expired_at = builder.expired_at;
+impl StaticTokenStorage {
+    pub fn new(access_token: impl Into<String>, token_type: Arc<dyn TokenType>) -> Self {
+        StaticTokenStorage {
+            access_token: access_token.into(),
+            token_type,
+        }
+    }
+}
 
-        debug!("fresh access token: {:#?}", guard);
+#[async_trait]
+impl TokenStorage for StaticTokenStorage {
+    async fn token(&self) -> Result<String> {
+        Ok(self.access_token.clone())
+    }
 
-        Ok(guard.access_token.clone())
+    async fn refresh_access_token(&self) -> Result<String> {
+        Ok(self.access_token.clone())
     }
 
     fn token_type(&self) -> Arc<dyn TokenType> {
         self.token_type.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{AccessToken, AppConfig};
+
+    struct Config;
+
+    #[async_trait]
+    impl TokenType for Config {
+        async fn token(&self) -> Result<AccessToken> {
+            unreachable!("static token never refreshes")
+        }
+
+        fn app_config(&self) -> AppConfig {
+            AppConfig {
+                app_id: "wx123".to_string(),
+                secret: "secret".to_string(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_static_token_storage() {
+        let storage = StaticTokenStorage::new("external_token", Arc::new(Config));
+        assert_eq!(storage.token().await.unwrap(), "external_token");
+        assert_eq!(storage.refresh_access_token().await.unwrap(), "external_token");
+        assert_eq!(storage.token_type().app_config().app_id, "wx123");
+    }
+}
@@ -58,7 +58,7 @@ impl TokenType for StableToken {
 
         let response = self.client.execute(request).await?;
         let response_body = response.into_body();
-        let token_builder = serde_json::from_slice::<MpResponse<AccessTokenBuilder>>(&response_body)?;
+        let token_builder = MpResponse::<AccessTokenBuilder>::from_slice(&response_body)?;
         let token = token_builder.extract()?;
         let token = token.build();
         debug!("stable access token: {:#?}", token);
@@ -104,7 +104,7 @@ impl TokenType for NonStableToken {
 
         let response = self.client.execute(request).await?;
         let response_body = response.into_body();
-        let token_builder = serde_json::from_slice::<MpResponse<AccessTokenBuilder>>(&response_body)?;
+        let token_builder = MpResponse::<AccessTokenBuilder>::from_slice(&response_body)?;
         let token = token_builder.extract()?;
         let token = token.build();
         Ok(token)
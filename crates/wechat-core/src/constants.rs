@@ -6,7 +6,7 @@ pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
 /// base url
 pub const END_POINT_BASE_URL: &str = "https://api.weixin.qq.com";
 
-/// HTTP 客户端的 User-Agent 字符串
+/// HTTP 客户端默认的 User-Agent 字符串，可通过 [`DefaultHeadersHttpClient`](crate::client::DefaultHeadersHttpClient) 替换
 pub const HTTP_CLIENT_USER_AGENT: &str =
     "wechat-core/3.4.3 (A rust sdk for wechat miniprogram and mp server api)";
 
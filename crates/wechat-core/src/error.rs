@@ -3,19 +3,27 @@
 //! 该模块定义了与微信小程序 API 交互过程中可能遇到的所有错误类型，
 //! 包括微信官方错误码映射和第三方库错误转换。
 
+#[cfg(feature = "crypto")]
 use aes::cipher::InvalidLength as AesInvalidLength;
+#[cfg(feature = "crypto")]
 use aes::cipher::block_padding::Error as UnpadError;
+#[cfg(feature = "crypto")]
 use base64::DecodeError as Base64DecodeError;
 use http::Error as HttpError;
+#[cfg(feature = "reqwest")]
 use reqwest::Error as ReqwestError;
+use crate::i18n::localize;
+use chrono::{DateTime, FixedOffset, Utc};
 use serde_json::Error as SerdeJsonError;
 use serde_repr::Deserialize_repr;
+use std::fmt;
+use std::time::Duration;
 use strum::Display;
 
-/// 微信小程序 SDK 错误枚举
+/// 微信小程序 SDK 错误类型，通过 [`Error::kind`] 取得
 #[non_exhaustive]
 #[derive(Debug, thiserror::Error)]
-pub enum Error {
+pub enum ErrorKind {
     /// 微信系统繁忙，请稍候再试
     #[error("system error: {0}")]
     System(String),
@@ -36,6 +44,10 @@ pub enum Error {
     #[error("invalid code: {0}")]
     InvalidCode(String),
 
+    /// 登录 code 已被使用过
+    #[error("code been used: {0}")]
+    CodeUsed(String),
+
     /// 请求参数错误
     #[error("invalid parameter: {0}")]
     InvalidParameter(String),
@@ -120,22 +132,42 @@ pub enum Error {
     #[error("request denied one hour: {0}")]
     RequestDeniedOneHour(String),
 
+    /// 没有调用权限，如 ShortLink 接口目前只开放给电商类目（电商平台、商家自营、跨境电商）
+    #[error("category not allowed: {0}")]
+    CategoryNotAllowed(String),
+
     /// AES 解密时数据填充错误
+    #[cfg(feature = "crypto")]
     #[error("unpad error: {0}")]
     Unpad(#[from] UnpadError),
 
     /// AES 加解密长度错误
+    #[cfg(feature = "crypto")]
     #[error("aes invalid length: {0}")]
     AesInvalidLength(#[from] AesInvalidLength),
 
     /// Base64 解码错误
+    #[cfg(feature = "crypto")]
     #[error("base64 decode error: {0}")]
     Base64Decode(#[from] Base64DecodeError),
 
-    /// Reqwet 请求错误
+    /// Reqwest 请求错误
+    #[cfg(feature = "reqwest")]
     #[error("reqwest: {0}")]
     Reqwest(#[from] ReqwestError),
 
+    /// 业务自有登录态（如 JWT）格式错误或签名不一致
+    #[error("invalid token: {0}")]
+    InvalidToken(String),
+
+    /// 业务自有登录态已过期，需要重新登录
+    #[error("token expired: {0}")]
+    TokenExpired(String),
+
+    /// 解密数据的 watermark.appid 与当前小程序不一致
+    #[error("watermark appid mismatch: expected {expected}, got {actual}")]
+    WatermarkMismatch { expected: String, actual: String },
+
     /// JSON 序列化/反序列化错误
     #[error("json error: {0}")]
     SerdeJson(#[from] SerdeJsonError),
@@ -144,18 +176,526 @@ pub enum Error {
     #[error("internal error: {0}")]
     InternalServer(String),
 
+    /// 接口返回了非 2xx 的 HTTP 状态码
+    #[error("http status {status}: {body}")]
+    HttpStatus {
+        status: u16,
+        /// 响应头中的 Content-Type，网关错误页通常为 `text/html`
+        content_type: Option<String>,
+        body: String,
+    },
+
+    /// 响应体解析失败
+    #[error("decode response error: {source}, body: {body}")]
+    Decode {
+        source: SerdeJsonError,
+        /// 脱敏、截断后的原始响应体，见 [`body_snippet`](crate::utils::body_snippet)
+        body: String,
+    },
+
     /// HTTP 请求错误
     #[error("http: {0}")]
     Http(#[from] HttpError),
 
+    /// 调用超时
+    #[error("request timed out after {0:?}")]
+    Timeout(Duration),
+
+    /// 调用被取消
+    #[error("request cancelled")]
+    Cancelled,
+
+    /// 读写响应数据时的 IO 错误
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+
     /// Url 解析错误
     #[error("url parse error: {0}")]
     UrlParse(#[from] url::ParseError),
+
+    /// 未单独映射的微信接口错误码
+    #[error("wechat error {code}: {message}")]
+    Wechat { code: i32, message: String },
+}
+
+/// 微信小程序 SDK 错误
+///
+/// 具体的错误类型见 [`ErrorKind`]，按类型匹配时使用 [`kind`](Self::kind)。
+/// 请求管道会附加出错的接口地址，见 [`endpoint`](Self::endpoint)，
+/// 接口地址只出现在 `Display` 输出中，不影响按类型匹配。
+///
+/// ```
+/// use wechat_core::{Error, ErrorKind};
+///
+/// let error = Error::from(ErrorKind::InvalidCode("invalid code".to_string()))
+///     .with_endpoint("https://api.weixin.qq.com/sns/jscode2session");
+/// assert!(matches!(error.kind(), ErrorKind::InvalidCode(_)));
+/// assert_eq!(error.endpoint(), Some("https://api.weixin.qq.com/sns/jscode2session"));
+/// assert!(error.requires_relogin());
+/// ```
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+    endpoint: Option<String>,
+    trace_id: Option<String>,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.endpoint {
+            Some(endpoint) => write!(f, "{}: {}", endpoint, self.kind),
+            None => self.kind.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.kind.source()
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Error {
+            kind,
+            endpoint: None,
+            trace_id: None,
+        }
+    }
+}
+
+/// 为 `ErrorKind` 中 `#[from]` 的第三方错误类型实现到 [`Error`] 的转换，保证 `?` 可用
+macro_rules! impl_from_for_error {
+    ($($(#[$meta:meta])* $source:ty),* $(,)?) => {
+        $(
+            $(#[$meta])*
+            impl From<$source> for Error {
+                fn from(error: $source) -> Self {
+                    ErrorKind::from(error).into()
+                }
+            }
+        )*
+    };
+}
+
+impl_from_for_error!(
+    #[cfg(feature = "crypto")]
+    UnpadError,
+    #[cfg(feature = "crypto")]
+    AesInvalidLength,
+    #[cfg(feature = "crypto")]
+    Base64DecodeError,
+    #[cfg(feature = "reqwest")]
+    ReqwestError,
+    SerdeJsonError,
+    HttpError,
+    std::io::Error,
+    url::ParseError,
+);
+
+impl Error {
+    /// 根据微信返回的 errcode 与 errmsg 构造错误
+    pub fn from_code(code: i32, message: String) -> Self {
+        match ErrorCode::try_from(code) {
+            Ok(code) => ErrorKind::from((code, message)).into(),
+            Err(code) => ErrorKind::Wechat { code, message }.into(),
+        }
+    }
+
+    /// 错误类型，按具体类型匹配时使用
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// 同 [`kind`](Self::kind)，取得所有权
+    pub fn into_kind(self) -> ErrorKind {
+        self.kind
+    }
+
+    /// 附加出错的接口地址，请求管道在返回错误前统一调用，已经带有接口地址的错误保持不变
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        if self.endpoint.is_none() {
+            self.endpoint = Some(endpoint.into());
+        }
+        self
+    }
+
+    /// 附加微信请求标识，解析错误响应时调用
+    pub fn with_trace_id(mut self, trace_id: impl Into<String>) -> Self {
+        self.trace_id = Some(trace_id.into());
+        self
+    }
+
+    /// 微信返回的 errcode，本地产生的错误返回 `None`
+    ///
+    /// `InvalidParameter` 也用于本地参数校验，同样返回 `None`。
+    pub fn code(&self) -> Option<i32> {
+        let code = match &self.kind {
+            ErrorKind::System(_) => ErrorCode::System,
+            ErrorKind::InvalidCredential(_) => ErrorCode::InvalidCredential,
+            ErrorKind::InvalidGrantType(_) => ErrorCode::InvalidGrantType,
+            ErrorKind::InvalidAppId(_) => ErrorCode::InvalidAppId,
+            ErrorKind::InvalidCode(_) => ErrorCode::InvalidCode,
+            ErrorKind::CodeUsed(_) => ErrorCode::CodeUsed,
+            ErrorKind::InvalidSecret(_) => ErrorCode::InvalidSecret,
+            ErrorKind::ForbiddenIp(_) => ErrorCode::ForbiddenIp,
+            ErrorKind::CodeBlocked(_) => ErrorCode::CodeBlocked,
+            ErrorKind::SecretFrozen(_) => ErrorCode::SecretFrozen,
+            ErrorKind::MissingAccessToken(_) => ErrorCode::MissingAccessToken,
+            ErrorKind::MissingAppId(_) => ErrorCode::MissingAppId,
+            ErrorKind::MissingSecret(_) => ErrorCode::MissingSecret,
+            ErrorKind::MissingCode(_) => ErrorCode::MissingCode,
+            ErrorKind::RequiredPostMethod(_) => ErrorCode::RequiredPostMethod,
+            ErrorKind::DailyRequestLimitExceeded(_) => ErrorCode::DailyRequestLimitExceeded,
+            ErrorKind::RateLimitExceeded(_) => ErrorCode::RateLimitExceeded,
+            ErrorKind::ForbiddenToken(_) => ErrorCode::ForbiddenToken,
+            ErrorKind::AccountFrozen(_) => ErrorCode::AccountFrozen,
+            ErrorKind::ThirdPartyToken(_) => ErrorCode::ThirdPartyToken,
+            ErrorKind::SessionKeyNotExistedOrExpired(_) => ErrorCode::SessionKeyNotExistedOrExpired,
+            ErrorKind::InvalidSignatureMethod(_) => ErrorCode::InvalidSignatureMethod,
+            ErrorKind::InvalidSignature(_) => ErrorCode::InvalidSignature,
+            ErrorKind::ConfirmRequired(_) => ErrorCode::ConfirmRequired,
+            ErrorKind::RequestDeniedOneDay(_) => ErrorCode::RequestDeniedOneDay,
+            ErrorKind::RequestDeniedOneHour(_) => ErrorCode::RequestDeniedOneHour,
+            ErrorKind::CategoryNotAllowed(_) => ErrorCode::NotHavePermission,
+            ErrorKind::Wechat { code, .. } => return Some(*code),
+            _ => return None,
+        };
+        Some(code as i32)
+    }
+
+    /// 微信返回的错误信息，本地产生的错误返回 `None`
+    pub fn message(&self) -> Option<&str> {
+        match &self.kind {
+            ErrorKind::System(message)
+            | ErrorKind::InvalidCredential(message)
+            | ErrorKind::InvalidGrantType(message)
+            | ErrorKind::InvalidAppId(message)
+            | ErrorKind::InvalidCode(message)
+            | ErrorKind::CodeUsed(message)
+            | ErrorKind::InvalidSecret(message)
+            | ErrorKind::ForbiddenIp(message)
+            | ErrorKind::CodeBlocked(message)
+            | ErrorKind::SecretFrozen(message)
+            | ErrorKind::MissingAccessToken(message)
+            | ErrorKind::MissingAppId(message)
+            | ErrorKind::MissingSecret(message)
+            | ErrorKind::MissingCode(message)
+            | ErrorKind::RequiredPostMethod(message)
+            | ErrorKind::DailyRequestLimitExceeded(message)
+            | ErrorKind::RateLimitExceeded(message)
+            | ErrorKind::ForbiddenToken(message)
+            | ErrorKind::AccountFrozen(message)
+            | ErrorKind::ThirdPartyToken(message)
+            | ErrorKind::SessionKeyNotExistedOrExpired(message)
+            | ErrorKind::InvalidSignatureMethod(message)
+            | ErrorKind::InvalidSignature(message)
+            | ErrorKind::ConfirmRequired(message)
+            | ErrorKind::RequestDeniedOneDay(message)
+            | ErrorKind::RequestDeniedOneHour(message)
+            | ErrorKind::CategoryNotAllowed(message)
+            | ErrorKind::Wechat { message, .. } => Some(message),
+            _ => None,
+        }
+    }
+
+    /// 微信请求标识，向微信反馈问题（如内容安全误判）时需要提供
+    ///
+    /// 取自响应体中的 `trace_id`，或 errmsg 末尾的 `rid: xxx`，
+    /// [`message`](Self::message) 保持微信返回的原样。
+    pub fn trace_id(&self) -> Option<&str> {
+        self.trace_id.as_deref()
+    }
+
+    /// 是否需要用户重新登录（重新调用 `wx.login` 获取 code）
+    ///
+    /// 登录 code 无效、已被使用，session_key 或自有登录态已过期时返回 `true`，
+    /// 业务侧可以据此提示“请重新登录”，而不是返回服务器错误。
+    pub fn requires_relogin(&self) -> bool {
+        matches!(
+            self.kind,
+            ErrorKind::InvalidCode(_)
+                | ErrorKind::CodeUsed(_)
+                | ErrorKind::SessionKeyNotExistedOrExpired(_)
+                | ErrorKind::TokenExpired(_)
+        )
+    }
+
+    /// 出错的接口地址（如果已知）
+    pub fn endpoint(&self) -> Option<&str> {
+        self.endpoint.as_deref()
+    }
+
+    /// 建议的重试等待时间，只对频率限制、系统繁忙等可以稍后重试的错误返回
+    ///
+    /// 微信不会在响应中给出具体时间，按错误码的限制周期估算：
+    /// - `-1` 系统繁忙：1 秒
+    /// - `45011` 频率限制：60 秒
+    /// - `45009` 天级别频率限制：到北京时间次日零点
+    /// - `89507` / `89506` IP 被拒绝：1 小时 / 24 小时
+    pub fn retry_after(&self) -> Option<Duration> {
+        match &self.kind {
+            ErrorKind::System(_) => Some(Duration::from_secs(1)),
+            ErrorKind::RateLimitExceeded(_) => Some(Duration::from_secs(60)),
+            ErrorKind::DailyRequestLimitExceeded(_) => Some(until_next_day_in_beijing(Utc::now())),
+            ErrorKind::RequestDeniedOneHour(_) => Some(Duration::from_secs(60 * 60)),
+            ErrorKind::RequestDeniedOneDay(_) => Some(Duration::from_secs(24 * 60 * 60)),
+            _ => None,
+        }
+    }
+
+    /// 出错时的 HTTP 状态码（如果已知）
+    pub fn status(&self) -> Option<u16> {
+        match &self.kind {
+            ErrorKind::HttpStatus { status, .. } => Some(*status),
+            #[cfg(feature = "reqwest")]
+            ErrorKind::Reqwest(e) => e.status().map(|status| status.as_u16()),
+            _ => None,
+        }
+    }
+
+    /// 出错时响应的 Content-Type（如果已知）
+    pub fn content_type(&self) -> Option<&str> {
+        match &self.kind {
+            ErrorKind::HttpStatus { content_type, .. } => content_type.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// 出错时脱敏、截断后的原始响应体（如果已知）
+    pub fn body(&self) -> Option<&str> {
+        match &self.kind {
+            ErrorKind::HttpStatus { body, .. } | ErrorKind::Decode { body, .. } => Some(body),
+            _ => None,
+        }
+    }
+
+    /// 作为 HTTP 接口的错误返回给前端时建议的状态码
+    ///
+    /// - `400`：参数校验失败、解密失败等请求数据问题
+    /// - `401`：需要重新登录（见 [`requires_relogin`](Self::requires_relogin)）或签名无效
+    /// - `403`：登录被微信风控拦截
+    /// - `429`：触发微信频率限制
+    /// - `502` / `503` / `504`：微信接口异常、系统繁忙、调用超时
+    /// - `500`：其余错误，通常是 AppSecret、IP 白名单等服务端配置问题
+    pub fn http_status(&self) -> u16 {
+        if self.requires_relogin() {
+            return 401;
+        }
+
+        match &self.kind {
+            ErrorKind::InvalidParameter(_)
+            | ErrorKind::MissingCode(_)
+            | ErrorKind::WatermarkMismatch { .. }
+            | ErrorKind::UrlParse(_) => 400,
+            #[cfg(feature = "crypto")]
+            ErrorKind::Unpad(_) | ErrorKind::AesInvalidLength(_) | ErrorKind::Base64Decode(_) => {
+                400
+            }
+            ErrorKind::InvalidSignature(_) | ErrorKind::InvalidToken(_) => 401,
+            ErrorKind::CodeBlocked(_) => 403,
+            ErrorKind::RateLimitExceeded(_) | ErrorKind::DailyRequestLimitExceeded(_) => 429,
+            ErrorKind::System(_) => 503,
+            ErrorKind::Timeout(_) => 504,
+            ErrorKind::HttpStatus { .. } | ErrorKind::Decode { .. } | ErrorKind::Wechat { .. } => {
+                502
+            }
+            #[cfg(feature = "reqwest")]
+            ErrorKind::Reqwest(_) => 502,
+            _ => 500,
+        }
+    }
+
+    /// 常见 errcode 的释义与排查建议，未收录的错误码和本地产生的错误返回 `None`
+    ///
+    /// 按 [`i18n::language`](crate::i18n::language) 返回中文或英文，适合写入日志或运维告警。
+    ///
+    /// ```
+    /// use wechat_core::Error;
+    ///
+    /// let error = Error::from_code(41030, "invalid page rid: 6710c4ab".to_string());
+    /// assert!(error.hint().unwrap().contains("check_path"));
+    /// ```
+    pub fn hint(&self) -> Option<&'static str> {
+        let code = self.code()?;
+        ERRCODE_HINTS
+            .iter()
+            .find(|(hint_code, _, _)| *hint_code == code)
+            .map(|(_, zh, en)| localize(zh, en))
+    }
+
+    /// 是否为微信服务端错误：HTTP 5xx 或系统繁忙（errcode -1），与参数错误不同，可以稍后重试
+    pub fn is_server_error(&self) -> bool {
+        matches!(self.kind, ErrorKind::System(_))
+            || self.status().is_some_and(|status| status >= 500)
+    }
+}
+
+/// 常见 errcode 的释义与排查建议：(errcode, 中文, English)
+const ERRCODE_HINTS: &[(i32, &str, &str)] = &[
+    (
+        -1,
+        "微信系统繁忙，稍候重试即可；持续出现时检查微信公众平台的接口状态公告",
+        "WeChat is busy, retry later; if it persists, check the platform status announcements",
+    ),
+    (
+        40001,
+        "access_token 无效：检查 AppSecret 是否正确；普通版 access_token 被其他服务刷新后旧值立即失效，\
+         建议改用稳定版 access_token 或由中控服务统一获取",
+        "access_token is invalid: check the AppSecret; a regular access_token becomes invalid once \
+         another service refreshes it, use the stable access_token or a central token service",
+    ),
+    (
+        40003,
+        "openid 不合法：确认 openid 属于当前小程序，不要混用公众号或其他小程序的 openid",
+        "invalid openid: make sure it belongs to this mini program, not an official account or \
+         another app",
+    ),
+    (
+        40013,
+        "AppID 不合法：检查是否有多余空格、大小写是否正确，注意不要使用公众号的 AppID",
+        "invalid AppID: check for stray spaces and letter case, not an official account AppID",
+    ),
+    (
+        40029,
+        "code 无效：code 只能使用一次，5 分钟内有效，并且必须由当前 AppID 的小程序调用 wx.login 获取",
+        "invalid code: a code can be used once within 5 minutes and must come from wx.login of \
+         this AppID",
+    ),
+    (
+        40037,
+        "template_id 不正确：检查模板是否已添加到当前小程序的「我的模板」",
+        "invalid template_id: make sure the template is added to this mini program",
+    ),
+    (
+        40125,
+        "AppSecret 无效：在管理后台重置 AppSecret 后需要同步更新服务端配置",
+        "invalid AppSecret: update the server configuration after resetting the AppSecret",
+    ),
+    (
+        40159,
+        "path 不合法：检查路径长度不超过 1024 字节，不要以 / 开头，页面需要已发布",
+        "invalid path: keep it within 1024 bytes, without a leading /, and on a released page",
+    ),
+    (
+        40163,
+        "code 已被使用：每次登录都重新调用 wx.login，避免前端重复提交或服务端重试同一个 code",
+        "code already used: call wx.login for every login, do not resubmit or retry the same code",
+    ),
+    (
+        40164,
+        "IP 不在白名单：在管理后台「开发管理 - 开发设置」中添加服务器的出口 IP",
+        "IP not in whitelist: add the server egress IP in the development settings",
+    ),
+    (
+        40226,
+        "高风险用户，登录被微信拦截：提示用户稍后再试，不要反复重试",
+        "high-risk user, login blocked by WeChat: ask the user to try later, do not retry",
+    ),
+    (
+        40243,
+        "AppSecret 已被冻结：登录小程序管理后台解冻后再调用",
+        "AppSecret frozen: unfreeze it in the mini program admin console",
+    ),
+    (
+        41001,
+        "缺少 access_token：检查 TokenStorage 是否返回了空字符串",
+        "missing access_token: check whether the TokenStorage returned an empty string",
+    ),
+    (
+        41030,
+        "page 不存在：确认页面已在线上版本发布，路径不要以 / 开头；\
+         开发调试时设置 check_path 为 false 并指定 env_version",
+        "page not found: make sure the page is released and has no leading /; \
+         while developing, set check_path to false and specify env_version",
+    ),
+    (
+        43101,
+        "用户拒绝接受消息：用户未订阅该模板或订阅次数已用完，需要前端重新调用 wx.requestSubscribeMessage",
+        "user refused the message: the subscription is missing or used up, \
+         call wx.requestSubscribeMessage again",
+    ),
+    (
+        43104,
+        "没有调用权限：该接口只对部分类目开放，检查小程序的服务类目",
+        "no permission: this API is only open to certain categories, check the service categories",
+    ),
+    (
+        45009,
+        "超过天级别调用额度：北京时间次日零点恢复，紧急时可以调用 clear_quota 接口重置",
+        "daily quota exceeded: it resets at midnight Beijing time, or call clear_quota",
+    ),
+    (
+        45011,
+        "调用太频繁：降低并发或加入退避重试，批量任务建议限制并发数",
+        "too many requests: reduce concurrency or back off, limit concurrency for batch jobs",
+    ),
+    (
+        47003,
+        "模板参数不准确：检查字段名与模板一致，取值符合各类型的长度和格式限制",
+        "invalid template data: check field names and the length and format limits of each type",
+    ),
+    (
+        48001,
+        "接口未授权：确认小程序已开通该接口，第三方平台需要确认权限集已授权",
+        "API unauthorized: enable the API, or authorize it for the third-party platform",
+    ),
+    (
+        61024,
+        "第三方平台接口需要使用第三方平台的 authorizer_access_token",
+        "third-party platform APIs require the authorizer_access_token",
+    ),
+    (
+        85096,
+        "scancode_time 为系统保留参数，不能出现在小程序码的页面参数中",
+        "scancode_time is reserved and must not appear in QR code page parameters",
+    ),
+    (
+        87007,
+        "session_key 不存在或已过期：让用户重新调用 wx.login 登录",
+        "session_key missing or expired: ask the user to log in with wx.login again",
+    ),
+    (
+        87009,
+        "签名无效：签名需要使用最新的 session_key 计算，前端再次调用 wx.login 会刷新 session_key",
+        "invalid signature: sign with the latest session_key, wx.login refreshes it",
+    ),
+    (
+        89503,
+        "调用需要管理员确认：在微信中确认 IP 调用请求后重试",
+        "administrator confirmation required: approve the IP request in WeChat, then retry",
+    ),
+    (
+        89506,
+        "IP 调用请求已被管理员拒绝，24 小时后再试，调用前先与管理员确认",
+        "IP request rejected by the administrator, retry after 24 hours",
+    ),
+    (
+        89507,
+        "IP 调用请求已被管理员拒绝，1 小时后再试，调用前先与管理员确认",
+        "IP request rejected by the administrator, retry after 1 hour",
+    ),
+];
+
+/// 距离北京时间（UTC+8）次日零点的时长，微信的天级别额度按北京时间重置
+fn until_next_day_in_beijing(now: DateTime<Utc>) -> Duration {
+    let beijing = FixedOffset::east_opt(8 * 60 * 60).expect("valid offset");
+    let now = now.with_timezone(&beijing);
+    let tomorrow = now
+        .date_naive()
+        .succ_opt()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .and_then(|midnight| midnight.and_local_timezone(beijing).single());
+
+    tomorrow
+        .and_then(|tomorrow| (tomorrow - now).to_std().ok())
+        .unwrap_or(Duration::from_secs(24 * 60 * 60))
 }
 
 // impl From<UnpadError> for Error {
 //     fn from(error: UnpadError) -> Self {
-//         Error::Unpad(error)
+//         ErrorKind::Unpad(error)
 //     }
 // }
 
@@ -178,6 +718,8 @@ pub enum ErrorCode {
     InvalidCode = 40029,
     #[strum(serialize = "参数错误")]
     InvalidParameter = 40097,
+    #[strum(serialize = "code 已被使用")]
+    CodeUsed = 40163,
     #[strum(serialize = "无效的appsecret，请检查appsecret的正确性")]
     InvalidSecret = 40125,
     #[strum(serialize = "将ip添加到ip白名单列表即可")]
@@ -233,38 +775,201 @@ pub enum ErrorCode {
     NotHavePermission = 43104,
 }
 
-impl From<(ErrorCode, String)> for Error {
+impl TryFrom<i32> for ErrorCode {
+    type Error = i32;
+
+    fn try_from(code: i32) -> Result<Self, Self::Error> {
+        use ErrorCode::*;
+
+        let code = match code {
+            -1 => System,
+            40001 => InvalidCredential,
+            40002 => InvalidGrantType,
+            40013 => InvalidAppId,
+            40029 => InvalidCode,
+            40097 => InvalidParameter,
+            40163 => CodeUsed,
+            40125 => InvalidSecret,
+            40164 => ForbiddenIp,
+            40226 => CodeBlocked,
+            40243 => SecretFrozen,
+            41001 => MissingAccessToken,
+            41002 => MissingAppId,
+            41004 => MissingSecret,
+            41008 => MissingCode,
+            43002 => RequiredPostMethod,
+            45009 => DailyRequestLimitExceeded,
+            45011 => RateLimitExceeded,
+            50004 => ForbiddenToken,
+            50007 => AccountFrozen,
+            61024 => ThirdPartyToken,
+            87007 => SessionKeyNotExistedOrExpired,
+            87008 => InvalidSignatureMethod,
+            87009 => InvalidSignature,
+            89503 => ConfirmRequired,
+            89506 => RequestDeniedOneDay,
+            89507 => RequestDeniedOneHour,
+            40066 => InvalidUrl,
+            40225 => InvalidPageTitle,
+            85400 => ReachMaxLongTimeQuotaLimit,
+            43104 => NotHavePermission,
+            other => return Err(other),
+        };
+
+        Ok(code)
+    }
+}
+
+impl From<(ErrorCode, String)> for ErrorKind {
     fn from((code, message): (ErrorCode, String)) -> Self {
         use ErrorCode::*;
 
         match code {
-            System => Error::System(message),
-            InvalidCredential => Error::InvalidCredential(message),
-            InvalidGrantType => Error::InvalidGrantType(message),
-            InvalidAppId => Error::InvalidAppId(message),
-            InvalidCode => Error::InvalidCode(message),
-            InvalidParameter => Error::InvalidParameter(message),
-            InvalidSecret => Error::InvalidSecret(message),
-            ForbiddenIp => Error::ForbiddenIp(message),
-            CodeBlocked => Error::CodeBlocked(message),
-            SecretFrozen => Error::SecretFrozen(message),
-            MissingAccessToken => Error::MissingAccessToken(message),
-            MissingAppId => Error::MissingAppId(message),
-            MissingSecret => Error::MissingSecret(message),
-            MissingCode => Error::MissingCode(message),
-            RequiredPostMethod => Error::RequiredPostMethod(message),
-            DailyRequestLimitExceeded => Error::DailyRequestLimitExceeded(message),
-            RateLimitExceeded => Error::RateLimitExceeded(message),
-            ForbiddenToken => Error::ForbiddenToken(message),
-            AccountFrozen => Error::AccountFrozen(message),
-            ThirdPartyToken => Error::ThirdPartyToken(message),
-            SessionKeyNotExistedOrExpired => Error::SessionKeyNotExistedOrExpired(message),
-            InvalidSignatureMethod => Error::InvalidSignatureMethod(message),
-            InvalidSignature => Error::InvalidSignature(message),
-            ConfirmRequired => Error::ConfirmRequired(message),
-            RequestDeniedOneDay => Error::RequestDeniedOneDay(message),
-            RequestDeniedOneHour => Error::RequestDeniedOneHour(message),
-            _ => Error::InvalidParameter(message),
+            System => ErrorKind::System(message),
+            InvalidCredential => ErrorKind::InvalidCredential(message),
+            InvalidGrantType => ErrorKind::InvalidGrantType(message),
+            InvalidAppId => ErrorKind::InvalidAppId(message),
+            InvalidCode => ErrorKind::InvalidCode(message),
+            CodeUsed => ErrorKind::CodeUsed(message),
+            InvalidSecret => ErrorKind::InvalidSecret(message),
+            ForbiddenIp => ErrorKind::ForbiddenIp(message),
+            CodeBlocked => ErrorKind::CodeBlocked(message),
+            SecretFrozen => ErrorKind::SecretFrozen(message),
+            MissingAccessToken => ErrorKind::MissingAccessToken(message),
+            MissingAppId => ErrorKind::MissingAppId(message),
+            MissingSecret => ErrorKind::MissingSecret(message),
+            MissingCode => ErrorKind::MissingCode(message),
+            RequiredPostMethod => ErrorKind::RequiredPostMethod(message),
+            DailyRequestLimitExceeded => ErrorKind::DailyRequestLimitExceeded(message),
+            RateLimitExceeded => ErrorKind::RateLimitExceeded(message),
+            ForbiddenToken => ErrorKind::ForbiddenToken(message),
+            AccountFrozen => ErrorKind::AccountFrozen(message),
+            ThirdPartyToken => ErrorKind::ThirdPartyToken(message),
+            SessionKeyNotExistedOrExpired => ErrorKind::SessionKeyNotExistedOrExpired(message),
+            InvalidSignatureMethod => ErrorKind::InvalidSignatureMethod(message),
+            InvalidSignature => ErrorKind::InvalidSignature(message),
+            ConfirmRequired => ErrorKind::ConfirmRequired(message),
+            RequestDeniedOneDay => ErrorKind::RequestDeniedOneDay(message),
+            RequestDeniedOneHour => ErrorKind::RequestDeniedOneHour(message),
+            NotHavePermission => ErrorKind::CategoryNotAllowed(message),
+            // 没有单独变体的错误码保留原始 errcode，`InvalidParameter` 只用于本地参数校验
+            InvalidParameter | InvalidUrl | InvalidPageTitle | ReachMaxLongTimeQuotaLimit => {
+                ErrorKind::Wechat {
+                    code: code as i32,
+                    message,
+                }
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::error::Error as _;
+
+    #[test]
+    fn test_retry_after() {
+        let error = Error::from_code(45011, "api freq out of limit".to_string());
+        assert_eq!(error.retry_after(), Some(Duration::from_secs(60)));
+        assert_eq!(Error::from(ErrorKind::InvalidCode(String::new())).retry_after(), None);
+    }
+
+    #[test]
+    fn test_login_errors() {
+        let error = Error::from_code(40163, "code been used".to_string());
+        assert!(matches!(error.kind(), ErrorKind::CodeUsed(_)));
+        assert_eq!(error.code(), Some(40163));
+        assert!(error.requires_relogin());
+
+        let error = Error::from_code(40029, "invalid code".to_string());
+        assert_eq!(error.code(), Some(40029));
+        assert!(error.requires_relogin());
+
+        let error = Error::from_code(45011, "api freq out of limit".to_string());
+        assert_eq!(error.code(), Some(45011));
+        assert!(!error.requires_relogin());
+
+        assert_eq!(Error::from_code(61450, "system error".to_string()).code(), Some(61450));
+
+        // 已收录但没有单独变体的错误码
+        for code in [40097, 40066, 40225, 85400] {
+            let error = Error::from_code(code, "error".to_string());
+            assert!(matches!(error.kind(), ErrorKind::Wechat { .. }));
+            assert_eq!(error.code(), Some(code));
+            assert_eq!(error.http_status(), 502);
+        }
+
+        let error = Error::from_code(43104, "this appid does not have permission".to_string());
+        assert!(matches!(error.kind(), ErrorKind::CategoryNotAllowed(_)));
+        assert_eq!(error.code(), Some(43104));
+        assert_eq!(Error::from(ErrorKind::InvalidParameter("path".to_string())).code(), None);
+    }
+
+    #[test]
+    fn test_http_status() {
+        assert_eq!(Error::from(ErrorKind::InvalidParameter("path".to_string())).http_status(), 400);
+        assert_eq!(Error::from_code(40029, "invalid code".to_string()).http_status(), 401);
+        assert_eq!(Error::from_code(87009, "invalid signature".to_string()).http_status(), 401);
+        assert_eq!(Error::from_code(45011, "api freq out of limit".to_string()).http_status(), 429);
+        assert_eq!(Error::from_code(-1, "system error".to_string()).http_status(), 503);
+        assert_eq!(Error::from_code(40125, "invalid appsecret".to_string()).http_status(), 500);
+        assert_eq!(Error::from_code(61450, "system error".to_string()).http_status(), 502);
+        let error = Error::from(ErrorKind::InvalidToken("bad signature".to_string()));
+        assert_eq!(error.http_status(), 401);
+        assert!(Error::from(ErrorKind::TokenExpired("expired".to_string())).requires_relogin());
+    }
+
+    #[test]
+    fn test_with_endpoint() {
+        let endpoint = "https://api.weixin.qq.com/wxa/getwxacode";
+        let error = Error::from_code(45011, "api freq out of limit".to_string())
+            .with_endpoint(endpoint);
+        assert_eq!(error.endpoint(), Some(endpoint));
+        assert!(matches!(error.kind(), ErrorKind::RateLimitExceeded(_)));
+        assert_eq!(error.code(), Some(45011));
+        assert_eq!(error.http_status(), 429);
+        assert_eq!(
+            error.to_string(),
+            format!("{}: rate limit exceeded: api freq out of limit", endpoint)
+        );
+        // 接口地址不是独立的错误层级，错误链中不会重复出现同一条信息
+        assert!(error.source().is_none());
+
+        // 已有接口地址的错误保持不变
+        let error = error.with_endpoint("https://api.weixin.qq.com/other");
+        assert_eq!(error.endpoint(), Some(endpoint));
+        assert!(matches!(error.into_kind(), ErrorKind::RateLimitExceeded(_)));
+
+        let error = Error::from(ErrorKind::HttpStatus {
+            status: 502,
+            content_type: None,
+            body: String::new(),
+        });
+        assert_eq!(error.endpoint(), None);
+        let error = error.with_endpoint(endpoint);
+        assert!(matches!(error.kind(), ErrorKind::HttpStatus { .. }));
+        assert_eq!(error.endpoint(), Some(endpoint));
+    }
+
+    #[test]
+    fn test_hint() {
+        let error = Error::from_code(41030, "invalid page".to_string());
+        assert!(error.hint().unwrap().contains("check_path"));
+        assert!(Error::from_code(40029, "invalid code".to_string()).hint().is_some());
+        assert_eq!(Error::from_code(61450, "system error".to_string()).hint(), None);
+        assert_eq!(Error::from(ErrorKind::InvalidParameter("path".to_string())).hint(), None);
+
+        let mut codes: Vec<_> = ERRCODE_HINTS.iter().map(|(code, _, _)| *code).collect();
+        codes.dedup();
+        assert_eq!(codes.len(), ERRCODE_HINTS.len());
+    }
+
+    #[test]
+    fn test_until_next_day_in_beijing() {
+        // 北京时间 2024-01-01 23:30
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 15, 30, 0).unwrap();
+        assert_eq!(until_next_day_in_beijing(now), Duration::from_secs(30 * 60));
+    }
+}
@@ -0,0 +1,84 @@
+//! 错误与校验信息的语言
+//!
+//! 参数校验失败（[`ErrorKind::InvalidParameter`](crate::ErrorKind::InvalidParameter)）、页面路径错误等
+//! 由 SDK 生成的信息默认使用中文，启用 `en` feature 后默认使用英文，也可以在运行时通过
+//! [`set_language`] 切换。微信接口返回的 errmsg 保持原样。
+//!
+//! ```
+//! use wechat_core::i18n::{self, Language};
+//!
+//! i18n::set_language(Language::En);
+//! let message = wechat_core::localized!("{} 不能为空", "{} is required", "path");
+//! assert_eq!(message, "path is required");
+//! ```
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// 信息语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    /// 中文
+    Zh,
+    /// 英文
+    En,
+}
+
+impl Default for Language {
+    /// 启用 `en` feature 时为英文，否则为中文
+    fn default() -> Self {
+        if cfg!(feature = "en") {
+            Language::En
+        } else {
+            Language::Zh
+        }
+    }
+}
+
+const ZH: u8 = 0;
+const EN: u8 = 1;
+
+static LANGUAGE: AtomicU8 = AtomicU8::new(if cfg!(feature = "en") { EN } else { ZH });
+
+/// 当前使用的语言
+pub fn language() -> Language {
+    match LANGUAGE.load(Ordering::Relaxed) {
+        EN => Language::En,
+        _ => Language::Zh,
+    }
+}
+
+/// 设置全局语言，对之后生成的信息生效
+pub fn set_language(language: Language) {
+    let value = match language {
+        Language::Zh => ZH,
+        Language::En => EN,
+    };
+    LANGUAGE.store(value, Ordering::Relaxed);
+}
+
+/// 按当前语言选择文本
+pub fn localize<'a>(zh: &'a str, en: &'a str) -> &'a str {
+    match language() {
+        Language::Zh => zh,
+        Language::En => en,
+    }
+}
+
+/// 按当前语言格式化信息，中英文各提供一个格式字符串，参数相同
+///
+/// ```
+/// use wechat_core::i18n::{self, Language};
+///
+/// i18n::set_language(Language::Zh);
+/// let message = wechat_core::localized!("宽度不能超过 {}", "width must not exceed {}", 1280);
+/// assert_eq!(message, "宽度不能超过 1280");
+/// ```
+#[macro_export]
+macro_rules! localized {
+    ($zh:literal, $en:literal $(, $arg:expr)* $(,)?) => {
+        match $crate::i18n::language() {
+            $crate::i18n::Language::Zh => format!($zh $(, $arg)*),
+            $crate::i18n::Language::En => format!($en $(, $arg)*),
+        }
+    };
+}
@@ -5,12 +5,43 @@
 //! - HTTP 客户端抽象
 //! - Token 存储 trait
 //! - 共用的错误类型
+//! - 统一的响应包络
 //! - 共用的常量
+//! - 字段命名约定 [`naming`]
+//! - 声明式接口定义宏 [`endpoint!`]
+//!
+//! # Feature
+//!
+//! - `reqwest`（默认开启）：基于 reqwest 的默认 HTTP 客户端 [`client::ReqwestHttpClient`]
+//! - `crypto`（默认开启）：AES-128-CBC 解密 [`utils::aes_decrypt`]
+//! - `tokio`（默认开启）：依赖 tokio 运行时的后台任务 [`utils::BackgroundTask`]、
+//!   超时与取消 [`utils::CallExt`]、请求重试 [`client::RetryHttpClient`]
+//! - `en`：SDK 生成的错误与校验信息默认使用英文，见 [`i18n`]
+//! - `axum` / `actix`：[`Error`] 实现 axum 的 `IntoResponse` / actix-web 的 `ResponseError`，
+//!   状态码见 [`Error::http_status`]
+//! - `log`：没有设置 tracing subscriber 时，内部日志通过 `log` crate 输出，
+//!   target 与 tracing 相同，均为模块路径（如 `wechat_core::client`）
+//! - `simd-json`：使用 simd-json 解析响应体，见 [`response::Response::from_slice`]
+
+mod macros;
 
 pub mod client;
 pub mod constants;
 pub mod error;
+pub mod i18n;
+pub mod naming;
+pub mod response;
 pub mod utils;
+#[cfg(any(feature = "axum", feature = "actix"))]
+mod web;
 
-pub use error::{Error, ErrorCode};
+pub use error::{Error, ErrorCode, ErrorKind};
 pub type Result<T> = std::result::Result<T, Error>;
+
+/// 供宏展开使用，不属于公开 API
+#[doc(hidden)]
+pub mod __private {
+    pub use http;
+    pub use serde_json;
+    pub use tracing;
+}
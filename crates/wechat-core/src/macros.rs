@@ -0,0 +1,286 @@
+//! 声明式接口定义宏
+//!
+//! 绝大多数微信接口的调用流程都是一样的：带上 access_token、序列化参数、发送请求、
+//! 按 errcode 解析响应。[`endpoint!`](crate::endpoint) 根据声明生成对应的调用方法，
+//! 新增接口时只需要声明方法名、HTTP 方法、参数、返回类型和接口地址。
+
+/// 在 `impl` 块中声明接口调用方法
+///
+/// 要求 `self.client.core` 为 [`WechatCore`](crate::client::WechatCore)
+/// （`WechatMinapp`、`WechatMp` 均满足）。
+///
+/// - `GET name(a: A, b: B) -> T = URL;`：参数作为 query 参数，视为幂等接口
+/// - `POST name(args: A) -> T = URL;`：参数序列化为 JSON 请求体，视为非幂等接口
+/// - `POST name() -> T = URL;`：空请求体
+/// - `POST idempotent name(args: A) -> T = URL;`：查询类的 POST 接口，重试时视为幂等，
+///   见 [`Idempotency`](crate::utils::Idempotency)
+///
+/// # 示例
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use wechat_core::client::WechatCore;
+///
+/// pub struct Client {
+///     pub core: WechatCore,
+/// }
+///
+/// pub struct Template {
+///     pub client: Client,
+/// }
+///
+/// #[derive(Debug, Serialize, Deserialize)]
+/// pub struct DelTemplateArgs {
+///     pub template_id: String,
+/// }
+///
+/// #[derive(Debug, Deserialize)]
+/// pub struct Keywords {
+///     pub count: i32,
+/// }
+///
+/// impl Template {
+///     wechat_core::endpoint! {
+///         /// 获取模板关键词
+///         pub GET get_keywords(tid: &str) -> Keywords =
+///             "https://api.weixin.qq.com/wxaapi/newtmpl/getpubtemplatekeywords";
+///
+///         /// 删除模板
+///         pub POST del_template(args: DelTemplateArgs) -> () =
+///             "https://api.weixin.qq.com/wxaapi/newtmpl/deltemplate";
+///
+///         /// 获取模板列表
+///         pub POST idempotent get_templates() -> Keywords =
+///             "https://api.weixin.qq.com/wxaapi/newtmpl/gettemplate";
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! endpoint {
+    () => {};
+
+    (
+        $(#[$meta:meta])*
+        $vis:vis GET $name:ident($($param:ident : $param_ty:ty),* $(,)?) -> $ret:ty = $url:expr;
+        $($rest:tt)*
+    ) => {
+        $(#[$meta])*
+        $vis async fn $name(&self $(, $param: $param_ty)*) -> $crate::Result<$ret> {
+            $crate::__private::tracing::debug!(
+                concat!(stringify!($name) $(, " ", stringify!($param), ": {:?}")*)
+                $(, &$param)*
+            );
+
+            #[allow(unused_mut)]
+            let mut query = $crate::__private::serde_json::Map::new();
+            query.insert(
+                "access_token".to_string(),
+                self.client.core.token().await?.into(),
+            );
+            $(
+                query.insert(
+                    stringify!($param).to_string(),
+                    $crate::__private::serde_json::to_value(&$param)?,
+                );
+            )*
+
+            let request = $crate::utils::RequestBuilder::new($url)
+                .method($crate::__private::http::Method::GET)
+                .query($crate::__private::serde_json::Value::Object(query))
+                .idempotency($crate::utils::Idempotency::Idempotent)
+                .build()?;
+
+            let response = self.client.core.client.execute(request).await?;
+
+            $crate::utils::ResponseExt::to_json::<$ret>(response)
+        }
+
+        $crate::endpoint! { $($rest)* }
+    };
+
+    (
+        $(#[$meta:meta])*
+        $vis:vis POST idempotent $name:ident($($arg:ident : $arg_ty:ty)?) -> $ret:ty = $url:expr;
+        $($rest:tt)*
+    ) => {
+        $crate::endpoint! {
+            @post Idempotent
+            $(#[$meta])*
+            $vis $name($($arg: $arg_ty)?) -> $ret = $url;
+        }
+
+        $crate::endpoint! { $($rest)* }
+    };
+
+    (
+        $(#[$meta:meta])*
+        $vis:vis POST $name:ident($($arg:ident : $arg_ty:ty)?) -> $ret:ty = $url:expr;
+        $($rest:tt)*
+    ) => {
+        $crate::endpoint! {
+            @post NonIdempotent
+            $(#[$meta])*
+            $vis $name($($arg: $arg_ty)?) -> $ret = $url;
+        }
+
+        $crate::endpoint! { $($rest)* }
+    };
+
+    (
+        @post $idempotency:ident
+        $(#[$meta:meta])*
+        $vis:vis $name:ident($($arg:ident : $arg_ty:ty)?) -> $ret:ty = $url:expr;
+    ) => {
+        $(#[$meta])*
+        $vis async fn $name(&self $(, $arg: $arg_ty)?) -> $crate::Result<$ret> {
+            $crate::__private::tracing::debug!(
+                concat!(stringify!($name) $(, " ", stringify!($arg), " {:?}")?)
+                $(, &$arg)?
+            );
+
+            let query = $crate::__private::serde_json::json!({
+                "access_token": self.client.core.token().await?
+            });
+
+            #[allow(unused_mut)]
+            let mut body = $crate::__private::serde_json::json!({});
+            $(
+                body = $crate::__private::serde_json::to_value(&$arg)?;
+            )?
+
+            let request = $crate::utils::RequestBuilder::new($url)
+                .query(query)
+                .body(body)
+                .idempotency($crate::utils::Idempotency::$idempotency)
+                .build()?;
+
+            let response = self.client.core.client.execute(request).await?;
+
+            $crate::utils::ResponseExt::to_json::<$ret>(response)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::{HttpClient, TokenStorage, TokenType, WechatCore};
+    use crate::utils::Idempotency;
+    use crate::Result;
+    use async_trait::async_trait;
+    use http::{Method, Request, Response};
+    use serde::{Deserialize, Serialize};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct RecordClient {
+        requests: Mutex<Vec<Request<Vec<u8>>>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for RecordClient {
+        async fn execute(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+            self.requests.lock().unwrap().push(request);
+            Ok(Response::new(br#"{"errcode":0,"errmsg":"ok","count":1}"#.to_vec()))
+        }
+    }
+
+    struct StaticToken;
+
+    #[async_trait]
+    impl TokenStorage for StaticToken {
+        async fn token(&self) -> Result<String> {
+            Ok("token".to_string())
+        }
+
+        async fn refresh_access_token(&self) -> Result<String> {
+            self.token().await
+        }
+
+        fn token_type(&self) -> Arc<dyn TokenType> {
+            unimplemented!()
+        }
+    }
+
+    struct Client {
+        core: WechatCore,
+    }
+
+    struct Api {
+        client: Client,
+    }
+
+    #[derive(Debug, Serialize)]
+    struct Args {
+        id: i32,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Count {
+        count: i32,
+    }
+
+    impl Api {
+        crate::endpoint! {
+            GET get_count(tid: &str, limit: i32) -> Count = "https://api.weixin.qq.com/get";
+            POST post_args(args: Args) -> Count = "https://api.weixin.qq.com/post";
+            POST post_empty() -> Count = "https://api.weixin.qq.com/empty";
+            POST idempotent post_query(args: Args) -> Count = "https://api.weixin.qq.com/query";
+        }
+    }
+
+    fn api() -> (Api, Arc<RecordClient>) {
+        let http_client = Arc::new(RecordClient::default());
+        let core = WechatCore::custom(http_client.clone(), Arc::new(StaticToken));
+        (
+            Api {
+                client: Client { core },
+            },
+            http_client,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_get_endpoint() {
+        let (api, http_client) = api();
+        assert_eq!(api.get_count("t1", 10).await.unwrap(), Count { count: 1 });
+
+        let requests = http_client.requests.lock().unwrap();
+        assert_eq!(requests[0].method(), Method::GET);
+        let query = requests[0].uri().query().unwrap();
+        assert!(query.contains("access_token=token"));
+        assert!(query.contains("tid=t1"));
+        assert!(query.contains("limit=10"));
+    }
+
+    #[tokio::test]
+    async fn test_post_endpoint() {
+        let (api, http_client) = api();
+        api.post_args(Args { id: 7 }).await.unwrap();
+        api.post_empty().await.unwrap();
+
+        let requests = http_client.requests.lock().unwrap();
+        assert_eq!(requests[0].method(), Method::POST);
+        assert_eq!(requests[0].body(), br#"{"id":7}"#);
+        assert_eq!(requests[1].body(), b"{}");
+    }
+
+    #[tokio::test]
+    async fn test_idempotency() {
+        let (api, http_client) = api();
+        api.get_count("t1", 10).await.unwrap();
+        api.post_args(Args { id: 7 }).await.unwrap();
+        api.post_query(Args { id: 7 }).await.unwrap();
+
+        let requests = http_client.requests.lock().unwrap();
+        let idempotency: Vec<_> = requests.iter().map(Idempotency::of).collect();
+        assert_eq!(
+            idempotency,
+            [
+                Idempotency::Idempotent,
+                Idempotency::NonIdempotent,
+                Idempotency::Idempotent
+            ]
+        );
+        assert_eq!(requests[2].body(), br#"{"id":7}"#);
+    }
+}
@@ -0,0 +1,256 @@
+//! 微信接口的字段命名约定
+//!
+//! 微信不同场景的 JSON 字段命名并不统一：
+//!
+//! | 场景 | 约定 | 示例 |
+//! | --- | --- | --- |
+//! | 服务端接口的请求参数与响应 | [`Naming::SnakeCase`] | `session_key`、`page_path` |
+//! | 前端加密数据解密后的明文（手机号、用户信息、微信运动） | [`Naming::CamelCase`] | `phoneNumber`、`nickName` |
+//! | 消息推送（JSON 格式） | [`Naming::PascalCase`] | `ToUserName`、`MsgType` |
+//!
+//! 自定义类型按上表选择 `#[serde(rename_all = "...")]`，取值为 [`Naming::serde_name`]。
+//! 测试中可以用 [`Naming::check`] 校验请求参数序列化后的字段名，及早发现漏写的 rename；
+//! 响应类型要用真实的响应解析，确认字段没有因为名字不一致落入 flatten 的 `extra`；
+//! [`Naming::convert_keys`] 可以把一种约定的 JSON 整体转换为另一种约定。
+//!
+//! 已知的例外：解密数据中的 `watermark.appid` 全部小写，同时满足 snake_case 和 camelCase。
+
+use crate::{localized, ErrorKind, Result};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fmt;
+
+/// 字段命名约定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Naming {
+    /// `page_path`
+    SnakeCase,
+    /// `phoneNumber`
+    CamelCase,
+    /// `ToUserName`
+    PascalCase,
+}
+
+impl Naming {
+    /// 对应的 `#[serde(rename_all = "...")]` 取值
+    pub const fn serde_name(self) -> &'static str {
+        match self {
+            Naming::SnakeCase => "snake_case",
+            Naming::CamelCase => "camelCase",
+            Naming::PascalCase => "PascalCase",
+        }
+    }
+
+    /// 按约定转换字段名，输入可以是任意一种约定
+    ///
+    /// ```
+    /// use wechat_core::naming::Naming;
+    ///
+    /// assert_eq!(Naming::CamelCase.rename("pure_phone_number"), "purePhoneNumber");
+    /// assert_eq!(Naming::SnakeCase.rename("ToUserName"), "to_user_name");
+    /// assert_eq!(Naming::PascalCase.rename("msg_id"), "MsgId");
+    /// ```
+    pub fn rename(self, key: &str) -> String {
+        let words = split_words(key);
+        match self {
+            Naming::SnakeCase => words.join("_"),
+            Naming::CamelCase => words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| if i == 0 { word.clone() } else { capitalize(word) })
+                .collect(),
+            Naming::PascalCase => words.iter().map(|word| capitalize(word)).collect(),
+        }
+    }
+
+    /// 字段名是否符合约定
+    pub fn matches(self, key: &str) -> bool {
+        let Some(first) = key.chars().next() else {
+            return false;
+        };
+        let is_snake = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_';
+        let alphanumeric = key.chars().all(|c| c.is_ascii_alphanumeric());
+        match self {
+            Naming::SnakeCase => first.is_ascii_lowercase() && key.chars().all(is_snake),
+            Naming::CamelCase => first.is_ascii_lowercase() && alphanumeric,
+            Naming::PascalCase => first.is_ascii_uppercase() && alphanumeric,
+        }
+    }
+
+    /// 校验序列化后的全部字段名（包括嵌套对象）是否符合约定
+    ///
+    /// 不符合时返回 [`ErrorKind::InvalidParameter`]，列出全部不符合的字段路径。
+    ///
+    /// ```
+    /// use serde::Serialize;
+    /// use wechat_core::naming::Naming;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Args {
+    ///     page_path: String,
+    ///     #[serde(rename = "envVersion")]
+    ///     env_version: String,
+    /// }
+    ///
+    /// let args = Args { page_path: "pages/index".into(), env_version: "release".into() };
+    /// let error = Naming::SnakeCase.check(&args).unwrap_err();
+    /// assert!(error.to_string().contains("envVersion"));
+    /// ```
+    pub fn check<T: Serialize + ?Sized>(self, value: &T) -> Result<()> {
+        let value = serde_json::to_value(value)?;
+        let mut mismatched = Vec::new();
+        self.collect_mismatched(&value, "", &mut mismatched);
+
+        if mismatched.is_empty() {
+            Ok(())
+        } else {
+            Err(ErrorKind::InvalidParameter(localized!(
+                "字段命名不符合 {} 约定: {}",
+                "fields do not follow {}: {}",
+                self,
+                mismatched.join(", ")
+            ))
+            .into())
+        }
+    }
+
+    fn collect_mismatched(self, value: &Value, path: &str, mismatched: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    let path = if path.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", path, key)
+                    };
+                    if !self.matches(key) {
+                        mismatched.push(path.clone());
+                    }
+                    self.collect_mismatched(value, &path, mismatched);
+                }
+            }
+            Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    self.collect_mismatched(item, &format!("{}[{}]", path, i), mismatched);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 把 JSON 中全部对象（包括嵌套对象）的字段名转换为该约定
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// use wechat_core::naming::Naming;
+    ///
+    /// let value = json!({"phoneNumber": "138", "watermark": {"appid": "wx"}});
+    /// assert_eq!(
+    ///     Naming::SnakeCase.convert_keys(value),
+    ///     json!({"phone_number": "138", "watermark": {"appid": "wx"}})
+    /// );
+    /// ```
+    pub fn convert_keys(self, value: Value) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(key, value)| (self.rename(&key), self.convert_keys(value)))
+                    .collect::<Map<_, _>>(),
+            ),
+            Value::Array(items) => {
+                Value::Array(items.into_iter().map(|item| self.convert_keys(item)).collect())
+            }
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for Naming {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.serde_name())
+    }
+}
+
+/// 按下划线和大小写边界拆分为小写单词
+fn split_words(key: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut prev_lower = false;
+
+    for c in key.chars() {
+        if c == '_' || c == '-' {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_ascii_uppercase() && prev_lower && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        current.push(c.to_ascii_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_rename() {
+        for (snake, camel, pascal) in [
+            ("phone_number", "phoneNumber", "PhoneNumber"),
+            ("to_user_name", "toUserName", "ToUserName"),
+            ("appid", "appid", "Appid"),
+            ("thing1", "thing1", "Thing1"),
+        ] {
+            for key in [snake, camel, pascal] {
+                assert_eq!(Naming::SnakeCase.rename(key), snake);
+                assert_eq!(Naming::CamelCase.rename(key), camel);
+                assert_eq!(Naming::PascalCase.rename(key), pascal);
+            }
+            assert!(Naming::SnakeCase.matches(snake));
+            assert!(Naming::CamelCase.matches(camel));
+            assert!(Naming::PascalCase.matches(pascal));
+        }
+
+        assert!(!Naming::SnakeCase.matches("phoneNumber"));
+        assert!(!Naming::CamelCase.matches("phone_number"));
+        assert!(!Naming::PascalCase.matches("msgType"));
+        assert!(!Naming::SnakeCase.matches(""));
+    }
+
+    #[test]
+    fn test_check() {
+        let value = json!({"page": "a", "line_color": {"r": 0}, "list": [{"badKey": 1}]});
+        let error = Naming::SnakeCase.check(&value).unwrap_err();
+        assert!(error.to_string().contains("list[0].badKey"));
+        assert!(!error.to_string().contains("line_color"));
+
+        assert!(Naming::SnakeCase.check(&json!({"line_color": {"r": 0}})).is_ok());
+        assert!(Naming::PascalCase.check(&json!({"ToUserName": "gh"})).is_ok());
+    }
+
+    #[test]
+    fn test_convert_keys() {
+        let value = json!({"ToUserName": "gh", "List": [{"MsgId": 1}]});
+        assert_eq!(
+            Naming::SnakeCase.convert_keys(value),
+            json!({"to_user_name": "gh", "list": [{"msg_id": 1}]})
+        );
+    }
+}
@@ -0,0 +1,372 @@
+//! 微信接口统一响应包络
+//!
+//! 微信接口出错时返回 `{"errcode": 40001, "errmsg": "..."}`，成功时部分接口也会带上
+//! `{"errcode": 0, "errmsg": "ok"}`。[`Response`] 统一根据 `errcode` 判断成功与否，
+//! 所有模块都通过它解析响应，避免 HTTP 200 + 非 0 errcode 被当成成功。
+
+use crate::utils::body_snippet;
+use crate::{Error, ErrorKind, Result};
+use serde::de::{DeserializeOwned, Deserializer, Error as DeError};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::ops::Deref;
+use tracing::error;
+
+/// 微信接口返回的数据结构
+#[derive(Debug, Clone, PartialEq)]
+pub enum Response<T> {
+    /// errcode 不存在或为 0
+    Success { data: T },
+    /// errcode 非 0
+    Error {
+        code: i32,
+        message: String,
+        /// 微信请求标识，见 [`Error::trace_id`]
+        trace_id: Option<String>,
+    },
+}
+
+impl<T: DeserializeOwned> Response<T> {
+    /// 从响应体字节解析
+    ///
+    /// 成功响应直接从字节反序列化为 `T`，不经过中间的 [`Value`]，数据分析、日志查询等
+    /// 大响应可以开启 `simd-json` feature 进一步降低解析耗时。
+    pub fn from_slice(body: &[u8]) -> Result<Self> {
+        Self::parse_slice(body).map_err(|source| {
+            ErrorKind::Decode {
+                source,
+                body: body_snippet(body),
+            }
+            .into()
+        })
+    }
+
+    fn parse_slice(body: &[u8]) -> serde_json::Result<Self> {
+        // 先只读取 errcode，成功时把响应体直接解析为 T
+        if from_bytes::<Status>(body).is_ok_and(|status| status.errcode == 0)
+            && let Ok(data) = from_bytes::<T>(body)
+        {
+            return Ok(Response::Success { data });
+        }
+
+        // 错误响应、只返回 errcode/errmsg 等情况按 JSON 值处理，这类响应体都很小
+        Self::parse(&from_bytes(body)?)
+    }
+
+    /// 从 JSON 值解析
+    pub fn from_value(value: Value) -> Result<Self> {
+        Self::parse(&value).map_err(|source| {
+            ErrorKind::Decode {
+                source,
+                body: body_snippet(value.to_string().as_bytes()),
+            }
+            .into()
+        })
+    }
+
+    fn parse(value: &Value) -> serde_json::Result<Self> {
+        let code = value.get("errcode").and_then(Value::as_i64).unwrap_or(0);
+
+        if code != 0 {
+            let message = value
+                .get("errmsg")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            // 内容安全等接口在响应体中单独返回 trace_id，其余接口附在 errmsg 末尾
+            let trace_id = value
+                .get("trace_id")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .or_else(|| rid(&message));
+            return Ok(Response::Error {
+                code: code as i32,
+                message,
+                trace_id,
+            });
+        }
+
+        // 只返回 errcode/errmsg 的接口，允许解析为 `()` 等空结果
+        let status_only = value
+            .as_object()
+            .is_some_and(|map| map.keys().all(|key| key == "errcode" || key == "errmsg"));
+
+        match T::deserialize(value) {
+            Ok(data) => Ok(Response::Success { data }),
+            Err(e) if status_only => T::deserialize(Value::Null)
+                .map(|data| Response::Success { data })
+                .map_err(|_| e),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<T> Response<T> {
+    /// 是否成功
+    pub fn is_success(&self) -> bool {
+        matches!(self, Response::Success { .. })
+    }
+
+    /// 获取微信返回的数据，errcode 非 0 时转换为对应的 [`Error`]
+    pub fn extract(self) -> Result<T> {
+        match self {
+            Self::Success { data } => Ok(data),
+            Self::Error {
+                code,
+                message,
+                trace_id,
+            } => {
+                error!("微信返回错误: code={}, message={}", code, message);
+                let error = Error::from_code(code, message);
+                Err(match trace_id {
+                    Some(trace_id) => error.with_trace_id(trace_id),
+                    None => error,
+                })
+            }
+        }
+    }
+}
+
+/// errmsg 末尾的请求标识，如 `invalid credential rid: 6710c4ab-1c3b62d0`
+fn rid(message: &str) -> Option<String> {
+    let (_, rid) = message.rsplit_once("rid: ")?;
+    rid.split_whitespace().next().map(str::to_string)
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for Response<T> {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        Self::parse(&value).map_err(D::Error::custom)
+    }
+}
+
+/// 响应的状态字段，其余字段直接跳过
+#[derive(Deserialize)]
+struct Status {
+    #[serde(default)]
+    errcode: i64,
+}
+
+/// 从字节反序列化
+#[cfg(not(feature = "simd-json"))]
+fn from_bytes<T: DeserializeOwned>(body: &[u8]) -> serde_json::Result<T> {
+    serde_json::from_slice(body)
+}
+
+/// 使用 simd-json 从字节反序列化
+///
+/// simd-json 会原地修改缓冲区，需要先复制一份；解析失败时改用 serde_json，
+/// 兼容两者的细微差异并得到统一的错误类型。
+#[cfg(feature = "simd-json")]
+fn from_bytes<T: DeserializeOwned>(body: &[u8]) -> serde_json::Result<T> {
+    let mut buffer = body.to_vec();
+    simd_json::serde::from_slice(&mut buffer).or_else(|_| serde_json::from_slice(body))
+}
+
+/// 响应中没有在结构体里声明的字段
+///
+/// 微信接口经常新增返回字段，结果类型通过 `#[serde(flatten)]` 把它们收集到这里，
+/// 升级 SDK 之前也能读取新字段。`errcode`、`errmsg` 由 [`Response`] 统一处理，不会被收集。
+///
+/// ```
+/// use serde::Deserialize;
+/// use wechat_core::response::ExtraFields;
+///
+/// #[derive(Deserialize)]
+/// struct Link {
+///     link: String,
+///     #[serde(flatten)]
+///     extra: ExtraFields,
+/// }
+///
+/// let link: Link =
+///     serde_json::from_str(r#"{"errcode":0,"errmsg":"ok","link":"a","expire_time":1}"#)?;
+/// assert_eq!(link.extra.get("expire_time"), Some(&serde_json::json!(1)));
+/// assert_eq!(link.extra.get_as::<u64>("expire_time"), Some(1));
+/// assert!(link.extra.get("errcode").is_none());
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct ExtraFields(Map<String, Value>);
+
+impl ExtraFields {
+    /// 读取字段并反序列化为指定类型，字段不存在或类型不匹配时返回 `None`
+    pub fn get_as<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.0
+            .get(key)
+            .and_then(|value| T::deserialize(value).ok())
+    }
+
+    /// 转换为 JSON 对象
+    pub fn into_inner(self) -> Map<String, Value> {
+        self.0
+    }
+}
+
+impl Deref for ExtraFields {
+    type Target = Map<String, Value>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'de> Deserialize<'de> for ExtraFields {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut fields = Map::deserialize(deserializer)?;
+        fields.remove("errcode");
+        fields.remove("errmsg");
+        Ok(ExtraFields(fields))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Link {
+        link: String,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Status {
+        errcode: i32,
+        errmsg: String,
+    }
+
+    #[test]
+    fn test_success() {
+        let response = Response::<Link>::from_slice(br#"{"link":"a"}"#).unwrap();
+        assert_eq!(response.extract().unwrap().link, "a");
+
+        let response =
+            Response::<Link>::from_slice(br#"{"errcode":0,"errmsg":"ok","link":"b"}"#).unwrap();
+        assert_eq!(response.extract().unwrap().link, "b");
+    }
+
+    #[test]
+    fn test_status_only() {
+        let body = br#"{"errcode":0,"errmsg":"ok"}"#;
+        assert!(Response::<()>::from_slice(body).unwrap().extract().is_ok());
+
+        let status = Response::<Status>::from_slice(body)
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert_eq!(status.errcode, 0);
+    }
+
+    #[test]
+    fn test_errcode_is_error() {
+        // 即使数据结构本身可以匹配，非 0 errcode 也视为失败
+        let body = br#"{"errcode":40001,"errmsg":"invalid credential"}"#;
+        let response = Response::<Status>::from_slice(body).unwrap();
+        assert!(!response.is_success());
+        assert!(matches!(
+            response.extract().unwrap_err().kind(),
+            ErrorKind::InvalidCredential(_)
+        ));
+    }
+
+    #[test]
+    fn test_error_with_trace_id() {
+        let body = br#"{"errcode":-1,"errmsg":"system error","trace_id":"60ae120f-371d5872"}"#;
+        let error = Response::<Link>::from_slice(body).unwrap().extract().unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::System(_)));
+        assert_eq!(error.trace_id(), Some("60ae120f-371d5872"));
+        // 错误信息保持微信返回的原样
+        assert_eq!(error.message(), Some("system error"));
+
+        let body = br#"{"errcode":40001,"errmsg":"invalid credential rid: 64b7a1c2-5e0f6b3d"}"#;
+        let error = Response::<Link>::from_slice(body).unwrap().extract().unwrap_err();
+        assert_eq!(error.trace_id(), Some("64b7a1c2-5e0f6b3d"));
+        assert_eq!(error.message(), Some("invalid credential rid: 64b7a1c2-5e0f6b3d"));
+
+        // 响应体中的 trace_id 优先于 errmsg 中的 rid
+        let body = br#"{"errcode":-1,"errmsg":"busy rid: 64b7a1c2","trace_id":"60ae120f"}"#;
+        let error = Response::<Link>::from_slice(body).unwrap().extract().unwrap_err();
+        assert_eq!(error.trace_id(), Some("60ae120f"));
+        assert_eq!(error.message(), Some("busy rid: 64b7a1c2"));
+
+        let body = br#"{"errcode":-1,"errmsg":"system error"}"#;
+        let error = Response::<Link>::from_slice(body).unwrap().extract().unwrap_err();
+        assert_eq!(error.trace_id(), None);
+    }
+
+    #[test]
+    fn test_extra_fields() {
+        #[derive(Debug, Deserialize, Serialize)]
+        struct LinkWithExtra {
+            link: String,
+            #[serde(flatten)]
+            extra: ExtraFields,
+        }
+
+        let body = br#"{"errcode":0,"errmsg":"ok","link":"a","expire_time":1,"tag":"x"}"#;
+        let link = Response::<LinkWithExtra>::from_slice(body)
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert_eq!(link.extra.len(), 2);
+        assert_eq!(link.extra.get_as::<String>("tag").as_deref(), Some("x"));
+        assert_eq!(link.extra.get_as::<String>("expire_time"), None);
+
+        // 序列化时新字段原样输出
+        let json = serde_json::to_value(&link).unwrap();
+        assert_eq!(json, serde_json::json!({"link":"a","expire_time":1,"tag":"x"}));
+
+        let link: LinkWithExtra = serde_json::from_str(r#"{"link":"a"}"#).unwrap();
+        assert!(link.extra.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_errcode() {
+        let body = br#"{"errcode":99999,"errmsg":"unknown"}"#;
+        let response = Response::<Link>::from_slice(body).unwrap();
+        assert_eq!(
+            response,
+            Response::Error {
+                code: 99999,
+                message: "unknown".to_string(),
+                trace_id: None,
+            }
+        );
+        assert!(matches!(
+            response.extract().unwrap_err().kind(),
+            ErrorKind::Wechat { code: 99999, .. }
+        ));
+    }
+
+    #[test]
+    fn test_from_slice_matches_from_value() {
+        let bodies: [&[u8]; 5] = [
+            br#"{"link":"a"}"#,
+            br#"{"errcode":0,"errmsg":"ok","link":"b"}"#,
+            br#"{"errcode":"0","link":"c"}"#,
+            br#"{"errcode":40001,"errmsg":"invalid credential","link":"d"}"#,
+            br#"{"errcode":0,"errmsg":"ok"}"#,
+        ];
+        for body in bodies {
+            let value: Value = serde_json::from_slice(body).unwrap();
+            assert_eq!(
+                Response::<Value>::from_slice(body).unwrap(),
+                Response::<Value>::from_value(value.clone()).unwrap()
+            );
+            assert_eq!(
+                Response::<Option<Link>>::from_slice(body).ok(),
+                Response::<Option<Link>>::from_value(value).ok()
+            );
+        }
+
+        let error = Response::<Link>::from_slice(br#"{"link":1}"#).unwrap_err();
+        assert_eq!(error.body(), Some(r#"{"link":1}"#));
+    }
+}
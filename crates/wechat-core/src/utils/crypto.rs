@@ -1,19 +1,35 @@
 //! 微信相关的加密解密工具
 //!
-//! 提供 AES-128-CBC 解密、HMAC-SHA256 签名等功能
+//! 提供 AES-128-CBC 解密、HMAC-SHA256 签名、消息推送安全模式解密等功能
 
-use crate::{Error, Result};
+#[cfg(feature = "crypto")]
+use crate::localized;
+use crate::{ErrorKind, Result};
+#[cfg(feature = "crypto")]
 use aes::{
-    Aes128,
-    cipher::{Array, BlockModeDecrypt, KeyIvInit, block_padding::Pkcs7},
+    Aes128, Aes256,
+    cipher::{
+        Array, BlockModeDecrypt, KeyIvInit,
+        block_padding::{NoPadding, Pkcs7},
+    },
 };
+#[cfg(feature = "crypto")]
 use base64::{Engine, engine::general_purpose::STANDARD};
+#[cfg(feature = "crypto")]
 use cbc::Decryptor;
 use hex::encode;
 use hmac::{Hmac, KeyInit, Mac};
+use sha1::{Digest, Sha1};
 use sha2::Sha256;
 
+#[cfg(feature = "crypto")]
 type Aes128CbcDec = Decryptor<Aes128>;
+#[cfg(feature = "crypto")]
+type Aes256CbcDec = Decryptor<Aes256>;
+
+/// 消息推送安全模式的填充块大小，与微信的实现一致
+#[cfg(feature = "crypto")]
+const CALLBACK_BLOCK_SIZE: usize = 32;
 
 /// 使用 AES-128-CBC 算法解密数据，数据采用 PKCS#7 填充
 ///
@@ -26,17 +42,91 @@ type Aes128CbcDec = Decryptor<Aes128>;
 /// # 返回
 ///
 /// 解密后的字节数据
+#[cfg(feature = "crypto")]
 pub fn aes_decrypt(encrypted_data: &str, session_key: &str, iv: &str) -> Result<Vec<u8>> {
     let key = STANDARD.decode(session_key)?;
     let iv = STANDARD.decode(iv)?;
-    let key =
-        Array::slice_as_array(&key).ok_or(Error::InvalidParameter("invalid key".to_string()))?;
-    let iv = Array::slice_as_array(&iv).ok_or(Error::InvalidParameter("invalid iv".to_string()))?;
     let encrypted_data = STANDARD.decode(encrypted_data)?;
 
-    let decryptor = Aes128CbcDec::new(&key, &iv);
+    aes_decrypt_raw(&encrypted_data, &key, &iv)
+}
+
+/// 使用 AES-128-CBC 算法解密已经解码的数据，数据采用 PKCS#7 填充
+///
+/// `key` 与 `iv` 都必须是 16 字节。
+#[cfg(feature = "crypto")]
+pub fn aes_decrypt_raw(encrypted_data: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>> {
+    let key =
+        Array::slice_as_array(key).ok_or(ErrorKind::InvalidParameter("invalid key".to_string()))?;
+    let iv =
+        Array::slice_as_array(iv).ok_or(ErrorKind::InvalidParameter("invalid iv".to_string()))?;
+
+    let decryptor = Aes128CbcDec::new(key, iv);
+
+    Ok(decryptor.decrypt_padded_vec::<Pkcs7>(encrypted_data)?)
+}
+
+/// 解密消息推送安全模式下的 `Encrypt` 字段
+///
+/// `encoding_aes_key` 为后台配置的 43 位 EncodingAESKey。解密后的明文为
+/// 16 字节随机串 + 4 字节网络字节序的消息长度 + 消息 + AppID，按 32 字节 PKCS#7 填充。
+///
+/// 返回消息与 AppID，调用方需要确认 AppID 与自己的一致。
+#[cfg(feature = "crypto")]
+pub fn decrypt_callback(encrypt: &str, encoding_aes_key: &str) -> Result<(String, String)> {
+    let invalid = || {
+        ErrorKind::InvalidParameter(localized!(
+            "消息推送密文格式不正确",
+            "malformed encrypted callback message"
+        ))
+    };
+
+    let key = STANDARD.decode(format!("{}=", encoding_aes_key))?;
+    let key: &[u8; 32] = key.as_slice().try_into().map_err(|_| {
+        ErrorKind::InvalidParameter(localized!(
+            "EncodingAESKey 应为 43 位 base64 字符",
+            "EncodingAESKey must be 43 base64 characters"
+        ))
+    })?;
+    let iv = Array::slice_as_array(&key[..16]).ok_or_else(invalid)?;
+    let encrypted = STANDARD.decode(encrypt)?;
+
+    let decryptor = Aes256CbcDec::new(&(*key).into(), iv);
+    let mut plain = decryptor.decrypt_padded_vec::<NoPadding>(&encrypted)?;
+
+    let pad = plain.last().copied().ok_or_else(invalid)? as usize;
+    if pad == 0 || pad > CALLBACK_BLOCK_SIZE || pad > plain.len() {
+        return Err(invalid().into());
+    }
+    plain.truncate(plain.len() - pad);
+
+    if plain.len() < 20 {
+        return Err(invalid().into());
+    }
+    let length = u32::from_be_bytes([plain[16], plain[17], plain[18], plain[19]]) as usize;
+    let (message, app_id) = plain[20..].split_at_checked(length).ok_or_else(invalid)?;
 
-    Ok(decryptor.decrypt_padded_vec::<Pkcs7>(&encrypted_data)?)
+    let message = String::from_utf8(message.to_vec()).map_err(|_| invalid())?;
+    let app_id = String::from_utf8(app_id.to_vec()).map_err(|_| invalid())?;
+    Ok((message, app_id))
+}
+
+/// 消息推送的签名
+///
+/// 将 token、timestamp、nonce 与 `encrypt`（明文模式校验 URL 时不传）按字典序排序后拼接，
+/// 取 SHA1 摘要。安全模式下与 URL 上的 `msg_signature` 比较，明文模式下与 `signature` 比较。
+pub fn callback_signature(
+    token: &str,
+    timestamp: &str,
+    nonce: &str,
+    encrypt: Option<&str>,
+) -> String {
+    let mut parts: Vec<&str> = [Some(token), Some(timestamp), Some(nonce), encrypt]
+        .into_iter()
+        .flatten()
+        .collect();
+    parts.sort_unstable();
+    sha1_hex(parts.concat().as_bytes())
 }
 
 /// 使用 HMAC-SHA256 算法签名数据
@@ -50,8 +140,51 @@ pub fn aes_decrypt(encrypted_data: &str, session_key: &str, iv: &str) -> Result<
 ///
 /// hex 编码的签名
 pub fn hmac_sha256(data: &[u8], key: &str) -> Result<String> {
-    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes())?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes())
+        .map_err(|e| ErrorKind::InvalidParameter(e.to_string()))?;
     mac.update(data);
     let hasher = mac.finalize();
     Ok(encode(hasher.into_bytes()))
 }
+
+/// 计算数据的 SHA1 摘要
+///
+/// # 返回
+///
+/// hex 编码（小写）的摘要
+pub fn sha1_hex(data: &[u8]) -> String {
+    encode(Sha1::digest(data))
+}
+
+/// 常量时间比较两个字节串，避免签名校验泄漏时间信息
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_callback_signature() {
+        // 明文模式校验 URL 与安全模式校验消息使用同一算法
+        let signature = callback_signature("token", "1409659813", "1372623149", None);
+        assert_eq!(signature, sha1_hex(b"13726231491409659813token"));
+        let signature = callback_signature("token", "1409659813", "1372623149", Some("msg"));
+        assert_eq!(signature, sha1_hex(b"13726231491409659813msgtoken"));
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn test_decrypt_callback() {
+        let key = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8";
+        let encrypt = "4j/AuRx71kQlxVlzbpsMWCGLo9iz5SSBvbERLlgcRJC3VzGVl99RIwG6wrO8UYPQ75yW8Hc18vKUx6kPT8WqzA==";
+        let (message, app_id) = decrypt_callback(encrypt, key).unwrap();
+        assert_eq!(message, "hello");
+        assert_eq!(app_id, "wx1111111111111111");
+
+        assert!(decrypt_callback(encrypt, "short").is_err());
+        let other_key = "ZGVmZ2hpamtsbW5vcHFyc3R1dnd4eXp7fH1+f4CBgoM";
+        assert!(decrypt_callback(encrypt, other_key).is_err());
+    }
+}
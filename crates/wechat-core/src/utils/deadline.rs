@@ -0,0 +1,123 @@
+//! 单次调用的超时与取消
+//!
+//! 所有接口方法返回的都是 `Future<Output = Result<T>>`，[`CallExt`] 为它们提供
+//! `with_timeout`、`with_deadline`、`with_cancellation` 包装。超时或取消时内部的
+//! Future 会被丢弃，正在进行的网络请求随之中断。
+
+use crate::{ErrorKind, Result};
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+/// 接口调用的超时/取消扩展
+///
+/// # 示例
+///
+/// ```
+/// use std::time::Duration;
+/// use wechat_core::utils::{CallExt, CancellationToken};
+/// use wechat_core::{Error, ErrorKind};
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let slow = async {
+///     tokio::time::sleep(Duration::from_secs(10)).await;
+///     Ok::<_, Error>(())
+/// };
+/// let result = slow.with_timeout(Duration::from_millis(10)).await;
+/// assert!(matches!(result.unwrap_err().kind(), ErrorKind::Timeout(_)));
+///
+/// let token = CancellationToken::new();
+/// token.cancel();
+/// let result = async { Ok::<_, Error>(1) }.with_cancellation(token).await;
+/// assert!(matches!(result.unwrap_err().kind(), ErrorKind::Cancelled));
+/// # }
+/// ```
+pub trait CallExt<T>: Future<Output = Result<T>> + Send + Sized
+where
+    T: Send,
+{
+    /// 超过 `timeout` 未完成时返回 [`ErrorKind::Timeout`]
+    fn with_timeout(self, timeout: Duration) -> impl Future<Output = Result<T>> + Send {
+        async move {
+            tokio::time::timeout(timeout, self)
+                .await
+                .unwrap_or(Err(ErrorKind::Timeout(timeout).into()))
+        }
+    }
+
+    /// 到达 `deadline` 仍未完成时返回 [`ErrorKind::Timeout`]
+    fn with_deadline(self, deadline: Instant) -> impl Future<Output = Result<T>> + Send {
+        async move {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            tokio::time::timeout_at(deadline, self)
+                .await
+                .unwrap_or(Err(ErrorKind::Timeout(timeout).into()))
+        }
+    }
+
+    /// `token` 被取消时立即返回 [`ErrorKind::Cancelled`]
+    fn with_cancellation(
+        self,
+        token: CancellationToken,
+    ) -> impl Future<Output = Result<T>> + Send {
+        async move {
+            token
+                .run_until_cancelled(self)
+                .await
+                .unwrap_or(Err(ErrorKind::Cancelled.into()))
+        }
+    }
+}
+
+impl<T, F> CallExt<T> for F
+where
+    F: Future<Output = Result<T>> + Send,
+    T: Send,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[tokio::test]
+    async fn test_completes_in_time() {
+        let result = async { Ok::<_, Error>(1) }
+            .with_timeout(Duration::from_secs(1))
+            .await;
+        assert_eq!(result.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_deadline() {
+        let slow = async {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            Ok::<_, Error>(())
+        };
+        let result = tokio::spawn(slow.with_deadline(Instant::now() + Duration::from_millis(10)))
+            .await
+            .unwrap();
+        assert!(matches!(
+            result.unwrap_err().kind(),
+            ErrorKind::Timeout(timeout) if !timeout.is_zero()
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_while_pending() {
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            canceller.cancel();
+        });
+
+        let result = std::future::pending::<Result<()>>()
+            .with_cancellation(token)
+            .await;
+        assert!(matches!(result.unwrap_err().kind(), ErrorKind::Cancelled));
+    }
+}
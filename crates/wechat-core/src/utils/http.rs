@@ -1,8 +1,10 @@
-use crate::{Result, constants, error::{Error, ErrorCode}};
+use super::multipart::Multipart;
+use super::redact::{body_snippet, redact_url, redact_value};
+use crate::{Result, constants, error::{Error, ErrorKind}, response::Response as WechatResponse};
 use http::{HeaderValue, Method, Request, Response, header};
-use serde::{Deserialize, de::DeserializeOwned};
+use serde::de::DeserializeOwned;
 use serde_json::{Map, Value};
-use tracing::{debug, error};
+use tracing::debug;
 use url::Url;
 
 pub fn parse_url(url: impl Into<String>) -> Result<Url> {
@@ -32,22 +34,23 @@ pub fn build_request(
     if let Some(value) = query
         && value.is_object()
     {
-        debug!("req query value:{:?}", value);
+        debug!("req query value:{:?}", redact_value(&value));
 
         value
             .as_object()
             .unwrap_or(&default_map)
             .iter()
             .for_each(|item| {
-                debug!("req query item:{:?}", item);
                 let value_str = match item.1 {
                     Value::String(s) => s,
-                    _ => &value.to_string(),
+                    other => &other.to_string(),
                 };
                 req_url.query_pairs_mut().append_pair(item.0, value_str);
             });
     }
 
+    debug!("req url: {}", redact_url(&req_url));
+
     let req_builder = Request::builder()
         .uri(req_url.to_string())
         .header(header::USER_AGENT, constants::HTTP_CLIENT_USER_AGENT)
@@ -73,7 +76,7 @@ pub fn build_request(
     if let Some(value) = body
         && value.is_object()
     {
-        debug!("builder body {:?}", &value);
+        debug!("builder body {:?}", redact_value(&value));
         let body = serde_json::to_vec(&value)?;
         Ok(req_builder_with_headers.body(body)?)
     } else {
@@ -81,6 +84,53 @@ pub fn build_request(
     }
 }
 
+/// 请求的接口地址（不含 query 参数）
+///
+/// `HttpClient` 实现可将其写入响应的 extensions，响应解析失败或微信返回错误码时会附带到错误中。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint(pub String);
+
+impl Endpoint {
+    /// 从完整 URL 中提取接口地址，去掉 query 参数避免泄露 access_token
+    pub fn from_url(url: &Url) -> Self {
+        let mut url = url.clone();
+        url.set_query(None);
+        url.set_fragment(None);
+        Endpoint(url.to_string())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// 接口的幂等性
+///
+/// 由接口定义标注并写入请求的 extensions，[`RetryHttpClient`](crate::client::RetryHttpClient)
+/// 据此判断请求能否重试。没有标注的请求按 HTTP 方法推断：GET 视为幂等，其余视为非幂等。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Idempotency {
+    /// 查询类接口，重复请求没有副作用
+    Idempotent,
+    /// 写入类接口（发送消息、生成链接、使用一次性 code 等），重复请求可能产生副作用
+    NonIdempotent,
+}
+
+impl Idempotency {
+    /// 请求标注的幂等性，没有标注时按 HTTP 方法推断
+    pub fn of<T>(request: &Request<T>) -> Self {
+        match request.extensions().get::<Idempotency>() {
+            Some(idempotency) => *idempotency,
+            None if request.method() == Method::GET => Idempotency::Idempotent,
+            None => Idempotency::NonIdempotent,
+        }
+    }
+
+    pub fn is_idempotent(self) -> bool {
+        self == Idempotency::Idempotent
+    }
+}
+
 #[derive(Debug)]
 pub struct RequestBuilder {
     url: String,
@@ -88,6 +138,7 @@ pub struct RequestBuilder {
     headers: Option<Value>,
     query: Option<Value>,
     body: Option<Value>,
+    idempotency: Option<Idempotency>,
 }
 
 impl RequestBuilder {
@@ -98,6 +149,7 @@ impl RequestBuilder {
             headers: None,
             query: None,
             body: None,
+            idempotency: None,
         }
     }
 
@@ -121,8 +173,25 @@ impl RequestBuilder {
         self
     }
 
+    /// 标注接口的幂等性，见 [`Idempotency`]
+    pub fn idempotency(mut self, idempotency: Idempotency) -> Self {
+        self.idempotency = Some(idempotency);
+        self
+    }
+
     pub fn build(self) -> Result<Request<Vec<u8>>> {
-        build_request(&self.url, self.method, self.headers, self.query, self.body)
+        let mut request =
+            build_request(&self.url, self.method, self.headers, self.query, self.body)?;
+        if let Some(idempotency) = self.idempotency {
+            request.extensions_mut().insert(idempotency);
+        }
+        Ok(request)
+    }
+
+    /// 构建 multipart/form-data 请求，忽略已设置的 JSON body
+    pub fn build_multipart(self, form: Multipart) -> Result<Request<Multipart>> {
+        let request = RequestBuilder { body: None, ..self }.build()?;
+        Ok(request.map(|_| form))
     }
 }
 
@@ -139,55 +208,128 @@ impl ResponseExt for Response<Vec<u8>> {
     where
         T: DeserializeOwned + std::fmt::Debug,
     {
-        if self.status().is_success() {
-            let (_parts, body) = self.into_parts();
+        if !self.status().is_success() {
+            return Err(status_error(self));
+        }
 
-            let json = serde_json::from_slice::<MpResponse<T>>(&body.to_vec())?;
+        let (parts, body) = self.into_parts();
+        let endpoint = parts.extensions.get::<Endpoint>();
 
-            debug!("response result: {:#?}", json);
+        let json =
+            MpResponse::<T>::from_slice(&body).map_err(|error| with_endpoint(error, endpoint))?;
 
-            Ok(json.extract()?)
-        } else {
-            let (_parts, body) = self.into_parts();
-            let message = String::from_utf8_lossy(&body.to_vec()).to_string();
-            Err(Error::InternalServer(message))
-        }
+        debug!("response result: {:#?}", json);
+
+        json.extract().map_err(|error| with_endpoint(error, endpoint))
     }
+
     fn to_raw(self) -> Result<Vec<u8>> {
         if self.status().is_success() {
             Ok(self.into_body())
         } else {
-            let (_parts, body) = self.into_parts();
-            let message = String::from_utf8_lossy(&body.to_vec()).to_string();
-            Err(Error::InternalServer(message))
+            Err(status_error(self))
         }
     }
 }
 
-/// 微信小程序返回的数据结构
-#[derive(Debug, Deserialize)]
-#[serde(untagged)]
-pub enum MpResponse<T> {
-    Success {
-        #[serde(flatten)]
-        data: T,
-    },
-    Error {
-        #[serde(rename = "errcode")]
-        code: ErrorCode,
-        #[serde(rename = "errmsg")]
-        message: String,
-    },
+fn status_error(response: Response<Vec<u8>>) -> Error {
+    let (parts, body) = response.into_parts();
+
+    let error = ErrorKind::HttpStatus {
+        status: parts.status.as_u16(),
+        content_type: parts
+            .headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+        body: body_snippet(&body),
+    };
+    with_endpoint(error.into(), parts.extensions.get::<Endpoint>())
 }
 
-impl<T> MpResponse<T> {
-    pub(crate) fn extract(self) -> Result<T> {
-        match self {
-            Self::Success { data } => Ok(data),
-            Self::Error { code, message } => {
-                error!("微信返回错误: code={}, message={}", code, message);
-                Err((code, message).into())
-            }
-        }
+/// 附加响应中记录的接口地址，见 [`Error::with_endpoint`]
+fn with_endpoint(error: Error, endpoint: Option<&Endpoint>) -> Error {
+    match endpoint {
+        Some(endpoint) => error.with_endpoint(endpoint.as_str()),
+        None => error,
+    }
+}
+
+/// 下载类接口（小程序码、媒体文件等）出错时，微信以 JSON 返回错误信息
+///
+/// 非 2xx 或 JSON/文本响应返回 `true`，此时响应体不应直接当作文件内容写出，
+/// 需要先读取完整响应并经过 [`check_download`] 检查。
+pub fn should_check_download<T>(response: &Response<T>) -> bool {
+    !response.status().is_success()
+        || response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.contains("json") || value.starts_with("text/plain"))
+}
+
+/// 检查下载类接口的完整响应，HTTP 状态码错误或 errcode 非 0 时返回对应错误
+pub fn check_download(response: Response<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+    if !response.status().is_success() {
+        return Err(status_error(response));
+    }
+
+    if let Ok(json) = MpResponse::<Value>::from_slice(response.body())
+        && let Err(error) = json.extract()
+    {
+        return Err(with_endpoint(error, response.extensions().get::<Endpoint>()));
+    }
+
+    Ok(response)
+}
+
+/// 微信接口返回的数据结构，等同于 [`Response`](crate::response::Response)
+pub type MpResponse<T> = WechatResponse<T>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as StdError;
+
+    fn response(status: u16, body: &str) -> Response<Vec<u8>> {
+        let mut response = http::Response::builder()
+            .status(status)
+            .header(http::header::CONTENT_TYPE, "text/html")
+            .body(body.as_bytes().to_vec())
+            .unwrap();
+        let url =
+            Url::parse("https://api.weixin.qq.com/wxa/genwxashortlink?access_token=abc").unwrap();
+        response.extensions_mut().insert(Endpoint::from_url(&url));
+        response
+    }
+
+    #[test]
+    fn test_http_status_error() {
+        let error = response(502, "bad gateway").to_raw().unwrap_err();
+        assert_eq!(error.status(), Some(502));
+        assert_eq!(error.content_type(), Some("text/html"));
+        assert!(error.is_server_error());
+        assert_eq!(
+            error.endpoint(),
+            Some("https://api.weixin.qq.com/wxa/genwxashortlink")
+        );
+    }
+
+    #[test]
+    fn test_decode_error_keeps_source() {
+        let error = response(200, "not json")
+            .to_json::<serde_json::Value>()
+            .unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Decode { .. }));
+        assert!(error.endpoint().is_some());
+        assert!(error.source().is_some());
+        assert_eq!(error.body(), Some("not json"));
+
+        let error = response(200, r#"{"errcode":0,"session_key":"abc","openid":1"#)
+            .to_json::<serde_json::Value>()
+            .unwrap_err();
+        assert_eq!(error.body(), Some(r#"{"errcode":0,"session_key":"********","openid":1"#));
+        assert!(error.to_string().contains("session_key"));
+        assert!(!error.to_string().contains("abc"));
     }
 }
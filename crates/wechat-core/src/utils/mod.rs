@@ -1,5 +1,33 @@
-pub mod http;
 pub mod crypto;
+#[cfg(feature = "tokio")]
+pub mod deadline;
+pub mod http;
+pub mod multipart;
+pub mod paginate;
+pub mod redact;
+#[cfg(feature = "tokio")]
+pub mod task;
+pub mod time;
 
-pub use http::{RequestBuilder, ResponseExt, MpResponse, build_request, parse_query, parse_url};
-pub use crypto::{aes_decrypt, hmac_sha256};
+#[cfg(feature = "crypto")]
+pub use crypto::{aes_decrypt, aes_decrypt_raw, decrypt_callback};
+pub use crypto::{callback_signature, constant_time_eq, hmac_sha256, sha1_hex};
+#[cfg(feature = "tokio")]
+pub use deadline::CallExt;
+pub use futures_util::stream::Stream;
+pub use tokio::io::AsyncWrite;
+#[cfg(feature = "tokio")]
+pub use tokio_util::sync::CancellationToken;
+pub use http::{
+    Endpoint, Idempotency, MpResponse, RequestBuilder, ResponseExt, build_request, check_download,
+    parse_query, parse_url, should_check_download,
+};
+pub use multipart::{Multipart, Part};
+pub use paginate::Paginator;
+pub use redact::{
+    BODY_SNIPPET_LIMIT, REDACTED, Redacted, body_snippet, is_sensitive_key, redact_body,
+    redact_url, redact_value,
+};
+#[cfg(feature = "tokio")]
+pub use task::BackgroundTask;
+pub use time::UnixTimestamp;
@@ -0,0 +1,212 @@
+//! multipart/form-data 请求体
+//!
+//! 临时素材、图片 OCR、云存储等接口需要以 multipart/form-data 上传文件。
+//! [`Multipart`] 只负责组装表单并编码为字节，不依赖具体的 HTTP 客户端，
+//! 通过 [`HttpClient::execute_multipart`](crate::client::HttpClient::execute_multipart) 发送。
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 表单中的一个字段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part {
+    name: String,
+    file_name: Option<String>,
+    content_type: Option<String>,
+    data: Vec<u8>,
+}
+
+impl Part {
+    /// 文本字段
+    pub fn text(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Part {
+            name: name.into(),
+            file_name: None,
+            content_type: None,
+            data: value.into().into_bytes(),
+        }
+    }
+
+    /// 文件字段，默认 `Content-Type` 为 `application/octet-stream`
+    pub fn bytes(name: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        Part {
+            name: name.into(),
+            file_name: None,
+            content_type: Some("application/octet-stream".to_string()),
+            data: data.into(),
+        }
+    }
+
+    /// 设置文件名
+    pub fn file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = Some(file_name.into());
+        self
+    }
+
+    /// 设置 `Content-Type`，例如 `image/jpeg`
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// 文件名，没有设置时为 `None`
+    pub(crate) fn file(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// 是否为文本字段
+    pub(crate) fn is_text(&self) -> bool {
+        self.content_type.is_none()
+    }
+}
+
+/// multipart/form-data 表单
+///
+/// # 示例
+///
+/// ```
+/// use wechat_core::utils::{Multipart, Part};
+///
+/// let form = Multipart::new()
+///     .text("type", "image")
+///     .part(Part::bytes("media", vec![0xff, 0xd8]).file_name("a.jpg").content_type("image/jpeg"));
+///
+/// assert!(form.content_type().starts_with("multipart/form-data; boundary="));
+/// assert_eq!(form.parts().len(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Multipart {
+    boundary: String,
+    parts: Vec<Part>,
+}
+
+impl Default for Multipart {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Multipart {
+    pub fn new() -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos() as u64)
+            .unwrap_or_default();
+        let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+
+        Multipart {
+            boundary: format!("----WechatFormBoundary{:016x}{:08x}", nanos, count),
+            parts: Vec::new(),
+        }
+    }
+
+    /// 添加字段
+    pub fn part(mut self, part: Part) -> Self {
+        self.parts.push(part);
+        self
+    }
+
+    /// 添加文本字段
+    pub fn text(self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.part(Part::text(name, value))
+    }
+
+    /// 添加文件字段
+    pub fn file(
+        self,
+        name: impl Into<String>,
+        file_name: impl Into<String>,
+        data: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.part(Part::bytes(name, data).file_name(file_name))
+    }
+
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    pub fn parts(&self) -> &[Part] {
+        &self.parts
+    }
+
+    /// 请求头 `Content-Type` 的值
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    /// 编码为请求体
+    pub fn into_bytes(self) -> Vec<u8> {
+        let mut body = Vec::new();
+
+        for part in self.parts {
+            body.extend_from_slice(format!("--{}\r\n", self.boundary).as_bytes());
+
+            let mut disposition = format!(
+                "Content-Disposition: form-data; name=\"{}\"",
+                escape_quoted(&part.name)
+            );
+            if let Some(file_name) = &part.file_name {
+                disposition.push_str(&format!("; filename=\"{}\"", escape_quoted(file_name)));
+            }
+            body.extend_from_slice(disposition.as_bytes());
+            body.extend_from_slice(b"\r\n");
+
+            if let Some(content_type) = &part.content_type {
+                body.extend_from_slice(format!("Content-Type: {}\r\n", content_type).as_bytes());
+            }
+
+            body.extend_from_slice(b"\r\n");
+            body.extend_from_slice(&part.data);
+            body.extend_from_slice(b"\r\n");
+        }
+
+        body.extend_from_slice(format!("--{}--\r\n", self.boundary).as_bytes());
+        body
+    }
+}
+
+fn escape_quoted(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let form = Multipart::new().text("type", "image").part(
+            Part::bytes("media", b"abc".to_vec())
+                .file_name("a.jpg")
+                .content_type("image/jpeg"),
+        );
+        let boundary = form.boundary().to_string();
+        let body = String::from_utf8(form.into_bytes()).unwrap();
+
+        let expected = format!(
+            "--{b}\r\nContent-Disposition: form-data; name=\"type\"\r\n\r\nimage\r\n\
+             --{b}\r\nContent-Disposition: form-data; name=\"media\"; filename=\"a.jpg\"\r\n\
+             Content-Type: image/jpeg\r\n\r\nabc\r\n--{b}--\r\n",
+            b = boundary
+        );
+        assert_eq!(body, expected);
+    }
+
+    #[test]
+    fn test_unique_boundary() {
+        assert_ne!(Multipart::new().boundary(), Multipart::new().boundary());
+    }
+}
@@ -0,0 +1,170 @@
+//! 分页接口辅助工具
+//!
+//! 微信的列表接口大多使用 offset/limit（或 start/limit）分页，[`Paginator`] 负责维护偏移量，
+//! 逐页拉取数据，直到某一页返回的条数少于 `limit` 为止。
+
+use crate::Result;
+use futures_util::stream::{self, Stream};
+use std::collections::VecDeque;
+use std::future::Future;
+
+/// offset/limit 分页拉取器
+///
+/// `fetch` 接收 `(offset, limit)` 并返回该页数据。
+///
+/// # 示例
+///
+/// ```
+/// use wechat_core::utils::Paginator;
+///
+/// # async fn run() -> wechat_core::Result<()> {
+/// let source: Vec<u32> = (0..25).collect();
+///
+/// let all = Paginator::new(10, |offset, limit| {
+///     let page = source.iter().skip(offset).take(limit).copied().collect::<Vec<_>>();
+///     async move { Ok(page) }
+/// })
+/// .collect_all()
+/// .await?;
+///
+/// assert_eq!(all.len(), 25);
+/// # Ok(())
+/// # }
+/// ```
+pub struct Paginator<F> {
+    fetch: F,
+    offset: usize,
+    limit: usize,
+    finished: bool,
+}
+
+impl<F, Fut, T> Paginator<F>
+where
+    F: FnMut(usize, usize) -> Fut,
+    Fut: Future<Output = Result<Vec<T>>>,
+{
+    /// 创建分页拉取器，`limit` 为每页条数（最小为 1）
+    pub fn new(limit: usize, fetch: F) -> Self {
+        Paginator {
+            fetch,
+            offset: 0,
+            limit: limit.max(1),
+            finished: false,
+        }
+    }
+
+    /// 设置起始偏移量
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// 当前偏移量
+    pub fn current_offset(&self) -> usize {
+        self.offset
+    }
+
+    /// 是否已经拉取完毕
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// 拉取下一页，全部拉取完毕后返回 `Ok(None)`
+    pub async fn next_page(&mut self) -> Result<Option<Vec<T>>> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        let page = (self.fetch)(self.offset, self.limit).await?;
+
+        self.offset += page.len();
+        if page.len() < self.limit {
+            self.finished = true;
+        }
+
+        if page.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(page))
+        }
+    }
+
+    /// 拉取全部数据
+    pub async fn collect_all(mut self) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        while let Some(page) = self.next_page().await? {
+            items.extend(page);
+        }
+        Ok(items)
+    }
+
+    /// 转换为逐条产出数据的 `Stream`，出错时产出错误并结束
+    pub fn into_stream(self) -> impl Stream<Item = Result<T>> {
+        stream::unfold(
+            (self, VecDeque::new()),
+            |(mut paginator, mut buffer)| async move {
+                loop {
+                    if let Some(item) = buffer.pop_front() {
+                        return Some((Ok(item), (paginator, buffer)));
+                    }
+
+                    match paginator.next_page().await {
+                        Ok(Some(page)) => buffer.extend(page),
+                        Ok(None) => return None,
+                        Err(e) => {
+                            paginator.finished = true;
+                            return Some((Err(e), (paginator, buffer)));
+                        }
+                    }
+                }
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorKind;
+    use futures_util::StreamExt;
+
+    fn pages(total: usize) -> impl FnMut(usize, usize) -> std::future::Ready<Result<Vec<usize>>> {
+        move |offset, limit| std::future::ready(Ok((offset..total.min(offset + limit)).collect()))
+    }
+
+    #[tokio::test]
+    async fn test_collect_all() {
+        let items = Paginator::new(10, pages(25)).collect_all().await.unwrap();
+        assert_eq!(items, (0..25).collect::<Vec<_>>());
+
+        let items = Paginator::new(5, pages(10)).collect_all().await.unwrap();
+        assert_eq!(items.len(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_stream() {
+        let items: Vec<usize> = Paginator::new(4, pages(9))
+            .offset(2)
+            .into_stream()
+            .map(|item| item.unwrap())
+            .collect()
+            .await;
+        assert_eq!(items, (2..9).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_stream_stops_on_error() {
+        let stream = Paginator::new(2, |offset, _limit| async move {
+            if offset == 0 {
+                Ok(vec![1, 2])
+            } else {
+                Err(ErrorKind::System("busy".to_string()).into())
+            }
+        })
+        .into_stream();
+
+        let results: Vec<_> = stream.collect().await;
+        assert_eq!(results.len(), 3);
+        assert!(results[2].is_err());
+    }
+}
@@ -0,0 +1,262 @@
+//! 敏感信息脱敏工具
+//!
+//! 提供统一的脱敏包装类型 [`Redacted`]，以及在日志中隐藏 access_token、secret 等凭据的辅助函数。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use url::Url;
+
+/// 脱敏后的占位字符串
+pub const REDACTED: &str = "********";
+
+/// 需要在日志中隐藏的字段名（query 参数、请求体字段）
+pub const SENSITIVE_KEYS: &[&str] = &[
+    "access_token",
+    "secret",
+    "appsecret",
+    "session_key",
+    "js_code",
+    "refresh_token",
+];
+
+/// [`body_snippet`] 保留的最大字节数
+pub const BODY_SNIPPET_LIMIT: usize = 1024;
+
+/// 敏感值包装类型
+///
+/// `Debug` 和 `Display` 只输出占位符，序列化时保持原值，需要使用原值时调用 [`Redacted::expose`]。
+///
+/// # 示例
+///
+/// ```
+/// use wechat_core::utils::Redacted;
+///
+/// let secret = Redacted::new("my_secret".to_string());
+/// assert_eq!(format!("{:?}", secret), "********");
+/// assert_eq!(secret.expose(), "my_secret");
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Redacted<T>(T);
+
+impl<T> Redacted<T> {
+    /// 包装敏感值
+    pub fn new(value: T) -> Self {
+        Redacted(value)
+    }
+
+    /// 获取原始值的引用
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    /// 取出原始值
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Redacted<T> {
+    fn from(value: T) -> Self {
+        Redacted(value)
+    }
+}
+
+impl<T> fmt::Debug for Redacted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T> fmt::Display for Redacted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+/// 判断字段名是否为敏感字段
+pub fn is_sensitive_key(key: &str) -> bool {
+    SENSITIVE_KEYS
+        .iter()
+        .any(|sensitive| key.eq_ignore_ascii_case(sensitive))
+}
+
+/// 返回隐藏了敏感字段的 JSON 副本，用于日志输出
+pub fn redact_value(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    if is_sensitive_key(key) {
+                        (key.clone(), Value::String(REDACTED.to_string()))
+                    } else {
+                        (key.clone(), redact_value(value))
+                    }
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(redact_value).collect()),
+        _ => value.clone(),
+    }
+}
+
+/// 返回隐藏了敏感 query 参数的 URL 字符串，用于日志输出
+pub fn redact_url(url: &Url) -> String {
+    if url.query().is_none() {
+        return url.to_string();
+    }
+
+    let mut redacted = url.clone();
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(key, value)| {
+            if is_sensitive_key(&key) {
+                (key.to_string(), REDACTED.to_string())
+            } else {
+                (key.to_string(), value.to_string())
+            }
+        })
+        .collect();
+
+    redacted.query_pairs_mut().clear().extend_pairs(pairs);
+    redacted.to_string()
+}
+
+/// 响应体片段，用于错误信息和日志
+///
+/// 隐藏敏感字段的字符串值，超过 [`BODY_SNIPPET_LIMIT`] 字节时截断并标注总长度。
+/// 响应体可能不是合法 JSON（被截断或是网关错误页），因此按文本查找 `"字段名": "值"` 替换，
+/// 而不是先解析为 JSON。
+///
+/// ```
+/// use wechat_core::utils::body_snippet;
+///
+/// let body = br#"{"openid":"o1","session_key":"tiihtNczf5v6AKRyjwEUhQ==","#;
+/// assert_eq!(body_snippet(body), r#"{"openid":"o1","session_key":"********","#);
+/// ```
+pub fn body_snippet(body: &[u8]) -> String {
+    let mut snippet = redact_body(body);
+
+    if snippet.len() > BODY_SNIPPET_LIMIT {
+        let mut end = BODY_SNIPPET_LIMIT;
+        while !snippet.is_char_boundary(end) {
+            end -= 1;
+        }
+        snippet.truncate(end);
+        snippet.push_str(&format!("...({} bytes)", body.len()));
+    }
+    snippet
+}
+
+/// 隐藏请求体、响应体中敏感字段的字符串值，不截断
+///
+/// 与 [`body_snippet`] 相同按文本替换，非 UTF-8 内容会被替换为 `U+FFFD`。
+pub fn redact_body(body: &[u8]) -> String {
+    redact_text(&String::from_utf8_lossy(body))
+}
+
+/// 替换 JSON 文本中敏感字段的字符串值
+fn redact_text(text: &str) -> String {
+    let mut redacted = String::with_capacity(text.len());
+    let mut rest = text;
+
+    // 依次处理每个带引号的字符串，敏感字段名后紧跟 `: "` 时替换其值
+    while let Some(start) = rest.find('"') {
+        let Some(len) = string_end(&rest[start + 1..]) else {
+            break;
+        };
+        let key_end = start + 1 + len;
+        let key = &rest[start + 1..key_end];
+        redacted.push_str(&rest[..=key_end]);
+        rest = &rest[key_end + 1..];
+
+        if !is_sensitive_key(key) {
+            continue;
+        }
+        let Some(value) = rest.trim_start().strip_prefix(':') else {
+            continue;
+        };
+        let Some(value) = value.trim_start().strip_prefix('"') else {
+            continue;
+        };
+        redacted.push_str(&rest[..rest.len() - value.len()]);
+        redacted.push_str(REDACTED);
+        match string_end(value) {
+            Some(end) => {
+                redacted.push('"');
+                rest = &value[end + 1..];
+            }
+            None => rest = "",
+        }
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+/// 字符串值结束引号的位置，跳过转义字符
+fn string_end(value: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in value.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return Some(i),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacted_wrapper() {
+        let token = Redacted::new("token".to_string());
+        assert_eq!(format!("{:?}", token), REDACTED);
+        assert_eq!(token.to_string(), REDACTED);
+        assert_eq!(serde_json::to_string(&token).unwrap(), "\"token\"");
+        assert_eq!(token.into_inner(), "token");
+    }
+
+    #[test]
+    fn test_redact_value() {
+        let value = serde_json::json!({
+            "access_token": "token",
+            "appid": "wx123",
+            "nested": {"secret": "s", "page": "pages/index/index"}
+        });
+
+        let redacted = redact_value(&value);
+        assert_eq!(redacted["access_token"], REDACTED);
+        assert_eq!(redacted["appid"], "wx123");
+        assert_eq!(redacted["nested"]["secret"], REDACTED);
+        assert_eq!(redacted["nested"]["page"], "pages/index/index");
+    }
+
+    #[test]
+    fn test_body_snippet() {
+        let body = br#"{"openid": "o1", "session_key" : "a\"b", "list": ["secret"], "secret":1}"#;
+        assert_eq!(
+            body_snippet(body),
+            r#"{"openid": "o1", "session_key" : "********", "list": ["secret"], "secret":1}"#
+        );
+
+        // 截断的响应体
+        assert_eq!(body_snippet(br#"{"session_key":"abc"#), r#"{"session_key":"********"#);
+
+        let long = format!(r#"{{"data":"{}"}}"#, "中".repeat(BODY_SNIPPET_LIMIT));
+        let snippet = body_snippet(long.as_bytes());
+        assert!(snippet.len() <= BODY_SNIPPET_LIMIT + 20);
+        assert!(snippet.ends_with(&format!("...({} bytes)", long.len())));
+    }
+
+    #[test]
+    fn test_redact_url() {
+        let url = Url::parse("https://api.weixin.qq.com/wxa/getwxacode?access_token=abc&a=1").unwrap();
+        let redacted = redact_url(&url);
+        assert!(!redacted.contains("abc"));
+        assert!(redacted.contains("a=1"));
+    }
+}
@@ -0,0 +1,174 @@
+//! 后台任务
+//!
+//! access_token 预刷新、缓存清理等周期任务通过 [`BackgroundTask`] 启动。
+//! 句柄被丢弃时任务随之终止，服务下线时也可以调用 [`BackgroundTask::shutdown`]
+//! 等待任务退出，不会泄漏 tokio 任务。
+
+use std::future::Future;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::{MissedTickBehavior, interval};
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+/// 最短执行周期，`tokio::time::interval` 不接受为 0 的周期
+const MIN_PERIOD: Duration = Duration::from_millis(1);
+
+/// 后台周期任务句柄
+///
+/// 需要在 tokio 运行时中创建。
+///
+/// # 示例
+///
+/// ```
+/// use std::time::Duration;
+/// use wechat_core::utils::BackgroundTask;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let task = BackgroundTask::spawn_interval(Duration::from_secs(60), || async {
+///     // 定期执行的工作
+/// });
+///
+/// // 服务下线时
+/// task.shutdown().await;
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct BackgroundTask {
+    cancel: CancellationToken,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl BackgroundTask {
+    /// 启动周期任务，每隔 `period` 执行一次 `task`，首次立即执行
+    ///
+    /// 单次执行耗时超过 `period` 时不会补跑错过的周期。`period` 小于 1 毫秒（如来自配置的
+    /// `Duration::ZERO`）时按 1 毫秒执行。
+    pub fn spawn_interval<F, Fut>(period: Duration, mut task: F) -> Self
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        if period < MIN_PERIOD {
+            warn!("background task period {:?} is too short, using {:?}", period, MIN_PERIOD);
+        }
+        let period = period.max(MIN_PERIOD);
+        let cancel = CancellationToken::new();
+        let token = cancel.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut ticker = interval(period);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    _ = token.cancelled() => break,
+                    _ = ticker.tick() => {}
+                }
+
+                tokio::select! {
+                    _ = token.cancelled() => break,
+                    _ = task() => {}
+                }
+            }
+        });
+
+        BackgroundTask {
+            cancel,
+            handle: Some(handle),
+        }
+    }
+
+    /// 用于停止任务的取消令牌，可以与应用自身的关闭信号共用
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+
+    /// 任务是否已经退出
+    pub fn is_finished(&self) -> bool {
+        self.handle
+            .as_ref()
+            .is_none_or(|handle| handle.is_finished())
+    }
+
+    /// 停止任务并等待其退出
+    pub async fn shutdown(mut self) {
+        self.cancel.cancel();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.await;
+        }
+    }
+}
+
+impl Drop for BackgroundTask {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+        if let Some(handle) = self.handle.take() {
+            handle.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn counting_task(count: Arc<AtomicUsize>) -> BackgroundTask {
+        BackgroundTask::spawn_interval(Duration::from_millis(5), move || {
+            let count = count.clone();
+            async move {
+                count.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_shutdown() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let task = counting_task(count.clone());
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        task.shutdown().await;
+
+        let stopped = count.load(Ordering::SeqCst);
+        assert!(stopped >= 1);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(count.load(Ordering::SeqCst), stopped);
+    }
+
+    #[tokio::test]
+    async fn test_zero_period() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = count.clone();
+        let task = BackgroundTask::spawn_interval(Duration::ZERO, move || {
+            let count = counter.clone();
+            async move {
+                count.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!task.is_finished());
+        task.shutdown().await;
+        assert!(count.load(Ordering::SeqCst) >= 1);
+    }
+
+    #[tokio::test]
+    async fn test_drop_aborts() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let task = counting_task(count.clone());
+        let token = task.cancellation_token();
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(task);
+        assert!(token.is_cancelled());
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let stopped = count.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(count.load(Ordering::SeqCst), stopped);
+    }
+}
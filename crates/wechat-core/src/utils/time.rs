@@ -0,0 +1,100 @@
+//! 响应中的时间字段
+//!
+//! 微信接口返回的时间大多是 Unix 时间戳（秒），有的是数字，有的是数字字符串。
+//! [`UnixTimestamp`] 兼容两种格式，序列化时保持为数字，可以转换为 `DateTime<Utc>`。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+
+/// Unix 时间戳（秒）
+///
+/// # 示例
+///
+/// ```
+/// use wechat_core::utils::UnixTimestamp;
+///
+/// let time: UnixTimestamp = serde_json::from_str(r#""1477314187""#)?;
+/// assert_eq!(time.raw(), 1477314187);
+/// assert_eq!(time.to_datetime().to_rfc3339(), "2016-10-24T13:03:07+00:00");
+/// assert_eq!(serde_json::to_string(&time)?, "1477314187");
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
+pub struct UnixTimestamp(i64);
+
+impl UnixTimestamp {
+    pub fn new(seconds: i64) -> Self {
+        UnixTimestamp(seconds)
+    }
+
+    /// 微信返回的原始值
+    pub fn raw(&self) -> i64 {
+        self.0
+    }
+
+    /// 转换为 UTC 时间，超出 chrono 支持的范围时为 Unix 纪元
+    pub fn to_datetime(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.0, 0).unwrap_or_default()
+    }
+}
+
+impl From<UnixTimestamp> for DateTime<Utc> {
+    fn from(value: UnixTimestamp) -> Self {
+        value.to_datetime()
+    }
+}
+
+impl From<DateTime<Utc>> for UnixTimestamp {
+    fn from(value: DateTime<Utc>) -> Self {
+        UnixTimestamp(value.timestamp())
+    }
+}
+
+impl fmt::Display for UnixTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_datetime().to_rfc3339())
+    }
+}
+
+impl<'de> Deserialize<'de> for UnixTimestamp {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(i64),
+            String(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(seconds) => Ok(UnixTimestamp(seconds)),
+            Raw::String(value) => value
+                .trim()
+                .parse()
+                .map(UnixTimestamp)
+                .map_err(|_| serde::de::Error::custom(format!("invalid timestamp: {}", value))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unix_timestamp() {
+        let time: UnixTimestamp = serde_json::from_str("1477314187").unwrap();
+        assert_eq!(time, serde_json::from_str(r#"" 1477314187""#).unwrap());
+        assert_eq!(DateTime::<Utc>::from(time).timestamp(), 1477314187);
+        assert_eq!(UnixTimestamp::from(time.to_datetime()), time);
+        assert_eq!(time.to_string(), "2016-10-24T13:03:07+00:00");
+
+        assert!(serde_json::from_str::<UnixTimestamp>(r#""abc""#).is_err());
+        assert!(serde_json::from_str::<UnixTimestamp>("1.5").is_err());
+        assert_eq!(UnixTimestamp::new(i64::MAX).to_datetime(), DateTime::<Utc>::default());
+    }
+}
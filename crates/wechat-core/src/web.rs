@@ -0,0 +1,130 @@
+//! 错误作为 web 框架的响应
+//!
+//! 启用 `axum` 或 `actix` feature 后，[`Error`] 可以直接作为 handler 的错误类型返回，
+//! 状态码取自 [`Error::http_status`]，响应体为 JSON：
+//!
+//! ```json
+//! {"status": 401, "errcode": 40029, "message": "invalid code: ..."}
+//! ```
+//!
+//! 5xx 错误只返回通用提示，不把上游响应等内部细节暴露给前端。
+
+use crate::Error;
+use crate::i18n::localize;
+use serde::Serialize;
+
+/// 错误响应体
+#[derive(Debug, Serialize)]
+struct ErrorBody {
+    status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    errcode: Option<i32>,
+    message: String,
+}
+
+impl From<&Error> for ErrorBody {
+    fn from(error: &Error) -> Self {
+        let status = error.http_status();
+        let message = if status >= 500 {
+            localize("服务暂时不可用，请稍后再试", "service unavailable, please retry later")
+                .to_string()
+        } else {
+            // 不带接口地址，上游 URL 不返回给前端
+            error.kind().to_string()
+        };
+
+        ErrorBody {
+            status,
+            errcode: error.code(),
+            message,
+        }
+    }
+}
+
+#[cfg(feature = "axum")]
+impl axum_core::response::IntoResponse for Error {
+    fn into_response(self) -> axum_core::response::Response {
+        let status = http::StatusCode::from_u16(self.http_status())
+            .unwrap_or(http::StatusCode::INTERNAL_SERVER_ERROR);
+        let body = serde_json::to_vec(&ErrorBody::from(&self)).unwrap_or_default();
+
+        let mut response = (
+            status,
+            [(http::header::CONTENT_TYPE, "application/json")],
+            body,
+        )
+            .into_response();
+        if let Some(retry_after) = self.retry_after() {
+            response
+                .headers_mut()
+                .insert(http::header::RETRY_AFTER, retry_after.as_secs().into());
+        }
+        response
+    }
+}
+
+#[cfg(feature = "actix")]
+impl actix_web::ResponseError for Error {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        actix_web::http::StatusCode::from_u16(self.http_status())
+            .unwrap_or(actix_web::http::StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    fn error_response(&self) -> actix_web::HttpResponse {
+        let mut response = actix_web::HttpResponse::build(self.status_code());
+        if let Some(retry_after) = self.retry_after() {
+            response.insert_header(("Retry-After", retry_after.as_secs().to_string()));
+        }
+        response.json(ErrorBody::from(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorKind;
+
+    #[cfg(feature = "axum")]
+    #[test]
+    fn test_axum_response() {
+        use axum_core::response::IntoResponse;
+
+        let response = Error::from(ErrorKind::InvalidCode("invalid code".to_string()))
+            .with_endpoint("https://api.weixin.qq.com/sns/jscode2session")
+            .into_response();
+        assert_eq!(response.status(), http::StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers()[http::header::CONTENT_TYPE], "application/json");
+
+        let error = ErrorKind::RateLimitExceeded("api freq out of limit".to_string());
+        let response = Error::from(error).into_response();
+        assert_eq!(response.status(), http::StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[http::header::RETRY_AFTER], "60");
+    }
+
+    #[cfg(feature = "actix")]
+    #[tokio::test]
+    async fn test_actix_response() {
+        use actix_web::ResponseError;
+
+        let error = Error::from(ErrorKind::InvalidParameter("page path is required".to_string()))
+            .with_endpoint("https://api.weixin.qq.com/wxa/getwxacode");
+        let response = error.error_response();
+        assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+        let body = actix_web::body::to_bytes(response.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["status"], 400);
+        // 接口地址不出现在返回给前端的 message 中
+        assert_eq!(body["message"], "invalid parameter: page path is required");
+        assert!(body.get("errcode").is_none());
+
+        let error = Error::from(ErrorKind::HttpStatus {
+            status: 502,
+            content_type: None,
+            body: "bad gateway".to_string(),
+        });
+        let response = error.error_response();
+        assert_eq!(response.status(), actix_web::http::StatusCode::BAD_GATEWAY);
+        let body = actix_web::body::to_bytes(response.into_body()).await.unwrap();
+        assert!(!String::from_utf8_lossy(&body).contains("bad gateway"));
+    }
+}
@@ -0,0 +1,482 @@
+//! 数据分析
+//!
+//! 日期参数使用 [`ReportRange`]，在请求前校验微信对日期范围的要求。
+//! [`Analytics::fetch_overview`] 并发拉取某一天的访问概况、访问趋势和留存，
+//! 汇总为 [`Overview`]，供仪表盘一次渲染。
+//! [`Analytics::performance`] 查询运维中心的启动耗时、页面切换、内存等性能数据。
+//!
+//! ## 示例
+//!
+//! ```no_run
+//! use wechat_minapp::WechatMinapp;
+//! use wechat_minapp::new_type::ReportDate;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = WechatMinapp::new("app_id", "secret");
+//!
+//!     let overview = client.analytics().fetch_overview(ReportDate::yesterday()).await?;
+//!     if let Some(trend) = &overview.visit_trend {
+//!         println!("{} 访问人数 {}", overview.date, trend.visit_uv);
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::constants::Endpoint;
+use crate::new_type::{ReportDate, ReportRange};
+use crate::WechatMinapp;
+use chrono::{DateTime, Utc};
+use futures_util::future::try_join3;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use wechat_core::response::ExtraFields;
+use wechat_core::{localized, ErrorKind, Result};
+
+pub struct Analytics {
+    pub client: WechatMinapp,
+}
+
+impl Analytics {
+    pub fn new(client: impl Into<WechatMinapp>) -> Self {
+        Analytics {
+            client: client.into(),
+        }
+    }
+
+
+    wechat_core::endpoint! {
+        POST idempotent summary_list(range: ReportRange) -> DataList<DailySummary> =
+            Endpoint::DailySummary.url();
+
+        POST idempotent visit_trend_list(range: ReportRange) -> DataList<VisitTrend> =
+            Endpoint::DailyVisitTrend.url();
+
+        /// 获取用户访问小程序日留存
+        ///
+        /// 只能查询一天，使用 [`ReportRange::daily`] 创建参数。
+        ///
+        /// [官方文档](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/data-analysis/visit-retain/getDailyRetain.html)
+        pub POST idempotent daily_retain(range: ReportRange) -> RetainInfo =
+            Endpoint::DailyRetain.url();
+
+        POST idempotent performance_body(args: PerformanceArgs) -> PerformanceBody =
+            Endpoint::Performance.url();
+    }
+
+    /// 查询运维中心性能数据
+    ///
+    /// ```no_run
+    /// use chrono::{Duration, Utc};
+    /// use wechat_minapp::WechatMinapp;
+    /// use wechat_minapp::analytics::{PerformanceArgs, PerformanceModule};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = WechatMinapp::new("app_id", "secret");
+    ///
+    ///     let end = Utc::now();
+    ///     let args = PerformanceArgs::new(PerformanceModule::Boot, end - Duration::days(7), end)?
+    ///         .param("networktype", "wifi");
+    ///     for table in client.analytics().performance(args).await?.tables {
+    ///         println!("{}: {:?}", table.zh, table.lines);
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// [官方文档](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/operation/getPerformance.html)
+    pub async fn performance(&self, args: PerformanceArgs) -> Result<Performance> {
+        Ok(self.performance_body(args).await?.body)
+    }
+
+    /// 获取用户访问小程序数据概况
+    ///
+    /// 只能查询一天，使用 [`ReportRange::daily`] 创建参数。
+    ///
+    /// [官方文档](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/data-analysis/others/getDailySummary.html)
+    pub async fn daily_summary(&self, range: ReportRange) -> Result<Vec<DailySummary>> {
+        Ok(self.summary_list(range).await?.list)
+    }
+
+    /// 获取用户访问小程序数据日趋势
+    ///
+    /// 只能查询一天，使用 [`ReportRange::daily`] 创建参数。
+    ///
+    /// [官方文档](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/data-analysis/visit-trend/getDailyVisitTrend.html)
+    pub async fn daily_visit_trend(&self, range: ReportRange) -> Result<Vec<VisitTrend>> {
+        Ok(self.visit_trend_list(range).await?.list)
+    }
+
+    /// 并发拉取某一天的访问概况、访问趋势和留存
+    ///
+    /// 任意一个接口失败时返回该错误。当天没有数据的指标为 `None`。
+    pub async fn fetch_overview(&self, date: ReportDate) -> Result<Overview> {
+        let range = ReportRange::daily(date)?;
+        let (summary, visit_trend, retain) = try_join3(
+            self.daily_summary(range),
+            self.daily_visit_trend(range),
+            self.daily_retain(range),
+        )
+        .await?;
+
+        Ok(Overview {
+            date,
+            summary: summary.into_iter().next(),
+            visit_trend: visit_trend.into_iter().next(),
+            retain,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DataList<T> {
+    #[serde(default = "Vec::new")]
+    list: Vec<T>,
+}
+
+/// 用户访问小程序数据概况
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailySummary {
+    /// 日期，格式为 yyyymmdd
+    pub ref_date: String,
+    /// 累计用户数
+    pub visit_total: u64,
+    /// 转发次数
+    pub share_pv: u64,
+    /// 转发人数
+    pub share_uv: u64,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+/// 用户访问小程序数据趋势
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VisitTrend {
+    /// 日期，格式为 yyyymmdd
+    pub ref_date: String,
+    /// 打开次数
+    pub session_cnt: u64,
+    /// 访问次数
+    pub visit_pv: u64,
+    /// 访问人数
+    pub visit_uv: u64,
+    /// 新用户数
+    pub visit_uv_new: u64,
+    /// 人均停留时长（秒）
+    pub stay_time_uv: f64,
+    /// 次均停留时长（秒）
+    pub stay_time_session: f64,
+    /// 平均访问深度
+    pub visit_depth: f64,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+/// 用户访问小程序留存
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetainInfo {
+    /// 日期，格式为 yyyymmdd
+    pub ref_date: String,
+    /// 新增用户留存
+    #[serde(default)]
+    pub visit_uv_new: Vec<RetainItem>,
+    /// 活跃用户留存
+    #[serde(default)]
+    pub visit_uv: Vec<RetainItem>,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+/// 留存数据
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetainItem {
+    /// 标识，0 表示当天，1 表示 1 天后，依此类推
+    pub key: u32,
+    /// key 对应日期的新增用户数或活跃用户数
+    pub value: u64,
+}
+
+/// 某一天的数据汇总
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Overview {
+    /// 统计日期
+    pub date: ReportDate,
+    /// 访问概况
+    pub summary: Option<DailySummary>,
+    /// 访问趋势
+    pub visit_trend: Option<VisitTrend>,
+    /// 留存
+    pub retain: RetainInfo,
+}
+
+/// 运维中心性能数据的模块
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PerformanceModule {
+    /// 打开率（10016）
+    OpenRate,
+    /// 启动各阶段耗时（10017）
+    Boot,
+    /// 页面切换耗时（10021）
+    PageSwitch,
+    /// 内存指标（10022）
+    Memory,
+    /// 内存异常（10023）
+    MemoryWarning,
+    /// 其他模块，传入官方文档中的模块编号
+    Other(u32),
+}
+
+impl PerformanceModule {
+    /// 模块编号
+    pub fn code(self) -> u32 {
+        match self {
+            PerformanceModule::OpenRate => 10016,
+            PerformanceModule::Boot => 10017,
+            PerformanceModule::PageSwitch => 10021,
+            PerformanceModule::Memory => 10022,
+            PerformanceModule::MemoryWarning => 10023,
+            PerformanceModule::Other(code) => code,
+        }
+    }
+}
+
+/// 接口要求模块编号为字符串
+impl Serialize for PerformanceModule {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.code())
+    }
+}
+
+/// 查询时间区间，Unix 时间戳（秒）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PerformanceTime {
+    pub begin_timestamp: i64,
+    pub end_timestamp: i64,
+}
+
+/// 查询条件，如 `networktype`、`device_level`、`device`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PerformanceParam {
+    pub field: String,
+    pub value: String,
+}
+
+/// 查询性能数据的参数
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PerformanceArgs {
+    time: PerformanceTime,
+    module: PerformanceModule,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    params: Vec<PerformanceParam>,
+}
+
+impl PerformanceArgs {
+    /// 查询 `module` 在 `begin` 到 `end` 之间的数据，`begin` 不能晚于 `end`
+    pub fn new(
+        module: PerformanceModule,
+        begin: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Self> {
+        if begin > end {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "开始时间 {} 不能晚于结束时间 {}",
+                "begin time {} must not be later than end time {}",
+                begin,
+                end
+            ))
+            .into());
+        }
+
+        Ok(PerformanceArgs {
+            time: PerformanceTime {
+                begin_timestamp: begin.timestamp(),
+                end_timestamp: end.timestamp(),
+            },
+            module,
+            params: Vec::new(),
+        })
+    }
+
+    /// 追加查询条件
+    pub fn param(mut self, field: impl Into<String>, value: impl Into<String>) -> Self {
+        self.params.push(PerformanceParam {
+            field: field.into(),
+            value: value.into(),
+        });
+        self
+    }
+
+    pub fn module(&self) -> PerformanceModule {
+        self.module
+    }
+
+    pub fn time(&self) -> PerformanceTime {
+        self.time
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PerformanceBody {
+    #[serde(default, deserialize_with = "object_or_json_string")]
+    body: Performance,
+}
+
+/// `body` 可能是对象，也可能是 JSON 字符串
+fn object_or_json_string<'de, D>(deserializer: D) -> std::result::Result<Performance, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Value::deserialize(deserializer)? {
+        Value::String(body) if body.is_empty() => Ok(Performance::default()),
+        Value::String(body) => serde_json::from_str(&body).map_err(serde::de::Error::custom),
+        value => serde_json::from_value(value).map_err(serde::de::Error::custom),
+    }
+}
+
+/// 性能数据
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Performance {
+    /// 数据表，每个指标一张表
+    #[serde(default)]
+    pub tables: Vec<PerformanceTable>,
+    /// 数据表数量
+    #[serde(default)]
+    pub count: u64,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+/// 单个指标的数据表
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerformanceTable {
+    /// 指标 id
+    #[serde(default)]
+    pub id: String,
+    /// 按查询条件分组的数据
+    #[serde(default)]
+    pub lines: Vec<PerformanceLine>,
+    /// 指标中文名
+    #[serde(default)]
+    pub zh: String,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+/// 一组按日期排列的数据
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerformanceLine {
+    #[serde(default)]
+    pub fields: Vec<PerformanceField>,
+}
+
+/// 某一天的指标值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerformanceField {
+    /// 日期，格式为 yyyymmdd
+    pub refdate: String,
+    /// 指标值，微信可能返回字符串或数字
+    pub value: Value,
+}
+
+impl PerformanceField {
+    /// 数值形式的指标值
+    pub fn as_f64(&self) -> Option<f64> {
+        match &self.value {
+            Value::Number(value) => value.as_f64(),
+            Value::String(value) => value.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use http::{Request, Response};
+    use std::sync::Arc;
+    use crate::fixtures;
+    use wechat_core::client::HttpClient;
+
+    struct DatacubeClient;
+
+    #[async_trait]
+    impl HttpClient for DatacubeClient {
+        async fn execute(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+            let body: &str = match request.uri().path() {
+                "/datacube/getweanalysisappiddailysummarytrend" => {
+                    r#"{"list":[{"ref_date":"20240301","visit_total":391,"share_pv":572,
+                    "share_uv":383}]}"#
+                }
+                "/datacube/getweanalysisappiddailyvisittrend" => {
+                    r#"{"list":[{"ref_date":"20240301","session_cnt":142549,"visit_pv":472351,
+                    "visit_uv":55500,"visit_uv_new":5464,"stay_time_session":0,
+                    "visit_depth":1.9838,"stay_time_uv":11.5}]}"#
+                }
+                "/datacube/getweanalysisappiddailyretaininfo" => {
+                    r#"{"ref_date":"20240301","visit_uv_new":[{"key":0,"value":5464}],
+                    "visit_uv":[{"key":0,"value":55500}]}"#
+                }
+                "/wxa/business/performance/boot" => {
+                    let args: Value = serde_json::from_slice(request.body()).unwrap();
+                    assert_eq!(args["module"], "10017");
+                    assert_eq!(args["params"][0]["field"], "networktype");
+                    // body 为 JSON 字符串
+                    concat!(
+                        r#"{"errcode":0,"errmsg":"ok","body":"{\"tables\":[{\"id\":\"boot\","#,
+                        r#"\"lines\":[{\"fields\":[{\"refdate\":\"20240301\",\"value\":"#,
+                        r#"\"1.5\"}]}],\"zh\":\"启动总耗时\"}],\"count\":1}"}"#
+                    )
+                }
+                _ => r#"{"errcode":61501,"errmsg":"date range error"}"#,
+            };
+            Ok(Response::new(body.as_bytes().to_vec()))
+        }
+    }
+
+    fn analytics() -> Analytics {
+        Analytics::new(fixtures::client_with(Arc::new(DatacubeClient)))
+    }
+
+    #[tokio::test]
+    async fn test_fetch_overview() {
+        let date: ReportDate = "20240301".parse().unwrap();
+        let overview = analytics().fetch_overview(date).await.unwrap();
+
+        assert_eq!(overview.date, date);
+        assert_eq!(overview.summary.unwrap().visit_total, 391);
+        assert_eq!(overview.visit_trend.unwrap().visit_uv, 55500);
+        assert_eq!(overview.retain.visit_uv_new[0], RetainItem { key: 0, value: 5464 });
+
+        let today = ReportDate::new(ReportDate::yesterday().date().succ_opt().unwrap());
+        let error = analytics().fetch_overview(today).await.unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidParameter(_)));
+    }
+
+    #[tokio::test]
+    async fn test_performance() {
+        let end = Utc::now();
+        let begin = end - chrono::Duration::days(1);
+        let args = PerformanceArgs::new(PerformanceModule::Boot, begin, end)
+            .unwrap()
+            .param("networktype", "wifi");
+        let performance = analytics().performance(args).await.unwrap();
+
+        assert_eq!(performance.count, 1);
+        let table = &performance.tables[0];
+        assert_eq!(table.zh, "启动总耗时");
+        assert_eq!(table.lines[0].fields[0].as_f64(), Some(1.5));
+
+        let error = PerformanceArgs::new(PerformanceModule::Memory, end, begin).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidParameter(_)));
+    }
+}
@@ -0,0 +1,300 @@
+//! 登录后签发自有登录态
+//!
+//! 常见的登录流程是 `code2Session` 换取 openid 后给前端下发 JWT，之后的请求携带 JWT 识别用户，
+//! session_key 只保存在服务端。[`TokenIssuer`] 使用 HS256 签发和校验包含 openid 的 JWT，
+//! [`User::login_and_issue`] 把登录和签发合为一步。需要启用 `auth` feature。
+//!
+//! 校验失败返回 [`ErrorKind::InvalidToken`]，过期返回 [`ErrorKind::TokenExpired`]，
+//! 两者的 [`Error::http_status`](crate::Error::http_status) 都是 401，
+//! 后者的 [`Error::requires_relogin`](crate::Error::requires_relogin) 为 `true`。
+//!
+//! ## 示例
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use wechat_minapp::WechatMinapp;
+//! use wechat_minapp::auth::TokenIssuer;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = WechatMinapp::new("app_id", "secret");
+//!     let issuer = TokenIssuer::new("jwt-signing-key").ttl(Duration::from_secs(7 * 24 * 3600));
+//!
+//!     // 登录接口：把 token 返回给前端
+//!     let login = client.user().login_and_issue("0816abc123def456", &issuer).await?;
+//!     println!("token: {}", login.token);
+//!
+//!     // 业务接口：从请求头取出 token 校验
+//!     let claims = issuer.verify(&login.token)?;
+//!     println!("openid: {}", claims.open_id());
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::user::{unix_now, Credential, User};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, KeyInit, Mac};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fmt;
+use std::time::Duration;
+use wechat_core::utils::REDACTED;
+use wechat_core::{localized, ErrorKind, Result};
+
+/// JWT 头部，固定为 HS256
+const HEADER: &str = r#"{"alg":"HS256","typ":"JWT"}"#;
+
+/// 默认有效期 7 天
+const DEFAULT_TTL: Duration = Duration::from_secs(7 * 24 * 3600);
+
+/// token 中携带的用户信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claims {
+    /// 用户的 openid
+    pub sub: String,
+    /// 用户的 unionid（如果有）
+    #[serde(rename = "unionid", default, skip_serializing_if = "Option::is_none")]
+    pub union_id: Option<String>,
+    /// 签发方，见 [`TokenIssuer::issuer`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// 签发时间（Unix 时间戳，秒）
+    pub iat: u64,
+    /// 过期时间（Unix 时间戳，秒）
+    pub exp: u64,
+}
+
+impl Claims {
+    /// 用户的 openid
+    pub fn open_id(&self) -> &str {
+        &self.sub
+    }
+}
+
+/// 登录并签发 token 的结果
+#[derive(Debug, Clone)]
+pub struct LoginToken {
+    /// 签发的 token，返回给前端
+    pub token: String,
+    /// token 中的用户信息
+    pub claims: Claims,
+    /// 登录凭证，session_key 只应保存在服务端
+    pub credential: Credential,
+}
+
+/// 使用 HS256 签发与校验 JWT
+#[derive(Clone)]
+pub struct TokenIssuer {
+    key: Vec<u8>,
+    ttl: Duration,
+    issuer: Option<String>,
+}
+
+impl fmt::Debug for TokenIssuer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenIssuer")
+            .field("key", &REDACTED)
+            .field("ttl", &self.ttl)
+            .field("issuer", &self.issuer)
+            .finish()
+    }
+}
+
+impl TokenIssuer {
+    /// 使用签名密钥创建，token 默认有效期 7 天
+    pub fn new(key: impl AsRef<[u8]>) -> Self {
+        TokenIssuer {
+            key: key.as_ref().to_vec(),
+            ttl: DEFAULT_TTL,
+            issuer: None,
+        }
+    }
+
+    /// token 有效期
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// 签发方，设置后校验时要求 token 的 `iss` 一致
+    pub fn issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// 为登录凭证签发 token
+    pub fn issue(&self, credential: &Credential) -> Result<String> {
+        self.sign(&self.claims(credential))
+    }
+
+    /// 校验 token 并返回其中的用户信息
+    pub fn verify(&self, token: &str) -> Result<Claims> {
+        let mut mac = self.mac()?;
+        let mut parts = token.trim().split('.');
+        let (Some(header), Some(payload), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(ErrorKind::InvalidToken(localized!("格式不正确", "malformed token")).into());
+        };
+
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| {
+            ErrorKind::InvalidToken(localized!("签名格式不正确", "malformed signature"))
+        })?;
+        mac.update(format!("{}.{}", header, payload).as_bytes());
+        mac.verify_slice(&signature)
+            .map_err(|_| ErrorKind::InvalidToken(localized!("签名不一致", "signature mismatch")))?;
+
+        let header: serde_json::Value = decode_part(header)?;
+        if header.get("alg").and_then(|alg| alg.as_str()) != Some("HS256") {
+            return Err(ErrorKind::InvalidToken(localized!(
+                "不支持的签名算法",
+                "unsupported algorithm"
+            ))
+            .into());
+        }
+
+        let claims: Claims = decode_part(payload)?;
+        if self.issuer.is_some() && claims.iss != self.issuer {
+            return Err(ErrorKind::InvalidToken(localized!("签发方不一致", "issuer mismatch")).into());
+        }
+        if claims.exp <= unix_now() {
+            return Err(ErrorKind::TokenExpired(localized!(
+                "token 已于 {} 过期",
+                "token expired at {}",
+                claims.exp
+            ))
+            .into());
+        }
+
+        Ok(claims)
+    }
+
+    fn claims(&self, credential: &Credential) -> Claims {
+        let now = unix_now();
+        Claims {
+            sub: credential.open_id().to_string(),
+            union_id: credential.union_id().map(str::to_string),
+            iss: self.issuer.clone(),
+            iat: now,
+            exp: now + self.ttl.as_secs(),
+        }
+    }
+
+    fn sign(&self, claims: &Claims) -> Result<String> {
+        let mut mac = self.mac()?;
+        let signing_input = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(HEADER),
+            URL_SAFE_NO_PAD.encode(serde_json::to_vec(claims)?)
+        );
+        mac.update(signing_input.as_bytes());
+        let signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+        Ok(format!("{}.{}", signing_input, signature))
+    }
+
+    fn mac(&self) -> Result<Hmac<Sha256>> {
+        if self.key.is_empty() {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "签名密钥不能为空",
+                "signing key is required"
+            ))
+            .into());
+        }
+        Hmac::<Sha256>::new_from_slice(&self.key)
+            .map_err(|e| ErrorKind::InvalidParameter(e.to_string()).into())
+    }
+}
+
+fn decode_part<T: DeserializeOwned>(part: &str) -> Result<T> {
+    URL_SAFE_NO_PAD
+        .decode(part)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or_else(|| {
+            ErrorKind::InvalidToken(localized!("内容格式不正确", "malformed payload")).into()
+        })
+}
+
+impl User {
+    /// 使用 `wx.login` 的 code 登录，并为登录的用户签发 token
+    ///
+    /// 需要服务端保存 session_key 时，可以把返回的 `credential` 写入
+    /// [`SessionStorage`](crate::user::SessionStorage)。
+    pub async fn login_and_issue(&self, code: &str, issuer: &TokenIssuer) -> Result<LoginToken> {
+        let credential = self.login(code).await?;
+        let claims = issuer.claims(&credential);
+        let token = issuer.sign(&claims)?;
+
+        Ok(LoginToken {
+            token,
+            claims,
+            credential,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credential() -> Credential {
+        serde_json::from_value(serde_json::json!({
+            "openid": "o1",
+            "session_key": "MDEyMzQ1Njc4OWFiY2RlZg==",
+            "unionid": "u1"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_issue_and_verify() {
+        let issuer = TokenIssuer::new("secret").issuer("my-app");
+        let token = issuer.issue(&credential()).unwrap();
+        assert_eq!(token.split('.').count(), 3);
+        assert!(!token.contains("MDEyMzQ1Njc4OWFiY2RlZg"));
+
+        let claims = issuer.verify(&token).unwrap();
+        assert_eq!(claims.open_id(), "o1");
+        assert_eq!(claims.union_id.as_deref(), Some("u1"));
+        assert_eq!(claims.exp - claims.iat, DEFAULT_TTL.as_secs());
+
+        // 密钥或签发方不一致
+        let error = TokenIssuer::new("other").issuer("my-app").verify(&token).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidToken(_)));
+        let error = TokenIssuer::new("secret").issuer("other").verify(&token).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidToken(_)));
+    }
+
+    #[test]
+    fn test_verify_invalid() {
+        let issuer = TokenIssuer::new("secret");
+        let token = issuer.issue(&credential()).unwrap();
+
+        // 篡改 payload
+        let mut parts: Vec<&str> = token.split('.').collect();
+        let payload = URL_SAFE_NO_PAD.encode(r#"{"sub":"o2","iat":0,"exp":99999999999}"#);
+        parts[1] = &payload;
+        let error = issuer.verify(&parts.join(".")).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidToken(_)));
+        assert_eq!(error.http_status(), 401);
+
+        assert!(matches!(issuer.verify("a.b").unwrap_err().kind(), ErrorKind::InvalidToken(_)));
+        assert!(matches!(issuer.verify("a.b.c").unwrap_err().kind(), ErrorKind::InvalidToken(_)));
+        assert!(matches!(
+            TokenIssuer::new("").issue(&credential()).unwrap_err().kind(),
+            ErrorKind::InvalidParameter(_)
+        ));
+    }
+
+    #[test]
+    fn test_expired() {
+        let issuer = TokenIssuer::new("secret").ttl(Duration::ZERO);
+        let token = issuer.issue(&credential()).unwrap();
+
+        let error = issuer.verify(&token).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::TokenExpired(_)));
+        assert!(error.requires_relogin());
+    }
+}
@@ -0,0 +1,165 @@
+//! 批量调用
+//!
+//! 批量生成小程序码、清洗历史内容时一次要调用成百上千次接口。[`BatchOptions`] 控制并发数、
+//! 每秒请求数与失败重试，单个失败不影响其他参数，最终按输入顺序汇总到 [`BatchResult`]。
+
+use futures_util::stream::{self, StreamExt};
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::{interval, sleep, MissedTickBehavior};
+use tracing::warn;
+use wechat_core::{Error, ErrorKind};
+
+/// 重试间隔的初始值，之后每次翻倍
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(200);
+
+/// 批量调用的并发、限速与重试配置
+///
+/// 可以直接传入并发数，`usize` 会转换为不限速、不重试的配置。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchOptions {
+    concurrency: usize,
+    rate_limit: Option<u32>,
+    retries: u32,
+}
+
+impl BatchOptions {
+    /// 最多同时进行 `concurrency` 个请求，为 0 时按 1 处理
+    pub fn new(concurrency: usize) -> Self {
+        BatchOptions {
+            concurrency: concurrency.max(1),
+            rate_limit: None,
+            retries: 0,
+        }
+    }
+
+    /// 每秒最多发起 `per_second` 个请求，为 0 时不限速
+    pub fn rate_limit(mut self, per_second: u32) -> Self {
+        self.rate_limit = (per_second > 0).then_some(per_second);
+        self
+    }
+
+    /// 单个请求遇到系统繁忙、频率限制或网络错误时最多重试 `retries` 次
+    ///
+    /// 频率限制按 [`Error::retry_after`] 建议的时间等待，其余错误从 200ms 开始指数退避。
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+}
+
+impl From<usize> for BatchOptions {
+    fn from(concurrency: usize) -> Self {
+        BatchOptions::new(concurrency)
+    }
+}
+
+/// 批量调用的汇总结果，元素中的 `usize` 为参数在输入中的下标
+#[derive(Debug)]
+pub struct BatchResult<T> {
+    pub succeeded: Vec<(usize, T)>,
+    pub failed: Vec<(usize, Error)>,
+}
+
+impl<T> BatchResult<T> {
+    fn from_results(results: Vec<(usize, Result<T, Error>)>) -> Self {
+        let mut batch = BatchResult {
+            succeeded: Vec::new(),
+            failed: Vec::new(),
+        };
+
+        for (index, result) in results {
+            match result {
+                Ok(value) => batch.succeeded.push((index, value)),
+                Err(error) => batch.failed.push((index, error)),
+            }
+        }
+        batch.succeeded.sort_by_key(|(index, _)| *index);
+        batch.failed.sort_by_key(|(index, _)| *index);
+        batch
+    }
+
+    /// 是否全部成功
+    pub fn is_all_ok(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// 总数
+    pub fn len(&self) -> usize {
+        self.succeeded.len() + self.failed.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// 需要重试时返回等待时间
+fn retry_delay(error: &Error, attempt: u32) -> Option<Duration> {
+    let backoff = RETRY_BASE_DELAY.saturating_mul(2u32.saturating_pow(attempt));
+    match error.kind() {
+        ErrorKind::System(_) | ErrorKind::Io(_) | ErrorKind::Timeout(_) => Some(backoff),
+        ErrorKind::RateLimitExceeded(_) => error.retry_after(),
+        #[cfg(feature = "reqwest")]
+        ErrorKind::Reqwest(e) if e.is_connect() || e.is_timeout() => Some(backoff),
+        _ if error.status().is_some_and(|status| status >= 500) => Some(backoff),
+        _ => None,
+    }
+}
+
+/// 按配置并发调用 `call`，汇总每个参数的结果，重试时传入参数的副本
+pub(crate) async fn run<A, T, F, Fut>(
+    args: impl IntoIterator<Item = A>,
+    options: BatchOptions,
+    call: F,
+) -> BatchResult<T>
+where
+    A: Clone,
+    F: Fn(A) -> Fut,
+    Fut: Future<Output = Result<T, Error>>,
+{
+    let ticker = options.rate_limit.map(|per_second| {
+        // 每秒超过 10 亿次时间隔会舍入为 0，interval 不接受为 0 的周期
+        let period = (Duration::from_secs(1) / per_second).max(Duration::from_nanos(1));
+        let mut ticker = interval(period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Mutex::new(ticker)
+    });
+    let ticker = ticker.as_ref();
+    let call = &call;
+
+    let results = stream::iter(args.into_iter().enumerate())
+        .map(|(index, args)| async move {
+            let mut attempt = 0;
+            loop {
+                if let Some(ticker) = ticker {
+                    ticker.lock().await.tick().await;
+                }
+
+                let result = call(args.clone()).await;
+                match &result {
+                    Err(error) if attempt < options.retries => {
+                        if let Some(delay) = retry_delay(error, attempt) {
+                            warn!("batch item {} failed: {}, retry in {:?}", index, error, delay);
+                            sleep(delay).await;
+                            attempt += 1;
+                            continue;
+                        }
+                    }
+                    _ => {}
+                }
+                return (index, result);
+            }
+        })
+        .buffer_unordered(options.concurrency)
+        .collect::<Vec<_>>()
+        .await;
+
+    BatchResult::from_results(results)
+}
+
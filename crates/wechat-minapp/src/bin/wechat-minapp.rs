@@ -0,0 +1,196 @@
+//! wechat-minapp 命令行工具
+//!
+//! 用于运维排障、快速验证 appid 配置，也可作为 SDK 的端到端冒烟测试。需要启用 `cli` feature：
+//!
+//! ```bash
+//! cargo install wechat-minapp --features cli
+//!
+//! export WECHAT_APP_ID=wx...
+//! export WECHAT_APP_SECRET=...
+//!
+//! wechat-minapp token
+//! wechat-minapp qrcode --page pages/index/index --scene id=1 -o code.png
+//! wechat-minapp short-link --path pages/index/index --title 首页
+//! wechat-minapp rid 61725984-6126f6f9-040f19c4
+//! ```
+
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use wechat_minapp::link::ShortLinkArgs;
+use wechat_minapp::qr::{MinappEnvVersion, QrCodeArgs, UnlimitedQrCodeArgs};
+use wechat_minapp::{
+    Error, NonStableToken, ReqwestHttpClient, Result, StableToken, TokenType, WechatMinapp,
+};
+
+/// 微信小程序服务端 API 命令行工具
+#[derive(Debug, Parser)]
+#[command(name = "wechat-minapp", version)]
+struct Cli {
+    /// 小程序 appid
+    #[arg(long, env = "WECHAT_APP_ID")]
+    app_id: String,
+
+    /// 小程序 secret
+    #[arg(long, env = "WECHAT_APP_SECRET", hide_env_values = true)]
+    secret: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// 获取接口调用凭据 access_token
+    Token {
+        /// 使用普通版接口，默认使用稳定版接口
+        #[arg(long)]
+        non_stable: bool,
+
+        /// 强制刷新稳定版 access_token，会使之前的 access_token 失效
+        #[arg(long, conflicts_with = "non_stable")]
+        force_refresh: bool,
+    },
+
+    /// 生成小程序码并保存到文件
+    ///
+    /// 指定 --scene 时调用不限数量的 getUnlimitedQRCode，否则调用 getQRCode，
+    /// 此时 --page 可以带 query 参数。
+    Qrcode {
+        /// 页面路径
+        #[arg(long)]
+        page: String,
+
+        /// 场景值，最多 32 个可见字符
+        #[arg(long)]
+        scene: Option<String>,
+
+        /// 小程序码宽度（像素）
+        #[arg(long)]
+        width: Option<u16>,
+
+        /// 小程序版本：release、trial、develop
+        #[arg(long, default_value = "release")]
+        env: MinappEnvVersion,
+
+        /// 保存的文件路径
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+
+    /// 生成小程序短链接（仅电商类目可用）
+    ShortLink {
+        /// 页面路径，可以带 query 参数
+        #[arg(long)]
+        path: String,
+
+        /// 页面标题
+        #[arg(long)]
+        title: Option<String>,
+
+        /// 生成永久有效的短链接
+        #[arg(long)]
+        permanent: bool,
+    },
+
+    /// 查询接口报错返回的 rid 详情
+    Rid {
+        /// 错误信息中的 rid
+        rid: String,
+    },
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            if let Some(rid) = e.trace_id() {
+                eprintln!("可以执行 `wechat-minapp rid {}` 查询请求详情", rid);
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let client = WechatMinapp::new(&cli.app_id, &cli.secret);
+
+    match cli.command {
+        Command::Token {
+            non_stable,
+            force_refresh,
+        } => {
+            let http_client = Arc::new(ReqwestHttpClient::new());
+            let token_type: Box<dyn TokenType> = if non_stable {
+                Box::new(NonStableToken::new(&cli.app_id, &cli.secret, http_client))
+            } else {
+                Box::new(StableToken::new(&cli.app_id, &cli.secret, force_refresh, http_client))
+            };
+            let token = token_type.token().await?;
+            println!("access_token: {}", token.access_token);
+            println!("expired_at: {}", token.expired_at);
+        }
+        Command::Qrcode {
+            page,
+            scene,
+            width,
+            env,
+            output,
+        } => {
+            let qr = client.qr();
+            let qr_code = match scene {
+                Some(scene) => {
+                    let mut builder = UnlimitedQrCodeArgs::builder()
+                        .page(page)
+                        .scene(scene)
+                        .env_version(env);
+                    if let Some(width) = width {
+                        builder = builder.width(width);
+                    }
+                    qr.unlimited_qr_code(builder.build()?).await?
+                }
+                None => {
+                    let mut builder = QrCodeArgs::builder().path(page).env_version(env);
+                    if let Some(width) = width {
+                        builder = builder.width(width);
+                    }
+                    qr.qr_code(builder.build()?).await?
+                }
+            };
+            qr_code.save_to(&output).await?;
+            println!(
+                "saved {} ({}, {} bytes)",
+                output.display(),
+                qr_code.mime_type(),
+                qr_code.buffer().len()
+            );
+        }
+        Command::ShortLink {
+            path,
+            title,
+            permanent,
+        } => {
+            let mut builder = ShortLinkArgs::builder().path(path);
+            if let Some(title) = title {
+                builder = builder.page_title(title);
+            }
+            if permanent {
+                builder = builder.with_permanent();
+            }
+            let short_link = client.link().short_link(builder.build()?).await?;
+            println!("{}", short_link.link());
+        }
+        Command::Rid { rid } => {
+            let info = client.openapi().rid_info(&rid).await?;
+            let info = serde_json::to_string_pretty(&info).map_err(Error::from)?;
+            println!("{}", info);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,353 @@
+//! 消息推送的签名校验、解密与多小程序路由
+
+use super::{CallbackMessage, Dispatched, Router};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use tracing::debug;
+use wechat_core::utils::{callback_signature, constant_time_eq, decrypt_callback, REDACTED};
+use wechat_core::{localized, Error, ErrorKind, Result};
+
+/// 微信推送时 URL 上携带的参数
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallbackQuery {
+    /// 明文模式的签名
+    #[serde(default)]
+    pub signature: String,
+    #[serde(default)]
+    pub timestamp: String,
+    #[serde(default)]
+    pub nonce: String,
+    /// 配置服务器地址时的随机字符串，校验通过后原样返回
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub echostr: Option<String>,
+    /// 安全模式为 `aes`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypt_type: Option<String>,
+    /// 安全模式的消息签名
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub msg_signature: Option<String>,
+}
+
+/// 安全模式下的推送数据，ToUserName 之外的字段都在密文中
+#[derive(Deserialize)]
+struct EncryptedBody {
+    #[serde(rename = "Encrypt")]
+    encrypt: String,
+}
+
+/// 单个小程序的消息推送配置
+///
+/// 对应小程序管理后台「开发管理 - 消息推送」中的 Token 与 EncodingAESKey，
+/// 没有设置 EncodingAESKey 时按明文模式处理。
+#[derive(Clone)]
+pub struct CallbackConfig {
+    app_id: String,
+    token: String,
+    encoding_aes_key: Option<String>,
+}
+
+impl fmt::Debug for CallbackConfig {
+    // 为了安全，不打印 token 与 EncodingAESKey
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CallbackConfig")
+            .field("app_id", &self.app_id)
+            .field("token", &REDACTED)
+            .field("encrypted", &self.encoding_aes_key.is_some())
+            .finish()
+    }
+}
+
+impl CallbackConfig {
+    /// 明文模式的配置
+    pub fn new(app_id: impl Into<String>, token: impl Into<String>) -> Self {
+        CallbackConfig {
+            app_id: app_id.into(),
+            token: token.into(),
+            encoding_aes_key: None,
+        }
+    }
+
+    /// 设置 EncodingAESKey，按安全模式解密推送数据
+    pub fn encoding_aes_key(mut self, encoding_aes_key: impl Into<String>) -> Self {
+        self.encoding_aes_key = Some(encoding_aes_key.into());
+        self
+    }
+
+    pub fn app_id(&self) -> &str {
+        &self.app_id
+    }
+
+    /// 校验配置服务器地址时的请求，通过后返回需要原样响应的 `echostr`
+    pub fn verify_url<'a>(&self, query: &'a CallbackQuery) -> Option<&'a str> {
+        let expected = callback_signature(&self.token, &query.timestamp, &query.nonce, None);
+        let echostr = query.echostr.as_deref()?;
+        constant_time_eq(expected.as_bytes(), query.signature.as_bytes()).then_some(echostr)
+    }
+
+    /// 校验签名并解析推送数据，安全模式下同时解密并校验 AppID
+    pub fn decode(&self, query: &CallbackQuery, body: &str) -> Result<CallbackMessage> {
+        let Some(encoding_aes_key) = &self.encoding_aes_key else {
+            let expected = callback_signature(&self.token, &query.timestamp, &query.nonce, None);
+            if !constant_time_eq(expected.as_bytes(), query.signature.as_bytes()) {
+                return Err(invalid_signature());
+            }
+            return CallbackMessage::from_json(body);
+        };
+
+        let body: EncryptedBody = serde_json::from_str(body)?;
+        self.decrypt(query, &body.encrypt, encoding_aes_key)
+    }
+
+    fn decrypt(
+        &self,
+        query: &CallbackQuery,
+        encrypt: &str,
+        encoding_aes_key: &str,
+    ) -> Result<CallbackMessage> {
+        let msg_signature = query.msg_signature.as_deref().unwrap_or_default();
+        let expected =
+            callback_signature(&self.token, &query.timestamp, &query.nonce, Some(encrypt));
+        if !constant_time_eq(expected.as_bytes(), msg_signature.as_bytes()) {
+            return Err(invalid_signature());
+        }
+
+        let (message, app_id) = decrypt_callback(encrypt, encoding_aes_key)?;
+        if app_id != self.app_id {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "推送数据的 AppID 为 {}，与配置的 {} 不一致",
+                "callback app_id {} does not match configured {}",
+                app_id,
+                self.app_id
+            ))
+            .into());
+        }
+        CallbackMessage::from_json(&message)
+    }
+}
+
+fn invalid_signature() -> Error {
+    ErrorKind::InvalidSignature(localized!("消息推送签名校验失败", "callback signature mismatch")).into()
+}
+
+/// 同一服务接收多个小程序的消息推送时，按 ToUserName 分发到对应的配置与 [`Router`]
+///
+/// 每个小程序使用各自的 Token 与 EncodingAESKey 校验、解密，再交给各自的 [`Router`] 处理。
+///
+/// ```
+/// use wechat_minapp::callback::{CallbackConfig, CallbackQuery, MultiAppRouter, Router};
+///
+/// # #[tokio::main]
+/// # async fn main() -> wechat_minapp::Result<()> {
+/// let shop = Router::new().on("text", |message, _event| async move {
+///     println!("商城收到 {}", message.from_user_name);
+///     Ok(())
+/// });
+/// let router = MultiAppRouter::new()
+///     .app(
+///         "gh_shop",
+///         CallbackConfig::new("wx1111111111111111", "token")
+///             .encoding_aes_key("AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8"),
+///         shop,
+///     )
+///     .app("gh_news", CallbackConfig::new("wx2222222222222222", "token2"), Router::new());
+///
+/// // 在 HTTP 框架中取得 URL 参数与请求体后分发
+/// # let query = CallbackQuery::default();
+/// # let body = r#"{"ToUserName":"gh_unknown","Encrypt":""}"#;
+/// let result = router.dispatch(&query, body).await;
+/// # assert!(result.is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MultiAppRouter {
+    apps: HashMap<String, (CallbackConfig, Router)>,
+}
+
+/// 读取推送数据中的 ToUserName，明文与安全模式都有该字段
+#[derive(Deserialize)]
+struct Recipient {
+    #[serde(rename = "ToUserName")]
+    to_user_name: String,
+}
+
+impl MultiAppRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册小程序，`user_name` 为小程序的原始 ID（gh_ 开头），即推送中的 ToUserName
+    ///
+    /// 同一个 `user_name` 重复注册时，后注册的生效。
+    pub fn app(
+        mut self,
+        user_name: impl Into<String>,
+        config: CallbackConfig,
+        router: Router,
+    ) -> Self {
+        self.apps.insert(user_name.into(), (config, router));
+        self
+    }
+
+    /// 校验配置服务器地址时的请求
+    ///
+    /// 该请求不带 ToUserName，依次使用已注册小程序的 Token 校验，任一通过即返回 `echostr`。
+    pub fn verify_url<'a>(&self, query: &'a CallbackQuery) -> Option<&'a str> {
+        self.apps
+            .values()
+            .find_map(|(config, _)| config.verify_url(query))
+    }
+
+    /// 按 ToUserName 找到对应的小程序，校验签名并解析推送数据
+    pub fn decode(&self, query: &CallbackQuery, body: &str) -> Result<CallbackMessage> {
+        self.route(query, body).map(|(message, _)| message)
+    }
+
+    /// 解析推送数据并交给对应小程序的 [`Router`] 处理
+    pub async fn dispatch(&self, query: &CallbackQuery, body: &str) -> Result<Dispatched> {
+        let (message, router) = self.route(query, body)?;
+        router.dispatch(message).await
+    }
+
+    fn route(&self, query: &CallbackQuery, body: &str) -> Result<(CallbackMessage, &Router)> {
+        let recipient: Recipient = serde_json::from_str(body)?;
+        let Some((config, router)) = self.apps.get(&recipient.to_user_name) else {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "未注册的小程序：{}",
+                "unregistered mini program: {}",
+                recipient.to_user_name
+            ))
+            .into());
+        };
+        debug!("route callback for {} to {}", recipient.to_user_name, config.app_id);
+
+        let message = config.decode(query, body)?;
+        Ok((message, router))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    const KEY_A: &str = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8";
+    const KEY_B: &str = "ZGVmZ2hpamtsbW5vcHFyc3R1dnd4eXp7fH1+f4CBgoM";
+
+    /// gh_aaa 的文本消息，使用 KEY_A 加密，AppID 为 wx1111111111111111
+    const ENCRYPT_A: &str = concat!(
+        "4j/AuRx71kQlxVlzbpsMWCfzY2bLsF3vHbWmQj78/VCE+EmxYvjCqKHlht1HrFyJSVGJXmICuOUF1HRWqFhMFCG",
+        "cHtatwwLQyBxZddUrWsjJXii0Kl2nuqIw/FREYBQO9VvckyblNEwdqoFwDWGXI98LQ+KZqziKK074bIdiHzh1cv",
+        "CD+K5L8rDu4Qtq7iWmucW4Cz+cR+TzhjBzyP4m7w=="
+    );
+
+    /// gh_bbb 的 wxa_media_check 事件，使用 KEY_B 加密，AppID 为 wx2222222222222222
+    const ENCRYPT_B: &str = concat!(
+        "cQtjmYniyiO+3Vmb/QD6Eg0t11S50NEp5+ez9QZZSk2UxdOw8YSIbe6xQ0UbBro24pRB+1eIJjDWSFwX9yONHWn",
+        "ntNeSsO5fUpko3xIerpzGYqLTtvQOXDCI1lswYnxJxTU+atCSm4U1WllyH86trQbBGiHus9ha5qAizHgbsTnKZt",
+        "dDQzLQ1yTrV5NB3COwAW2jeNfdsy35bEsGAfyLQOVKqpJxTdFMeH53hwTRPyXyNj8O4M/hFMdwP+3gBQ9E"
+    );
+
+    fn encrypted(token: &str, to_user_name: &str, encrypt: &str) -> (CallbackQuery, String) {
+        let msg_signature = callback_signature(token, "1409659813", "1372623149", Some(encrypt));
+        let query = CallbackQuery {
+            timestamp: "1409659813".to_string(),
+            nonce: "1372623149".to_string(),
+            encrypt_type: Some("aes".to_string()),
+            msg_signature: Some(msg_signature),
+            ..Default::default()
+        };
+        let body = format!(r#"{{"ToUserName":"{}","Encrypt":"{}"}}"#, to_user_name, encrypt);
+        (query, body)
+    }
+
+    fn config_a() -> CallbackConfig {
+        CallbackConfig::new("wx1111111111111111", "token_a").encoding_aes_key(KEY_A)
+    }
+
+    #[test]
+    fn test_decode() {
+        let (query, body) = encrypted("token_a", "gh_aaa", ENCRYPT_A);
+        let message = config_a().decode(&query, &body).unwrap();
+        assert_eq!(message.to_user_name, "gh_aaa");
+        assert_eq!(message.extra.get_as::<String>("Content").unwrap(), "你好");
+
+        // 签名错误
+        let (query, body) = encrypted("token_b", "gh_aaa", ENCRYPT_A);
+        let error = config_a().decode(&query, &body).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidSignature(_)));
+
+        // AppID 不一致
+        let (query, body) = encrypted("token_a", "gh_aaa", ENCRYPT_A);
+        let config = CallbackConfig::new("wx2222222222222222", "token_a").encoding_aes_key(KEY_A);
+        assert!(matches!(
+            config.decode(&query, &body).unwrap_err().kind(),
+            ErrorKind::InvalidParameter(_)
+        ));
+    }
+
+    #[test]
+    fn test_verify_url() {
+        let query = CallbackQuery {
+            signature: callback_signature("token_a", "1409659813", "1372623149", None),
+            timestamp: "1409659813".to_string(),
+            nonce: "1372623149".to_string(),
+            echostr: Some("echo".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(config_a().verify_url(&query), Some("echo"));
+
+        let router = MultiAppRouter::new()
+            .app("gh_bbb", CallbackConfig::new("wx2222222222222222", "token_b"), Router::new())
+            .app("gh_aaa", config_a(), Router::new());
+        assert_eq!(router.verify_url(&query), Some("echo"));
+        let router = MultiAppRouter::new()
+            .app("gh_bbb", CallbackConfig::new("wx2222222222222222", "token_b"), Router::new());
+        assert_eq!(router.verify_url(&query), None);
+    }
+
+    #[tokio::test]
+    async fn test_multi_app_dispatch() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_a = seen.clone();
+        let seen_b = seen.clone();
+        let router = MultiAppRouter::new()
+            .app(
+                "gh_aaa",
+                config_a(),
+                Router::new().on("text", move |message, _event| {
+                    seen_a.lock().unwrap().push(format!("a {}", message.from_user_name));
+                    async { Ok(()) }
+                }),
+            )
+            .app(
+                "gh_bbb",
+                CallbackConfig::new("wx2222222222222222", "token_b").encoding_aes_key(KEY_B),
+                Router::new().fallback(move |_message, event| {
+                    seen_b.lock().unwrap().push(format!("b {}", event.name()));
+                    async { Ok(()) }
+                }),
+            );
+
+        let (query, body) = encrypted("token_a", "gh_aaa", ENCRYPT_A);
+        assert_eq!(router.dispatch(&query, &body).await.unwrap(), Dispatched::Handled);
+        let (query, body) = encrypted("token_b", "gh_bbb", ENCRYPT_B);
+        assert_eq!(router.dispatch(&query, &body).await.unwrap(), Dispatched::Fallback);
+        assert_eq!(*seen.lock().unwrap(), ["a OPENID", "b wxa_media_check"]);
+
+        // gh_bbb 的 Token 不能用于 gh_aaa 的推送
+        let (query, body) = encrypted("token_b", "gh_aaa", ENCRYPT_A);
+        assert!(matches!(
+            router.decode(&query, &body).unwrap_err().kind(),
+            ErrorKind::InvalidSignature(_)
+        ));
+
+        // 未注册的小程序是推送数据的问题，不是微信返回的 40013
+        let (query, body) = encrypted("token_a", "gh_ccc", ENCRYPT_A);
+        let error = router.decode(&query, &body).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidParameter(_)));
+        assert_eq!(error.code(), None);
+        assert_eq!(error.http_status(), 400);
+    }
+}
@@ -0,0 +1,202 @@
+//! 推送消息去重
+
+use async_trait::async_trait;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+use wechat_core::Result;
+
+/// 默认记录时长，覆盖微信的全部重试
+const DEFAULT_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// 定义推送消息去重记录的行为
+#[async_trait]
+pub trait Deduplicator: Send + Sync {
+    /// 记录消息指纹，首次出现返回 `true`，已经记录过返回 `false`
+    ///
+    /// 检查与记录必须是原子操作，否则并发的重试推送可能都被当作首次出现。
+    async fn first_seen(&self, fingerprint: &str) -> Result<bool>;
+
+    /// 删除消息指纹的记录，处理失败时调用，微信重试推送时会再次处理
+    async fn release(&self, fingerprint: &str) -> Result<()>;
+}
+
+#[derive(Debug, Default)]
+struct Seen {
+    expires: HashMap<String, Instant>,
+    // 按记录顺序排列，过期时间单调递增，从队头清理过期记录
+    order: VecDeque<(Instant, String)>,
+}
+
+/// 内存去重，进程重启后失效，只适合单实例或测试使用
+#[derive(Debug, Clone)]
+pub struct MemoryDeduplicator {
+    seen: Arc<Mutex<Seen>>,
+    ttl: Duration,
+}
+
+impl Default for MemoryDeduplicator {
+    fn default() -> Self {
+        MemoryDeduplicator {
+            seen: Arc::default(),
+            ttl: DEFAULT_TTL,
+        }
+    }
+}
+
+impl MemoryDeduplicator {
+    /// 记录保留 5 分钟
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录保留时长
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+}
+
+#[async_trait]
+impl Deduplicator for MemoryDeduplicator {
+    async fn first_seen(&self, fingerprint: &str) -> Result<bool> {
+        let now = Instant::now();
+        let mut seen = self.seen.lock().await;
+
+        while let Some((expires_at, _)) = seen.order.front() {
+            if *expires_at > now {
+                break;
+            }
+            if let Some((expires_at, key)) = seen.order.pop_front() {
+                if seen.expires.get(&key) == Some(&expires_at) {
+                    seen.expires.remove(&key);
+                }
+            }
+        }
+
+        if seen.expires.contains_key(fingerprint) {
+            return Ok(false);
+        }
+        let expires_at = now + self.ttl;
+        seen.expires.insert(fingerprint.to_string(), expires_at);
+        seen.order.push_back((expires_at, fingerprint.to_string()));
+        Ok(true)
+    }
+
+    async fn release(&self, fingerprint: &str) -> Result<()> {
+        // order 中的记录过期时会因为时间不一致被跳过
+        self.seen.lock().await.expires.remove(fingerprint);
+        Ok(())
+    }
+}
+
+/// redis 去重，需要启用 `redis` feature
+///
+/// 使用 `SET key 1 NX EX ttl` 记录消息指纹，多个实例收到同一条推送时只有一个会处理。
+///
+/// ```no_run
+/// use wechat_minapp::callback::RedisDeduplicator;
+///
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = redis::Client::open("redis://127.0.0.1/")?;
+/// let connection = client.get_multiplexed_async_connection().await?;
+/// let dedup = RedisDeduplicator::new(connection).prefix("shop:callback:");
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "redis")]
+#[derive(Clone)]
+pub struct RedisDeduplicator {
+    connection: redis::aio::MultiplexedConnection,
+    prefix: String,
+    ttl: Duration,
+}
+
+#[cfg(feature = "redis")]
+impl std::fmt::Debug for RedisDeduplicator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisDeduplicator")
+            .field("prefix", &self.prefix)
+            .field("ttl", &self.ttl)
+            .finish()
+    }
+}
+
+#[cfg(feature = "redis")]
+impl RedisDeduplicator {
+    /// 使用默认的 key 前缀 `wechat_minapp:callback:` 创建，记录保留 5 分钟
+    pub fn new(connection: redis::aio::MultiplexedConnection) -> Self {
+        RedisDeduplicator {
+            connection,
+            prefix: "wechat_minapp:callback:".to_string(),
+            ttl: DEFAULT_TTL,
+        }
+    }
+
+    /// key 前缀
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// 记录保留时长
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl Deduplicator for RedisDeduplicator {
+    async fn first_seen(&self, fingerprint: &str) -> Result<bool> {
+        let mut connection = self.connection.clone();
+        let reply: Option<String> = redis::cmd("SET")
+            .arg(format!("{}{}", self.prefix, fingerprint))
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(self.ttl.as_secs().max(1))
+            .query_async(&mut connection)
+            .await
+            .map_err(|e| wechat_core::ErrorKind::InternalServer(format!("redis: {}", e)))?;
+
+        Ok(reply.is_some())
+    }
+
+    async fn release(&self, fingerprint: &str) -> Result<()> {
+        let mut connection = self.connection.clone();
+        redis::cmd("DEL")
+            .arg(format!("{}{}", self.prefix, fingerprint))
+            .query_async::<()>(&mut connection)
+            .await
+            .map_err(|e| wechat_core::ErrorKind::InternalServer(format!("redis: {}", e)).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_memory_deduplicator() {
+        let dedup = MemoryDeduplicator::new().ttl(Duration::from_secs(10));
+
+        assert!(dedup.first_seen("a").await.unwrap());
+        assert!(!dedup.first_seen("a").await.unwrap());
+        assert!(dedup.first_seen("b").await.unwrap());
+
+        tokio::time::advance(Duration::from_secs(11)).await;
+        assert!(dedup.first_seen("a").await.unwrap());
+        assert_eq!(dedup.seen.lock().await.expires.len(), 1);
+
+        // 撤销后再次出现视为首次，过期清理不会误删新记录
+        dedup.release("a").await.unwrap();
+        tokio::time::advance(Duration::from_secs(5)).await;
+        assert!(dedup.first_seen("a").await.unwrap());
+        tokio::time::advance(Duration::from_secs(6)).await;
+        assert!(!dedup.first_seen("a").await.unwrap());
+    }
+}
@@ -0,0 +1,212 @@
+//! 推送消息的类型化内容
+
+use super::CallbackMessage;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// 推送消息或事件的内容
+///
+/// 未收录或字段不符合预期的推送转换为 [`Event::Unknown`]，保留原始数据，
+/// 微信新增事件类型时不会因为解析失败丢失消息。
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Event {
+    /// 文本消息
+    Text {
+        /// 文本内容
+        content: String,
+    },
+    /// 图片消息
+    Image {
+        /// 图片链接
+        pic_url: String,
+        /// 图片的临时素材 ID
+        media_id: String,
+    },
+    /// 小程序卡片消息
+    MiniprogramPage {
+        /// 标题
+        title: String,
+        /// 小程序 appid
+        app_id: String,
+        /// 小程序页面路径
+        page_path: String,
+        /// 封面图片的临时 cdn 链接
+        thumb_url: String,
+        /// 封面图片的临时素材 ID
+        thumb_media_id: String,
+    },
+    /// 用户进入客服会话
+    UserEnterTempsession {
+        /// 开发者在客服会话按钮设置的 session-from 属性
+        session_from: String,
+    },
+    /// 未收录的消息或事件
+    Unknown {
+        /// 消息类型
+        msg_type: String,
+        /// 事件类型
+        event: Option<String>,
+        /// 推送的原始数据
+        raw: Value,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TextBody {
+    content: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ImageBody {
+    pic_url: String,
+    media_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct MiniprogramPageBody {
+    title: String,
+    #[serde(rename = "AppId")]
+    app_id: String,
+    page_path: String,
+    #[serde(default)]
+    thumb_url: String,
+    #[serde(default)]
+    thumb_media_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TempsessionBody {
+    #[serde(default)]
+    session_from: String,
+}
+
+impl Event {
+    /// 路由使用的事件名：事件推送为 Event 字段，普通消息为 MsgType 字段
+    pub fn name(&self) -> &str {
+        match self {
+            Event::Text { .. } => "text",
+            Event::Image { .. } => "image",
+            Event::MiniprogramPage { .. } => "miniprogrampage",
+            Event::UserEnterTempsession { .. } => "user_enter_tempsession",
+            Event::Unknown {
+                msg_type, event, ..
+            } => event.as_deref().unwrap_or(msg_type),
+        }
+    }
+
+    /// 是否为未收录的消息或事件
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Event::Unknown { .. })
+    }
+}
+
+impl From<&CallbackMessage> for Event {
+    fn from(message: &CallbackMessage) -> Self {
+        let raw = serde_json::to_value(message).unwrap_or_default();
+        let known = match (message.msg_type.as_str(), message.event.as_deref()) {
+            ("text", _) => body::<TextBody>(&raw).map(|body| Event::Text {
+                content: body.content,
+            }),
+            ("image", _) => body::<ImageBody>(&raw).map(|body| Event::Image {
+                pic_url: body.pic_url,
+                media_id: body.media_id,
+            }),
+            ("miniprogrampage", _) => {
+                body::<MiniprogramPageBody>(&raw).map(|body| Event::MiniprogramPage {
+                    title: body.title,
+                    app_id: body.app_id,
+                    page_path: body.page_path,
+                    thumb_url: body.thumb_url,
+                    thumb_media_id: body.thumb_media_id,
+                })
+            }
+            ("event", Some("user_enter_tempsession")) => {
+                body::<TempsessionBody>(&raw).map(|body| Event::UserEnterTempsession {
+                    session_from: body.session_from,
+                })
+            }
+            _ => None,
+        };
+
+        known.unwrap_or_else(|| Event::Unknown {
+            msg_type: message.msg_type.clone(),
+            event: message.event.clone(),
+            raw,
+        })
+    }
+}
+
+fn body<T: DeserializeOwned>(raw: &Value) -> Option<T> {
+    T::deserialize(raw).ok()
+}
+
+impl CallbackMessage {
+    /// 转换为类型化的 [`Event`]，不会失败
+    pub fn to_event(&self) -> Event {
+        Event::from(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(body: &str) -> Event {
+        CallbackMessage::from_json(body).unwrap().to_event()
+    }
+
+    #[test]
+    fn test_known_events() {
+        let text = event(
+            r#"{"ToUserName":"gh_123","FromUserName":"OPENID","CreateTime":1482048670,
+            "MsgType":"text","Content":"你好","MsgId":1}"#,
+        );
+        assert_eq!(text, Event::Text { content: "你好".to_string() });
+        assert_eq!(text.name(), "text");
+
+        let page = event(
+            r#"{"ToUserName":"gh_123","FromUserName":"OPENID","CreateTime":1482048670,
+            "MsgType":"miniprogrampage","MsgId":2,"Title":"标题","AppId":"wx123",
+            "PagePath":"pages/index/index","ThumbUrl":"https://example.com/a.png",
+            "ThumbMediaId":"media"}"#,
+        );
+        assert!(matches!(page, Event::MiniprogramPage { ref page_path, .. }
+            if page_path == "pages/index/index"));
+
+        let tempsession = event(
+            r#"{"ToUserName":"gh_123","FromUserName":"OPENID","CreateTime":1482048670,
+            "MsgType":"event","Event":"user_enter_tempsession","SessionFrom":"from"}"#,
+        );
+        assert_eq!(tempsession.name(), "user_enter_tempsession");
+    }
+
+    #[test]
+    fn test_unknown_event() {
+        let unknown = event(
+            r#"{"ToUserName":"gh_123","FromUserName":"system","CreateTime":1482048670,
+            "MsgType":"event","Event":"wxa_media_check","trace_id":"trace"}"#,
+        );
+        assert!(unknown.is_unknown());
+        assert_eq!(unknown.name(), "wxa_media_check");
+        let Event::Unknown { msg_type, raw, .. } = unknown else {
+            unreachable!()
+        };
+        assert_eq!(msg_type, "event");
+        assert_eq!(raw["trace_id"], "trace");
+        assert_eq!(raw["FromUserName"], "system");
+
+        // 字段不符合预期时同样保留原始数据
+        let malformed = event(
+            r#"{"ToUserName":"gh_123","FromUserName":"OPENID","CreateTime":1482048670,
+            "MsgType":"image","MsgId":3}"#,
+        );
+        assert!(malformed.is_unknown());
+        assert_eq!(malformed.name(), "image");
+    }
+}
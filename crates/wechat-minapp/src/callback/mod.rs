@@ -0,0 +1,178 @@
+//! 消息推送
+//!
+//! 解析微信推送到服务器的消息与事件（需要在小程序管理后台将数据格式设置为 JSON）。
+//! [`CallbackMessage::to_event`] 转换为类型化的 [`Event`]，未收录的事件保留在
+//! [`Event::Unknown`] 中；[`Router`] 按事件名分发给处理函数，并支持兜底处理函数。
+//!
+//! 服务器没有在 5 秒内响应时微信会重试推送，同一条消息最多推送三次。
+//! [`Deduplicator`] 按 [`CallbackMessage::fingerprint`] 记录已经处理过的消息，
+//! 默认提供内存实现 [`MemoryDeduplicator`]，启用 `redis` feature 后可以使用
+//! `RedisDeduplicator` 在多实例之间共享。处理失败时调用 [`CallbackMessage::release`]
+//! 撤销记录，微信重试推送时会再次处理；[`Router::deduplicator`] 会自动完成这一步。
+//!
+//! [`CallbackConfig`] 按小程序的 Token 与 EncodingAESKey 校验签名、解密安全模式的推送；
+//! 同一服务接收多个小程序的推送时，[`MultiAppRouter`] 按 ToUserName 分发到各自的配置与 [`Router`]。
+//!
+//! ## 示例
+//!
+//! ```
+//! use wechat_minapp::callback::{CallbackMessage, MemoryDeduplicator};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> wechat_minapp::Result<()> {
+//! let dedup = MemoryDeduplicator::new();
+//! let body = r#"{"ToUserName":"gh_123","FromUserName":"OPENID","CreateTime":1482048670,
+//!     "MsgType":"text","Content":"你好","MsgId":1234567890123456}"#;
+//!
+//! let message = CallbackMessage::from_json(body)?;
+//! assert!(message.clone().deduplicate(&dedup).await?.is_some());
+//!
+//! // 微信重试推送的同一条消息被丢弃
+//! assert!(message.clone().deduplicate(&dedup).await?.is_none());
+//!
+//! // 处理失败时撤销记录，重试推送的消息会再次处理
+//! message.release(&dedup).await?;
+//! assert!(message.deduplicate(&dedup).await?.is_some());
+//! # Ok(())
+//! # }
+//! ```
+
+mod app;
+mod dedup;
+mod event;
+mod router;
+
+#[cfg(feature = "redis")]
+pub use dedup::RedisDeduplicator;
+pub use app::{CallbackConfig, CallbackQuery, MultiAppRouter};
+pub use dedup::{Deduplicator, MemoryDeduplicator};
+pub use event::Event;
+pub use router::{Dispatched, Router};
+
+use serde::{Deserialize, Serialize};
+use wechat_core::response::ExtraFields;
+use wechat_core::utils::UnixTimestamp;
+use wechat_core::Result;
+
+/// 微信推送的消息或事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallbackMessage {
+    /// 小程序的原始 ID
+    #[serde(rename = "ToUserName")]
+    pub to_user_name: String,
+    /// 发送者的 openid，系统事件为 `"system"` 等固定值
+    #[serde(rename = "FromUserName")]
+    pub from_user_name: String,
+    /// 消息创建时间
+    #[serde(rename = "CreateTime")]
+    pub create_time: UnixTimestamp,
+    /// 消息类型，事件推送为 `event`
+    #[serde(rename = "MsgType")]
+    pub msg_type: String,
+    /// 事件类型，只有事件推送才有
+    #[serde(rename = "Event", default, skip_serializing_if = "Option::is_none")]
+    pub event: Option<String>,
+    /// 消息 ID，普通消息才有
+    #[serde(rename = "MsgId", default, skip_serializing_if = "Option::is_none")]
+    pub msg_id: Option<u64>,
+    /// 消息或事件的其余字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+impl CallbackMessage {
+    /// 解析推送的 JSON 数据
+    pub fn from_json(body: &str) -> Result<Self> {
+        Ok(serde_json::from_str(body)?)
+    }
+
+    /// 是否为事件推送
+    pub fn is_event(&self) -> bool {
+        self.msg_type == "event"
+    }
+
+    /// 消息指纹，用于去重
+    ///
+    /// 普通消息使用 MsgId；事件没有 MsgId，使用 FromUserName + CreateTime + Event，
+    /// 与微信文档推荐的排重方式一致。都带上 ToUserName，多个小程序可以共用一个 [`Deduplicator`]。
+    pub fn fingerprint(&self) -> String {
+        match self.msg_id {
+            Some(msg_id) => format!("{}:{}", self.to_user_name, msg_id),
+            None => format!(
+                "{}:{}:{}:{}",
+                self.to_user_name,
+                self.from_user_name,
+                self.create_time.raw(),
+                self.event.as_deref().unwrap_or(&self.msg_type)
+            ),
+        }
+    }
+
+    /// 是否已经处理过，首次调用会记录该消息
+    ///
+    /// 适合需要标记重复消息、仍然继续处理的场景。
+    pub async fn is_duplicate(&self, dedup: &dyn Deduplicator) -> Result<bool> {
+        Ok(!dedup.first_seen(&self.fingerprint()).await?)
+    }
+
+    /// 丢弃重复的消息，已经处理过时返回 `None`
+    ///
+    /// 返回 `Some` 时消息已被记录，处理失败需要调用 [`release`](Self::release)，
+    /// 否则微信重试推送的消息会被当作重复丢弃。
+    pub async fn deduplicate(self, dedup: &dyn Deduplicator) -> Result<Option<Self>> {
+        if self.is_duplicate(dedup).await? {
+            Ok(None)
+        } else {
+            Ok(Some(self))
+        }
+    }
+
+    /// 撤销该消息的去重记录
+    pub async fn release(&self, dedup: &dyn Deduplicator) -> Result<()> {
+        dedup.release(&self.fingerprint()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = r#"{"ToUserName":"gh_123","FromUserName":"OPENID",
+        "CreateTime":1482048670,"MsgType":"text","Content":"你好","MsgId":1234567890123456}"#;
+
+    const EVENT: &str = r#"{"ToUserName":"gh_123","FromUserName":"OPENID",
+        "CreateTime":"1482048670","MsgType":"event","Event":"user_enter_tempsession",
+        "SessionFrom":"sessionFrom"}"#;
+
+    #[test]
+    fn test_from_json() {
+        let message = CallbackMessage::from_json(TEXT).unwrap();
+        assert!(!message.is_event());
+        assert_eq!(message.extra.get_as::<String>("Content").unwrap(), "你好");
+        assert_eq!(message.fingerprint(), "gh_123:1234567890123456");
+
+        let event = CallbackMessage::from_json(EVENT).unwrap();
+        assert!(event.is_event());
+        assert_eq!(event.msg_id, None);
+        assert_eq!(event.fingerprint(), "gh_123:OPENID:1482048670:user_enter_tempsession");
+
+        assert!(CallbackMessage::from_json(r#"{"MsgType":"text"}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_deduplicate() {
+        let dedup = MemoryDeduplicator::new();
+        let event = CallbackMessage::from_json(EVENT).unwrap();
+
+        assert!(!event.is_duplicate(&dedup).await.unwrap());
+        assert!(event.is_duplicate(&dedup).await.unwrap());
+        assert!(event.clone().deduplicate(&dedup).await.unwrap().is_none());
+
+        let text = CallbackMessage::from_json(TEXT).unwrap();
+        assert_eq!(text.clone().deduplicate(&dedup).await.unwrap(), Some(text.clone()));
+
+        event.release(&dedup).await.unwrap();
+        assert!(!event.is_duplicate(&dedup).await.unwrap());
+        assert!(text.is_duplicate(&dedup).await.unwrap());
+    }
+}
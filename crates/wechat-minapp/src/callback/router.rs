@@ -0,0 +1,259 @@
+//! 按事件名分发推送消息
+
+use super::{CallbackMessage, Deduplicator, Event};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tracing::warn;
+use wechat_core::Result;
+
+type HandlerFuture = Pin<Box<dyn Future<Output = Result<()>> + Send>>;
+type Handler = Arc<dyn Fn(CallbackMessage, Event) -> HandlerFuture + Send + Sync>;
+
+/// 推送消息的分发结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dispatched {
+    /// 由注册的处理函数处理
+    Handled,
+    /// 没有对应的处理函数，由兜底处理函数处理
+    Fallback,
+    /// 没有对应的处理函数，也没有注册兜底处理函数
+    Ignored,
+    /// 重复推送的消息，没有交给处理函数
+    Duplicate,
+}
+
+/// 按 [`Event::name`] 把推送消息分发给对应的处理函数
+///
+/// 没有对应处理函数的消息（包括 [`Event::Unknown`]）交给 [`fallback`](Self::fallback)
+/// 注册的兜底处理函数，可以记录原始数据或转发到消息队列，避免丢消息。
+///
+/// ```
+/// use wechat_minapp::callback::{CallbackMessage, Dispatched, Event, Router};
+///
+/// # #[tokio::main]
+/// # async fn main() -> wechat_minapp::Result<()> {
+/// let router = Router::new()
+///     .on("text", |message: CallbackMessage, event: Event| async move {
+///         println!("{} 发送了 {:?}", message.from_user_name, event);
+///         Ok(())
+///     })
+///     .fallback(|_message, event| async move {
+///         if let Event::Unknown { raw, .. } = event {
+///             println!("未处理的推送 {}", raw);
+///         }
+///         Ok(())
+///     });
+///
+/// let message = CallbackMessage::from_json(
+///     r#"{"ToUserName":"gh_123","FromUserName":"system","CreateTime":1482048670,
+///     "MsgType":"event","Event":"wxa_media_check"}"#,
+/// )?;
+/// assert_eq!(router.dispatch(message).await?, Dispatched::Fallback);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct Router {
+    handlers: HashMap<String, Handler>,
+    fallback: Option<Handler>,
+    deduplicator: Option<Arc<dyn Deduplicator>>,
+}
+
+impl fmt::Debug for Router {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Router")
+            .field("handlers", &self.handlers.keys().collect::<Vec<_>>())
+            .field("fallback", &self.fallback.is_some())
+            .field("deduplicator", &self.deduplicator.is_some())
+            .finish()
+    }
+}
+
+fn handler<F, Fut>(handler: F) -> Handler
+where
+    F: Fn(CallbackMessage, Event) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<()>> + Send + 'static,
+{
+    Arc::new(move |message, event| Box::pin(handler(message, event)))
+}
+
+impl Router {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册处理函数，`name` 为事件推送的 Event 字段或普通消息的 MsgType 字段
+    ///
+    /// 同一个 `name` 重复注册时，后注册的生效。
+    pub fn on<F, Fut>(mut self, name: impl Into<String>, handler_fn: F) -> Self
+    where
+        F: Fn(CallbackMessage, Event) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.handlers.insert(name.into(), handler(handler_fn));
+        self
+    }
+
+    /// 注册兜底处理函数，处理没有对应处理函数的消息
+    pub fn fallback<F, Fut>(mut self, handler_fn: F) -> Self
+    where
+        F: Fn(CallbackMessage, Event) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.fallback = Some(handler(handler_fn));
+        self
+    }
+
+    /// 分发前丢弃重复推送的消息
+    ///
+    /// 处理函数返回错误时撤销记录，微信重试推送时会再次处理。
+    pub fn deduplicator(mut self, deduplicator: Arc<dyn Deduplicator>) -> Self {
+        self.deduplicator = Some(deduplicator);
+        self
+    }
+
+    /// 分发推送消息，返回处理函数的结果
+    pub async fn dispatch(&self, message: CallbackMessage) -> Result<Dispatched> {
+        let Some(deduplicator) = &self.deduplicator else {
+            return self.handle(message).await;
+        };
+
+        let fingerprint = message.fingerprint();
+        if !deduplicator.first_seen(&fingerprint).await? {
+            return Ok(Dispatched::Duplicate);
+        }
+        let result = self.handle(message).await;
+        if result.is_err() {
+            // 撤销失败只影响微信重试时能否再次处理，返回处理函数自身的错误
+            if let Err(error) = deduplicator.release(&fingerprint).await {
+                warn!("failed to release callback {}: {}", fingerprint, error);
+            }
+        }
+        result
+    }
+
+    async fn handle(&self, message: CallbackMessage) -> Result<Dispatched> {
+        let event = message.to_event();
+
+        if let Some(handler) = self.handlers.get(event.name()) {
+            handler(message, event).await?;
+            return Ok(Dispatched::Handled);
+        }
+
+        match &self.fallback {
+            Some(fallback) => {
+                fallback(message, event).await?;
+                Ok(Dispatched::Fallback)
+            }
+            None => {
+                warn!("no handler for callback {}: {}", event.name(), message.fingerprint());
+                Ok(Dispatched::Ignored)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::callback::MemoryDeduplicator;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use wechat_core::ErrorKind;
+
+    fn message(msg_type: &str, event: &str) -> CallbackMessage {
+        CallbackMessage::from_json(&format!(
+            r#"{{"ToUserName":"gh_123","FromUserName":"OPENID","CreateTime":1482048670,
+            "MsgType":"{}","Event":"{}","Content":"你好"}}"#,
+            msg_type, event
+        ))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_dispatch() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let text_seen = seen.clone();
+        let fallback_seen = seen.clone();
+        let router = Router::new()
+            .on("text", move |_message, event| {
+                text_seen.lock().unwrap().push(event.name().to_string());
+                async { Ok(()) }
+            })
+            .on("user_enter_tempsession", |_message, _event| async {
+                Err(ErrorKind::System("busy".to_string()).into())
+            })
+            .fallback(move |_message, event| {
+                fallback_seen.lock().unwrap().push(format!("fallback {}", event.name()));
+                async { Ok(()) }
+            });
+
+        let dispatched = router.dispatch(message("text", "")).await.unwrap();
+        assert_eq!(dispatched, Dispatched::Handled);
+        let dispatched = router.dispatch(message("event", "new_event")).await.unwrap();
+        assert_eq!(dispatched, Dispatched::Fallback);
+        assert_eq!(*seen.lock().unwrap(), ["text", "fallback new_event"]);
+
+        let result = router.dispatch(message("event", "user_enter_tempsession")).await;
+        assert!(result.is_err());
+
+        let dispatched = Router::new().dispatch(message("voice", "")).await.unwrap();
+        assert_eq!(dispatched, Dispatched::Ignored);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_deduplicate() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let text_attempts = attempts.clone();
+        let router = Router::new()
+            .on("text", move |_message, _event| {
+                // 第一次处理失败
+                let failed = text_attempts.fetch_add(1, Ordering::SeqCst) == 0;
+                async move {
+                    if failed {
+                        return Err(ErrorKind::System("busy".to_string()).into());
+                    }
+                    Ok(())
+                }
+            })
+            .deduplicator(Arc::new(MemoryDeduplicator::new()));
+
+        // 失败后撤销记录，微信重试推送时再次处理
+        assert!(router.dispatch(message("text", "")).await.is_err());
+        let dispatched = router.dispatch(message("text", "")).await.unwrap();
+        assert_eq!(dispatched, Dispatched::Handled);
+
+        let dispatched = router.dispatch(message("text", "")).await.unwrap();
+        assert_eq!(dispatched, Dispatched::Duplicate);
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_release_failed() {
+        struct FailingRelease;
+
+        #[async_trait::async_trait]
+        impl Deduplicator for FailingRelease {
+            async fn first_seen(&self, _fingerprint: &str) -> Result<bool> {
+                Ok(true)
+            }
+
+            async fn release(&self, _fingerprint: &str) -> Result<()> {
+                Err(ErrorKind::System("redis down".to_string()).into())
+            }
+        }
+
+        let router = Router::new()
+            .on("text", |_message, _event| async {
+                Err(ErrorKind::System("busy".to_string()).into())
+            })
+            .deduplicator(Arc::new(FailingRelease));
+
+        // 撤销记录失败时仍返回处理函数的错误
+        let error = router.dispatch(message("text", "")).await.unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::System(message) if message == "busy"));
+    }
+}
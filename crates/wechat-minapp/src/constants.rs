@@ -14,6 +14,7 @@
 //!
 //! - [`CHECK_SESSION_KEY_END_POINT`] - 检查会话密钥有效性
 //! - [`RESET_SESSION_KEY_END_POINT`] - 重置用户会话密钥
+//! - [`USER_ENCRYPT_KEY_END_POINT`] - 获取用户加密 key
 //!
 //! ## 用户信息相关
 //!
@@ -24,11 +25,22 @@
 //!
 //! - [`QR_CODE_ENDPOINT`] - 生成小程序二维码
 //! - [`MSG_SEC_CHECK_END_POINT`] - 内容安全检测
+//! - [`MEDIA_CHECK_ASYNC_END_POINT`] - 音视频、图片内容安全异步检测
+//! - [`URL_SCHEME_END_POINT`] - 获取 URL Scheme
+//!
+//! # 接口定义
+//!
+//! 模块内部通过 [`Endpoint`] 发起请求，每个接口的 HTTP 方法和地址在编译期绑定，
+//! 避免复制粘贴时用错常量或方法。
 //!
 //! # 版本信息
 //!
 //! 这些端点对应微信小程序最新的 API 版本，会随着微信官方 API 的更新而维护。
 
+use http::Method;
+use std::fmt;
+use wechat_core::utils::{Idempotency, RequestBuilder};
+
 /// wechat-minapp crate 当前版本
 pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -85,6 +97,13 @@ pub const QR_CODE_ENDPOINT: &str = "https://api.weixin.qq.com/wxa/getwxacode";
 /// [文本安全检测](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/sec-center/sec-check/msgSecCheck.html)
 pub const MSG_SEC_CHECK_END_POINT: &str = "https://api.weixin.qq.com/wxa/msg_sec_check";
 
+/// 音视频、图片内容安全异步检测的 API 端点
+///
+/// # 官方文档
+///
+/// [多媒体内容安全识别](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/sec-center/sec-check/mediaCheckAsync.html)
+pub const MEDIA_CHECK_ASYNC_END_POINT: &str = "https://api.weixin.qq.com/wxa/media_check_async";
+
 /// HTTP 客户端的 User-Agent 字符串
 pub const HTTP_CLIENT_USER_AGENT: &str =
     "wechat-minapp/3.4.1 (A rust sdk for wechat miniprogram server api)";
@@ -96,6 +115,13 @@ pub const HTTP_CLIENT_USER_AGENT: &str =
 /// [获取 ShortLink](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/qrcode-link/short-link/generateShortLink.html)
 pub const SHORT_LINK_END_POINT: &str = "https://api.weixin.qq.com/wxa/genwxashortlink";
 
+/// 获取 URL Link 的 API 端点
+///
+/// # 官方文档
+///
+/// [获取 URL Link](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/qrcode-link/url-link/generateUrlLink.html)
+pub const URL_LINK_END_POINT: &str = "https://api.weixin.qq.com/wxa/generate_urllink";
+
 /// 生成小程序无限制小程序码的 API 端点
 ///
 /// # 官方文档
@@ -121,3 +147,344 @@ pub const TEMPLATE_MESSAGE_SEND_END_POINT: &str =
 /// [发送服务号模板消息](https://developers.weixin.qq.com/doc/service/api/notify/template/api_sendtemplatemessage.html)
 pub const MP_MESSAGE_SEND_END_POINT: &str =
     "https://api.weixin.qq.com/cgi-bin/message/template/send";
+
+/// 查询 rid 信息的 API 端点
+///
+/// # 官方文档
+///
+/// [查询 rid 信息](https://developers.weixin.qq.com/miniprogram/dev/server/API/openApi-mgnt/api_getridinfo.html)
+pub const RID_INFO_END_POINT: &str = "https://api.weixin.qq.com/cgi-bin/openapi/rid/get";
+
+/// 查询接口调用额度的 API 端点
+///
+/// # 官方文档
+///
+/// [查询 API 调用额度](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/openApi-mgnt/getApiQuota.html)
+pub const API_QUOTA_END_POINT: &str = "https://api.weixin.qq.com/cgi-bin/openapi/quota/get";
+
+/// 获取用户访问小程序数据概况的 API 端点
+///
+/// # 官方文档
+///
+/// [获取用户访问小程序数据概况](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/data-analysis/others/getDailySummary.html)
+pub const DAILY_SUMMARY_END_POINT: &str =
+    "https://api.weixin.qq.com/datacube/getweanalysisappiddailysummarytrend";
+
+/// 获取用户访问小程序数据日趋势的 API 端点
+///
+/// # 官方文档
+///
+/// [获取用户访问小程序数据日趋势](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/data-analysis/visit-trend/getDailyVisitTrend.html)
+pub const DAILY_VISIT_TREND_END_POINT: &str =
+    "https://api.weixin.qq.com/datacube/getweanalysisappiddailyvisittrend";
+
+/// 获取用户访问小程序日留存的 API 端点
+///
+/// # 官方文档
+///
+/// [获取用户访问小程序日留存](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/data-analysis/visit-retain/getDailyRetain.html)
+pub const DAILY_RETAIN_END_POINT: &str =
+    "https://api.weixin.qq.com/datacube/getweanalysisappiddailyretaininfo";
+
+/// 查询运维中心性能数据的 API 端点
+///
+/// # 官方文档
+///
+/// [查询性能数据](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/operation/getPerformance.html)
+pub const PERFORMANCE_END_POINT: &str =
+    "https://api.weixin.qq.com/wxa/business/performance/boot";
+
+/// 小程序内部搜索的 API 端点
+///
+/// # 官方文档
+///
+/// [小程序内部搜索](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/search/siteSearch.html)
+pub const SITE_SEARCH_END_POINT: &str = "https://api.weixin.qq.com/wxa/sitesearch";
+
+/// 本地图片搜索的 API 端点
+///
+/// # 官方文档
+///
+/// [本地图片搜索](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/search/imageSearch.html)
+pub const IMAGE_SEARCH_END_POINT: &str = "https://api.weixin.qq.com/wxa/imagesearch";
+
+/// 提交小程序页面 url 及参数信息的 API 端点
+///
+/// # 官方文档
+///
+/// [提交页面](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/search/submitPages.html)
+pub const SUBMIT_PAGES_END_POINT: &str =
+    "https://api.weixin.qq.com/wxa/search/wxaapi_submitpages";
+
+/// 获取支持的快递公司列表的 API 端点
+///
+/// # 官方文档
+///
+/// [获取支持的快递公司列表](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/express/express-by-business/getAllDelivery.html)
+pub const ALL_DELIVERY_END_POINT: &str =
+    "https://api.weixin.qq.com/cgi-bin/express/business/delivery/getall";
+
+/// 查询运单轨迹的 API 端点
+///
+/// # 官方文档
+///
+/// [查询运单轨迹](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/express/express-by-business/getPath.html)
+pub const EXPRESS_PATH_END_POINT: &str =
+    "https://api.weixin.qq.com/cgi-bin/express/business/path/get";
+
+/// 获取直播间列表的 API 端点
+///
+/// # 官方文档
+///
+/// [获取直播间列表](https://developers.weixin.qq.com/miniprogram/dev/platform-capabilities/industry/liveplayer/studio-api.html#8)
+pub const LIVE_INFO_END_POINT: &str = "https://api.weixin.qq.com/wxa/business/getliveinfo";
+
+/// 直播商品添加并提审的 API 端点
+///
+/// # 官方文档
+///
+/// [商品添加并提审](https://developers.weixin.qq.com/miniprogram/dev/platform-capabilities/industry/liveplayer/commodity-api.html#1)
+pub const LIVE_ADD_GOODS_END_POINT: &str = "https://api.weixin.qq.com/wxaapi/broadcast/goods/add";
+
+/// 上传临时素材的 API 端点
+///
+/// # 官方文档
+///
+/// [新增图片素材](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/kf-mgnt/kf-message/uploadTempMedia.html)
+pub const UPLOAD_TEMP_MEDIA_END_POINT: &str = "https://api.weixin.qq.com/cgi-bin/media/upload";
+
+/// 获取用户加密 key 的 API 端点
+///
+/// # 官方文档
+///
+/// [获取用户encryptKey](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/internet/getUserEncryptKey.html)
+pub const USER_ENCRYPT_KEY_END_POINT: &str =
+    "https://api.weixin.qq.com/wxa/business/getuserencryptkey";
+
+/// 获取 URL Scheme 的 API 端点
+///
+/// # 官方文档
+///
+/// [获取 scheme 码](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/qrcode-link/url-scheme/generateScheme.html)
+pub const URL_SCHEME_END_POINT: &str = "https://api.weixin.qq.com/wxa/generatescheme";
+
+/// 小程序服务端接口
+///
+/// 每个接口绑定 HTTP 方法和地址，通过 [`Endpoint::request`] 创建请求，
+/// 不需要在调用处重复指定。
+///
+/// ```
+/// use http::Method;
+/// use wechat_minapp::constants::Endpoint;
+///
+/// assert_eq!(Endpoint::CheckSessionKey.method(), Method::GET);
+/// assert_eq!(Endpoint::ShortLink.url(), "https://api.weixin.qq.com/wxa/genwxashortlink");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Endpoint {
+    /// 获取稳定版接口调用凭据
+    StableAccessToken,
+    /// 获取接口调用凭据
+    AccessToken,
+    /// 检查加密信息是否由微信生成
+    CheckSessionKey,
+    /// 重置用户会话密钥
+    ResetSessionKey,
+    /// 获取手机号
+    Phone,
+    /// 小程序登录 code2Session
+    Authentication,
+    /// 获取小程序码
+    QrCode,
+    /// 获取不限制的小程序码
+    UnlimitedQrCode,
+    /// 文本内容安全识别
+    MsgSecCheck,
+    /// 多媒体内容安全识别
+    MediaCheckAsync,
+    /// 获取 ShortLink
+    ShortLink,
+    /// 获取 URL Link
+    UrlLink,
+    /// 发送订阅消息
+    TemplateMessageSend,
+    /// 发送服务号模板消息
+    MpMessageSend,
+    /// 查询 rid 信息
+    RidInfo,
+    /// 查询 API 调用额度
+    ApiQuota,
+    /// 获取用户访问小程序数据概况
+    DailySummary,
+    /// 获取用户访问小程序数据日趋势
+    DailyVisitTrend,
+    /// 获取用户访问小程序日留存
+    DailyRetain,
+    /// 查询运维中心性能数据
+    Performance,
+    /// 小程序内部搜索
+    SiteSearch,
+    /// 本地图片搜索
+    ImageSearch,
+    /// 提交页面到微信搜索
+    SubmitPages,
+    /// 获取支持的快递公司列表
+    AllDelivery,
+    /// 查询运单轨迹
+    ExpressPath,
+    /// 获取直播间列表
+    LiveInfo,
+    /// 直播商品添加并提审
+    LiveAddGoods,
+    /// 上传临时素材
+    UploadTempMedia,
+    /// 获取用户加密 key
+    UserEncryptKey,
+    /// 获取 URL Scheme
+    UrlScheme,
+}
+
+impl Endpoint {
+    /// 全部接口
+    pub const ALL: [Endpoint; 30] = [
+        Endpoint::StableAccessToken,
+        Endpoint::AccessToken,
+        Endpoint::CheckSessionKey,
+        Endpoint::ResetSessionKey,
+        Endpoint::Phone,
+        Endpoint::Authentication,
+        Endpoint::QrCode,
+        Endpoint::UnlimitedQrCode,
+        Endpoint::MsgSecCheck,
+        Endpoint::MediaCheckAsync,
+        Endpoint::ShortLink,
+        Endpoint::UrlLink,
+        Endpoint::TemplateMessageSend,
+        Endpoint::MpMessageSend,
+        Endpoint::RidInfo,
+        Endpoint::ApiQuota,
+        Endpoint::DailySummary,
+        Endpoint::DailyVisitTrend,
+        Endpoint::DailyRetain,
+        Endpoint::Performance,
+        Endpoint::SiteSearch,
+        Endpoint::ImageSearch,
+        Endpoint::SubmitPages,
+        Endpoint::AllDelivery,
+        Endpoint::ExpressPath,
+        Endpoint::LiveInfo,
+        Endpoint::LiveAddGoods,
+        Endpoint::UploadTempMedia,
+        Endpoint::UserEncryptKey,
+        Endpoint::UrlScheme,
+    ];
+
+    /// 接口地址
+    pub const fn url(self) -> &'static str {
+        match self {
+            Endpoint::StableAccessToken => STABLE_ACCESS_TOKEN_END_POINT,
+            Endpoint::AccessToken => ACCESS_TOKEN_END_POINT,
+            Endpoint::CheckSessionKey => CHECK_SESSION_KEY_END_POINT,
+            Endpoint::ResetSessionKey => RESET_SESSION_KEY_END_POINT,
+            Endpoint::Phone => PHONE_END_POINT,
+            Endpoint::Authentication => AUTHENTICATION_END_POINT,
+            Endpoint::QrCode => QR_CODE_ENDPOINT,
+            Endpoint::UnlimitedQrCode => UNLIMITIED_QR_CODE_ENDPOINT,
+            Endpoint::MsgSecCheck => MSG_SEC_CHECK_END_POINT,
+            Endpoint::MediaCheckAsync => MEDIA_CHECK_ASYNC_END_POINT,
+            Endpoint::ShortLink => SHORT_LINK_END_POINT,
+            Endpoint::UrlLink => URL_LINK_END_POINT,
+            Endpoint::TemplateMessageSend => TEMPLATE_MESSAGE_SEND_END_POINT,
+            Endpoint::MpMessageSend => MP_MESSAGE_SEND_END_POINT,
+            Endpoint::RidInfo => RID_INFO_END_POINT,
+            Endpoint::ApiQuota => API_QUOTA_END_POINT,
+            Endpoint::DailySummary => DAILY_SUMMARY_END_POINT,
+            Endpoint::DailyVisitTrend => DAILY_VISIT_TREND_END_POINT,
+            Endpoint::DailyRetain => DAILY_RETAIN_END_POINT,
+            Endpoint::Performance => PERFORMANCE_END_POINT,
+            Endpoint::SiteSearch => SITE_SEARCH_END_POINT,
+            Endpoint::ImageSearch => IMAGE_SEARCH_END_POINT,
+            Endpoint::SubmitPages => SUBMIT_PAGES_END_POINT,
+            Endpoint::AllDelivery => ALL_DELIVERY_END_POINT,
+            Endpoint::ExpressPath => EXPRESS_PATH_END_POINT,
+            Endpoint::LiveInfo => LIVE_INFO_END_POINT,
+            Endpoint::LiveAddGoods => LIVE_ADD_GOODS_END_POINT,
+            Endpoint::UploadTempMedia => UPLOAD_TEMP_MEDIA_END_POINT,
+            Endpoint::UserEncryptKey => USER_ENCRYPT_KEY_END_POINT,
+            Endpoint::UrlScheme => URL_SCHEME_END_POINT,
+        }
+    }
+
+    /// 接口要求的 HTTP 方法
+    pub fn method(self) -> Method {
+        match self {
+            Endpoint::AccessToken
+            | Endpoint::CheckSessionKey
+            | Endpoint::ResetSessionKey
+            | Endpoint::Authentication
+            | Endpoint::AllDelivery => Method::GET,
+            _ => Method::POST,
+        }
+    }
+
+    /// 接口的幂等性
+    ///
+    /// 查询类接口可以放心重试；发送消息、生成链接，以及使用一次性 code 的登录、获取手机号
+    /// 为非幂等接口，[`RetryHttpClient`](wechat_core::client::RetryHttpClient) 默认不会在
+    /// 请求可能已被处理时重试。
+    pub fn idempotency(self) -> Idempotency {
+        match self {
+            Endpoint::ResetSessionKey
+            | Endpoint::Phone
+            | Endpoint::Authentication
+            | Endpoint::ShortLink
+            | Endpoint::UrlLink
+            | Endpoint::UrlScheme
+            | Endpoint::TemplateMessageSend
+            | Endpoint::MpMessageSend
+            | Endpoint::LiveAddGoods => Idempotency::NonIdempotent,
+            _ => Idempotency::Idempotent,
+        }
+    }
+
+    /// 创建已设置地址、HTTP 方法和幂等性的请求
+    pub fn request(self) -> RequestBuilder {
+        RequestBuilder::new(self.url())
+            .method(self.method())
+            .idempotency(self.idempotency())
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method(), self.url())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_endpoints() {
+        let urls: HashSet<_> = Endpoint::ALL.iter().map(|endpoint| endpoint.url()).collect();
+        assert_eq!(urls.len(), Endpoint::ALL.len());
+
+        for endpoint in Endpoint::ALL {
+            assert!(endpoint.url().starts_with(END_POINT_BASE_URL));
+            let request = endpoint.request().build().unwrap();
+            assert_eq!(request.method(), endpoint.method());
+            assert_eq!(request.uri().to_string(), endpoint.url());
+            assert_eq!(Idempotency::of(&request), endpoint.idempotency());
+        }
+        assert!(!Endpoint::TemplateMessageSend.idempotency().is_idempotent());
+
+        assert_eq!(Endpoint::UnlimitedQrCode.url(), UNLIMITIED_QR_CODE_ENDPOINT);
+        assert_eq!(
+            Endpoint::Authentication.to_string(),
+            "GET https://api.weixin.qq.com/sns/jscode2session"
+        );
+    }
+}
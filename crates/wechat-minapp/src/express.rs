@@ -0,0 +1,341 @@
+//! 物流助手
+//!
+//! 快递公司编码使用 [`DeliveryId`]，常用快递公司有对应的变体，不必手写编码；
+//! 其他快递公司可以使用 [`DeliveryId::Custom`]，或从 [`Express::all_delivery`] 的结果中取得。
+//!
+//! ## 示例
+//!
+//! ```no_run
+//! use wechat_minapp::WechatMinapp;
+//! use wechat_minapp::express::{DeliveryId, PathArgs};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = WechatMinapp::new("app_id", "secret");
+//!
+//!     let args = PathArgs::new("order_id", "openid", DeliveryId::SF, "SF1234567890");
+//!     let path = client.express().path(args).await?;
+//!     for item in path.path_item_list {
+//!         println!("{} {}", item.action_time, item.action_msg);
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::constants::Endpoint;
+use crate::WechatMinapp;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+use wechat_core::response::ExtraFields;
+use wechat_core::utils::UnixTimestamp;
+use wechat_core::Result;
+
+pub struct Express {
+    pub client: WechatMinapp,
+}
+
+impl Express {
+    pub fn new(client: impl Into<WechatMinapp>) -> Self {
+        Express {
+            client: client.into(),
+        }
+    }
+
+
+    wechat_core::endpoint! {
+        GET delivery_list() -> DeliveryList = Endpoint::AllDelivery.url();
+
+        /// 查询运单轨迹
+        ///
+        /// [官方文档](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/express/express-by-business/getPath.html)
+        pub POST idempotent path(args: PathArgs) -> PathInfo = Endpoint::ExpressPath.url();
+    }
+
+    /// 获取支持的快递公司列表
+    ///
+    /// [官方文档](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/express/express-by-business/getAllDelivery.html)
+    pub async fn all_delivery(&self) -> Result<Vec<Delivery>> {
+        Ok(self.delivery_list().await?.data)
+    }
+}
+
+/// 快递公司编码
+///
+/// 序列化为微信使用的编码字符串，未收录的编码反序列化为 [`DeliveryId::Custom`]，
+/// 不会因为微信新增快递公司而解析失败。
+///
+/// ```
+/// use wechat_minapp::express::DeliveryId;
+///
+/// assert_eq!(DeliveryId::SF.to_string(), "SF");
+/// assert_eq!("ZTO".parse::<DeliveryId>()?, DeliveryId::ZTO);
+/// assert_eq!(DeliveryId::from("OTP"), DeliveryId::Custom("OTP".to_string()));
+/// assert_eq!(serde_json::to_value(DeliveryId::JTSD)?, "JTSD");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum DeliveryId {
+    /// 顺丰速运
+    SF,
+    /// 圆通速递
+    YTO,
+    /// 中通快递
+    ZTO,
+    /// 申通快递
+    STO,
+    /// 韵达快递
+    YUNDA,
+    /// 极兔速递
+    JTSD,
+    /// 京东物流
+    JD,
+    /// 中国邮政速递物流
+    EMS,
+    /// 德邦快递
+    DB,
+    /// 百世快递
+    BEST,
+    /// 宅急送
+    ZJS,
+    /// 未收录的快递公司编码
+    Custom(String),
+}
+
+impl DeliveryId {
+    /// 已收录的快递公司
+    pub const KNOWN: [DeliveryId; 11] = [
+        DeliveryId::SF,
+        DeliveryId::YTO,
+        DeliveryId::ZTO,
+        DeliveryId::STO,
+        DeliveryId::YUNDA,
+        DeliveryId::JTSD,
+        DeliveryId::JD,
+        DeliveryId::EMS,
+        DeliveryId::DB,
+        DeliveryId::BEST,
+        DeliveryId::ZJS,
+    ];
+
+    /// 微信使用的快递公司编码
+    pub fn code(&self) -> &str {
+        match self {
+            DeliveryId::SF => "SF",
+            DeliveryId::YTO => "YTO",
+            DeliveryId::ZTO => "ZTO",
+            DeliveryId::STO => "STO",
+            DeliveryId::YUNDA => "YUNDA",
+            DeliveryId::JTSD => "JTSD",
+            DeliveryId::JD => "JD",
+            DeliveryId::EMS => "EMS",
+            DeliveryId::DB => "DB",
+            DeliveryId::BEST => "BEST",
+            DeliveryId::ZJS => "ZJS",
+            DeliveryId::Custom(code) => code,
+        }
+    }
+
+    /// 快递公司名称，[`DeliveryId::Custom`] 返回 `None`
+    pub fn name(&self) -> Option<&'static str> {
+        let name = match self {
+            DeliveryId::SF => "顺丰速运",
+            DeliveryId::YTO => "圆通速递",
+            DeliveryId::ZTO => "中通快递",
+            DeliveryId::STO => "申通快递",
+            DeliveryId::YUNDA => "韵达快递",
+            DeliveryId::JTSD => "极兔速递",
+            DeliveryId::JD => "京东物流",
+            DeliveryId::EMS => "中国邮政速递物流",
+            DeliveryId::DB => "德邦快递",
+            DeliveryId::BEST => "百世快递",
+            DeliveryId::ZJS => "宅急送",
+            DeliveryId::Custom(_) => return None,
+        };
+        Some(name)
+    }
+
+    /// 是否为未收录的快递公司编码
+    pub fn is_custom(&self) -> bool {
+        matches!(self, DeliveryId::Custom(_))
+    }
+}
+
+impl From<&str> for DeliveryId {
+    fn from(code: &str) -> Self {
+        let code = code.trim();
+        DeliveryId::KNOWN
+            .into_iter()
+            .find(|known| known.code() == code)
+            .unwrap_or_else(|| DeliveryId::Custom(code.to_string()))
+    }
+}
+
+impl From<String> for DeliveryId {
+    fn from(code: String) -> Self {
+        DeliveryId::from(code.as_str())
+    }
+}
+
+impl FromStr for DeliveryId {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(DeliveryId::from(s))
+    }
+}
+
+impl fmt::Display for DeliveryId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl Serialize for DeliveryId {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for DeliveryId {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(DeliveryId::from)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DeliveryList {
+    #[serde(default)]
+    data: Vec<Delivery>,
+}
+
+/// 快递公司
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Delivery {
+    /// 快递公司编码
+    pub delivery_id: DeliveryId,
+    /// 快递公司名称
+    pub delivery_name: String,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+/// 查询运单轨迹的参数
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PathArgs {
+    order_id: String,
+    openid: String,
+    delivery_id: DeliveryId,
+    waybill_id: String,
+}
+
+impl PathArgs {
+    pub fn new(
+        order_id: impl Into<String>,
+        openid: impl Into<String>,
+        delivery_id: impl Into<DeliveryId>,
+        waybill_id: impl Into<String>,
+    ) -> Self {
+        PathArgs {
+            order_id: order_id.into(),
+            openid: openid.into(),
+            delivery_id: delivery_id.into(),
+            waybill_id: waybill_id.into(),
+        }
+    }
+}
+
+/// 运单轨迹
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PathInfo {
+    /// 用户 openid
+    pub openid: String,
+    /// 快递公司编码
+    pub delivery_id: DeliveryId,
+    /// 运单号
+    pub waybill_id: String,
+    /// 轨迹节点数量
+    #[serde(default)]
+    pub path_item_num: u32,
+    /// 轨迹节点列表
+    #[serde(default)]
+    pub path_item_list: Vec<PathItem>,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+/// 轨迹节点
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PathItem {
+    /// 轨迹节点时间
+    pub action_time: UnixTimestamp,
+    /// 轨迹节点类型
+    pub action_type: u32,
+    /// 轨迹节点详情
+    pub action_msg: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn test_delivery_id() {
+        for known in DeliveryId::KNOWN {
+            assert_eq!(DeliveryId::from(known.code()), known);
+            assert!(known.name().is_some());
+            assert!(!known.is_custom());
+        }
+
+        let custom: DeliveryId = serde_json::from_str(r#""OTP""#).unwrap();
+        assert_eq!(custom, DeliveryId::Custom("OTP".to_string()));
+        assert!(custom.is_custom());
+        assert_eq!(custom.name(), None);
+        assert_eq!(serde_json::to_string(&custom).unwrap(), r#""OTP""#);
+        assert_eq!(" YTO ".parse::<DeliveryId>().unwrap(), DeliveryId::YTO);
+    }
+
+    #[tokio::test]
+    async fn test_all_delivery() {
+        let (client, http_client) = fixtures::client(
+            r#"{"count":2,"data":[{"delivery_id":"SF","delivery_name":"顺丰速运"},
+            {"delivery_id":"OTP","delivery_name":"承诺达特快"}]}"#,
+        );
+        let deliveries = Express::new(client).all_delivery().await.unwrap();
+
+        assert_eq!(deliveries[0].delivery_id, DeliveryId::SF);
+        assert_eq!(deliveries[1].delivery_id, DeliveryId::Custom("OTP".to_string()));
+        let requests = http_client.requests();
+        assert_eq!(requests[0].method, http::Method::GET);
+        assert!(requests[0].url.starts_with(Endpoint::AllDelivery.url()));
+    }
+
+    #[tokio::test]
+    async fn test_path() {
+        let (client, http_client) = fixtures::client(
+            r#"{"openid":"OPENID","delivery_id":"ZTO","waybill_id":"78230000",
+            "path_item_num":1,"path_item_list":[{"action_time":1533052800,
+            "action_type":100001,"action_msg":"快递员已成功取件"}]}"#,
+        );
+        let args = PathArgs::new("01234567890123456789", "OPENID", DeliveryId::ZTO, "78230000");
+        let path = Express::new(client).path(args).await.unwrap();
+
+        assert_eq!(path.delivery_id, DeliveryId::ZTO);
+        assert_eq!(path.path_item_list[0].action_time.raw(), 1533052800);
+        let requests = http_client.requests();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["delivery_id"], "ZTO");
+    }
+}
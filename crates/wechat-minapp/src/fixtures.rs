@@ -0,0 +1,171 @@
+//! 测试夹具
+//!
+//! 提供脱敏后的微信接口响应 JSON 和对应的构造函数，下游写单元测试时不需要自己伪造结构。
+//! 响应常量可以直接交给 [`DryRunHttpClient`] 模拟接口返回，构造函数返回解析好的类型，
+//! 适合测试只依赖返回值的业务逻辑。
+//!
+//! 需要启用 `test-util` feature，建议只在 `[dev-dependencies]` 中启用：
+//!
+//! ```toml
+//! [dev-dependencies]
+//! wechat-minapp = { version = "4", features = ["test-util"] }
+//! ```
+//!
+//! ## 示例
+//!
+//! ```
+//! use wechat_minapp::fixtures;
+//! use wechat_minapp::user::User;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> wechat_minapp::Result<()> {
+//! let (client, http_client) = fixtures::client(fixtures::LOGIN_RESPONSE);
+//! let credential = User::new(client).login("code").await?;
+//!
+//! assert_eq!(credential.open_id(), fixtures::OPEN_ID);
+//! assert_eq!(credential.session_key(), fixtures::credential().session_key());
+//! assert_eq!(http_client.requests().len(), 1);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::user::{Contact, Credential};
+use crate::WechatMinapp;
+use std::sync::Arc;
+use wechat_core::client::{DryRunHttpClient, HttpClient, StableToken, StaticTokenStorage};
+
+#[cfg(feature = "security")]
+use crate::minapp_security::MsgSecCheckResult;
+
+/// 夹具使用的 appid
+pub const APP_ID: &str = "wx4f4bc4dec97d474b";
+
+/// 夹具使用的 access_token
+pub const ACCESS_TOKEN: &str = "ACCESS_TOKEN";
+
+/// 夹具使用的 openid
+pub const OPEN_ID: &str = "oGZUI0egBJY1zhBYw2KhdUfwVJJE";
+
+/// 夹具使用的 session_key，base64 编码的 16 字节
+pub const SESSION_KEY: &str = "tiihtNczf5v6AKRyjwEUhQ==";
+
+/// 夹具使用的 unionid
+pub const UNION_ID: &str = "oTmHYjg-tElZ68xxxxxxxxhy1Rgk";
+
+/// 小程序登录 code2Session 的响应
+pub const LOGIN_RESPONSE: &str = r#"{"openid":"oGZUI0egBJY1zhBYw2KhdUfwVJJE",
+    "session_key":"tiihtNczf5v6AKRyjwEUhQ==","unionid":"oTmHYjg-tElZ68xxxxxxxxhy1Rgk"}"#;
+
+/// 获取手机号 getPhoneNumber 的响应，手机号信息在 `phone_info` 中，字段为 camelCase
+pub const CONTACT_RESPONSE: &str = r#"{"errcode":0,"errmsg":"ok",
+    "phone_info":{"phoneNumber":"13800001234","purePhoneNumber":"13800001234","countryCode":86,
+    "watermark":{"timestamp":1637744274,"appid":"wx4f4bc4dec97d474b"}}}"#;
+
+/// 内容安全检测通过的响应
+#[cfg(feature = "security")]
+pub const MSG_SEC_CHECK_PASS_RESPONSE: &str = r#"{"errcode":0,"errmsg":"ok",
+    "result":{"suggest":"pass","label":100},
+    "detail":[{"strategy":"content_model","errcode":0,"suggest":"pass","label":100,"prob":90}],
+    "trace_id":"60ae120f-371d5872-7941a05b"}"#;
+
+/// 内容安全检测命中辱骂标签的响应
+#[cfg(feature = "security")]
+pub const MSG_SEC_CHECK_RISKY_RESPONSE: &str = r#"{"errcode":0,"errmsg":"ok",
+    "result":{"suggest":"risky","label":20012},
+    "detail":[{"strategy":"content_model","errcode":0,"suggest":"risky","label":20012,"prob":95},
+    {"strategy":"keyword","errcode":0,"suggest":"risky","label":20012,"level":20,
+    "keyword":"脱敏关键词"}],
+    "trace_id":"60ae120f-371d5872-7941a05c"}"#;
+
+/// access_token 无效的错误响应
+pub const INVALID_TOKEN_RESPONSE: &str =
+    r#"{"errcode":40001,"errmsg":"invalid credential, access_token is invalid or not latest"}"#;
+
+/// 登录 code 无效的错误响应
+pub const INVALID_CODE_RESPONSE: &str = r#"{"errcode":40029,"errmsg":"invalid code"}"#;
+
+/// 系统繁忙的错误响应
+pub const SYSTEM_BUSY_RESPONSE: &str = r#"{"errcode":-1,"errmsg":"system error"}"#;
+
+/// 返回固定响应的客户端，以及用于检查请求的 [`DryRunHttpClient`]
+///
+/// 客户端使用 [`APP_ID`] 和固定的 [`ACCESS_TOKEN`]，不会请求 access_token。
+pub fn client(response: impl Into<Vec<u8>>) -> (WechatMinapp, Arc<DryRunHttpClient>) {
+    let http_client = Arc::new(DryRunHttpClient::with_response(response));
+    (client_with(http_client.clone()), http_client)
+}
+
+/// 使用自定义 [`HttpClient`] 的客户端，适合按请求地址返回不同响应的测试
+///
+/// 与 [`client`] 相同使用 [`APP_ID`] 和固定的 [`ACCESS_TOKEN`]。
+pub fn client_with(http_client: Arc<dyn HttpClient>) -> WechatMinapp {
+    let token_type = Arc::new(StableToken::new(APP_ID, "secret", false, http_client.clone()));
+    let token_storage = Arc::new(StaticTokenStorage::new(ACCESS_TOKEN, token_type));
+    WechatMinapp::custom(http_client, token_storage)
+}
+
+/// [`LOGIN_RESPONSE`] 对应的登录凭证，带有 [`APP_ID`]
+pub fn credential() -> Credential {
+    parse::<Credential>(LOGIN_RESPONSE).with_app_id(APP_ID)
+}
+
+/// [`CONTACT_RESPONSE`] 对应的手机号信息
+pub fn contact() -> Contact {
+    let mut response: serde_json::Value = parse(CONTACT_RESPONSE);
+    serde_json::from_value(response["phone_info"].take()).expect("fixture is valid json")
+}
+
+/// [`MSG_SEC_CHECK_PASS_RESPONSE`] 对应的检测结果
+#[cfg(feature = "security")]
+pub fn msg_sec_check_pass() -> MsgSecCheckResult {
+    parse(MSG_SEC_CHECK_PASS_RESPONSE)
+}
+
+/// [`MSG_SEC_CHECK_RISKY_RESPONSE`] 对应的检测结果
+#[cfg(feature = "security")]
+pub fn msg_sec_check_risky() -> MsgSecCheckResult {
+    parse(MSG_SEC_CHECK_RISKY_RESPONSE)
+}
+
+fn parse<T: serde::de::DeserializeOwned>(json: &str) -> T {
+    serde_json::from_str(json).expect("fixture is valid json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user::User;
+
+    #[tokio::test]
+    async fn test_fixtures() {
+        let (minapp, http_client) = client(CONTACT_RESPONSE);
+        let contact = User::new(minapp).get_contact("code", None).await.unwrap();
+        assert_eq!(contact, super::contact());
+        assert_eq!(contact.masked(), "138****1234");
+        assert_eq!(contact.country_code(), "86");
+        assert_eq!(contact.e164().unwrap(), "+8613800001234");
+        assert!(http_client.requests()[0].url.contains("access_token="));
+
+        assert_eq!(credential().open_id(), OPEN_ID);
+        assert_eq!(credential().session_key(), SESSION_KEY);
+        assert_eq!(credential().union_id(), Some(UNION_ID));
+
+        let (minapp, _) = client(INVALID_CODE_RESPONSE);
+        let error = User::new(minapp).login("code").await.unwrap_err();
+        assert_eq!(error.code(), Some(40029));
+    }
+
+    #[cfg(feature = "security")]
+    #[tokio::test]
+    async fn test_msg_sec_check_fixtures() {
+        use crate::minapp_security::{Args, MinappSecurity, Scene};
+
+        assert!(msg_sec_check_pass().is_pass());
+        assert!(msg_sec_check_risky().is_risky());
+
+        let (minapp, _) = client(MSG_SEC_CHECK_RISKY_RESPONSE);
+        let args = Args::new("content", Scene::Comment, OPEN_ID);
+        let result = MinappSecurity::new(minapp).msg_sec_check(&args).await.unwrap();
+        assert_eq!(result, msg_sec_check_risky());
+    }
+}
@@ -12,6 +12,7 @@
 //! - 内容安全检测
 //! - 生成小程序链接
 //! - 发送小程序模板消息
+//! - 查询 rid 信息
 //!
 //! # 特性
 //! - 异步支持
@@ -22,23 +23,128 @@
 //! - 简单易用的 API
 //! - 详细的文档
 //! - 单元测试覆盖
+//!
+//! # Feature
+//!
+//! 除 `image`、`axum`、`actix`、`en`、`auth`、`redis`、`log`、`simd-json`、`test-util`、`cli` 外默认全部开启，只需要部分接口时可以关闭默认 feature 按需选择，编译出更小的二进制：
+//!
+//! - `reqwest`：基于 reqwest 的默认 HTTP 客户端（[`WechatMinapp::new`]）
+//! - `crypto`：解密用户信息、用户加密 key 加密的数据（AES-128-CBC）
+//! - `tokio`：依赖 tokio 运行时的后台任务、超时与取消、请求重试，关闭后可以在其他运行时中使用
+//! - `qr`：小程序码
+//! - `link`：小程序链接：ShortLink、URL Link、URL Scheme，与 `qr` 同时开启时支持 scheme 超限回退小程序码
+//! - `security`：内容安全检测
+//! - `template`：模板消息
+//! - `analytics`：数据分析
+//! - `express`：物流助手
+//! - `live`：小程序直播
+//! - `search`：小程序搜索：站内搜索、图片搜索、提交页面
+//! - `callback`：消息推送解析、去重、签名校验与解密，支持多个小程序共用一个服务
+//! - `image`（默认关闭）：将小程序码解码为 `image::DynamicImage`
+//! - `axum` / `actix`（默认关闭）：`QrCode`、`Error` 可直接作为 axum / actix-web handler 的返回值
+//! - `en`（默认关闭）：SDK 生成的错误与校验信息默认使用英文，也可以运行时调用
+//!   [`i18n::set_language`] 切换
+//! - `auth`（默认关闭）：登录后签发包含 openid 的 JWT（HS256）并校验，见 [`auth`] 模块
+//! - `redis`（默认关闭）：小程序码的 redis 缓存 `qr::RedisQrCodeCache`、消息推送去重
+//!   `callback::RedisDeduplicator`
+//! - `log`（默认关闭）：没有设置 tracing subscriber 时，内部日志通过 `log` crate 输出，
+//!   适用于使用 env_logger 等 log 实现的项目，见下文[日志](#日志)
+//! - `simd-json`（默认关闭）：使用 simd-json 解析响应体，数据分析等数 MB 的响应解析更快
+//! - `test-util`（默认关闭）：测试夹具，提供脱敏的接口响应和构造函数，见 `fixtures` 模块
+//! - `cli`（默认关闭）：命令行工具 `wechat-minapp`，可以获取 access_token、生成小程序码和短链接、
+//!   查询 rid，方便排障和验证 appid 配置
+//!
+//! ```toml
+//! wechat-minapp = { version = "4", default-features = false, features = ["reqwest"] }
+//! ```
+//!
+//! # 日志
+//!
+//! SDK 内部使用 `tracing` 记录日志，target 为模块路径，例如 `wechat_core::client`、
+//! `wechat_minapp::qr`，可以按 crate 或模块过滤：
+//!
+//! ```bash
+//! RUST_LOG=wechat_core=debug,wechat_minapp::qr=debug cargo run
+//! ```
+//!
+//! 使用 `log` + `env_logger` 的项目开启 `log` feature 即可看到 SDK 日志；
+//! 已经使用 tracing 的项目可以通过 `tracing-log` 把其他库的 `log` 日志转发给 tracing。
+//!
+//! # 示例
+//!
+//! ```no_run
+//! use wechat_minapp::WechatMinapp;
+//!
+//! # async fn run() -> wechat_minapp::Result<()> {
+//! let client = WechatMinapp::new("app_id", "secret");
+//!
+//! // 各接口模块通过客户端上的访问器获取，无需手动 clone
+//! let credential = client.user().login("code").await?;
+//! let qr_code = client.qr().unlimited_qr_code(
+//!     wechat_minapp::qr::UnlimitedQrCodeArgs::builder()
+//!         .page("pages/index/index")
+//!         .scene("a=1")
+//!         .build()?,
+//! ).await?;
+//! # Ok(())
+//! # }
+//! ```
 
 // 重新导出 core 的内容
+#[cfg(feature = "reqwest")]
+pub use wechat_core::client::ReqwestHttpClient;
+#[cfg(feature = "redis")]
+pub use wechat_core::client::RedisResponseCache;
+#[cfg(feature = "tokio")]
+pub use wechat_core::{
+    client::{RetryHttpClient, RetryOn, RetryPolicy},
+    utils::{BackgroundTask, CallExt, CancellationToken},
+};
 pub use wechat_core::{
     client::{
-        HttpClient, MemoryTokenStorage, NonStableToken, ReqwestHttpClient, StableToken, TokenType,
-        TokenStorage, WechatCore, AppConfig, AccessToken,
+        CachedHttpClient, ConcurrencyLimitHttpClient, CurlLogHttpClient, DefaultHeadersHttpClient,
+        DryRunHttpClient, HttpClient, KeyValueBackend, KvTokenStorage, MemoryResponseCache,
+        MemoryTokenStorage, NonStableToken, RecordedRequest, RefreshCoordinator, RefreshStats,
+        ResponseCache, StableToken, StaticTokenStorage, TokenStore, TokenType, TokenStorage,
+        WechatCore, AppConfig, AccessToken,
+    },
+    error::{Error, ErrorCode, ErrorKind},
+    i18n,
+    naming,
+    response::ExtraFields,
+    utils::{
+        Multipart, Part, RequestBuilder, ResponseExt, MpResponse, Redacted, UnixTimestamp,
+        build_request, parse_query, parse_url,
     },
-    error::{Error, ErrorCode},
-    utils::{RequestBuilder, ResponseExt, MpResponse, build_request, parse_query, parse_url},
     Result,
 };
 
+#[cfg(feature = "analytics")]
+pub mod analytics;
+#[cfg(feature = "auth")]
+pub mod auth;
+#[cfg(any(feature = "qr", feature = "security", feature = "template"))]
+pub mod batch;
+#[cfg(feature = "callback")]
+pub mod callback;
 pub mod constants;
+#[cfg(feature = "express")]
+pub mod express;
+#[cfg(any(test, feature = "test-util"))]
+pub mod fixtures;
+#[cfg(feature = "link")]
 pub mod link;
+#[cfg(feature = "live")]
+pub mod live;
+#[cfg(feature = "security")]
 pub mod minapp_security;
 pub mod new_type;
+pub mod openapi;
+#[cfg(feature = "qr")]
 pub mod qr;
+#[cfg(feature = "search")]
+pub mod search;
+#[cfg(feature = "template")]
 pub mod template_message;
 pub mod user;
 
@@ -50,14 +156,40 @@ pub struct WechatMinapp {
     pub core: WechatCore,
 }
 
+/// 子模块可以直接从客户端的引用创建，内部的 HTTP 客户端和令牌存储通过 `Arc` 共享，不会复制
+///
+/// ```no_run
+/// use wechat_minapp::{WechatMinapp, user::User};
+///
+/// let client = WechatMinapp::new("app_id", "secret");
+/// let user = User::new(&client);
+/// let another = User::new(&client);
+/// ```
+impl From<&WechatMinapp> for WechatMinapp {
+    fn from(client: &WechatMinapp) -> Self {
+        client.clone()
+    }
+}
+
 impl WechatMinapp {
-    /// 使用默认配置创建客户端
+    /// 使用默认配置创建客户端，需要启用 `reqwest` feature
+    #[cfg(feature = "reqwest")]
     pub fn new(app_id: &str, secret: &str) -> Self {
         WechatMinapp {
             core: WechatCore::new(app_id, secret),
         }
     }
 
+    /// 校验 App ID 与 Secret 的格式后创建客户端，需要启用 `reqwest` feature
+    ///
+    /// 校验规则见 [`AppConfig::validate`]，格式不正确时返回 [`ErrorKind::InvalidParameter`]。
+    #[cfg(feature = "reqwest")]
+    pub fn try_new(app_id: &str, secret: &str) -> Result<Self> {
+        Ok(WechatMinapp {
+            core: WechatCore::try_new(app_id, secret)?,
+        })
+    }
+
     /// 使用自定义配置创建客户端
     pub fn custom(http_client: Arc<dyn HttpClient>, token_storage: Arc<dyn TokenStorage>) -> Self {
         WechatMinapp {
@@ -65,6 +197,44 @@ impl WechatMinapp {
         }
     }
 
+    /// 返回使用指定 access_token 的客户端，适用于 access_token 由其他系统下发的场景
+    ///
+    /// 返回的客户端与原客户端共用 HTTP 客户端，但不再从 `TokenStorage` 取值，也不会自行刷新。
+    /// 各接口模块通过 `new` 传入即可：
+    ///
+    /// ```no_run
+    /// use wechat_minapp::WechatMinapp;
+    /// use wechat_minapp::user::User;
+    ///
+    /// let client = WechatMinapp::new("app_id", "secret");
+    /// let user = User::new(client.with_token("ACCESS_TOKEN"));
+    /// ```
+    pub fn with_token(&self, access_token: impl Into<String>) -> Self {
+        WechatMinapp {
+            core: self.core.with_token(access_token),
+        }
+    }
+
+    /// 返回限制最大并发请求数的客户端，超出上限的请求排队等待
+    ///
+    /// 返回的客户端及其克隆共享同一个并发上限，适用于批量生成小程序码等批量任务。
+    pub fn with_concurrency_limit(&self, max_concurrency: usize) -> Self {
+        WechatMinapp {
+            core: self.core.with_concurrency_limit(max_concurrency),
+        }
+    }
+
+    /// 返回请求失败时按 `policy` 重试的客户端，原客户端不受影响
+    ///
+    /// 发送消息、生成链接等非幂等接口（见 [`Endpoint::idempotency`](constants::Endpoint::idempotency)）
+    /// 默认只在连接失败、频率限制等请求确定没有被处理时重试，不会重复发送。
+    #[cfg(feature = "tokio")]
+    pub fn with_retry(&self, policy: RetryPolicy) -> Self {
+        WechatMinapp {
+            core: self.core.with_retry(policy),
+        }
+    }
+
     /// 获取 access token
     pub async fn token(&self) -> Result<String> {
         self.core.token().await
@@ -75,25 +245,255 @@ impl WechatMinapp {
         self.core.app_config()
     }
 
+    /// 调用 SDK 尚未封装的接口，自动注入 access_token 并返回原始 JSON
+    ///
+    /// 详见 [`WechatCore::request_raw`]。
+    ///
+    /// ```no_run
+    /// use http::Method;
+    /// use serde_json::json;
+    /// use wechat_minapp::WechatMinapp;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = WechatMinapp::new("app_id", "secret");
+    ///     let body = json!({ "code": "xxx" });
+    ///     let value = client
+    ///         .request_raw(Method::POST, "/wxa/getpluginopenpid", None, Some(body))
+    ///         .await?;
+    ///     println!("{}", value["openpid"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn request_raw(
+        &self,
+        method: http::Method,
+        path: &str,
+        query: Option<serde_json::Value>,
+        body: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        self.core.request_raw(method, path, query, body).await
+    }
+
+    /// 健康检查，详见 [`OpenApi::healthcheck`](openapi::OpenApi::healthcheck)
+    pub async fn healthcheck(&self) -> openapi::HealthCheck {
+        self.openapi().healthcheck().await
+    }
+
     /// 获取内部 core client
     pub fn client(&self) -> &WechatCore {
         &self.core
     }
+
+    /// 用户相关接口
+    pub fn user(&self) -> user::User {
+        user::User::new(self)
+    }
+
+    /// 小程序码接口
+    #[cfg(feature = "qr")]
+    pub fn qr(&self) -> qr::Qr {
+        qr::Qr::new(self)
+    }
+
+    /// 小程序链接接口
+    #[cfg(feature = "link")]
+    pub fn link(&self) -> link::Link {
+        link::Link::new(self)
+    }
+
+    /// 内容安全检测接口
+    #[cfg(feature = "security")]
+    pub fn security(&self) -> minapp_security::MinappSecurity {
+        minapp_security::MinappSecurity::new(self)
+    }
+
+    /// 数据分析接口
+    #[cfg(feature = "analytics")]
+    pub fn analytics(&self) -> analytics::Analytics {
+        analytics::Analytics::new(self)
+    }
+
+    /// 物流助手接口
+    #[cfg(feature = "express")]
+    pub fn express(&self) -> express::Express {
+        express::Express::new(self)
+    }
+
+    /// 小程序直播接口
+    #[cfg(feature = "live")]
+    pub fn live(&self) -> live::Live {
+        live::Live::new(self)
+    }
+
+    /// 小程序搜索接口
+    #[cfg(feature = "search")]
+    pub fn search(&self) -> search::Search {
+        search::Search::new(self)
+    }
+
+    /// openApi 管理接口
+    pub fn openapi(&self) -> openapi::OpenApi {
+        openapi::OpenApi::new(self)
+    }
+
+    /// 模板消息接口
+    #[cfg(feature = "template")]
+    pub fn template_message(&self) -> template_message::TemplateMessage {
+        template_message::TemplateMessage::new(self)
+    }
 }
 
-// 向后兼容别名
+// 向后兼容别名，旧的 `client::Client` 架构已统一到 [`WechatMinapp`]
+#[deprecated(note = "请使用 WechatMinapp")]
 pub type WechatMinappSDK = WechatMinapp;
+#[deprecated(note = "请使用 NonStableToken")]
 pub type NormalToken = NonStableToken;
 
 // 错误转换实现
 impl From<crate::new_type::PagePathError> for Error {
     fn from(value: crate::new_type::PagePathError) -> Self {
-        Error::InvalidParameter(value.to_string())
+        ErrorKind::InvalidParameter(value.to_string()).into()
     }
 }
 
 impl From<crate::new_type::ValidationSceneError> for Error {
     fn from(value: crate::new_type::ValidationSceneError) -> Self {
-        Error::InvalidParameter(value.to_string())
+        ErrorKind::InvalidParameter(value.to_string()).into()
+    }
+}
+
+impl From<crate::new_type::OpenIdError> for Error {
+    fn from(value: crate::new_type::OpenIdError) -> Self {
+        ErrorKind::InvalidParameter(value.to_string()).into()
+    }
+}
+
+impl From<crate::new_type::ReportDateError> for Error {
+    fn from(value: crate::new_type::ReportDateError) -> Self {
+        ErrorKind::InvalidParameter(value.to_string()).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wechat_core::naming::Naming;
+
+    /// 请求参数序列化后的字段名必须是 snake_case，漏写或写错 rename 时失败
+    #[test]
+    fn test_request_naming() {
+        let date = crate::new_type::ReportDate::from_ymd(2024, 3, 1).unwrap();
+        let range = crate::new_type::ReportRange::daily(date).unwrap();
+        Naming::SnakeCase.check(&range).unwrap();
+
+        #[cfg(feature = "qr")]
+        {
+            use crate::qr::{MinappEnvVersion, QrCodeArgs, Rgb, UnlimitedQrCodeArgs};
+
+            let args = QrCodeArgs::builder()
+                .path("pages/index/index?id=1")
+                .width(430)
+                .line_color(Rgb::new(0, 0, 0))
+                .with_is_hyaline()
+                .env_version(MinappEnvVersion::Trial)
+                .build()
+                .unwrap();
+            Naming::SnakeCase.check(&args).unwrap();
+
+            let args = UnlimitedQrCodeArgs::builder()
+                .page("pages/index/index")
+                .scene("id=1")
+                .line_color(Rgb::new(0, 0, 0))
+                .env_version(MinappEnvVersion::Develop)
+                .build()
+                .unwrap();
+            Naming::SnakeCase.check(&args).unwrap();
+        }
+
+        #[cfg(feature = "link")]
+        {
+            let args = crate::link::ShortLinkArgs::builder()
+                .path("pages/index/index")
+                .page_title("title")
+                .with_permanent()
+                .build()
+                .unwrap();
+            Naming::SnakeCase.check(&args).unwrap();
+
+            let args = crate::link::UrlSchemeArgs::builder()
+                .path("pages/index/index")
+                .query("id=1")
+                .env_version(crate::link::MinappEnvVersion::Trial)
+                .build()
+                .unwrap();
+            Naming::SnakeCase.check(&args).unwrap();
+        }
+
+        #[cfg(feature = "template")]
+        {
+            let args = crate::template_message::SendMessageArgs::builder()
+                .touser("openid")
+                .template_id("template_id")
+                .page("pages/index/index")
+                .data(serde_json::json!({"thing1": {"value": "value"}}))
+                .miniprogram_state("formal")
+                .lang("zh_CN")
+                .build()
+                .unwrap();
+            Naming::SnakeCase.check(&args).unwrap();
+        }
+
+        #[cfg(feature = "security")]
+        {
+            use crate::minapp_security::{Args, Scene};
+
+            let args = Args::new("content", Scene::Profile, "openid");
+            Naming::SnakeCase.check(&args).unwrap();
+        }
+
+        #[cfg(feature = "express")]
+        {
+            use crate::express::{DeliveryId, PathArgs};
+
+            let args = PathArgs::new("order_id", "openid", DeliveryId::SF, "waybill_id");
+            Naming::SnakeCase.check(&args).unwrap();
+        }
+    }
+
+    /// 真实响应的字段都有对应的声明，没有因为漏写 rename 落入 `extra`
+    #[test]
+    fn test_response_naming() {
+        use crate::fixtures;
+
+        let credential = fixtures::credential();
+        assert_eq!(credential.union_id(), Some(fixtures::UNION_ID));
+        assert!(credential.extra().is_empty(), "{:?}", credential.extra());
+        Naming::SnakeCase.check(&credential).unwrap();
+
+        // 手机号信息与解密数据一样使用 camelCase，watermark.appid 全部小写
+        let contact = fixtures::contact();
+        assert!(contact.extra().is_empty(), "{:?}", contact.extra());
+        Naming::CamelCase.check(&contact).unwrap();
+
+        #[cfg(feature = "security")]
+        for result in [fixtures::msg_sec_check_pass(), fixtures::msg_sec_check_risky()] {
+            assert!(result.extra.is_empty(), "{:?}", result.extra);
+            assert!(result.result.unwrap().extra.is_empty());
+            for detail in result.detail.unwrap() {
+                assert!(detail.extra.is_empty(), "{:?}", detail.extra);
+            }
+        }
+    }
+
+    /// 消息推送序列化后保持微信的 PascalCase 字段名
+    #[cfg(feature = "callback")]
+    #[test]
+    fn test_callback_naming() {
+        let message = crate::callback::CallbackMessage::from_json(
+            r#"{"ToUserName":"gh_123","FromUserName":"OPENID","CreateTime":1482048670,
+            "MsgType":"text","Content":"hi","MsgId":1}"#,
+        )
+        .unwrap();
+        Naming::PascalCase.check(&message).unwrap();
     }
 }
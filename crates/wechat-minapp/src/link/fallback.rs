@@ -0,0 +1,279 @@
+//! URL Scheme 失败时回退为小程序码
+//!
+//! 运营场景通常优先发送 URL Scheme，生成数量超限、频率过快或没有生成权限时，
+//! 改为生成小程序码。[`Link::scheme_or_qr_code`] 一次调用处理这些错误分支，
+//! 返回 scheme 或小程序码以及回退原因。
+
+use super::{Link, UrlScheme, UrlSchemeArgs};
+use crate::qr::{Qr, QrCode, QrCodeArgs, UnlimitedQrCodeArgs};
+use std::fmt;
+use tracing::warn;
+use wechat_core::response::Response as MpResponse;
+use wechat_core::Result;
+
+/// 回退生成小程序码的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FallbackReason {
+    /// 超过生成数量上限：当天 scheme 与 URL Link 总数上限（45009）或长期有效 scheme 上限（85400）
+    QuotaExceeded { code: i32, message: String },
+    /// 生成频率过快（44990、45011）
+    RateLimited { code: i32, message: String },
+    /// 没有生成权限或权限被封禁（40002、40013、43104）
+    NoPermission { code: i32, message: String },
+}
+
+impl FallbackReason {
+    /// 可以通过回退处理的 errcode，其余错误（如页面不存在）小程序码同样会失败，直接返回
+    fn from_code(code: i32, message: String) -> Option<Self> {
+        match code {
+            45009 | 85400 => Some(FallbackReason::QuotaExceeded { code, message }),
+            44990 | 45011 => Some(FallbackReason::RateLimited { code, message }),
+            40002 | 40013 | 43104 => Some(FallbackReason::NoPermission { code, message }),
+            _ => None,
+        }
+    }
+
+    /// 生成 scheme 时微信返回的 errcode
+    pub fn code(&self) -> i32 {
+        match self {
+            FallbackReason::QuotaExceeded { code, .. }
+            | FallbackReason::RateLimited { code, .. }
+            | FallbackReason::NoPermission { code, .. } => *code,
+        }
+    }
+
+    /// 生成 scheme 时微信返回的错误信息
+    pub fn message(&self) -> &str {
+        match self {
+            FallbackReason::QuotaExceeded { message, .. }
+            | FallbackReason::RateLimited { message, .. }
+            | FallbackReason::NoPermission { message, .. } => message,
+        }
+    }
+}
+
+impl fmt::Display for FallbackReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            FallbackReason::QuotaExceeded { .. } => "quota exceeded",
+            FallbackReason::RateLimited { .. } => "rate limited",
+            FallbackReason::NoPermission { .. } => "no permission",
+        };
+        write!(f, "{} ({}): {}", kind, self.code(), self.message())
+    }
+}
+
+/// 回退时生成的小程序码
+#[derive(Debug, Clone, PartialEq)]
+pub enum QrCodeFallback {
+    /// 普通小程序码，path 可以带参数，有数量限制
+    Limited(QrCodeArgs),
+    /// 不限制数量的小程序码，参数通过 scene 传递
+    Unlimited(UnlimitedQrCodeArgs),
+}
+
+impl From<QrCodeArgs> for QrCodeFallback {
+    fn from(args: QrCodeArgs) -> Self {
+        QrCodeFallback::Limited(args)
+    }
+}
+
+impl From<UnlimitedQrCodeArgs> for QrCodeFallback {
+    fn from(args: UnlimitedQrCodeArgs) -> Self {
+        QrCodeFallback::Unlimited(args)
+    }
+}
+
+/// [`Link::scheme_or_qr_code`] 的结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemeOrQrCode {
+    /// 成功生成 scheme
+    Scheme(UrlScheme),
+    /// scheme 生成失败，回退生成的小程序码
+    QrCode {
+        qr_code: QrCode,
+        reason: FallbackReason,
+    },
+}
+
+impl SchemeOrQrCode {
+    /// 是否回退为小程序码
+    pub fn is_fallback(&self) -> bool {
+        matches!(self, SchemeOrQrCode::QrCode { .. })
+    }
+
+    pub fn scheme(&self) -> Option<&UrlScheme> {
+        match self {
+            SchemeOrQrCode::Scheme(scheme) => Some(scheme),
+            SchemeOrQrCode::QrCode { .. } => None,
+        }
+    }
+
+    pub fn qr_code(&self) -> Option<&QrCode> {
+        match self {
+            SchemeOrQrCode::QrCode { qr_code, .. } => Some(qr_code),
+            SchemeOrQrCode::Scheme(_) => None,
+        }
+    }
+
+    /// 回退原因
+    pub fn reason(&self) -> Option<&FallbackReason> {
+        match self {
+            SchemeOrQrCode::QrCode { reason, .. } => Some(reason),
+            SchemeOrQrCode::Scheme(_) => None,
+        }
+    }
+}
+
+impl Link {
+    /// 优先生成 URL Scheme，超过数量上限、频率过快或没有生成权限时回退生成小程序码
+    ///
+    /// 页面不存在、小程序未发布等错误生成小程序码同样会失败，直接返回错误，不再回退。
+    ///
+    /// ```no_run
+    /// use wechat_minapp::WechatMinapp;
+    /// use wechat_minapp::link::{Link, SchemeOrQrCode, UrlSchemeArgs};
+    /// use wechat_minapp::qr::QrCodeArgs;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let link = Link::new(WechatMinapp::new("app_id", "secret"));
+    ///     let scheme = UrlSchemeArgs::builder().path("pages/index/index").query("id=1").build()?;
+    ///     let qr_code = QrCodeArgs::builder().path("pages/index/index?id=1").build()?;
+    ///
+    ///     match link.scheme_or_qr_code(scheme, qr_code).await? {
+    ///         SchemeOrQrCode::Scheme(scheme) => println!("短信链接: {}", scheme.openlink()),
+    ///         SchemeOrQrCode::QrCode { qr_code, reason } => {
+    ///             println!("scheme 不可用（{}），改发小程序码", reason);
+    ///             qr_code.save_to("qrcode.png").await?;
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn scheme_or_qr_code(
+        &self,
+        scheme: UrlSchemeArgs,
+        qr_code: impl Into<QrCodeFallback>,
+    ) -> Result<SchemeOrQrCode> {
+        let response = self.url_scheme_response(scheme).await?;
+        let reason = match &response {
+            MpResponse::Success { .. } => None,
+            MpResponse::Error { code, message, .. } => {
+                FallbackReason::from_code(*code, message.clone())
+            }
+        };
+
+        // 成功或不需要回退的错误按原样返回
+        let Some(reason) = reason else {
+            return response.extract().map(SchemeOrQrCode::Scheme);
+        };
+        warn!("url scheme unavailable, falling back to qr code: {}", reason);
+
+        let qr = Qr::new(self.client.clone());
+        let qr_code = match qr_code.into() {
+            QrCodeFallback::Limited(args) => qr.qr_code(args).await?,
+            QrCodeFallback::Unlimited(args) => qr.unlimited_qr_code(args).await?,
+        };
+
+        Ok(SchemeOrQrCode::QrCode { qr_code, reason })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use async_trait::async_trait;
+    use http::{Request, Response};
+    use std::sync::Arc;
+    use wechat_core::client::HttpClient;
+    use wechat_core::ErrorKind;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+    /// scheme 接口返回固定响应，小程序码接口返回图片
+    struct RouteClient {
+        scheme: &'static str,
+    }
+
+    #[async_trait]
+    impl HttpClient for RouteClient {
+        async fn execute(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+            let response = match request.uri().path() {
+                "/wxa/generatescheme" => Response::new(self.scheme.as_bytes().to_vec()),
+                _ => Response::builder()
+                    .header("content-type", "image/png")
+                    .body(PNG.to_vec())
+                    .unwrap(),
+            };
+            Ok(response)
+        }
+    }
+
+    fn link(scheme: &'static str) -> Link {
+        Link::new(fixtures::client_with(Arc::new(RouteClient { scheme })))
+    }
+
+    fn args() -> (UrlSchemeArgs, QrCodeArgs) {
+        let scheme = UrlSchemeArgs::builder().path("pages/index/index").build().unwrap();
+        let qr_code = QrCodeArgs::builder().path("pages/index/index").build().unwrap();
+        (scheme, qr_code)
+    }
+
+    #[tokio::test]
+    async fn test_scheme() {
+        let (scheme, qr_code) = args();
+        let result = link(r#"{"errcode":0,"errmsg":"ok","openlink":"weixin://dl/business/?t=X"}"#)
+            .scheme_or_qr_code(scheme, qr_code)
+            .await
+            .unwrap();
+        assert!(!result.is_fallback());
+        assert_eq!(result.scheme().unwrap().openlink(), "weixin://dl/business/?t=X");
+    }
+
+    #[tokio::test]
+    async fn test_fallback() {
+        let (scheme, qr_code) = args();
+        let result = link(r#"{"errcode":85400,"errmsg":"reach max long time quota limit"}"#)
+            .scheme_or_qr_code(scheme, qr_code)
+            .await
+            .unwrap();
+        assert!(result.is_fallback());
+        assert_eq!(result.qr_code().unwrap().buffer(), PNG);
+        assert!(matches!(
+            result.reason(),
+            Some(FallbackReason::QuotaExceeded { code: 85400, .. })
+        ));
+
+        let (scheme, _) = args();
+        let unlimited = UnlimitedQrCodeArgs::builder()
+            .page("pages/index/index")
+            .scene("id=1")
+            .build()
+            .unwrap();
+        let result = link(r#"{"errcode":44990,"errmsg":"frequency limit"}"#)
+            .scheme_or_qr_code(scheme, unlimited)
+            .await
+            .unwrap();
+        assert_eq!(result.reason().unwrap().code(), 44990);
+    }
+
+    #[tokio::test]
+    async fn test_no_fallback() {
+        let (scheme, qr_code) = args();
+        let error = link(r#"{"errcode":40165,"errmsg":"invalid weapp pagepath"}"#)
+            .scheme_or_qr_code(scheme, qr_code)
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), Some(40165));
+
+        let (scheme, _) = args();
+        let error = link(r#"{"errcode":45009,"errmsg":"reach max api daily quota limit"}"#)
+            .url_scheme(scheme)
+            .await
+            .unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::DailyRequestLimitExceeded(_)));
+    }
+}
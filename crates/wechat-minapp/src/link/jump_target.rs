@@ -0,0 +1,207 @@
+//! 链接打开的小程序页面
+//!
+//! URL Link、URL Scheme、Short Link 的跳转目标结构一致：`path`、`query`、`env_version`，
+//! 共用 [`JumpTarget`] 和同一套校验。
+
+use crate::new_type::{MinappEnvVersion, NonQueryPagePath, PagePath};
+use serde::{Deserialize, Serialize};
+use wechat_core::localized;
+use wechat_core::{ErrorKind, Result};
+
+/// query 最大长度
+const MAX_QUERY_LENGTH: usize = 512;
+
+/// 链接打开的小程序页面
+///
+/// 通过 [`JumpTarget::builder()`] 创建：
+///
+/// ```
+/// use wechat_minapp::link::JumpTarget;
+/// use wechat_minapp::new_type::MinappEnvVersion;
+///
+/// let target = JumpTarget::builder()
+///     .path("pages/index/index")
+///     .query("id=1&from=sms")
+///     .env_version(MinappEnvVersion::Trial)
+///     .build()
+///     .unwrap();
+/// assert_eq!(target.query(), Some("id=1&from=sms"));
+/// assert_eq!(target.page_url(), "pages/index/index?id=1&from=sms");
+///
+/// // 页面参数需要放在 query 中
+/// assert!(JumpTarget::builder().path("pages/index/index?id=1").build().is_err());
+///
+/// // 或者通过 page_url 一起传入
+/// let target = JumpTarget::builder().page_url("pages/index/index?id=1").build().unwrap();
+/// assert_eq!(target.path(), "pages/index/index");
+/// assert_eq!(target.query(), Some("id=1"));
+/// ```
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct JumpTarget {
+    path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    query: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    env_version: Option<MinappEnvVersion>,
+}
+
+impl JumpTarget {
+    pub fn builder() -> JumpTargetBuilder {
+        JumpTargetBuilder::new()
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn query(&self) -> Option<&str> {
+        self.query.as_deref()
+    }
+
+    /// 要打开的小程序版本，未设置时为正式版
+    pub fn env_version(&self) -> Option<MinappEnvVersion> {
+        self.env_version
+    }
+
+    /// 带参数的页面路径，如 `pages/index/index?id=1`
+    pub fn page_url(&self) -> String {
+        match &self.query {
+            Some(query) => format!("{}?{}", self.path, query),
+            None => self.path.clone(),
+        }
+    }
+}
+
+/// [`JumpTarget`] 构建器
+#[derive(Debug, Default, Deserialize)]
+pub struct JumpTargetBuilder {
+    path: Option<String>,
+    query: Option<String>,
+    env_version: Option<MinappEnvVersion>,
+}
+
+impl JumpTargetBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 已发布小程序的页面路径，不能带参数
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// 页面参数，如 `id=1&from=sms`，最大 512 个字符
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.query = Some(query.into());
+        self
+    }
+
+    /// 带参数的页面路径，`?` 之后的部分作为 query，替换之前设置的 path 和 query
+    pub fn page_url(mut self, page_url: impl Into<String>) -> Self {
+        let page_url = page_url.into();
+        match page_url.split_once('?') {
+            Some((path, query)) => {
+                self.path = Some(path.to_string());
+                self.query = Some(query.to_string());
+            }
+            None => {
+                self.path = Some(page_url);
+                self.query = None;
+            }
+        }
+        self
+    }
+
+    /// 要打开的小程序版本，默认正式版
+    pub fn env_version(mut self, version: MinappEnvVersion) -> Self {
+        self.env_version = Some(version);
+        self
+    }
+
+    pub fn build(self) -> Result<JumpTarget> {
+        let path = self.path.ok_or_else(|| {
+            ErrorKind::InvalidParameter(localized!("小程序页面路径不能为空", "page path is required"))
+        })?;
+        let path = NonQueryPagePath::new(path)?.into_inner();
+
+        let query = self.query.filter(|query| !query.is_empty());
+        if let Some(query) = &query {
+            let length = query.chars().count();
+            if length > MAX_QUERY_LENGTH {
+                return Err(ErrorKind::InvalidParameter(localized!(
+                    "query 最大长度 {} 个字符，当前 {} 个",
+                    "query must not exceed {} characters, got {}",
+                    MAX_QUERY_LENGTH,
+                    length
+                ))
+                .into());
+            }
+        }
+
+        let target = JumpTarget {
+            path,
+            query,
+            env_version: self.env_version,
+        };
+        // 带参数的完整路径按 PagePath 校验长度和系统保留参数
+        PagePath::new(&target.page_url())?;
+        Ok(target)
+    }
+}
+
+impl From<JumpTarget> for JumpTargetBuilder {
+    fn from(target: JumpTarget) -> Self {
+        JumpTargetBuilder {
+            path: Some(target.path),
+            query: target.query,
+            env_version: target.env_version,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_jump_target() {
+        let target = JumpTarget::builder()
+            .path("pages/index/index")
+            .query("id=1")
+            .env_version(MinappEnvVersion::Develop)
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&target).unwrap(),
+            json!({"path": "pages/index/index", "query": "id=1", "env_version": "develop"})
+        );
+
+        let builder = || JumpTarget::builder().path("pages/index/index");
+        assert_eq!(
+            serde_json::to_value(builder().query("").build().unwrap()).unwrap(),
+            json!({"path": "pages/index/index"})
+        );
+        assert!(JumpTarget::builder().build().is_err());
+        assert!(JumpTarget::builder().path("/pages/index").build().is_err());
+        assert!(builder().query("a".repeat(513)).build().is_err());
+        assert!(builder().query("a".repeat(512)).build().is_ok());
+        assert!(builder().query("scancode_time=1").build().is_err());
+        assert!(JumpTarget::builder().path("a".repeat(1025)).build().is_err());
+    }
+
+    #[test]
+    fn test_page_url() {
+        let target = JumpTarget::builder()
+            .query("id=2")
+            .page_url("pages/detail/detail")
+            .build()
+            .unwrap();
+        assert_eq!(target.query(), None);
+        assert_eq!(target.page_url(), "pages/detail/detail");
+
+        let target = JumpTarget::builder().page_url("pages/index/index?").build().unwrap();
+        assert_eq!(target.page_url(), "pages/index/index");
+    }
+}
@@ -2,18 +2,37 @@
 //!
 //! ## 功能
 //! - [`short_link`] 生成电商短链接
+//! - [`url_link`] 生成 URL Link
+//! - [`url_scheme`] 生成 URL Scheme，适用于短信、邮件、外部网页拉起小程序
+//! - [`Link::scheme_or_qr_code`] 优先生成 URL Scheme，超限时回退生成小程序码（需要 `qr` feature）
 //!
+//! 各类链接打开的小程序页面共用 [`JumpTarget`]。
+//!
+#[cfg(feature = "qr")]
+mod fallback;
+mod jump_target;
 pub mod short_link;
+pub mod url_link;
+pub mod url_scheme;
 
 use crate::WechatMinapp;
+#[cfg(feature = "qr")]
+pub use fallback::{FallbackReason, QrCodeFallback, SchemeOrQrCode};
+pub use crate::new_type::MinappEnvVersion;
+pub use jump_target::{JumpTarget, JumpTargetBuilder};
 pub use short_link::{ShortLink, ShortLinkArgs};
+pub use url_link::{UrlLink, UrlLinkArgs};
+pub use url_scheme::{UrlScheme, UrlSchemeArgs};
 
 pub struct Link {
     pub client: WechatMinapp,
 }
 
 impl Link {
-    pub fn new(client: WechatMinapp) -> Self {
-        Link { client }
+    pub fn new(client: impl Into<WechatMinapp>) -> Self {
+        Link {
+            client: client.into(),
+        }
     }
+
 }
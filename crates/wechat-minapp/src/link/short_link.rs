@@ -33,9 +33,11 @@
 //!
 
 use super::Link;
-use wechat_core::utils::{RequestBuilder, ResponseExt};
-use wechat_core::{Result, Error};
-use crate::constants;
+use wechat_core::utils::ResponseExt;
+use super::{JumpTarget, JumpTargetBuilder};
+use wechat_core::response::ExtraFields;
+use wechat_core::Result;
+use crate::constants::Endpoint;
 use serde::{Deserialize, Serialize};
 use tracing::debug;
 
@@ -66,18 +68,33 @@ use tracing::debug;
 ///     Ok(())
 /// }
 /// ```
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ShortLink {
     link: String,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    extra: ExtraFields,
+}
+
+impl ShortLink {
+    /// 短链接，如 `#小程序://小程序名称/xxx`
+    pub fn link(&self) -> &str {
+        &self.link
+    }
+
+    /// 微信新增、SDK 尚未声明的字段
+    pub fn extra(&self) -> &ExtraFields {
+        &self.extra
+    }
 }
 
 /// 短链接生成参数
 ///
 /// 用于配置短链接的生成选项，通过 [`ShortLinkArgs::builder()`] 方法创建。
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct ShortLinkArgs {
-    #[serde(rename = "page_url")]
-    path: String,
+    #[serde(rename = "page_url", with = "page_url")]
+    target: JumpTarget,
     #[serde(skip_serializing_if = "Option::is_none")]
     page_title: Option<String>,
     is_permanent: bool,
@@ -94,14 +111,16 @@ pub struct ShortLinkArgs {
 ///
 /// let args = ShortLinkArgs::builder()
 ///     .path("pages/index/index")
+///     .query("id=1")
 ///     .page_title("page title")
 ///     .with_permanent()
 ///     .build()
 ///     .unwrap();
+/// assert_eq!(args.path(), "pages/index/index?id=1");
 /// ```
 #[derive(Debug, Deserialize)]
 pub struct ShortLinkArgsBuilder {
-    path: Option<String>,
+    target: JumpTargetBuilder,
     page_title: Option<String>,
     is_permanent: Option<bool>,
 }
@@ -111,8 +130,14 @@ impl ShortLinkArgs {
         ShortLinkArgsBuilder::new()
     }
 
+    /// 带参数的页面路径，即接口中的 `page_url`
     pub fn path(&self) -> String {
-        self.path.clone()
+        self.target.page_url()
+    }
+
+    /// 打开的小程序页面
+    pub fn target(&self) -> &JumpTarget {
+        &self.target
     }
 
     pub fn page_title(&self) -> Option<String> {
@@ -127,14 +152,27 @@ impl ShortLinkArgs {
 impl ShortLinkArgsBuilder {
     pub fn new() -> Self {
         ShortLinkArgsBuilder {
-            path: None,
+            target: JumpTargetBuilder::new(),
             page_title: None,
             is_permanent: None,
         }
     }
 
+    /// 页面路径，可以带参数，如 `pages/index/index?id=1`
     pub fn path(mut self, path: impl Into<String>) -> Self {
-        self.path = Some(path.into());
+        self.target = self.target.page_url(path);
+        self
+    }
+
+    /// 页面参数，如 `id=1&from=sms`，替换 path 中带的参数
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.target = self.target.query(query);
+        self
+    }
+
+    /// 使用已经构建好的跳转目标，Short Link 不支持 `env_version`，只使用其中的 path 和 query
+    pub fn target(mut self, target: JumpTarget) -> Self {
+        self.target = target.into();
         self
     }
 
@@ -148,24 +186,8 @@ impl ShortLinkArgsBuilder {
     }
 
     pub fn build(self) -> Result<ShortLinkArgs> {
-        let path = self.path.map_or_else(
-            || {
-                Err(Error::InvalidParameter(
-                    "小程序页面路径不能为空".to_string(),
-                ))
-            },
-            |v| {
-                if v.len() > 1024 {
-                    return Err(Error::InvalidParameter(
-                        "页面路径最大长度 1024 个字符".to_string(),
-                    ));
-                }
-                Ok(v)
-            },
-        )?;
-
         Ok(ShortLinkArgs {
-            path,
+            target: self.target.build()?,
             page_title: self.page_title,
             is_permanent: self.is_permanent.unwrap_or(false),
         })
@@ -221,8 +243,26 @@ impl Link {
     ///
     /// - 网络错误
     /// - 认证错误（access_token 无效）
+    /// - [`ErrorKind::CategoryNotAllowed`](crate::ErrorKind::CategoryNotAllowed)：
+    ///   非电商类目小程序没有调用权限（errcode 43104）
     /// - 微信 API 返回错误
     /// - 参数序列化错误
+    ///
+    /// ```no_run
+    /// # use wechat_minapp::{Error, ErrorKind, WechatMinapp};
+    /// # use wechat_minapp::link::{Link, ShortLinkArgs};
+    /// # async fn run(link: Link, args: ShortLinkArgs) -> Result<(), Error> {
+    /// match link.short_link(args).await {
+    ///     Ok(short_link) => println!("{:?}", short_link),
+    ///     // 非电商类目回退为 URL Link 或小程序码
+    ///     Err(e) if matches!(e.kind(), ErrorKind::CategoryNotAllowed(_)) => {
+    ///         println!("当前类目不支持 ShortLink")
+    ///     }
+    ///     Err(e) => return Err(e),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
     pub async fn short_link(&self, args: ShortLinkArgs) -> Result<ShortLink> {
         debug!("get qr code args {:?}", &args);
 
@@ -230,13 +270,9 @@ impl Link {
             "access_token":self.client.token().await?
         });
 
-        let body = serde_json::to_value(ShortLinkArgs {
-            path: args.path.clone(),
-            page_title: args.page_title,
-            is_permanent: args.is_permanent,
-        })?;
+        let body = serde_json::to_value(&args)?;
 
-        let request = RequestBuilder::new(constants::SHORT_LINK_END_POINT)
+        let request = Endpoint::ShortLink.request()
             .query(query)
             .body(body)
             .build()?;
@@ -249,3 +285,49 @@ impl Link {
         response.to_json::<ShortLink>()
     }
 }
+
+/// `page_url` 字段：[`JumpTarget`] 序列化为带参数的页面路径
+mod page_url {
+    use super::{JumpTarget, JumpTargetBuilder};
+    use serde::de::Error as _;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(target: &JumpTarget, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&target.page_url())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<JumpTarget, D::Error> {
+        let page_url = String::deserialize(deserializer)?;
+        JumpTargetBuilder::new()
+            .page_url(page_url)
+            .build()
+            .map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_short_link_args_page_url() {
+        let args = ShortLinkArgs::builder()
+            .path("pages/index/index")
+            .query("id=1")
+            .build()
+            .unwrap();
+        let value = serde_json::to_value(&args).unwrap();
+        assert_eq!(
+            value,
+            json!({"page_url": "pages/index/index?id=1", "is_permanent": false})
+        );
+        assert_eq!(serde_json::from_value::<ShortLinkArgs>(value).unwrap(), args);
+        assert_eq!(args.target().query(), Some("id=1"));
+
+        let target = JumpTarget::builder().path("pages/detail/detail").build().unwrap();
+        let args = ShortLinkArgs::builder().target(target).build().unwrap();
+        assert_eq!(args.path(), "pages/detail/detail");
+        assert!(ShortLinkArgs::builder().path("/pages/index").build().is_err());
+    }
+}
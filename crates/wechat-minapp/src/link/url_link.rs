@@ -0,0 +1,231 @@
+//! 微信小程序 URL Link 生成模块
+//!
+//! 获取小程序 URL Link，适用于短信、邮件、网页、微信内等拉起小程序的业务场景。
+//! 目前只能生成到期失效的 URL Link，最长有效期 30 天。
+//! [官方文档](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/qrcode-link/url-link/generateUrlLink.html)
+//!
+//! ## 示例
+//!
+//! ```no_run
+//! use wechat_minapp::WechatMinapp;
+//! use wechat_minapp::link::{Link, UrlLinkArgs};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let link = Link::new(WechatMinapp::new("app_id", "secret"));
+//!
+//!     let args = UrlLinkArgs::builder()
+//!         .path("pages/index/index")
+//!         .query("id=1")
+//!         .expire_interval(7)
+//!         .build()?;
+//!     let url_link = link.url_link(args).await?;
+//!     println!("{}", url_link.url_link());
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use super::{JumpTarget, JumpTargetBuilder, Link};
+use crate::constants::Endpoint;
+use crate::new_type::MinappEnvVersion;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use wechat_core::utils::ResponseExt;
+use wechat_core::localized;
+use wechat_core::response::ExtraFields;
+use wechat_core::{ErrorKind, Result};
+
+/// URL Link 最长有效天数
+pub const MAX_EXPIRE_INTERVAL: u32 = 30;
+
+/// URL Link
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UrlLink {
+    url_link: String,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    extra: ExtraFields,
+}
+
+impl UrlLink {
+    /// 链接地址，如 `https://wxaurl.cn/BQZRrcFCPvg`
+    pub fn url_link(&self) -> &str {
+        &self.url_link
+    }
+
+    /// 微信新增、SDK 尚未声明的字段
+    pub fn extra(&self) -> &ExtraFields {
+        &self.extra
+    }
+}
+
+/// URL Link 生成参数，通过 [`UrlLinkArgs::builder()`] 创建
+///
+/// 跳转目标的 `path`、`query`、`env_version` 直接放在请求体顶层。
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct UrlLinkArgs {
+    #[serde(flatten)]
+    target: JumpTarget,
+    expire_type: u8,
+    expire_interval: u32,
+}
+
+impl UrlLinkArgs {
+    pub fn builder() -> UrlLinkArgsBuilder {
+        UrlLinkArgsBuilder::new()
+    }
+
+    /// 打开的小程序页面
+    pub fn target(&self) -> &JumpTarget {
+        &self.target
+    }
+
+    /// 有效天数
+    pub fn expire_interval(&self) -> u32 {
+        self.expire_interval
+    }
+}
+
+/// URL Link 参数构建器
+///
+/// ```
+/// use wechat_minapp::link::UrlLinkArgs;
+/// use wechat_minapp::new_type::MinappEnvVersion;
+///
+/// let args = UrlLinkArgs::builder()
+///     .path("pages/index/index")
+///     .query("id=1&from=sms")
+///     .env_version(MinappEnvVersion::Trial)
+///     .build()
+///     .unwrap();
+/// assert_eq!(args.expire_interval(), 30);
+///
+/// assert!(UrlLinkArgs::builder().path("pages/index/index?id=1").build().is_err());
+/// ```
+#[derive(Debug, Default)]
+pub struct UrlLinkArgsBuilder {
+    target: JumpTargetBuilder,
+    expire_interval: Option<u32>,
+}
+
+impl UrlLinkArgsBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 已发布小程序的页面路径，不能带参数
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.target = self.target.path(path);
+        self
+    }
+
+    /// 页面参数，如 `id=1&from=sms`，最大 512 个字符
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.target = self.target.query(query);
+        self
+    }
+
+    /// 要打开的小程序版本，默认正式版
+    pub fn env_version(mut self, version: MinappEnvVersion) -> Self {
+        self.target = self.target.env_version(version);
+        self
+    }
+
+    /// 使用已经构建好的跳转目标，替换之前设置的 path、query、env_version
+    pub fn target(mut self, target: JumpTarget) -> Self {
+        self.target = target.into();
+        self
+    }
+
+    /// 有效天数，1–30 天，默认 30 天
+    pub fn expire_interval(mut self, days: u32) -> Self {
+        self.expire_interval = Some(days);
+        self
+    }
+
+    pub fn build(self) -> Result<UrlLinkArgs> {
+        let target = self.target.build()?;
+
+        let expire_interval = self.expire_interval.unwrap_or(MAX_EXPIRE_INTERVAL);
+        if !(1..=MAX_EXPIRE_INTERVAL).contains(&expire_interval) {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "URL Link 有效天数必须在 1–{} 之间，当前为 {}",
+                "expire interval must be between 1 and {} days, got {}",
+                MAX_EXPIRE_INTERVAL,
+                expire_interval
+            ))
+            .into());
+        }
+
+        Ok(UrlLinkArgs {
+            target,
+            // 到期失效类型：1 表示按 expire_interval 天数失效
+            expire_type: 1,
+            expire_interval,
+        })
+    }
+}
+
+impl Link {
+    /// 生成 URL Link
+    ///
+    /// # 错误
+    ///
+    /// - 网络错误
+    /// - 认证错误（access_token 无效）
+    /// - [`ErrorKind::DailyRequestLimitExceeded`]：当天生成数量超过上限（errcode 45009）
+    /// - 微信 API 返回错误，如页面不存在（errcode 40165）、小程序未发布（errcode 85079）
+    pub async fn url_link(&self, args: UrlLinkArgs) -> Result<UrlLink> {
+        debug!("url link args {:?}", &args);
+
+        let query = serde_json::json!({
+            "access_token": self.client.token().await?
+        });
+
+        let request = Endpoint::UrlLink
+            .request()
+            .query(query)
+            .body(serde_json::to_value(&args)?)
+            .build()?;
+
+        let response = self.client.core.client.execute(request).await?;
+        debug!("url link response status: {}", response.status());
+
+        response.to_json::<UrlLink>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_url_link_args() {
+        let args = UrlLinkArgs::builder()
+            .path("pages/index/index")
+            .query("id=1")
+            .env_version(MinappEnvVersion::Develop)
+            .expire_interval(7)
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&args).unwrap(),
+            json!({
+                "path": "pages/index/index",
+                "query": "id=1",
+                "env_version": "develop",
+                "expire_type": 1,
+                "expire_interval": 7
+            })
+        );
+        assert_eq!(args.target().env_version(), Some(MinappEnvVersion::Develop));
+
+        assert!(UrlLinkArgs::builder().build().is_err());
+        let builder = || UrlLinkArgs::builder().path("pages/index/index");
+        assert!(builder().expire_interval(0).build().is_err());
+        assert!(builder().expire_interval(31).build().is_err());
+        assert!(builder().query("a".repeat(513)).build().is_err());
+    }
+}
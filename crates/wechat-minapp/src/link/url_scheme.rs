@@ -0,0 +1,258 @@
+//! 微信小程序 URL Scheme 生成模块
+//!
+//! 获取小程序 scheme 码，适用于短信、邮件、外部网页、微信内等拉起小程序的业务场景。
+//! 目前只能生成到期失效的 scheme，最长有效期 30 天；单个小程序每天生成 scheme 和 URL Link
+//! 的总数不超过 50 万。
+//! [官方文档](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/qrcode-link/url-scheme/generateScheme.html)
+//!
+//! ## 示例
+//!
+//! ```no_run
+//! use wechat_minapp::WechatMinapp;
+//! use wechat_minapp::link::{Link, UrlSchemeArgs};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let link = Link::new(WechatMinapp::new("app_id", "secret"));
+//!
+//!     let args = UrlSchemeArgs::builder()
+//!         .path("pages/index/index")
+//!         .query("id=1")
+//!         .expire_interval(7)
+//!         .build()?;
+//!     let scheme = link.url_scheme(args).await?;
+//!     println!("{}", scheme.openlink());
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use super::{JumpTarget, JumpTargetBuilder, Link};
+use crate::constants::Endpoint;
+use crate::new_type::MinappEnvVersion;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use wechat_core::localized;
+use wechat_core::response::{ExtraFields, Response as MpResponse};
+use wechat_core::utils::ResponseExt;
+use wechat_core::{ErrorKind, Result};
+
+/// scheme 最长有效天数
+pub const MAX_EXPIRE_INTERVAL: u32 = 30;
+
+/// URL Scheme
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UrlScheme {
+    openlink: String,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    extra: ExtraFields,
+}
+
+impl UrlScheme {
+    /// scheme 链接，如 `weixin://dl/business/?t=XTSkBZlzqmn`
+    pub fn openlink(&self) -> &str {
+        &self.openlink
+    }
+
+    /// 微信新增、SDK 尚未声明的字段
+    pub fn extra(&self) -> &ExtraFields {
+        &self.extra
+    }
+}
+
+/// URL Scheme 生成参数，通过 [`UrlSchemeArgs::builder()`] 创建
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct UrlSchemeArgs {
+    jump_wxa: JumpTarget,
+    is_expire: bool,
+    expire_type: u8,
+    expire_interval: u32,
+}
+
+impl UrlSchemeArgs {
+    pub fn builder() -> UrlSchemeArgsBuilder {
+        UrlSchemeArgsBuilder::new()
+    }
+
+    /// 打开的小程序页面
+    pub fn target(&self) -> &JumpTarget {
+        &self.jump_wxa
+    }
+
+    pub fn path(&self) -> &str {
+        self.jump_wxa.path()
+    }
+
+    pub fn query(&self) -> Option<&str> {
+        self.jump_wxa.query()
+    }
+
+    pub fn env_version(&self) -> Option<MinappEnvVersion> {
+        self.jump_wxa.env_version()
+    }
+
+    /// 有效天数
+    pub fn expire_interval(&self) -> u32 {
+        self.expire_interval
+    }
+}
+
+/// URL Scheme 参数构建器
+///
+/// ```
+/// use wechat_minapp::link::{JumpTarget, MinappEnvVersion, UrlSchemeArgs};
+///
+/// let args = UrlSchemeArgs::builder()
+///     .path("pages/index/index")
+///     .query("id=1&from=sms")
+///     .env_version(MinappEnvVersion::Trial)
+///     .build()
+///     .unwrap();
+/// assert_eq!(args.expire_interval(), 30);
+///
+/// assert!(UrlSchemeArgs::builder().path("pages/index/index?id=1").build().is_err());
+///
+/// // 也可以复用已经构建好的 JumpTarget
+/// let target = JumpTarget::builder().path("pages/index/index").build().unwrap();
+/// let args = UrlSchemeArgs::builder().target(target).build().unwrap();
+/// assert_eq!(args.path(), "pages/index/index");
+/// ```
+#[derive(Debug, Default)]
+pub struct UrlSchemeArgsBuilder {
+    target: JumpTargetBuilder,
+    expire_interval: Option<u32>,
+}
+
+impl UrlSchemeArgsBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 已发布小程序的页面路径，不能带参数
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.target = self.target.path(path);
+        self
+    }
+
+    /// 页面参数，如 `id=1&from=sms`，最大 512 个字符
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.target = self.target.query(query);
+        self
+    }
+
+    /// 要打开的小程序版本，默认正式版
+    pub fn env_version(mut self, version: MinappEnvVersion) -> Self {
+        self.target = self.target.env_version(version);
+        self
+    }
+
+    /// 使用已经构建好的跳转目标，替换之前设置的 path、query、env_version
+    pub fn target(mut self, target: JumpTarget) -> Self {
+        self.target = target.into();
+        self
+    }
+
+    /// 有效天数，1–30 天，默认 30 天
+    pub fn expire_interval(mut self, days: u32) -> Self {
+        self.expire_interval = Some(days);
+        self
+    }
+
+    pub fn build(self) -> Result<UrlSchemeArgs> {
+        let jump_wxa = self.target.build()?;
+
+        let expire_interval = self.expire_interval.unwrap_or(MAX_EXPIRE_INTERVAL);
+        if !(1..=MAX_EXPIRE_INTERVAL).contains(&expire_interval) {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "scheme 有效天数必须在 1–{} 之间，当前为 {}",
+                "expire interval must be between 1 and {} days, got {}",
+                MAX_EXPIRE_INTERVAL,
+                expire_interval
+            ))
+            .into());
+        }
+
+        Ok(UrlSchemeArgs {
+            jump_wxa,
+            is_expire: true,
+            // 到期失效类型：1 表示按 expire_interval 天数失效
+            expire_type: 1,
+            expire_interval,
+        })
+    }
+}
+
+impl Link {
+    /// 生成 URL Scheme
+    ///
+    /// # 错误
+    ///
+    /// - 网络错误
+    /// - 认证错误（access_token 无效）
+    /// - [`ErrorKind::DailyRequestLimitExceeded`]：当天生成数量超过上限（errcode 45009）
+    /// - 微信 API 返回错误，如页面不存在（errcode 40165）、小程序未发布（errcode 85079）
+    pub async fn url_scheme(&self, args: UrlSchemeArgs) -> Result<UrlScheme> {
+        self.url_scheme_response(args).await?.extract()
+    }
+
+    /// 生成 URL Scheme，返回未转换为 [`Error`] 的响应，保留原始 errcode
+    pub(super) async fn url_scheme_response(
+        &self,
+        args: UrlSchemeArgs,
+    ) -> Result<MpResponse<UrlScheme>> {
+        debug!("url scheme args {:?}", &args);
+
+        let query = serde_json::json!({
+            "access_token": self.client.token().await?
+        });
+
+        let request = Endpoint::UrlScheme
+            .request()
+            .query(query)
+            .body(serde_json::to_value(&args)?)
+            .build()?;
+
+        let response = self.client.core.client.execute(request).await?;
+        debug!("url scheme response status: {}", response.status());
+
+        MpResponse::from_slice(&response.to_raw()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_url_scheme_args() {
+        let args = UrlSchemeArgs::builder()
+            .path("pages/index/index")
+            .query("id=1")
+            .expire_interval(7)
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&args).unwrap(),
+            json!({
+                "jump_wxa": {"path": "pages/index/index", "query": "id=1"},
+                "is_expire": true,
+                "expire_type": 1,
+                "expire_interval": 7
+            })
+        );
+
+        assert!(UrlSchemeArgs::builder().build().is_err());
+        assert!(UrlSchemeArgs::builder().path("/pages/index").build().is_err());
+        let builder = || UrlSchemeArgs::builder().path("pages/index/index");
+        assert!(builder().expire_interval(0).build().is_err());
+        assert!(builder().expire_interval(31).build().is_err());
+        assert!(builder().query("a".repeat(513)).build().is_err());
+        assert_eq!(builder().query("").build().unwrap().query(), None);
+
+        let args = builder().env_version(MinappEnvVersion::Trial).build().unwrap();
+        assert_eq!(args.env_version(), Some(MinappEnvVersion::Trial));
+        assert_eq!(serde_json::to_value(&args).unwrap()["jump_wxa"]["env_version"], "trial");
+    }
+}
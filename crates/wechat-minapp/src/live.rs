@@ -0,0 +1,607 @@
+//! 小程序直播
+//!
+//! [`Live::live_info`] 按 start/limit 拉取一页直播间，[`Live::rooms_stream`] 自动翻页，
+//! 逐个产出全部直播间。直播间状态映射为 [`LiveStatus`]。
+//!
+//! 商品提审需要封面图的 media_id，[`Live::add_goods_with_image`] 先把本地图片上传为临时素材，
+//! 再用得到的 media_id 添加商品并提审。
+//!
+//! ## 示例
+//!
+//! ```no_run
+//! use futures_util::TryStreamExt;
+//! use wechat_minapp::WechatMinapp;
+//! use wechat_minapp::live::{GoodsInfo, GoodsPrice, LiveStatus};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = WechatMinapp::new("app_id", "secret");
+//!     let live = client.live();
+//!
+//!     let living: Vec<_> = live
+//!         .rooms_stream()
+//!         .try_filter(|room| std::future::ready(room.live_status == LiveStatus::Living))
+//!         .try_collect()
+//!         .await?;
+//!     println!("{} 个直播间正在直播", living.len());
+//!
+//!     let goods = GoodsInfo::new("咖啡豆", "pages/goods/index?id=1", GoodsPrice::Fixed(59.9));
+//!     let image = std::fs::read("cover.jpg")?;
+//!     let added = live.add_goods_with_image(goods, image, "cover.jpg").await?;
+//!     println!("商品 {} 审核单 {}", added.goods_id, added.audit_id);
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::constants::Endpoint;
+use crate::WechatMinapp;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tracing::debug;
+use wechat_core::response::ExtraFields;
+use wechat_core::utils::{Multipart, Paginator, Part, ResponseExt, Stream, UnixTimestamp};
+use wechat_core::{localized, ErrorKind, Result};
+
+/// 每页最多拉取的直播间数量
+pub const LIVE_INFO_PAGE_SIZE: u32 = 100;
+
+/// 没有直播间时 getLiveInfo 返回的 errcode
+const NO_ROOM_ERRCODE: i32 = 1;
+
+pub struct Live {
+    pub client: WechatMinapp,
+}
+
+impl Live {
+    pub fn new(client: impl Into<WechatMinapp>) -> Self {
+        Live {
+            client: client.into(),
+        }
+    }
+
+
+    wechat_core::endpoint! {
+        POST idempotent room_list(args: LiveInfoArgs) -> LiveInfo = Endpoint::LiveInfo.url();
+    }
+
+    /// 获取直播间列表
+    ///
+    /// `start` 为起始位置，`limit` 为拉取数量，最多 100 个。没有直播间时返回空列表。
+    ///
+    /// [官方文档](https://developers.weixin.qq.com/miniprogram/dev/platform-capabilities/industry/liveplayer/studio-api.html#8)
+    pub async fn live_info(&self, start: u32, limit: u32) -> Result<LiveInfo> {
+        let args = LiveInfoArgs {
+            start,
+            limit: limit.clamp(1, LIVE_INFO_PAGE_SIZE),
+        };
+        match self.room_list(args).await {
+            Err(e) if e.code() == Some(NO_ROOM_ERRCODE) => Ok(LiveInfo::default()),
+            result => result,
+        }
+    }
+
+    /// 逐个拉取全部直播间
+    ///
+    /// 基于 [`live_info`](Self::live_info) 自动翻页，每页拉取 100 个
+    pub fn rooms_stream(&self) -> impl Stream<Item = Result<RoomInfo>> + '_ {
+        Paginator::new(LIVE_INFO_PAGE_SIZE as usize, move |start, limit| async move {
+            let info = self.live_info(start as u32, limit as u32).await?;
+            Ok(info.room_info)
+        })
+        .into_stream()
+    }
+
+    /// 拉取全部直播间
+    pub async fn all_rooms(&self) -> Result<Vec<RoomInfo>> {
+        Paginator::new(LIVE_INFO_PAGE_SIZE as usize, |start, limit| async move {
+            let info = self.live_info(start as u32, limit as u32).await?;
+            Ok(info.room_info)
+        })
+        .collect_all()
+        .await
+    }
+
+    /// 上传图片临时素材，素材 3 天内有效
+    ///
+    /// `file_name` 的扩展名用于让微信识别图片格式，如 `cover.jpg`。
+    ///
+    /// [官方文档](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/kf-mgnt/kf-message/uploadTempMedia.html)
+    pub async fn upload_image(
+        &self,
+        image: impl Into<Vec<u8>>,
+        file_name: impl Into<String>,
+    ) -> Result<TempMedia> {
+        let image = image.into();
+        if image.is_empty() {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "图片内容不能为空",
+                "image must not be empty"
+            ))
+            .into());
+        }
+        debug!("upload_image {} bytes", image.len());
+
+        let query = serde_json::json!({
+            "access_token": self.client.token().await?,
+            "type": "image",
+        });
+        let form = Multipart::new().part(Part::bytes("media", image).file_name(file_name));
+        let request = Endpoint::UploadTempMedia
+            .request()
+            .query(query)
+            .build_multipart(form)?;
+
+        let response = self.client.core.client.execute_multipart(request).await?;
+        response.to_json::<TempMedia>()
+    }
+
+    wechat_core::endpoint! {
+        POST add_goods_body(args: AddGoodsArgs) -> AddedGoods = Endpoint::LiveAddGoods.url();
+    }
+
+    /// 添加商品并提审
+    ///
+    /// 需要先通过 [`GoodsInfo::cover_img`] 设置封面图的 media_id，
+    /// 使用本地图片时见 [`add_goods_with_image`](Self::add_goods_with_image)。
+    ///
+    /// [官方文档](https://developers.weixin.qq.com/miniprogram/dev/platform-capabilities/industry/liveplayer/commodity-api.html#1)
+    pub async fn add_goods(&self, goods: GoodsInfo) -> Result<AddedGoods> {
+        goods.validate()?;
+        self.add_goods_body(AddGoodsArgs {
+            goods_info: goods.into(),
+        })
+        .await
+    }
+
+    /// 上传本地图片作为封面图，再添加商品并提审
+    ///
+    /// 商品封面图尺寸最大 300 像素 * 300 像素。
+    pub async fn add_goods_with_image(
+        &self,
+        goods: GoodsInfo,
+        image: impl Into<Vec<u8>>,
+        file_name: impl Into<String>,
+    ) -> Result<AddedGoods> {
+        // 上传前先校验，避免白白消耗素材上传次数
+        goods.clone().cover_img("media_id").validate()?;
+
+        let media = self.upload_image(image, file_name).await?;
+        self.add_goods(goods.cover_img(media.media_id)).await
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct LiveInfoArgs {
+    start: u32,
+    limit: u32,
+}
+
+/// 临时素材
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TempMedia {
+    /// 素材类型
+    #[serde(rename = "type", default)]
+    pub media_type: String,
+    /// 素材的 media_id，3 天内有效
+    pub media_id: String,
+    /// 上传时间
+    pub created_at: UnixTimestamp,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+/// 直播商品价格
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GoodsPrice {
+    /// 一口价
+    Fixed(f64),
+    /// 价格区间，最低价与最高价
+    Range(f64, f64),
+    /// 显示折扣价，原价与现价
+    Discount { original: f64, current: f64 },
+}
+
+/// 直播商品信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoodsInfo {
+    /// 商品名称，最长 14 个汉字
+    pub name: String,
+    /// 商品的小程序页面路径
+    pub url: String,
+    /// 价格
+    pub price: GoodsPrice,
+    /// 封面图的 media_id
+    pub cover_img_url: String,
+    /// 商品页面所在小程序的 AppID，非本小程序的商品才需要填写
+    pub third_party_appid: Option<String>,
+}
+
+impl GoodsInfo {
+    pub fn new(name: impl Into<String>, url: impl Into<String>, price: GoodsPrice) -> Self {
+        GoodsInfo {
+            name: name.into(),
+            url: url.into(),
+            price,
+            cover_img_url: String::new(),
+            third_party_appid: None,
+        }
+    }
+
+    /// 设置封面图的 media_id
+    pub fn cover_img(mut self, media_id: impl Into<String>) -> Self {
+        self.cover_img_url = media_id.into();
+        self
+    }
+
+    /// 设置商品页面所在小程序的 AppID
+    pub fn third_party_appid(mut self, appid: impl Into<String>) -> Self {
+        self.third_party_appid = Some(appid.into());
+        self
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() || self.url.trim().is_empty() {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "商品名称与页面路径不能为空",
+                "goods name and url are required"
+            ))
+            .into());
+        }
+        if self.cover_img_url.is_empty() {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "商品封面图 media_id 不能为空",
+                "goods cover image media_id is required"
+            ))
+            .into());
+        }
+
+        let valid = match self.price {
+            GoodsPrice::Fixed(price) => price > 0.0,
+            GoodsPrice::Range(low, high) => low > 0.0 && low < high,
+            GoodsPrice::Discount { original, current } => current > 0.0 && current < original,
+        };
+        if !valid {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "商品价格不正确：{:?}",
+                "invalid goods price: {:?}",
+                self.price
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct AddGoodsArgs {
+    #[serde(rename = "goodsInfo")]
+    goods_info: GoodsInfoBody,
+}
+
+/// 提交给微信的商品信息
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GoodsInfoBody {
+    cover_img_url: String,
+    name: String,
+    price_type: u8,
+    price: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    price2: Option<f64>,
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    third_party_appid: Option<String>,
+}
+
+impl From<GoodsInfo> for GoodsInfoBody {
+    fn from(goods: GoodsInfo) -> Self {
+        let (price_type, price, price2) = match goods.price {
+            GoodsPrice::Fixed(price) => (1, price, None),
+            GoodsPrice::Range(low, high) => (2, low, Some(high)),
+            GoodsPrice::Discount { original, current } => (3, original, Some(current)),
+        };
+        GoodsInfoBody {
+            cover_img_url: goods.cover_img_url,
+            name: goods.name,
+            price_type,
+            price,
+            price2,
+            url: goods.url,
+            third_party_appid: goods.third_party_appid,
+        }
+    }
+}
+
+/// 添加商品的结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddedGoods {
+    /// 商品 ID
+    #[serde(rename = "goodsId")]
+    pub goods_id: u64,
+    /// 审核单 ID
+    #[serde(rename = "auditId")]
+    pub audit_id: u64,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+/// 直播间状态
+///
+/// 序列化为微信返回的数值，未收录的数值保留在 [`LiveStatus::Unknown`] 中。
+///
+/// ```
+/// use wechat_minapp::live::LiveStatus;
+///
+/// let status: LiveStatus = serde_json::from_str("101").unwrap();
+/// assert_eq!(status, LiveStatus::Living);
+/// assert_eq!(status.to_string(), "直播中");
+/// assert_eq!(LiveStatus::from(199), LiveStatus::Unknown(199));
+/// ```
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Clone, Copy)]
+#[serde(from = "i32", into = "i32")]
+pub enum LiveStatus {
+    /// 直播中
+    Living,
+    /// 未开始
+    NotStarted,
+    /// 已结束
+    Ended,
+    /// 禁播
+    Banned,
+    /// 暂停
+    Paused,
+    /// 异常
+    Abnormal,
+    /// 已过期
+    Expired,
+    /// 未收录的状态
+    Unknown(i32),
+}
+
+impl LiveStatus {
+    pub fn value(&self) -> i32 {
+        match self {
+            LiveStatus::Living => 101,
+            LiveStatus::NotStarted => 102,
+            LiveStatus::Ended => 103,
+            LiveStatus::Banned => 104,
+            LiveStatus::Paused => 105,
+            LiveStatus::Abnormal => 106,
+            LiveStatus::Expired => 107,
+            LiveStatus::Unknown(value) => *value,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LiveStatus::Living => "直播中",
+            LiveStatus::NotStarted => "未开始",
+            LiveStatus::Ended => "已结束",
+            LiveStatus::Banned => "禁播",
+            LiveStatus::Paused => "暂停",
+            LiveStatus::Abnormal => "异常",
+            LiveStatus::Expired => "已过期",
+            LiveStatus::Unknown(_) => "未知状态",
+        }
+    }
+
+    /// 直播已经结束，不会再开播
+    pub fn is_finished(&self) -> bool {
+        matches!(self, LiveStatus::Ended | LiveStatus::Banned | LiveStatus::Expired)
+    }
+}
+
+impl From<i32> for LiveStatus {
+    fn from(value: i32) -> Self {
+        match value {
+            101 => LiveStatus::Living,
+            102 => LiveStatus::NotStarted,
+            103 => LiveStatus::Ended,
+            104 => LiveStatus::Banned,
+            105 => LiveStatus::Paused,
+            106 => LiveStatus::Abnormal,
+            107 => LiveStatus::Expired,
+            other => LiveStatus::Unknown(other),
+        }
+    }
+}
+
+impl From<LiveStatus> for i32 {
+    fn from(status: LiveStatus) -> Self {
+        status.value()
+    }
+}
+
+impl fmt::Display for LiveStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// 一页直播间
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LiveInfo {
+    /// 直播间列表
+    #[serde(default)]
+    pub room_info: Vec<RoomInfo>,
+    /// 直播间总数
+    #[serde(default)]
+    pub total: u32,
+}
+
+/// 直播间
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoomInfo {
+    /// 直播间名称
+    pub name: String,
+    /// 直播间 ID
+    pub roomid: u64,
+    /// 背景图
+    #[serde(default)]
+    pub cover_img: String,
+    /// 分享图
+    #[serde(default)]
+    pub share_img: String,
+    /// 直播间状态
+    pub live_status: LiveStatus,
+    /// 计划开始时间
+    pub start_time: UnixTimestamp,
+    /// 计划结束时间
+    pub end_time: UnixTimestamp,
+    /// 主播名称
+    #[serde(default)]
+    pub anchor_name: String,
+    /// 直播间商品
+    #[serde(default)]
+    pub goods: Vec<RoomGoods>,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+/// 直播间商品
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoomGoods {
+    /// 商品 ID
+    #[serde(default)]
+    pub goods_id: u64,
+    /// 商品名称
+    pub name: String,
+    /// 商品封面图
+    #[serde(default)]
+    pub cover_img: String,
+    /// 商品小程序路径
+    #[serde(default)]
+    pub url: String,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use futures_util::TryStreamExt;
+    use http::{Request, Response};
+    use std::sync::{Arc, Mutex};
+    use crate::fixtures;
+    use wechat_core::client::HttpClient;
+
+    /// 共有 `total` 个直播间，按请求的 start/limit 返回
+    struct RoomsClient {
+        total: u32,
+        requests: Mutex<Vec<(u32, u32)>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for RoomsClient {
+        async fn execute(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+            let args: serde_json::Value = serde_json::from_slice(request.body()).unwrap();
+            let start = args["start"].as_u64().unwrap() as u32;
+            let limit = args["limit"].as_u64().unwrap() as u32;
+            self.requests.lock().unwrap().push((start, limit));
+
+            let rooms: Vec<_> = (start..self.total.min(start + limit))
+                .map(|id| {
+                    serde_json::json!({
+                        "name": format!("room {}", id),
+                        "roomid": id,
+                        "live_status": if id % 2 == 0 { 101 } else { 103 },
+                        "start_time": 1588237200,
+                        "end_time": 1588240800,
+                        "goods": [{"goods_id": 1, "name": "商品", "url": "pages/index"}],
+                    })
+                })
+                .collect();
+            let body = if rooms.is_empty() {
+                serde_json::json!({"errcode": 1, "errmsg": "no data"})
+            } else {
+                serde_json::json!({"errcode": 0, "room_info": rooms, "total": self.total})
+            };
+            Ok(Response::new(serde_json::to_vec(&body).unwrap()))
+        }
+    }
+
+    fn live_client(total: u32) -> (Live, Arc<RoomsClient>) {
+        let http_client = Arc::new(RoomsClient {
+            total,
+            requests: Mutex::new(Vec::new()),
+        });
+        (Live::new(fixtures::client_with(http_client.clone())), http_client)
+    }
+
+    #[tokio::test]
+    async fn test_add_goods_with_image() {
+        let (client, http_client) = fixtures::client(
+            r#"{"errcode":0,"type":"image","media_id":"MEDIA","created_at":1380000000,
+            "goodsId":51,"auditId":525022786}"#,
+        );
+        let live = Live::new(client);
+
+        let goods = GoodsInfo::new("咖啡豆", "pages/goods/index?id=1", GoodsPrice::Range(10.0, 20.5));
+        let added = live.add_goods_with_image(goods, vec![0xff, 0xd8], "a.jpg").await.unwrap();
+        assert_eq!(added.goods_id, 51);
+        assert_eq!(added.audit_id, 525022786);
+
+        let requests = http_client.requests();
+        assert!(requests[0].url.starts_with("https://api.weixin.qq.com/cgi-bin/media/upload?"));
+        assert!(requests[0].url.contains("type=image"));
+        assert!(requests[0].body_text().contains("name=\"media\"; filename=\"a.jpg\""));
+        let body: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({"goodsInfo": {"coverImgUrl": "MEDIA", "name": "咖啡豆",
+                "priceType": 2, "price": 10.0, "price2": 20.5, "url": "pages/goods/index?id=1"}})
+        );
+
+        // 参数不合法时不上传图片
+        let goods = GoodsInfo::new("咖啡豆", "pages/goods/index", GoodsPrice::Range(20.0, 10.0));
+        let error = live.add_goods_with_image(goods, vec![0xff], "a.jpg").await.unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidParameter(_)));
+        let goods = GoodsInfo::new("咖啡豆", "pages/goods/index", GoodsPrice::Fixed(1.0));
+        assert!(live.add_goods(goods).await.is_err());
+        assert_eq!(http_client.requests().len(), 2);
+    }
+
+    #[test]
+    fn test_live_status() {
+        for value in 101..=107 {
+            let status = LiveStatus::from(value);
+            assert_ne!(status, LiveStatus::Unknown(value));
+            assert_eq!(status.value(), value);
+        }
+        assert!(LiveStatus::Expired.is_finished());
+        assert!(!LiveStatus::Paused.is_finished());
+        assert_eq!(serde_json::to_string(&LiveStatus::Unknown(108)).unwrap(), "108");
+    }
+
+    #[tokio::test]
+    async fn test_live_info() {
+        let (live, http_client) = live_client(3);
+        let info = live.live_info(0, 500).await.unwrap();
+        assert_eq!(info.total, 3);
+        assert_eq!(info.room_info[1].live_status, LiveStatus::Ended);
+        assert_eq!(info.room_info[0].start_time.raw(), 1588237200);
+
+        assert_eq!(live.live_info(10, 10).await.unwrap(), LiveInfo::default());
+        assert_eq!(*http_client.requests.lock().unwrap(), [(0, 100), (10, 10)]);
+    }
+
+    #[tokio::test]
+    async fn test_rooms_stream() {
+        let (live, http_client) = live_client(250);
+        let rooms: Vec<RoomInfo> = live.rooms_stream().try_collect().await.unwrap();
+        assert_eq!(rooms.len(), 250);
+        assert_eq!(rooms[249].roomid, 249);
+        assert_eq!(*http_client.requests.lock().unwrap(), [(0, 100), (100, 100), (200, 100)]);
+
+        let (live, http_client) = live_client(200);
+        assert_eq!(live.all_rooms().await.unwrap().len(), 200);
+        // 恰好整页时需要多请求一次，由 errcode 1 结束
+        assert_eq!(http_client.requests.lock().unwrap().len(), 3);
+    }
+}
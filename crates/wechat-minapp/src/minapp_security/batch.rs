@@ -0,0 +1,134 @@
+//! 批量内容安全检测
+//!
+//! 清洗历史数据时需要把大量文本逐条送检，[`MinappSecurity::msg_sec_check_batch`]
+//! 以受控的并发数与请求频率调用接口，系统繁忙等临时错误自动重试，最终返回每条的结果或错误。
+
+use super::{Args, MinappSecurity, MsgSecCheckResult};
+use crate::batch::{self, BatchOptions, BatchResult};
+use tracing::debug;
+
+impl MinappSecurity {
+    /// 批量内容安全检测
+    ///
+    /// 直接传入并发数时不限速、不重试，需要时使用 [`BatchOptions`] 配置：
+    ///
+    /// ```no_run
+    /// use wechat_minapp::WechatMinapp;
+    /// use wechat_minapp::batch::BatchOptions;
+    /// use wechat_minapp::minapp_security::{Args, MinappSecurity, Scene};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let security = MinappSecurity::new(WechatMinapp::new("app_id", "secret"));
+    ///     let comments = vec!["评论一", "评论二", "评论三"];
+    ///     let items = comments
+    ///         .iter()
+    ///         .map(|comment| Args::new(*comment, Scene::Comment, "user_openid"));
+    ///
+    ///     // 最多 4 个并发，每秒最多 20 个请求，临时错误最多重试 2 次
+    ///     let options = BatchOptions::new(4).rate_limit(20).retries(2);
+    ///     let result = security.msg_sec_check_batch(items, options).await;
+    ///     for (index, check) in &result.succeeded {
+    ///         if !check.is_pass() {
+    ///             println!("第 {} 条评论需要处理: {:?}", index + 1, check.get_label());
+    ///         }
+    ///     }
+    ///     for (index, error) in &result.failed {
+    ///         eprintln!("第 {} 条评论检测失败: {}", index + 1, error);
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn msg_sec_check_batch<I>(
+        &self,
+        items: I,
+        options: impl Into<BatchOptions>,
+    ) -> BatchResult<MsgSecCheckResult>
+    where
+        I: IntoIterator<Item = Args>,
+    {
+        let result = batch::run(items, options.into(), |args| async move {
+            self.msg_sec_check(&args).await
+        })
+        .await;
+        debug!(
+            "batch msg_sec_check: {} succeeded, {} failed",
+            result.succeeded.len(),
+            result.failed.len()
+        );
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use crate::minapp_security::Scene;
+    use async_trait::async_trait;
+    use http::{Request, Response};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use wechat_core::client::HttpClient;
+    use wechat_core::{ErrorKind, Result};
+
+    /// 前 `busy` 次请求返回系统繁忙，之后返回通过
+    struct BusyClient {
+        busy: usize,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl HttpClient for BusyClient {
+        async fn execute(&self, _request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+            let body = if self.calls.fetch_add(1, Ordering::SeqCst) < self.busy {
+                r#"{"errcode":-1,"errmsg":"system error"}"#
+            } else {
+                r#"{"errcode":0,"errmsg":"ok","result":{"suggest":"pass","label":100}}"#
+            };
+            Ok(Response::new(body.as_bytes().to_vec()))
+        }
+    }
+
+    fn security(busy: usize) -> MinappSecurity {
+        let http_client = Arc::new(BusyClient {
+            busy,
+            calls: AtomicUsize::new(0),
+        });
+        MinappSecurity::new(fixtures::client_with(http_client))
+    }
+
+    fn items(count: usize) -> Vec<Args> {
+        (0..count)
+            .map(|id| Args::new(format!("评论{}", id), Scene::Comment, "openid"))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_msg_sec_check_batch() {
+        let result = security(0).msg_sec_check_batch(items(5), 2).await;
+
+        assert!(result.is_all_ok());
+        assert!(result.succeeded.iter().all(|(_, check)| check.is_pass()));
+        let indexes: Vec<usize> = result.succeeded.iter().map(|(index, _)| *index).collect();
+        assert_eq!(indexes, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_on_system_busy() {
+        // 不重试时系统繁忙直接失败
+        let result = security(1).msg_sec_check_batch(items(1), 1).await;
+        assert!(matches!(result.failed[0].1.kind(), ErrorKind::System(_)));
+
+        let result = security(2)
+            .msg_sec_check_batch(items(1), BatchOptions::new(1).retries(2))
+            .await;
+        assert!(result.is_all_ok());
+
+        let result = security(3)
+            .msg_sec_check_batch(items(1), BatchOptions::new(1).retries(2))
+            .await;
+        assert_eq!(result.failed.len(), 1);
+    }
+}
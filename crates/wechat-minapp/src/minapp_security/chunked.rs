@@ -0,0 +1,245 @@
+//! 超长文本分片检测
+//!
+//! 单次检测的文本不能超过 [`MAX_CONTENT_LENGTH`] 字，文章、长评论等内容需要拆分后分别送检。
+//! [`MinappSecurity::msg_sec_check_chunked`] 按句子边界分片并发检测，任一分片有风险即整体有风险，
+//! 同时返回各分片的检测明细。
+
+use super::msg_sec_check::MAX_CONTENT_LENGTH;
+use super::{Args, Label, MinappSecurity, MsgSecCheckResult, Suggest};
+use futures_util::stream::{self, StreamExt, TryStreamExt};
+use tracing::debug;
+use wechat_core::Result;
+
+/// 分片检测时最多同时进行的请求数
+const CHUNK_CONCURRENCY: usize = 4;
+
+/// 句子结束符，分片优先在这些字符之后切分
+const SENTENCE_TERMINATORS: &[char] = &['。', '！', '？', '；', '…', '!', '?', ';', '\n'];
+
+/// 单个分片的检测结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkResult {
+    /// 分片在全部分片中的下标
+    pub index: usize,
+    /// 分片文本
+    pub content: String,
+    /// 分片的检测结果
+    pub result: MsgSecCheckResult,
+}
+
+impl ChunkResult {
+    /// 分片的综合建议，接口未返回综合结果时按需要人工审核处理
+    pub fn suggest(&self) -> Suggest {
+        self.result.get_suggest().cloned().unwrap_or(Suggest::Review)
+    }
+}
+
+/// 分片检测的聚合结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkedCheckResult {
+    /// 整体建议：任一分片为 risky 即为 risky，否则任一分片为 review 即为 review
+    pub suggest: Suggest,
+    /// 决定整体建议的分片所命中的标签
+    pub label: Option<Label>,
+    /// 各分片的检测明细，按原文顺序排列
+    pub chunks: Vec<ChunkResult>,
+}
+
+impl ChunkedCheckResult {
+    fn from_chunks(chunks: Vec<ChunkResult>) -> Self {
+        // priority 越小越严重，相同时取靠前的分片
+        let decisive = chunks
+            .iter()
+            .min_by_key(|chunk| chunk.suggest().priority());
+
+        ChunkedCheckResult {
+            suggest: decisive.map(|chunk| chunk.suggest()).unwrap_or(Suggest::Pass),
+            label: decisive.and_then(|chunk| chunk.result.get_label().copied()),
+            chunks,
+        }
+    }
+
+    /// 检查是否通过
+    pub fn is_pass(&self) -> bool {
+        self.suggest.is_pass()
+    }
+
+    /// 检查是否有风险
+    pub fn is_risky(&self) -> bool {
+        self.suggest.is_risky()
+    }
+
+    /// 检查是否需要审核
+    pub fn needs_review(&self) -> bool {
+        self.suggest.needs_review()
+    }
+
+    /// 建议不为 pass 的分片
+    pub fn flagged_chunks(&self) -> Vec<&ChunkResult> {
+        self.chunks
+            .iter()
+            .filter(|chunk| !chunk.suggest().is_pass())
+            .collect()
+    }
+}
+
+/// 按句子边界把文本拆分为不超过 `max_chars` 字的分片
+///
+/// 尽量把多个完整的句子合并到同一分片，单个句子超长时按字数硬切。
+pub fn split_content(content: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_chars = 0;
+
+    for sentence in content.split_inclusive(SENTENCE_TERMINATORS) {
+        let sentence_chars = sentence.chars().count();
+
+        if current_chars + sentence_chars > max_chars && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+            current_chars = 0;
+        }
+
+        if sentence_chars > max_chars {
+            let chars: Vec<char> = sentence.chars().collect();
+            let mut pieces = chars.chunks(max_chars).map(String::from_iter).peekable();
+            while let Some(piece) = pieces.next() {
+                if pieces.peek().is_some() {
+                    chunks.push(piece);
+                } else {
+                    current_chars = piece.chars().count();
+                    current = piece;
+                }
+            }
+            continue;
+        }
+
+        current.push_str(sentence);
+        current_chars += sentence_chars;
+    }
+
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+impl MinappSecurity {
+    /// 超长文本分片检测
+    ///
+    /// 按句子边界把 `args.content` 拆分为不超过 [`MAX_CONTENT_LENGTH`] 字的分片并发检测，
+    /// 其余参数（场景、openid、标题等）每个分片都会携带。未超长的内容只检测一次。
+    /// 任一分片请求失败时返回该错误。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use wechat_minapp::WechatMinapp;
+    /// use wechat_minapp::minapp_security::{Args, MinappSecurity, Scene};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let security = MinappSecurity::new(WechatMinapp::new("app_id", "secret"));
+    ///     let article = "很长的文章内容。".repeat(1000);
+    ///     let args = Args::new(article, Scene::Forum, "user_openid");
+    ///
+    ///     let result = security.msg_sec_check_chunked(&args).await?;
+    ///     if result.is_risky() {
+    ///         for chunk in result.flagged_chunks() {
+    ///             println!("第 {} 段有风险: {:?}", chunk.index + 1, chunk.result.get_label());
+    ///         }
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn msg_sec_check_chunked(&self, args: &Args) -> Result<ChunkedCheckResult> {
+        let contents = split_content(&args.content, MAX_CONTENT_LENGTH);
+        debug!("msg_sec_check_chunked: {} chunks", contents.len());
+
+        let chunks = stream::iter(contents.into_iter().enumerate())
+            .map(|(index, content)| async move {
+                let chunk_args = Args {
+                    content,
+                    ..args.clone()
+                };
+                let result = self.msg_sec_check(&chunk_args).await?;
+                Ok::<_, wechat_core::Error>(ChunkResult {
+                    index,
+                    content: chunk_args.content,
+                    result,
+                })
+            })
+            .buffered(CHUNK_CONCURRENCY)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        Ok(ChunkedCheckResult::from_chunks(chunks))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use crate::minapp_security::Scene;
+    use async_trait::async_trait;
+    use http::{Request, Response};
+    use std::sync::Arc;
+    use wechat_core::client::HttpClient;
+
+    /// 内容包含“违规”时返回 risky
+    struct KeywordClient;
+
+    #[async_trait]
+    impl HttpClient for KeywordClient {
+        async fn execute(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+            let body = String::from_utf8_lossy(request.body());
+            let body = if body.contains("违规") {
+                r#"{"errcode":0,"errmsg":"ok","result":{"suggest":"risky","label":20002}}"#
+            } else {
+                r#"{"errcode":0,"errmsg":"ok","result":{"suggest":"pass","label":100}}"#
+            };
+            Ok(Response::new(body.as_bytes().to_vec()))
+        }
+    }
+
+    fn security() -> MinappSecurity {
+        MinappSecurity::new(fixtures::client_with(Arc::new(KeywordClient)))
+    }
+
+    #[test]
+    fn test_split_content() {
+        assert_eq!(split_content("你好。世界！", 10), vec!["你好。世界！"]);
+        assert_eq!(split_content("你好。世界！", 4), vec!["你好。", "世界！"]);
+        assert_eq!(split_content("一二三四五六七", 3), vec!["一二三", "四五六", "七"]);
+        assert_eq!(split_content("一二三四五。六", 3), vec!["一二三", "四五。", "六"]);
+        assert!(split_content("", 3).is_empty());
+
+        let content = "这是一句话。".repeat(1000);
+        let chunks = split_content(&content, MAX_CONTENT_LENGTH);
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|chunk| chunk.chars().count() <= MAX_CONTENT_LENGTH));
+        assert!(chunks.iter().all(|chunk| chunk.ends_with('。')));
+        assert_eq!(chunks.concat(), content);
+    }
+
+    #[tokio::test]
+    async fn test_msg_sec_check_chunked() {
+        let security = security();
+
+        let content = "正常内容。".repeat(1000);
+        let args = Args::new(content.clone(), Scene::Forum, "openid");
+        let result = security.msg_sec_check_chunked(&args).await.unwrap();
+        assert!(result.is_pass());
+        assert_eq!(result.label, Some(Label::Normal));
+        assert_eq!(result.chunks.len(), 2);
+
+        let args = Args::new(format!("{}违规内容。", content), Scene::Forum, "openid");
+        let result = security.msg_sec_check_chunked(&args).await.unwrap();
+        assert!(result.is_risky());
+        assert_eq!(result.label, Some(Label::Porn));
+        assert_eq!(result.flagged_chunks().len(), 1);
+        assert_eq!(result.flagged_chunks()[0].index, 2);
+    }
+}
@@ -0,0 +1,120 @@
+//! 多媒体内容安全异步检测
+//!
+//! [`MinappSecurity::media_check_async`] 提交图片或音频的地址后立即返回 `trace_id`，
+//! 检测结果通过 `wxa_media_check` 事件推送到消息回调，使用 `trace_id` 与提交的内容对应。
+//!
+//! ```no_run
+//! use wechat_minapp::WechatMinapp;
+//! use wechat_minapp::minapp_security::{MediaCheckArgs, MediaType, Scene};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = WechatMinapp::new("app_id", "secret");
+//!     let args = MediaCheckArgs::new(
+//!         "https://example.com/avatar.jpg",
+//!         MediaType::Image,
+//!         Scene::Profile,
+//!         "user_openid",
+//!     );
+//!     let response = client.security().media_check_async(&args).await?;
+//!     println!("等待回调推送检测结果: {}", response.trace_id);
+//!     Ok(())
+//! }
+//! ```
+
+use super::{MinappSecurity, Scene};
+use crate::constants::Endpoint;
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use wechat_core::response::ExtraFields;
+use wechat_core::{localized, ErrorKind, Result};
+
+/// 检测的媒体类型
+#[derive(Debug, Serialize_repr, Deserialize_repr, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MediaType {
+    /// 音频
+    Audio = 1,
+    /// 图片
+    Image = 2,
+}
+
+/// 多媒体内容安全检测参数
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MediaCheckArgs {
+    /// 要检测的多媒体地址，需要公网可以访问
+    pub media_url: String,
+    /// 媒体类型
+    pub media_type: MediaType,
+    /// 接口版本号，2.0 版本为固定值 2
+    pub version: u32,
+    /// 场景枚举值
+    pub scene: Scene,
+    /// 用户的 openid（用户需在近两小时访问过小程序）
+    pub openid: String,
+}
+
+impl MediaCheckArgs {
+    pub fn new(
+        media_url: impl Into<String>,
+        media_type: MediaType,
+        scene: Scene,
+        openid: impl Into<String>,
+    ) -> Self {
+        MediaCheckArgs {
+            media_url: media_url.into(),
+            media_type,
+            version: 2,
+            scene,
+            openid: openid.into(),
+        }
+    }
+
+    /// 验证参数是否有效
+    pub fn validate(&self) -> Result<()> {
+        if !self.media_url.starts_with("https://") && !self.media_url.starts_with("http://") {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "media_url 需要是 http 或 https 地址：{}",
+                "media_url must be an http or https url: {}",
+                self.media_url
+            ))
+            .into());
+        }
+
+        if self.openid.is_empty() {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "openid 是必填参数",
+                "openid is required"
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// 多媒体内容安全检测的提交结果
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct MediaCheckResponse {
+    /// 唯一请求标识，与回调推送中的 `trace_id` 对应
+    pub trace_id: String,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+impl MinappSecurity {
+    wechat_core::endpoint! {
+        POST idempotent media_check_body(args: &MediaCheckArgs) -> MediaCheckResponse =
+            Endpoint::MediaCheckAsync.url();
+    }
+
+    /// 多媒体内容安全识别
+    ///
+    /// 检测是异步的，结果通过 `wxa_media_check` 事件推送到消息回调，30 分钟内送达。
+    ///
+    /// [官方文档](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/sec-center/sec-check/mediaCheckAsync.html)
+    pub async fn media_check_async(&self, args: &MediaCheckArgs) -> Result<MediaCheckResponse> {
+        args.validate()?;
+        self.media_check_body(args).await
+    }
+}
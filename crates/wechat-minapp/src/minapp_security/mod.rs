@@ -1,68 +1,132 @@
 //! 微信小程序内容安全检测模块
 //!
 //! - [`msg_sec_check`][]: 文本内容安全检测。
+//! - [`chunked`][]: 超长文本分片检测。
+//! - [`media_check`][]: 图片、音频异步检测。
+//! - 批量检测 [`MinappSecurity::msg_sec_check_batch`]，支持并发、限速与重试。
+//! - 用户资料检测 [`MinappSecurity::check_profile`]，组合昵称、签名与头像的检测。
+//! - [`policy`][]: 按置信度阈值、关键词白名单等规则把检测结果归一化为放行、审核或拒绝。
 //!
 
+mod batch;
+pub mod chunked;
+pub mod media_check;
 pub mod msg_sec_check;
+pub mod policy;
+mod profile;
 
 use serde::{Deserialize, Serialize};
-use serde_repr::Deserialize_repr;
+use std::fmt;
 use strum::Display;
 
-pub use msg_sec_check::{Args, MsgSecCheckResult, Scene};
+pub use chunked::{ChunkResult, ChunkedCheckResult};
+pub use media_check::{MediaCheckArgs, MediaCheckResponse, MediaType};
+pub use msg_sec_check::{Args, DetailResult, MsgSecCheckResult, Scene, MAX_CONTENT_LENGTH};
+pub use policy::{Decision, SecurityPolicy, Verdict};
+pub use profile::ProfileCheckResult;
 
 use crate::WechatMinapp;
 
-#[derive(Debug, Deserialize_repr, Display, Serialize, PartialEq, Clone)]
-#[repr(i32)]
+/// 内容安全检测命中的标签
+///
+/// 序列化为微信返回的数值（如 `100`、`20001`），未收录的数值保留在 [`Label::Unknown`] 中，
+/// 微信新增标签时不会导致反序列化失败。
+///
+/// ```
+/// use wechat_minapp::minapp_security::Label;
+///
+/// let label: Label = serde_json::from_str("20002").unwrap();
+/// assert_eq!(label, Label::Porn);
+/// assert_eq!(label.to_string(), "色情");
+///
+/// let label: Label = serde_json::from_str("29999").unwrap();
+/// assert_eq!(label, Label::Unknown(29999));
+/// assert_eq!(label.value(), 29999);
+/// ```
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Clone, Copy)]
+#[serde(from = "i32", into = "i32")]
 pub enum Label {
-    #[strum(serialize = "正常")]
-    Normal = 100,
-
-    #[strum(serialize = "广告")]
-    Ad = 10001,
-
-    #[strum(serialize = "时政")]
-    Politics = 20001,
-
-    #[strum(serialize = "色情")]
-    Porn = 20002,
-
-    #[strum(serialize = "辱骂")]
-    Abuse = 20003,
-
-    #[strum(serialize = "违法犯罪")]
-    Illegal = 20006,
-
-    #[strum(serialize = "欺诈")]
-    Fraud = 20008,
-
-    #[strum(serialize = "低俗")]
-    Vulgar = 20012,
-
-    #[strum(serialize = "版权")]
-    Copyright = 20013,
-
-    #[strum(serialize = "其他")]
-    Other = 21000,
+    /// 正常
+    Normal,
+    /// 广告
+    Ad,
+    /// 时政
+    Politics,
+    /// 色情
+    Porn,
+    /// 辱骂
+    Abuse,
+    /// 违法犯罪
+    Illegal,
+    /// 欺诈
+    Fraud,
+    /// 低俗
+    Vulgar,
+    /// 版权
+    Copyright,
+    /// 其他
+    Other,
+    /// 未收录的标签
+    Unknown(i32),
 }
 
-// 可选：为方便使用，可以添加一些辅助方法
 impl Label {
-    /// 根据数值获取对应的标签枚举
+    /// 根据数值获取对应的标签枚举，未收录的数值返回 `None`
     pub fn from_value(value: i32) -> Option<Self> {
-        match value {
-            100 => Some(Label::Normal),
-            10001 => Some(Label::Ad),
-            20001 => Some(Label::Politics),
-            20002 => Some(Label::Porn),
-            20003 => Some(Label::Abuse),
-            20006 => Some(Label::Illegal),
-            20008 => Some(Label::Fraud),
-            20012 => Some(Label::Vulgar),
-            20013 => Some(Label::Copyright),
-            21000 => Some(Label::Other),
-            _ => None,
+        match Label::from(value) {
+            Label::Unknown(_) => None,
+            label => Some(label),
+        }
+    }
+
+    /// 微信返回的标签数值
+    pub fn value(&self) -> i32 {
+        match self {
+            Label::Normal => 100,
+            Label::Ad => 10001,
+            Label::Politics => 20001,
+            Label::Porn => 20002,
+            Label::Abuse => 20003,
+            Label::Illegal => 20006,
+            Label::Fraud => 20008,
+            Label::Vulgar => 20012,
+            Label::Copyright => 20013,
+            Label::Other => 21000,
+            Label::Unknown(value) => *value,
+        }
+    }
+
+    /// 类别名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Label::Normal => "正常",
+            Label::Ad => "广告",
+            Label::Politics => "时政",
+            Label::Porn => "色情",
+            Label::Abuse => "辱骂",
+            Label::Illegal => "违法犯罪",
+            Label::Fraud => "欺诈",
+            Label::Vulgar => "低俗",
+            Label::Copyright => "版权",
+            Label::Other => "其他",
+            Label::Unknown(_) => "未知",
+        }
+    }
+
+    /// 类别描述，可直接作为提示文案
+    pub fn description(&self) -> &'static str {
+        match self {
+            Label::Normal => "内容正常",
+            Label::Ad => "内容包含广告或营销推广信息",
+            Label::Politics => "内容涉及时政敏感信息",
+            Label::Porn => "内容包含色情信息",
+            Label::Abuse => "内容包含辱骂或人身攻击",
+            Label::Illegal => "内容涉及违法犯罪",
+            Label::Fraud => "内容涉嫌欺诈",
+            Label::Vulgar => "内容低俗",
+            Label::Copyright => "内容涉及版权问题",
+            Label::Other => "内容包含其他违规信息",
+            Label::Unknown(_) => "内容命中未知的违规类型",
         }
     }
 
@@ -77,6 +141,36 @@ impl Label {
     }
 }
 
+impl From<i32> for Label {
+    fn from(value: i32) -> Self {
+        match value {
+            100 => Label::Normal,
+            10001 => Label::Ad,
+            20001 => Label::Politics,
+            20002 => Label::Porn,
+            20003 => Label::Abuse,
+            20006 => Label::Illegal,
+            20008 => Label::Fraud,
+            20012 => Label::Vulgar,
+            20013 => Label::Copyright,
+            21000 => Label::Other,
+            other => Label::Unknown(other),
+        }
+    }
+}
+
+impl From<Label> for i32 {
+    fn from(label: Label) -> Self {
+        label.value()
+    }
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
 /// 内容安全检测建议
 #[derive(Debug, Deserialize, Serialize, Display, PartialEq, Clone)]
 pub enum Suggest {
@@ -136,9 +230,12 @@ pub struct MinappSecurity {
 }
 
 impl MinappSecurity {
-    pub fn new(client: WechatMinapp) -> Self {
-        MinappSecurity { client }
+    pub fn new(client: impl Into<WechatMinapp>) -> Self {
+        MinappSecurity {
+            client: client.into(),
+        }
     }
+
 }
 
 #[cfg(test)]
@@ -169,6 +266,21 @@ mod tests {
         assert_eq!(Suggest::Pass.priority(), 3);
     }
 
+    #[test]
+    fn test_label() {
+        assert_eq!(serde_json::from_str::<Label>("100").unwrap(), Label::Normal);
+        assert_eq!(serde_json::from_str::<Label>("20013").unwrap(), Label::Copyright);
+        assert_eq!(serde_json::from_str::<Label>("29999").unwrap(), Label::Unknown(29999));
+        assert_eq!(serde_json::to_string(&Label::Abuse).unwrap(), "20003");
+        assert_eq!(serde_json::to_string(&Label::Unknown(29999)).unwrap(), "29999");
+
+        assert_eq!(Label::Fraud.to_string(), "欺诈");
+        assert_eq!(Label::Fraud.description(), "内容涉嫌欺诈");
+        assert_eq!(Label::from_value(10001), Some(Label::Ad));
+        assert_eq!(Label::from_value(29999), None);
+        assert!(Label::Unknown(29999).is_violation());
+    }
+
     #[test]
     fn test_serialization() {
         // 测试序列化
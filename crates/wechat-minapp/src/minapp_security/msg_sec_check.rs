@@ -50,15 +50,22 @@
 //! ```
 
 use super::{Label, MinappSecurity, Suggest};
-use wechat_core::utils::{RequestBuilder, ResponseExt};
-use wechat_core::{Result, Error};
-use crate::constants;
+use wechat_core::utils::ResponseExt;
+use wechat_core::localized;
+use wechat_core::response::ExtraFields;
+use wechat_core::{Result, ErrorKind};
+use crate::constants::Endpoint;
 use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
 use tracing::debug;
 
+/// 单次检测的文本字数上限
+pub const MAX_CONTENT_LENGTH: usize = 2500;
+
 /// 内容安全检测场景
 ///
 /// 定义不同的内容检测场景，不同场景有不同的检测策略和敏感度。
+/// 序列化为数字 1–4，与微信接口的 `scene` 取值一致。
 ///
 /// # 场景说明
 ///
@@ -80,7 +87,8 @@ use tracing::debug;
 /// assert_eq!(profile_scene as u32, 1);
 /// assert_eq!(profile_scene.description(), "资料");
 /// ```
-#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[derive(Debug, Serialize_repr, Deserialize_repr, Clone, Copy, PartialEq)]
+#[repr(u8)]
 pub enum Scene {
     /// 资料
     Profile = 1,
@@ -112,10 +120,10 @@ pub enum Scene {
 /// use wechat_minapp::minapp_security::{Args, Scene};
 ///
 /// let args = Args::new("待检测的文本内容", Scene::Comment, "user_openid");
-/// assert_eq!(args.content_length(), 18);
-/// assert!(args.is_profile_scene());
+/// assert_eq!(args.content_length(), 8);
+/// assert!(!args.is_profile_scene());
 /// ```
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, PartialEq, Deserialize)]
 pub struct Args {
     /// 需检测的文本内容，文本字数的上限为2500字，需使用UTF-8编码
     pub content: String,
@@ -217,27 +225,38 @@ impl ArgsBuilder {
     pub fn build(self) -> Result<Args> {
         let content = self
             .content
-            .ok_or(Error::InvalidParameter("content 是必填参数".to_string()))?;
+            .ok_or_else(|| {
+                ErrorKind::InvalidParameter(localized!("content 是必填参数", "content is required"))
+            })?;
         //let version = self.version.unwrap_or(2); // 默认版本为2
         let scene = self
             .scene
-            .ok_or(Error::InvalidParameter("scene 是必填参数".to_string()))?;
+            .ok_or_else(|| {
+                ErrorKind::InvalidParameter(localized!("scene 是必填参数", "scene is required"))
+            })?;
         let openid = self
             .openid
-            .ok_or(Error::InvalidParameter("openid 是必填参数".to_string()))?;
+            .ok_or_else(|| {
+                ErrorKind::InvalidParameter(localized!("openid 是必填参数", "openid is required"))
+            })?;
 
         // 内容长度验证
-        if content.chars().count() > 2500 {
-            return Err(Error::InvalidParameter(
-                "content 长度不能超过2500字".to_string(),
-            ));
+        if content.chars().count() > MAX_CONTENT_LENGTH {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "content 长度不能超过{}字",
+                "content must not exceed {} characters",
+                MAX_CONTENT_LENGTH
+            ))
+            .into());
         }
 
         // 场景与签名的关联验证
         if self.signature.is_some() && scene != Scene::Profile {
-            return Err(Error::InvalidParameter(
-                "signature 仅在资料场景(scene=1)下有效".to_string(),
-            ));
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "signature 仅在资料场景(scene=1)下有效",
+                "signature is only valid in the profile scene (scene=1)"
+            ))
+            .into());
         }
 
         Ok(Args {
@@ -284,16 +303,21 @@ impl Args {
 
     /// 验证参数是否有效
     pub fn validate(&self) -> Result<()> {
-        if self.content.chars().count() > 2500 {
-            return Err(Error::InvalidParameter(
-                "content 长度不能超过2500字".to_string(),
-            ));
+        if self.content.chars().count() > MAX_CONTENT_LENGTH {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "content 长度不能超过{}字",
+                "content must not exceed {} characters",
+                MAX_CONTENT_LENGTH
+            ))
+            .into());
         }
 
         if self.signature.is_some() && !self.is_profile_scene() {
-            return Err(Error::InvalidParameter(
-                "signature 仅在资料场景(scene=1)下有效".to_string(),
-            ));
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "signature 仅在资料场景(scene=1)下有效",
+                "signature is only valid in the profile scene (scene=1)"
+            ))
+            .into());
         }
 
         Ok(())
@@ -335,8 +359,9 @@ impl Scene {
 /// - `suggest`: 检测建议
 /// - `label`: 命中的标签类型
 /// - `keyword`: 命中的自定义关键词
+/// - `level`: 命中关键词的等级
 /// - `prob`: 置信度，0-100，越高越可能属于当前标签
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct DetailResult {
     /// 策略类型
     pub strategy: String,
@@ -351,18 +376,27 @@ pub struct DetailResult {
     /// 命中的自定义关键词（可能不存在）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub keyword: Option<String>,
+    /// 命中关键词的等级，只有关键词策略返回（可能不存在）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<u32>,
     /// 0-100，代表置信度，越高代表越有可能属于当前返回的标签（label）（可能不存在）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prob: Option<f64>,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
 }
 
 /// 综合结果
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct ComprehensiveResult {
     /// 建议
     pub suggest: Suggest,
     /// 命中标签枚举值
     pub label: Label,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
 }
 
 /// 内容安全检测返回结果
@@ -398,7 +432,7 @@ pub struct ComprehensiveResult {
 /// }
 /// # }
 /// ```
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct MsgSecCheckResult {
     /// 错误码
     pub errcode: i32,
@@ -413,6 +447,9 @@ pub struct MsgSecCheckResult {
     /// 唯一请求标识，标记单次请求
     #[serde(skip_serializing_if = "Option::is_none")]
     pub trace_id: Option<String>,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
 }
 
 // 为 MsgSecCheckResult 实现一些便捷方法
@@ -475,7 +512,7 @@ impl MinappSecurity {
     ///
     /// - 参数验证错误
     /// - 网络错误
-    /// - 微信 API 返回错误
+    /// - 微信 API 返回错误，可通过 [`Error::trace_id`](crate::Error::trace_id) 获取请求标识，便于向微信反馈误判
     ///
     /// # 示例
     ///
@@ -519,17 +556,9 @@ impl MinappSecurity {
             "access_token":self.client.token().await?
         });
 
-        let body = serde_json::to_value(Args {
-            content: args.content.clone(),
-            version: args.version,
-            scene: args.scene,
-            openid: args.openid.clone(),
-            title: args.title.clone(),
-            nickname: args.nickname.clone(),
-            signature: args.signature.clone(),
-        })?;
-
-        let request = RequestBuilder::new(constants::MSG_SEC_CHECK_END_POINT)
+        let body = serde_json::to_value(args)?;
+
+        let request = Endpoint::MsgSecCheck.request()
             .query(query)
             .body(body)
             .build()?;
@@ -580,6 +609,17 @@ mod tests {
             .build();
         assert!(result.is_err());
 
+        // 按字符数而非字节数校验
+        let result = Args::builder()
+            .content("中".repeat(2500))
+            .scene(Scene::Comment)
+            .openid("openid")
+            .build();
+        assert!(result.is_ok());
+        assert!(Args::new("中".repeat(2501), Scene::Comment, "openid")
+            .validate()
+            .is_err());
+
         // 测试场景与签名验证
         let result = Args::builder()
             .content("内容")
@@ -595,6 +635,15 @@ mod tests {
         assert_eq!(Scene::from_value(1), Some(Scene::Profile));
         assert_eq!(Scene::Profile.description(), "资料");
         assert_eq!(Scene::Profile as u32, 1);
+
+        // 线上格式为数字，变体名不能作为 scene 发给微信
+        let scenes = [Scene::Profile, Scene::Comment, Scene::Forum, Scene::SocialLog];
+        for (value, scene) in (1..=4).zip(scenes) {
+            assert_eq!(serde_json::to_value(scene).unwrap(), serde_json::json!(value));
+            assert_eq!(serde_json::from_value::<Scene>(value.into()).unwrap(), scene);
+        }
+        assert!(serde_json::from_str::<Scene>(r#""Comment""#).is_err());
+        assert!(serde_json::from_str::<Scene>("5").is_err());
     }
 
     #[test]
@@ -614,9 +663,11 @@ mod tests {
             ],
             "result": {
                 "suggest": "pass",
-                "label": 100
+                "label": 100,
+                "risk_level": 1
             },
-            "trace_id": "test_trace_id"
+            "trace_id": "test_trace_id",
+            "new_field": "value"
         }"#;
 
         let result: MsgSecCheckResult = serde_json::from_str(json).unwrap();
@@ -627,6 +678,16 @@ mod tests {
         assert!(!result.needs_review());
         assert_eq!(result.get_valid_details().len(), 1);
         assert_eq!(result.trace_id, Some("test_trace_id".to_string()));
+
+        // 未声明的字段保留在 extra 中，errcode/errmsg 不重复保存
+        assert_eq!(result.extra.get_as::<String>("new_field").as_deref(), Some("value"));
+        assert!(!result.extra.contains_key("errcode"));
+        assert!(result.detail.as_ref().unwrap()[0].extra.is_empty());
+        let comprehensive = result.result.as_ref().unwrap();
+        assert_eq!(comprehensive.extra.get_as::<u8>("risk_level"), Some(1));
+
+        let value = serde_json::to_value(&result).unwrap();
+        assert_eq!(value["new_field"], "value");
     }
 
     #[test]
@@ -662,4 +723,45 @@ mod tests {
             Some("敏感词".to_string())
         );
     }
+
+    #[tokio::test]
+    async fn test_msg_sec_check_request() {
+        let (client, http_client) = crate::fixtures::client(
+            r#"{"errcode":0,"errmsg":"ok","result":{"suggest":"pass","label":100}}"#,
+        );
+        let security = MinappSecurity::new(client);
+
+        let args = Args::new("内容", Scene::Comment, "openid");
+        assert!(security.msg_sec_check(&args).await.unwrap().is_pass());
+
+        // 走统一的 HttpClient，而不是直接发起请求
+        let requests = http_client.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, http::Method::POST);
+        assert!(requests[0].url.contains(Endpoint::MsgSecCheck.url()));
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["scene"], 2);
+        assert_eq!(body["openid"], "openid");
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let args = Args::new("内容", Scene::Comment, "openid");
+        let value = serde_json::to_value(&args).unwrap();
+        // 微信要求 scene 为数字
+        assert_eq!(value["scene"], 2);
+        assert_eq!(serde_json::from_value::<Args>(value).unwrap(), args);
+
+        let json = r#"{
+            "errcode": 0,
+            "errmsg": "ok",
+            "result": {"suggest": "pass", "label": 100}
+        }"#;
+        let result: MsgSecCheckResult = serde_json::from_str(json).unwrap();
+        let cached = serde_json::to_string(&result).unwrap();
+        assert_eq!(
+            serde_json::from_str::<MsgSecCheckResult>(&cached).unwrap(),
+            result
+        );
+    }
 }
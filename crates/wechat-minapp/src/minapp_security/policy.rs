@@ -0,0 +1,254 @@
+//! 内容安全检测结果的本地判定策略
+//!
+//! 微信返回的 suggest、label、prob 需要结合业务再做判断，[`SecurityPolicy`] 集中配置
+//! 按标签的置信度阈值、忽略的标签与关键词白名单，把检测结果归一化为 [`Decision`]，
+//! 业务只需要处理最终决策。
+
+use super::{DetailResult, Label, MsgSecCheckResult, Suggest};
+use std::collections::{HashMap, HashSet};
+
+/// 最终决策，按严重程度排序：`Allow < Review < Deny`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Decision {
+    /// 放行
+    Allow,
+    /// 转人工审核
+    Review,
+    /// 拒绝
+    Deny,
+}
+
+impl From<&Suggest> for Decision {
+    fn from(suggest: &Suggest) -> Self {
+        match suggest {
+            Suggest::Pass => Decision::Allow,
+            Suggest::Review => Decision::Review,
+            Suggest::Risky => Decision::Deny,
+        }
+    }
+}
+
+/// 判定结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Verdict {
+    /// 最终决策
+    pub decision: Decision,
+    /// 决定该决策的标签，放行时为 `None`
+    pub label: Option<Label>,
+    /// 决定该决策的置信度
+    pub prob: Option<f64>,
+}
+
+impl Verdict {
+    fn allow() -> Self {
+        Verdict {
+            decision: Decision::Allow,
+            label: None,
+            prob: None,
+        }
+    }
+}
+
+/// 置信度阈值，`prob` 不低于 `deny` 时拒绝，不低于 `review` 时转人工审核
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Thresholds {
+    review: f64,
+    deny: f64,
+}
+
+/// 内容安全检测结果的本地判定策略
+///
+/// 逐条检查详细结果（errcode 为 0 的项），取最严重的决策：
+///
+/// - 标签被 [`ignore_label`](Self::ignore_label) 忽略，或命中的关键词在白名单中时，跳过该项；
+/// - 标签配置了阈值（或配置了 [`default_thresholds`](Self::default_thresholds)）且返回了
+///   `prob` 时，按阈值判定；
+/// - 否则沿用该项的 suggest，没有 suggest 时转人工审核。
+///
+/// 没有详细结果时，按综合结果的 suggest 判定。
+///
+/// ```
+/// use wechat_minapp::minapp_security::{Decision, Label, MsgSecCheckResult, SecurityPolicy};
+///
+/// let policy = SecurityPolicy::new()
+///     .thresholds(Label::Porn, 60.0, 90.0)
+///     .ignore_label(Label::Ad)
+///     .allow_keyword("清仓");
+///
+/// let result: MsgSecCheckResult = serde_json::from_str(
+///     r#"{"errcode":0,"errmsg":"ok","result":{"suggest":"risky","label":20002},
+///     "detail":[{"strategy":"content_model","errcode":0,"suggest":"risky",
+///     "label":20002,"prob":75}]}"#,
+/// )
+/// .unwrap();
+///
+/// let verdict = policy.evaluate(&result);
+/// assert_eq!(verdict.decision, Decision::Review);
+/// assert_eq!(verdict.label, Some(Label::Porn));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SecurityPolicy {
+    thresholds: HashMap<Label, Thresholds>,
+    default_thresholds: Option<Thresholds>,
+    ignored_labels: HashSet<Label>,
+    allowed_keywords: HashSet<String>,
+}
+
+impl SecurityPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置标签的置信度阈值（0-100），`review` 应不大于 `deny`
+    pub fn thresholds(mut self, label: Label, review: f64, deny: f64) -> Self {
+        self.thresholds.insert(label, Thresholds { review, deny });
+        self
+    }
+
+    /// 没有单独配置阈值的标签使用的置信度阈值
+    pub fn default_thresholds(mut self, review: f64, deny: f64) -> Self {
+        self.default_thresholds = Some(Thresholds { review, deny });
+        self
+    }
+
+    /// 忽略标签，如允许发布广告的场景忽略 [`Label::Ad`]
+    pub fn ignore_label(mut self, label: Label) -> Self {
+        self.ignored_labels.insert(label);
+        self
+    }
+
+    /// 关键词白名单，命中的自定义关键词在白名单中时忽略该项
+    pub fn allow_keyword(mut self, keyword: impl Into<String>) -> Self {
+        self.allowed_keywords.insert(keyword.into());
+        self
+    }
+
+    /// 判定检测结果
+    pub fn evaluate(&self, result: &MsgSecCheckResult) -> Verdict {
+        let details = result.get_valid_details();
+        if details.is_empty() {
+            return match &result.result {
+                Some(comprehensive) if !self.ignored_labels.contains(&comprehensive.label) => {
+                    self.verdict(Decision::from(&comprehensive.suggest), comprehensive.label, None)
+                }
+                _ => Verdict::allow(),
+            };
+        }
+
+        details
+            .into_iter()
+            .filter_map(|detail| self.evaluate_detail(detail))
+            .fold(Verdict::allow(), |decisive, verdict| {
+                // 相同决策时保留靠前的项
+                if verdict.decision > decisive.decision {
+                    verdict
+                } else {
+                    decisive
+                }
+            })
+    }
+
+    /// 判定单条详细结果，被忽略时返回 `None`
+    fn evaluate_detail(&self, detail: &DetailResult) -> Option<Verdict> {
+        let label = detail.label?;
+        if label.is_normal() || self.ignored_labels.contains(&label) {
+            return None;
+        }
+        if let Some(keyword) = &detail.keyword {
+            if self.allowed_keywords.contains(keyword) {
+                return None;
+            }
+        }
+
+        let thresholds = self.thresholds.get(&label).or(self.default_thresholds.as_ref());
+        let decision = match (thresholds, detail.prob) {
+            (Some(thresholds), Some(prob)) if prob >= thresholds.deny => Decision::Deny,
+            (Some(thresholds), Some(prob)) if prob >= thresholds.review => Decision::Review,
+            (Some(_), Some(_)) => Decision::Allow,
+            _ => detail.suggest.as_ref().map_or(Decision::Review, Decision::from),
+        };
+        Some(self.verdict(decision, label, detail.prob))
+    }
+
+    fn verdict(&self, decision: Decision, label: Label, prob: Option<f64>) -> Verdict {
+        match decision {
+            Decision::Allow => Verdict::allow(),
+            decision => Verdict {
+                decision,
+                label: Some(label),
+                prob,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(details: &str) -> MsgSecCheckResult {
+        serde_json::from_str(&format!(
+            r#"{{"errcode":0,"errmsg":"ok","result":{{"suggest":"risky","label":20002}},
+            "detail":[{}]}}"#,
+            details
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let policy = SecurityPolicy::new()
+            .thresholds(Label::Porn, 60.0, 90.0)
+            .default_thresholds(80.0, 95.0);
+
+        let check = |prob: f64| {
+            policy.evaluate(&result(&format!(
+                r#"{{"strategy":"content_model","errcode":0,"suggest":"risky","label":20002,
+                "prob":{}}}"#,
+                prob
+            )))
+        };
+        assert_eq!(check(95.0).decision, Decision::Deny);
+        assert_eq!(check(75.0).decision, Decision::Review);
+        assert_eq!(check(75.0).prob, Some(75.0));
+        assert_eq!(check(30.0), Verdict::allow());
+
+        // 未单独配置的标签使用默认阈值
+        let verdict = policy.evaluate(&result(
+            r#"{"strategy":"content_model","errcode":0,"suggest":"risky","label":20003,"prob":85}"#,
+        ));
+        assert_eq!(verdict.decision, Decision::Review);
+        assert_eq!(verdict.label, Some(Label::Abuse));
+    }
+
+    #[test]
+    fn test_ignore_and_allow_keyword() {
+        let policy = SecurityPolicy::new().ignore_label(Label::Ad).allow_keyword("清仓");
+        let verdict = policy.evaluate(&result(
+            r#"{"strategy":"content_model","errcode":0,"suggest":"risky","label":10001,"prob":99},
+            {"strategy":"keyword","errcode":0,"suggest":"risky","label":20006,"keyword":"清仓"},
+            {"strategy":"keyword","errcode":1,"suggest":"risky","label":20001}"#,
+        ));
+        assert_eq!(verdict, Verdict::allow());
+
+        // 没有配置阈值时沿用 suggest，取最严重的一项
+        let verdict = policy.evaluate(&result(
+            r#"{"strategy":"content_model","errcode":0,"suggest":"review","label":20012,"prob":60},
+            {"strategy":"keyword","errcode":0,"suggest":"risky","label":20006,"keyword":"枪"}"#,
+        ));
+        assert_eq!(verdict.decision, Decision::Deny);
+        assert_eq!(verdict.label, Some(Label::Illegal));
+    }
+
+    #[test]
+    fn test_without_details() {
+        let result: MsgSecCheckResult = serde_json::from_str(
+            r#"{"errcode":0,"errmsg":"ok","result":{"suggest":"review","label":10001}}"#,
+        )
+        .unwrap();
+        assert_eq!(SecurityPolicy::new().evaluate(&result).decision, Decision::Review);
+        let policy = SecurityPolicy::new().ignore_label(Label::Ad);
+        assert_eq!(policy.evaluate(&result).decision, Decision::Allow);
+        assert!(Decision::Allow < Decision::Review && Decision::Review < Decision::Deny);
+    }
+}
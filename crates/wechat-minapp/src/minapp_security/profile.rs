@@ -0,0 +1,245 @@
+//! 用户资料检测
+//!
+//! 修改昵称、签名、头像是资料场景（scene=1）最常见的组合，
+//! [`MinappSecurity::check_profile`] 同时发起文本检测与头像的异步检测，并汇总结论。
+
+use super::{Args, Label, MediaCheckArgs, MediaType, MinappSecurity, MsgSecCheckResult};
+use super::{Scene, Suggest};
+use futures_util::future;
+use tracing::debug;
+use wechat_core::{localized, ErrorKind, Result};
+
+/// 用户资料检测结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileCheckResult {
+    /// 昵称与签名的检测结果，两者都为空时为 `None`
+    pub text: Option<MsgSecCheckResult>,
+    /// 头像异步检测的 `trace_id`，结果通过 `wxa_media_check` 事件推送；未传头像时为 `None`
+    pub avatar_trace_id: Option<String>,
+}
+
+impl ProfileCheckResult {
+    /// 汇总建议
+    ///
+    /// 文本有风险时为 risky；文本需要审核，或头像的检测结果尚未推送时为 review；否则为 pass。
+    pub fn suggest(&self) -> Suggest {
+        let text = self
+            .text
+            .as_ref()
+            .map(|text| text.get_suggest().cloned().unwrap_or(Suggest::Review))
+            .unwrap_or(Suggest::Pass);
+        match text {
+            Suggest::Pass if self.avatar_trace_id.is_some() => Suggest::Review,
+            suggest => suggest,
+        }
+    }
+
+    /// 文本命中的标签
+    pub fn label(&self) -> Option<Label> {
+        self.text.as_ref().and_then(|text| text.get_label().copied())
+    }
+
+    /// 是否全部通过，头像检测未完成时为 `false`
+    pub fn is_pass(&self) -> bool {
+        self.suggest().is_pass()
+    }
+
+    /// 是否有风险
+    pub fn is_risky(&self) -> bool {
+        self.suggest().is_risky()
+    }
+}
+
+impl MinappSecurity {
+    /// 检测用户资料：昵称、签名与头像
+    ///
+    /// 昵称与签名合并为一次文本检测，头像地址提交异步检测，两者并发进行。
+    /// 空字符串与 `None` 表示该项未修改，不送检；三项都为空时返回错误。
+    ///
+    /// ```no_run
+    /// use wechat_minapp::WechatMinapp;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = WechatMinapp::new("app_id", "secret");
+    ///     let result = client
+    ///         .security()
+    ///         .check_profile(
+    ///             "user_openid",
+    ///             "新昵称",
+    ///             Some("个性签名"),
+    ///             Some("https://example.com/avatar.jpg"),
+    ///         )
+    ///         .await?;
+    ///
+    ///     if result.is_risky() {
+    ///         println!("资料违规: {:?}", result.label());
+    ///     } else if let Some(trace_id) = &result.avatar_trace_id {
+    ///         println!("文本通过，头像等待回调: {}", trace_id);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn check_profile(
+        &self,
+        openid: &str,
+        nickname: &str,
+        signature: Option<&str>,
+        avatar_url: Option<&str>,
+    ) -> Result<ProfileCheckResult> {
+        let signature = signature.filter(|signature| !signature.is_empty());
+        let avatar_url = avatar_url.filter(|avatar_url| !avatar_url.is_empty());
+
+        let content: Vec<&str> = [Some(nickname), signature]
+            .into_iter()
+            .flatten()
+            .filter(|text| !text.is_empty())
+            .collect();
+        if content.is_empty() && avatar_url.is_none() {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "昵称、签名与头像不能都为空",
+                "nickname, signature and avatar must not all be empty"
+            ))
+            .into());
+        }
+
+        let text_args = if content.is_empty() {
+            None
+        } else {
+            let mut builder = Args::builder()
+                .content(content.join("\n"))
+                .scene(Scene::Profile)
+                .openid(openid);
+            if !nickname.is_empty() {
+                builder = builder.nickname(nickname);
+            }
+            if let Some(signature) = signature {
+                builder = builder.signature(signature);
+            }
+            Some(builder.build()?)
+        };
+        let avatar_args = avatar_url
+            .map(|url| MediaCheckArgs::new(url, MediaType::Image, Scene::Profile, openid));
+        if let Some(args) = &avatar_args {
+            args.validate()?;
+        }
+
+        let text = async {
+            match &text_args {
+                Some(args) => self.msg_sec_check(args).await.map(Some),
+                None => Ok(None),
+            }
+        };
+        let avatar = async {
+            match &avatar_args {
+                Some(args) => self
+                    .media_check_async(args)
+                    .await
+                    .map(|response| Some(response.trace_id)),
+                None => Ok(None),
+            }
+        };
+        let (text, avatar_trace_id) = future::try_join(text, avatar).await?;
+
+        let result = ProfileCheckResult {
+            text,
+            avatar_trace_id,
+        };
+        debug!("check_profile suggest: {}", result.suggest());
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use async_trait::async_trait;
+    use http::{Request, Response};
+    use serde_json::Value;
+    use std::sync::{Arc, Mutex};
+    use wechat_core::client::HttpClient;
+
+    /// 文本检测返回 `suggest`，头像检测返回 trace_id
+    struct ProfileClient {
+        suggest: &'static str,
+        bodies: Mutex<Vec<Value>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for ProfileClient {
+        async fn execute(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+            let body: Value = serde_json::from_slice(request.body()).unwrap();
+            let response = if request.uri().path() == "/wxa/media_check_async" {
+                r#"{"errcode":0,"errmsg":"ok","trace_id":"avatar_trace"}"#.to_string()
+            } else {
+                format!(
+                    r#"{{"errcode":0,"errmsg":"ok","result":{{"suggest":"{}","label":{}}}}}"#,
+                    self.suggest,
+                    if self.suggest == "pass" { 100 } else { 20003 }
+                )
+            };
+            self.bodies.lock().unwrap().push(body);
+            Ok(Response::new(response.into_bytes()))
+        }
+    }
+
+    fn profile_security(suggest: &'static str) -> (MinappSecurity, Arc<ProfileClient>) {
+        let http_client = Arc::new(ProfileClient {
+            suggest,
+            bodies: Mutex::new(Vec::new()),
+        });
+        let client = fixtures::client_with(http_client.clone());
+        (MinappSecurity::new(client), http_client)
+    }
+
+    #[tokio::test]
+    async fn test_check_profile() {
+        let (security, http_client) = profile_security("pass");
+        let result = security
+            .check_profile("openid", "昵称", Some("签名"), Some("https://a/b.jpg"))
+            .await
+            .unwrap();
+        assert_eq!(result.avatar_trace_id.as_deref(), Some("avatar_trace"));
+        // 头像结果未推送前需要审核
+        assert_eq!(result.suggest(), Suggest::Review);
+
+        let bodies = http_client.bodies.lock().unwrap();
+        let text = bodies.iter().find(|body| body.get("content").is_some()).unwrap();
+        assert_eq!(text["content"], "昵称\n签名");
+        assert_eq!(text["scene"], 1);
+        assert_eq!(text["signature"], "签名");
+        let avatar = bodies.iter().find(|body| body.get("media_url").is_some()).unwrap();
+        assert_eq!(avatar["media_type"], 2);
+        assert_eq!(avatar["scene"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_check_profile_suggest() {
+        let (security, _) = profile_security("pass");
+        let result = security.check_profile("openid", "昵称", None, None).await.unwrap();
+        assert!(result.is_pass());
+
+        let (security, _) = profile_security("risky");
+        let result = security
+            .check_profile("openid", "昵称", None, Some("https://a/b.jpg"))
+            .await
+            .unwrap();
+        assert!(result.is_risky());
+        assert_eq!(result.label(), Some(Label::Abuse));
+    }
+
+    #[tokio::test]
+    async fn test_check_profile_invalid() {
+        let (security, http_client) = profile_security("pass");
+        let error = security.check_profile("openid", "", Some(""), None).await.unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidParameter(_)));
+
+        let error = security
+            .check_profile("openid", "昵称", None, Some("avatar.jpg"))
+            .await
+            .unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidParameter(_)));
+        assert!(http_client.bodies.lock().unwrap().is_empty());
+    }
+}
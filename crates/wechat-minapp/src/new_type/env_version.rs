@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use wechat_core::{localized, Error, ErrorKind, Result};
+
+/// 小程序环境版本
+///
+/// 指定小程序码、小程序链接打开的环境版本，不同环境版本对应不同的小程序实例。
+/// 序列化为小写的 `"release"`、`"trial"`、`"develop"`，也可以从配置中的字符串解析：
+///
+/// ```
+/// use wechat_minapp::new_type::MinappEnvVersion;
+///
+/// let version: MinappEnvVersion = "trial".parse().unwrap();
+/// assert_eq!(version, MinappEnvVersion::Trial);
+/// assert_eq!(version.to_string(), "trial");
+/// ```
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum MinappEnvVersion {
+    /// 正式版，用于生产环境
+    #[default]
+    Release,
+    /// 体验版，用于测试环境
+    Trial,
+    /// 开发版，用于开发环境
+    Develop,
+}
+
+impl MinappEnvVersion {
+    /// 微信接口使用的取值
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MinappEnvVersion::Release => "release",
+            MinappEnvVersion::Trial => "trial",
+            MinappEnvVersion::Develop => "develop",
+        }
+    }
+}
+
+impl fmt::Display for MinappEnvVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for MinappEnvVersion {
+    type Err = Error;
+
+    /// 忽略大小写和首尾空白
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "release" => Ok(MinappEnvVersion::Release),
+            "trial" => Ok(MinappEnvVersion::Trial),
+            "develop" => Ok(MinappEnvVersion::Develop),
+            _ => Err(ErrorKind::InvalidParameter(localized!(
+                "无效的小程序环境版本: {}，可选值为 release、trial、develop",
+                "invalid env version: {}, expected release, trial or develop",
+                s
+            ))
+            .into()),
+        }
+    }
+}
+
+impl From<MinappEnvVersion> for String {
+    fn from(value: MinappEnvVersion) -> Self {
+        value.as_str().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_version() {
+        for version in [
+            MinappEnvVersion::Release,
+            MinappEnvVersion::Trial,
+            MinappEnvVersion::Develop,
+        ] {
+            let json = serde_json::to_string(&version).unwrap();
+            assert_eq!(json, format!("\"{}\"", version));
+            assert_eq!(serde_json::from_str::<MinappEnvVersion>(&json).unwrap(), version);
+            assert_eq!(version.as_str().parse::<MinappEnvVersion>().unwrap(), version);
+        }
+
+        assert_eq!(" Develop ".parse::<MinappEnvVersion>().unwrap(), MinappEnvVersion::Develop);
+        assert!(matches!(
+            "beta".parse::<MinappEnvVersion>().unwrap_err().kind(),
+            ErrorKind::InvalidParameter(_)
+        ));
+        assert!(serde_json::from_str::<MinappEnvVersion>("\"Release\"").is_err());
+        assert_eq!(MinappEnvVersion::default(), MinappEnvVersion::Release);
+    }
+}
@@ -1,13 +1,24 @@
 //! 用于传参验证
 //!
+mod env_version;
 mod non_query_page_path;
+mod open_id;
 mod page_path;
+mod query_value;
+mod report_date;
 mod scene;
+mod scene_params;
 
+use wechat_core::i18n::localize;
 use wechat_core::Error;
+pub use env_version::MinappEnvVersion;
 pub use non_query_page_path::NonQueryPagePath;
+pub use open_id::{OpenId, OpenIdError, UnionId};
 pub use page_path::PagePath;
+pub use query_value::{decode_query_value, encode_query_value};
+pub use report_date::{ReportDate, ReportDateError, ReportRange};
 pub use scene::{SceneString, ValidationSceneError};
+pub use scene_params::SceneParams;
 use std::fmt;
 
 /// 页面路径验证错误类型
@@ -42,17 +53,25 @@ impl From<Error> for PagePathError {
 }
 impl fmt::Display for PagePathError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            PagePathError::StartsWithSlash => write!(f, "页面路径不能以斜杠开头"),
-            PagePathError::ContainsParams => {
-                write!(f, "页面路径不能携带参数，参数请放在scene字段里")
+        let message = match self {
+            PagePathError::StartsWithSlash => {
+                localize("页面路径不能以斜杠开头", "page path must not start with '/'")
             }
-            PagePathError::InvalidFormat => write!(f, "页面路径格式不正确"),
-            PagePathError::InvalidLength => write!(f, "页面路径最大长度 1024 个字符"),
-            PagePathError::Empty => write!(f, "页面路径不能为空"),
-            PagePathError::ReservedParameter => {
-                write!(f, "页面路径包含系统保留参数 'scancode_time'")
-            }
-        }
+            PagePathError::ContainsParams => localize(
+                "页面路径不能携带参数，参数请放在scene字段里",
+                "page path must not contain query parameters, put them in scene",
+            ),
+            PagePathError::InvalidFormat => localize("页面路径格式不正确", "invalid page path"),
+            PagePathError::InvalidLength => localize(
+                "页面路径最大长度 1024 个字符",
+                "page path must not exceed 1024 characters",
+            ),
+            PagePathError::Empty => localize("页面路径不能为空", "page path must not be empty"),
+            PagePathError::ReservedParameter => localize(
+                "页面路径包含系统保留参数 'scancode_time'",
+                "page path must not contain the reserved parameter 'scancode_time'",
+            ),
+        };
+        f.write_str(message)
     }
 }
@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use super::PagePathError;
 use wechat_core::constants;
 use wechat_core::utils::parse_url;
@@ -5,7 +6,8 @@ use std::fmt;
 use std::str::FromStr;
 
 /// 页面路径 newtype
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String")]
 pub struct NonQueryPagePath(String);
 
 impl NonQueryPagePath {
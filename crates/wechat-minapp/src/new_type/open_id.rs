@@ -0,0 +1,207 @@
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+use thiserror::Error;
+use wechat_core::localized;
+
+/// openid、unionid 的最大长度，与微信支付接口的限制一致
+const MAX_LEN: usize = 128;
+
+/// openid、unionid 校验错误
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum OpenIdError {
+    /// 为空
+    Empty,
+    /// 超过 128 个字符
+    TooLong(usize),
+    /// 包含字母、数字、`-`、`_` 以外的字符
+    InvalidChar(char),
+}
+
+impl fmt::Display for OpenIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            OpenIdError::Empty => localized!("openid 不能为空", "openid must not be empty"),
+            OpenIdError::TooLong(len) => localized!(
+                "openid 最大长度 128 个字符，当前 {} 个",
+                "openid must not exceed 128 characters, got {}",
+                len
+            ),
+            OpenIdError::InvalidChar(c) => localized!(
+                "openid 包含非法字符: {:?}",
+                "openid contains invalid character: {:?}",
+                c
+            ),
+        };
+        f.write_str(&message)
+    }
+}
+
+fn validate(value: &str) -> Result<(), OpenIdError> {
+    if value.is_empty() {
+        return Err(OpenIdError::Empty);
+    }
+    if value.len() > MAX_LEN {
+        return Err(OpenIdError::TooLong(value.chars().count()));
+    }
+    match value
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && *c != '-' && *c != '_')
+    {
+        Some(c) => Err(OpenIdError::InvalidChar(c)),
+        None => Ok(()),
+    }
+}
+
+macro_rules! id_type {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+        #[serde(try_from = "String", into = "String")]
+        pub struct $name(String);
+
+        impl $name {
+            /// 校验并创建
+            pub fn new(value: impl Into<String>) -> Result<Self, OpenIdError> {
+                let value = value.into();
+                validate(&value)?;
+                Ok($name(value))
+            }
+
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            pub fn into_inner(self) -> String {
+                self.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = OpenIdError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $name::new(s)
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = OpenIdError;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                $name::new(value)
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = OpenIdError;
+
+            fn try_from(value: &str) -> Result<Self, Self::Error> {
+                $name::new(value)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+    };
+}
+
+id_type! {
+    /// 用户在小程序下的唯一标识
+    ///
+    /// 创建时校验格式，序列化为字符串。微信支付相关 crate 的 openid 字段都是 `String`，
+    /// 通过 `String::from` / `.into()` 直接传入；需要 `&str` 的地方可以直接传 `&OpenId`。
+    ///
+    /// ```
+    /// use wechat_minapp::new_type::OpenId;
+    ///
+    /// let open_id: OpenId = "oGZUI0egBJY1zhBYw2KhdUfwVJJE".parse()?;
+    /// let payer_openid: String = open_id.clone().into();
+    /// assert_eq!(payer_openid, "oGZUI0egBJY1zhBYw2KhdUfwVJJE");
+    /// assert!(open_id.starts_with('o'));
+    ///
+    /// assert!(OpenId::new("").is_err());
+    /// assert!(serde_json::from_str::<OpenId>(r#""o 1""#).is_err());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    OpenId
+}
+
+id_type! {
+    /// 用户在开放平台账号下的唯一标识，校验规则与 [`OpenId`] 相同
+    UnionId
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_open_id() {
+        let open_id = OpenId::new("oGZUI0egBJY1zhBYw2KhdUfwVJJE").unwrap();
+        assert_eq!(open_id, "oGZUI0egBJY1zhBYw2KhdUfwVJJE");
+        assert_eq!(open_id.len(), 28);
+        assert_eq!(serde_json::to_value(&open_id).unwrap(), "oGZUI0egBJY1zhBYw2KhdUfwVJJE");
+
+        let ids: HashSet<OpenId> = [open_id.clone()].into();
+        assert!(ids.contains("oGZUI0egBJY1zhBYw2KhdUfwVJJE"));
+
+        assert_eq!(OpenId::new(""), Err(OpenIdError::Empty));
+        assert_eq!(OpenId::new("o-1_x/"), Err(OpenIdError::InvalidChar('/')));
+        assert_eq!(OpenId::new("o".repeat(129)), Err(OpenIdError::TooLong(129)));
+        assert!(OpenId::new("o".repeat(128)).is_ok());
+    }
+
+    #[test]
+    fn test_union_id() {
+        let union_id: UnionId =
+            serde_json::from_str(r#""oTmHYjg-tElZ68xxxxxxxxhy1Rgk""#).unwrap();
+        assert_eq!(union_id.as_str(), "oTmHYjg-tElZ68xxxxxxxxhy1Rgk");
+        assert_eq!(String::from(union_id.clone()), union_id.into_inner());
+        assert!(UnionId::try_from("o\u{4e2d}").is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use wechat_core::constants;
 use wechat_core::utils::{parse_query, parse_url};
 
@@ -5,10 +6,14 @@ use super::PagePathError;
 use std::fmt;
 use std::str::FromStr;
 
+/// 系统保留的页面参数
+const RESERVED_PARAMETER: &str = "scancode_time";
+
 /// 扫码进入的小程序页面路径
 /// 最大长度 128 个字符，不能为空；对于小游戏，可以只传入 query 部分，来实现传参效果，如：传入 "?foo=bar"，即可在 wx.getLaunchOptionsSync 接口中的 query 参数获取到 {foo:"bar"}。
 /// scancode_time为系统保留参数，不允许配置。
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String")]
 pub struct PagePath(String);
 
 impl PagePath {
@@ -29,13 +34,35 @@ impl PagePath {
         let query_pairs = parse_query(url)?;
         if query_pairs
             .iter()
-            .any(|item| item.0.contains("scancode_time"))
+            .any(|item| item.0 == RESERVED_PARAMETER)
         {
             return Err(PagePathError::ReservedParameter);
         }
         Ok(PagePath(path.to_string()))
     }
 
+    /// 去掉 query 中的系统保留参数 scancode_time 后再创建页面路径
+    ///
+    /// 其余参数原样保留；去掉后没有参数时，同时去掉 `?`。
+    pub fn without_reserved(path: &str) -> Result<Self, PagePathError> {
+        let path = match path.split_once('?') {
+            Some((page, query)) => {
+                let query = query
+                    .split('&')
+                    .filter(|pair| pair.split('=').next() != Some(RESERVED_PARAMETER))
+                    .collect::<Vec<_>>()
+                    .join("&");
+                if query.is_empty() {
+                    page.to_string()
+                } else {
+                    format!("{}?{}", page, query)
+                }
+            }
+            None => path.to_string(),
+        };
+        PagePath::new(&path)
+    }
+
     /// 获取内部路径引用
     pub fn as_str(&self) -> &str {
         &self.0
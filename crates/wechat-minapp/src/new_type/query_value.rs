@@ -0,0 +1,110 @@
+use wechat_core::{localized, ErrorKind, Result};
+
+/// 编码时的转义字符
+const ESCAPE: u8 = b'*';
+
+/// 不需要编码的字符：数字、大小写英文和 `-._`
+fn is_safe(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_')
+}
+
+/// 将任意字符串编码为可以放进 scene 和页面路径 query 的值
+///
+/// scene 不支持 `%`，无法使用 urlencode。这里把数字、大小写英文和 `-._` 以外的字符按
+/// UTF-8 字节编码为 `*XX`（两位大写十六进制），编码结果不包含 `&`、`=`，
+/// 可以直接作为 [`SceneParams`](super::SceneParams) 的值。注意中文每个字编码后占 9 个字符，
+/// scene 只有 32 个字符，长内容请改为在服务端保存后传 id。
+///
+/// ```
+/// use wechat_minapp::new_type::{decode_query_value, encode_query_value};
+///
+/// let encoded = encode_query_value("张三&a=1");
+/// assert_eq!(encoded, "*E5*BC*A0*E4*B8*89*26a*3D1");
+/// assert_eq!(decode_query_value(&encoded)?, "张三&a=1");
+/// # Ok::<(), wechat_minapp::Error>(())
+/// ```
+pub fn encode_query_value(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for &byte in value.as_bytes() {
+        if is_safe(byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push(ESCAPE as char);
+            encoded.push_str(&format!("{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// 解码 [`encode_query_value`] 编码的值
+///
+/// 小程序端可以用同样的规则解码：把 `*XX` 替换为 `%XX` 后调用 `decodeURIComponent`。
+pub fn decode_query_value(value: &str) -> Result<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        if bytes[index] != ESCAPE {
+            decoded.push(bytes[index]);
+            index += 1;
+            continue;
+        }
+
+        let byte = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| {
+                ErrorKind::InvalidParameter(localized!(
+                    "位置 {} 的转义序列不完整",
+                    "incomplete escape sequence at position {}",
+                    index
+                ))
+            })?;
+        decoded.push(byte);
+        index += 3;
+    }
+
+    String::from_utf8(decoded).map_err(|_| {
+        ErrorKind::InvalidParameter(localized!(
+            "解码结果不是合法的 UTF-8",
+            "decoded value is not valid UTF-8"
+        ))
+        .into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::new_type::SceneString;
+
+    #[test]
+    fn test_round_trip() {
+        for value in ["", "abc-1.2_3", "张三", "a b&c=d*e%f", "🦀"] {
+            let encoded = encode_query_value(value);
+            assert!(SceneString::new(&encoded).is_ok() || encoded.chars().count() > 32);
+            assert!(!encoded.contains(['&', '=', '%']));
+            assert_eq!(decode_query_value(&encoded).unwrap(), value);
+        }
+        assert_eq!(encode_query_value("a b"), "a*20b");
+        assert_eq!(decode_query_value("a*2fb").unwrap(), "a/b");
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert!(matches!(
+            decode_query_value("a*2").unwrap_err().kind(),
+            ErrorKind::InvalidParameter(_)
+        ));
+        assert!(matches!(
+            decode_query_value("*ZZ").unwrap_err().kind(),
+            ErrorKind::InvalidParameter(_)
+        ));
+        assert!(matches!(
+            decode_query_value("*E5").unwrap_err().kind(),
+            ErrorKind::InvalidParameter(_)
+        ));
+    }
+}
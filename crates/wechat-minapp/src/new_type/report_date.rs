@@ -0,0 +1,292 @@
+use chrono::{Datelike, Days, FixedOffset, Months, NaiveDate, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+use wechat_core::localized;
+
+/// 数据分析接口使用的日期格式
+const FORMAT: &str = "%Y%m%d";
+
+/// 数据分析日期参数错误
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ReportDateError {
+    /// 不是合法的 yyyymmdd 日期
+    InvalidFormat(String),
+    /// 开始日期晚于结束日期
+    InvalidRange(ReportDate, ReportDate),
+    /// 周数据的开始日期不是周一
+    NotWeekStart(ReportDate),
+    /// 月数据的开始日期不是每月 1 日
+    NotMonthStart(ReportDate),
+    /// 数据最晚只能查询到昨天
+    NotAvailable(ReportDate),
+}
+
+impl fmt::Display for ReportDateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            ReportDateError::InvalidFormat(value) => localized!(
+                "日期格式应为 yyyymmdd: {}",
+                "date must be in yyyymmdd format: {}",
+                value
+            ),
+            ReportDateError::InvalidRange(begin, end) => localized!(
+                "开始日期 {} 晚于结束日期 {}",
+                "begin date {} is after end date {}",
+                begin,
+                end
+            ),
+            ReportDateError::NotWeekStart(date) => localized!(
+                "周数据的开始日期必须是周一: {}",
+                "weekly report must begin on Monday: {}",
+                date
+            ),
+            ReportDateError::NotMonthStart(date) => localized!(
+                "月数据的开始日期必须是每月 1 日: {}",
+                "monthly report must begin on the first day of month: {}",
+                date
+            ),
+            ReportDateError::NotAvailable(date) => localized!(
+                "数据最晚只能查询到昨天: {}",
+                "report data is only available up to yesterday: {}",
+                date
+            ),
+        };
+        f.write_str(&message)
+    }
+}
+
+/// 数据分析接口的日期，序列化为 `yyyymmdd` 格式的字符串
+///
+/// ```
+/// use wechat_minapp::new_type::ReportDate;
+///
+/// let date: ReportDate = "20240301".parse()?;
+/// assert_eq!(date.to_string(), "20240301");
+/// assert_eq!(serde_json::to_value(date)?, "20240301");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ReportDate(NaiveDate);
+
+impl ReportDate {
+    pub fn new(date: NaiveDate) -> Self {
+        ReportDate(date)
+    }
+
+    /// 从年月日创建，日期不存在时返回 [`ReportDateError::InvalidFormat`]
+    pub fn from_ymd(year: i32, month: u32, day: u32) -> Result<Self, ReportDateError> {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .map(ReportDate)
+            .ok_or_else(|| {
+                ReportDateError::InvalidFormat(format!("{:04}{:02}{:02}", year, month, day))
+            })
+    }
+
+    /// 北京时间的昨天，即可以查询的最新一天
+    pub fn yesterday() -> Self {
+        ReportDate(beijing_today() - Days::new(1))
+    }
+
+    /// 获取内部的日期
+    pub fn date(&self) -> NaiveDate {
+        self.0
+    }
+}
+
+/// 微信按北京时间统计数据
+fn beijing_today() -> NaiveDate {
+    let offset = FixedOffset::east_opt(8 * 3600).expect("valid offset");
+    Utc::now().with_timezone(&offset).date_naive()
+}
+
+impl fmt::Display for ReportDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.format(FORMAT))
+    }
+}
+
+impl FromStr for ReportDate {
+    type Err = ReportDateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // chrono 允许省略前导零，这里要求严格的 8 位数字
+        if s.len() != 8 || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ReportDateError::InvalidFormat(s.to_string()));
+        }
+        NaiveDate::parse_from_str(s, FORMAT)
+            .map(ReportDate)
+            .map_err(|_| ReportDateError::InvalidFormat(s.to_string()))
+    }
+}
+
+impl TryFrom<String> for ReportDate {
+    type Error = ReportDateError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ReportDate> for String {
+    fn from(value: ReportDate) -> Self {
+        value.to_string()
+    }
+}
+
+impl From<NaiveDate> for ReportDate {
+    fn from(value: NaiveDate) -> Self {
+        ReportDate(value)
+    }
+}
+
+/// 数据分析接口的日期范围，序列化为 `{"begin_date": "...", "end_date": "..."}`，可以直接作为请求体
+///
+/// 日数据、周数据、月数据接口对范围的要求不同，分别使用 [`daily`](Self::daily)、
+/// [`weekly`](Self::weekly)、[`monthly`](Self::monthly) 创建，不满足要求时返回错误，
+/// 不必等微信接口报错。
+///
+/// ```
+/// use wechat_minapp::new_type::{ReportDate, ReportRange};
+///
+/// let range = ReportRange::weekly(ReportDate::from_ymd(2024, 3, 4)?)?;
+/// assert_eq!(range.end_date().to_string(), "20240310");
+///
+/// // 2024-03-05 是周二
+/// assert!(ReportRange::weekly(ReportDate::from_ymd(2024, 3, 5)?).is_err());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ReportRange {
+    begin_date: ReportDate,
+    end_date: ReportDate,
+}
+
+impl ReportRange {
+    /// 任意范围，开始日期不能晚于结束日期，结束日期最晚为昨天
+    pub fn new(begin_date: ReportDate, end_date: ReportDate) -> Result<Self, ReportDateError> {
+        Self::checked(begin_date, end_date, beijing_today())
+    }
+
+    /// 日数据：开始和结束日期相同
+    pub fn daily(date: ReportDate) -> Result<Self, ReportDateError> {
+        Self::new(date, date)
+    }
+
+    /// 周数据：开始日期为周一，结束日期为当周周日
+    pub fn weekly(begin_date: ReportDate) -> Result<Self, ReportDateError> {
+        if begin_date.0.weekday() != Weekday::Mon {
+            return Err(ReportDateError::NotWeekStart(begin_date));
+        }
+        Self::new(begin_date, ReportDate(begin_date.0 + Days::new(6)))
+    }
+
+    /// 月数据：开始日期为每月 1 日，结束日期为当月最后一天
+    pub fn monthly(begin_date: ReportDate) -> Result<Self, ReportDateError> {
+        if begin_date.0.day() != 1 {
+            return Err(ReportDateError::NotMonthStart(begin_date));
+        }
+        let end_date = begin_date.0 + Months::new(1) - Days::new(1);
+        Self::new(begin_date, ReportDate(end_date))
+    }
+
+    fn checked(
+        begin_date: ReportDate,
+        end_date: ReportDate,
+        today: NaiveDate,
+    ) -> Result<Self, ReportDateError> {
+        if begin_date > end_date {
+            return Err(ReportDateError::InvalidRange(begin_date, end_date));
+        }
+        if end_date.0 >= today {
+            return Err(ReportDateError::NotAvailable(end_date));
+        }
+        Ok(ReportRange {
+            begin_date,
+            end_date,
+        })
+    }
+
+    pub fn begin_date(&self) -> ReportDate {
+        self.begin_date
+    }
+
+    pub fn end_date(&self) -> ReportDate {
+        self.end_date
+    }
+
+    /// 范围包含的天数
+    pub fn days(&self) -> u64 {
+        (self.end_date.0 - self.begin_date.0).num_days() as u64 + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> ReportDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_report_date() {
+        assert_eq!(date("20240229").date(), NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+        for invalid in ["2024031", "2024-03-01", "20230229", "20241301", "２0240301"] {
+            assert!(matches!(
+                invalid.parse::<ReportDate>(),
+                Err(ReportDateError::InvalidFormat(_))
+            ));
+        }
+        assert!(ReportDate::from_ymd(2023, 2, 29).is_err());
+
+        let value = serde_json::to_value(date("20240301")).unwrap();
+        assert_eq!(value, "20240301");
+        assert!(serde_json::from_value::<ReportDate>("2024-03-01".into()).is_err());
+        assert!(ReportDate::yesterday().date() < beijing_today());
+    }
+
+    #[test]
+    fn test_report_range() {
+        let range = ReportRange::daily(date("20240301")).unwrap();
+        assert_eq!(range.days(), 1);
+        assert_eq!(
+            serde_json::to_value(range).unwrap(),
+            serde_json::json!({"begin_date": "20240301", "end_date": "20240301"})
+        );
+
+        let range = ReportRange::weekly(date("20240304")).unwrap();
+        assert_eq!(range.end_date(), date("20240310"));
+        assert_eq!(range.days(), 7);
+        assert!(matches!(
+            ReportRange::weekly(date("20240305")),
+            Err(ReportDateError::NotWeekStart(_))
+        ));
+
+        let range = ReportRange::monthly(date("20240201")).unwrap();
+        assert_eq!(range.end_date(), date("20240229"));
+        assert!(matches!(
+            ReportRange::monthly(date("20240202")),
+            Err(ReportDateError::NotMonthStart(_))
+        ));
+
+        assert!(matches!(
+            ReportRange::new(date("20240302"), date("20240301")),
+            Err(ReportDateError::InvalidRange(..))
+        ));
+    }
+
+    #[test]
+    fn test_not_available() {
+        let today = date("20240310").date();
+        assert!(ReportRange::checked(date("20240309"), date("20240309"), today).is_ok());
+        assert!(matches!(
+            ReportRange::checked(date("20240304"), date("20240310"), today),
+            Err(ReportDateError::NotAvailable(_))
+        ));
+        assert!(ReportRange::daily(ReportDate::yesterday()).is_ok());
+        assert!(ReportRange::daily(ReportDate::new(beijing_today())).is_err());
+    }
+}
@@ -1,27 +1,76 @@
+use super::{decode_query_value, encode_query_value};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::str::FromStr;
 use thiserror::Error;
+use wechat_core::localized;
 
 #[derive(Debug, Error)]
 pub enum ValidationSceneError {
-    #[error("字符串长度超过32个字符限制")]
+    /// 超过 32 个字符
     TooLong,
-    #[error("包含非法字符: {0}")]
+    /// 包含非法字符
     InvalidChar(char),
+    /// 参数名为空
+    EmptyKey,
+    /// 参数名或参数值包含分隔符 `&`、`=`
+    ReservedChar(char),
 }
 
+impl fmt::Display for ValidationSceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            ValidationSceneError::TooLong => localized!(
+                "字符串长度超过{}个字符限制",
+                "scene must not exceed {} characters",
+                MAX_SCENE_CHARS
+            ),
+            ValidationSceneError::InvalidChar(c) => {
+                localized!("包含非法字符: {}", "invalid character: {}", c)
+            }
+            ValidationSceneError::EmptyKey => {
+                localized!("参数名不能为空", "parameter name must not be empty")
+            }
+            ValidationSceneError::ReservedChar(c) => localized!(
+                "参数名或参数值不能包含分隔符: {}",
+                "parameter name or value must not contain separator: {}",
+                c
+            ),
+        };
+        f.write_str(&message)
+    }
+}
+
+/// scene 的最大可见字符数
+const MAX_SCENE_CHARS: usize = 32;
+
 // 合法的字符集：数字、大小写英文、!#$&'()*+,/:;=?@-._~
 const VALID_CHARS: &str =
     "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789!#$&'()*+,/:;=?@-._~";
 
 /// 最大32个可见字符，只支持数字，大小写英文以及部分特殊字符：!#$&'()*+,/:;=?@-._~，其它字符请自行编码为合法字符（因不支持%，中文无法使用 urlencode 处理，请使用其他编码方式）
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+/// 可以使用 [`SceneString::encode`] 按 [`encode_query_value`] 的规则编码后再校验：
+///
+/// ```
+/// use wechat_minapp::new_type::SceneString;
+///
+/// assert!(SceneString::new("张三").is_err());
+///
+/// let scene = SceneString::encode("张三")?;
+/// assert_eq!(scene.as_str(), "*E5*BC*A0*E4*B8*89");
+/// assert_eq!(scene.decode()?, "张三");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String")]
 pub struct SceneString(String);
 
 impl SceneString {
     /// 创建新的 SceneString，进行验证
     pub fn new(s: &str) -> Result<Self, ValidationSceneError> {
-        if s.len() > 32 {
+        // 按字符而非字节计数，中文等非法字符应报告 InvalidChar 而不是 TooLong
+        if s.chars().count() > MAX_SCENE_CHARS {
             return Err(ValidationSceneError::TooLong);
         }
 
@@ -34,6 +83,16 @@ impl SceneString {
         Ok(SceneString(s.to_string()))
     }
 
+    /// 编码任意字符串后创建 SceneString，编码后超过 32 个字符时返回 [`ValidationSceneError::TooLong`]
+    pub fn encode(value: &str) -> Result<Self, ValidationSceneError> {
+        SceneString::new(&encode_query_value(value))
+    }
+
+    /// 解码 [`encode`](Self::encode) 编码的 scene
+    pub fn decode(&self) -> wechat_core::Result<String> {
+        decode_query_value(&self.0)
+    }
+
     /// 获取内部字符串引用
     pub fn as_str(&self) -> &str {
         &self.0
@@ -69,6 +128,12 @@ impl fmt::Display for SceneString {
     }
 }
 
+impl From<SceneString> for String {
+    fn from(value: SceneString) -> Self {
+        value.0
+    }
+}
+
 impl TryFrom<String> for SceneString {
     type Error = ValidationSceneError;
 
@@ -126,6 +191,12 @@ mod tests {
             SceneString::new("中文"), // 包含中文
             Err(ValidationSceneError::InvalidChar(_))
         ));
+
+        // 11 个中文字符超过 32 字节但不足 32 个字符
+        assert!(matches!(
+            SceneString::new(&"中".repeat(11)),
+            Err(ValidationSceneError::InvalidChar('中'))
+        ));
     }
 
     #[test]
@@ -141,4 +212,29 @@ mod tests {
         let from_str_ref = SceneString::try_from("test123");
         assert!(from_str_ref.is_ok());
     }
+
+    #[test]
+    fn test_encode() {
+        let scene = SceneString::encode("id 1&a").unwrap();
+        assert_eq!(scene.as_str(), "id*201*26a");
+        assert_eq!(scene.decode().unwrap(), "id 1&a");
+
+        // 每个中文编码后占 9 个字符
+        assert!(SceneString::encode(&"中".repeat(3)).is_ok());
+        assert!(matches!(
+            SceneString::encode(&"中".repeat(4)),
+            Err(ValidationSceneError::TooLong)
+        ));
+    }
+
+    #[test]
+    fn test_serde() {
+        let scene = SceneString::new("a=1").unwrap();
+        let json = serde_json::to_string(&scene).unwrap();
+        assert_eq!(json, "\"a=1\"");
+        assert_eq!(serde_json::from_str::<SceneString>(&json).unwrap(), scene);
+
+        // 反序列化时同样校验
+        assert!(serde_json::from_str::<SceneString>("\"hello world\"").is_err());
+    }
 }
@@ -0,0 +1,208 @@
+use super::{decode_query_value, encode_query_value, SceneString, ValidationSceneError};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// 以键值对方式构建 scene 字符串
+///
+/// 键按字典序排列后拼接为 `a=1&b=2`，相同的参数总是得到相同的 scene，便于缓存和去重。
+/// [`to_scene`](Self::to_scene) 会校验合法字符和 32 个字符的长度上限；
+/// 扫码进入页面后，可以用 [`parse`](Self::parse) 将 scene 还原为键值对。
+///
+/// # 示例
+///
+/// ```
+/// use wechat_minapp::new_type::SceneParams;
+/// use wechat_minapp::qr::UnlimitedQrCodeArgs;
+///
+/// let scene = SceneParams::new().insert("uid", "42").insert("id", "123").to_scene()?;
+/// assert_eq!(scene.as_str(), "id=123&uid=42");
+///
+/// let args = UnlimitedQrCodeArgs::builder()
+///     .page("pages/index/index")
+///     .scene(scene)
+///     .build()?;
+///
+/// let params = SceneParams::parse("id=123&uid=42")?;
+/// assert_eq!(params.get("id"), Some("123"));
+/// # Ok::<(), wechat_minapp::Error>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SceneParams(BTreeMap<String, String>);
+
+impl SceneParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加参数，键已存在时覆盖原值
+    pub fn insert(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.0.insert(key.into(), value.into());
+        self
+    }
+
+    /// 读取参数
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    /// 按 [`encode_query_value`] 编码后添加参数，值可以包含中文、空格和 `&`、`=` 等字符
+    ///
+    /// ```
+    /// use wechat_minapp::new_type::SceneParams;
+    ///
+    /// let scene = SceneParams::new().insert_encoded("q", "a&b").to_scene()?;
+    /// assert_eq!(scene.as_str(), "q=a*26b");
+    ///
+    /// let params = SceneParams::parse(scene.as_str())?;
+    /// assert_eq!(params.get_decoded("q")?, Some("a&b".to_string()));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn insert_encoded(self, key: impl Into<String>, value: &str) -> Self {
+        self.insert(key, encode_query_value(value))
+    }
+
+    /// 读取并解码 [`insert_encoded`](Self::insert_encoded) 添加的参数
+    pub fn get_decoded(&self, key: &str) -> wechat_core::Result<Option<String>> {
+        self.get(key).map(decode_query_value).transpose()
+    }
+
+    /// 按键的字典序遍历参数
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// 校验并生成 scene 字符串
+    ///
+    /// 键不能为空，键和值都不能包含分隔符 `&`、`=`。
+    pub fn to_scene(&self) -> Result<SceneString, ValidationSceneError> {
+        for (key, value) in &self.0 {
+            if key.is_empty() {
+                return Err(ValidationSceneError::EmptyKey);
+            }
+            if let Some(c) = key.chars().chain(value.chars()).find(|c| matches!(c, '&' | '=')) {
+                return Err(ValidationSceneError::ReservedChar(c));
+            }
+        }
+
+        SceneString::new(&self.to_string())
+    }
+
+    /// 从 `a=1&b=2` 格式的 scene 还原参数
+    ///
+    /// 扫码进入页面时 `options.scene` 经过了 URL 编码，需要先 `decodeURIComponent` 再传入。
+    pub fn parse(scene: &str) -> Result<Self, ValidationSceneError> {
+        let scene = SceneString::new(scene)?;
+
+        let mut params = SceneParams::new();
+        for pair in scene.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            if key.is_empty() {
+                return Err(ValidationSceneError::EmptyKey);
+            }
+            params = params.insert(key, value);
+        }
+
+        Ok(params)
+    }
+}
+
+impl fmt::Display for SceneParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (key, value)) in self.iter().enumerate() {
+            if index > 0 {
+                f.write_str("&")?;
+            }
+            write!(f, "{}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SceneParams {
+    type Err = ValidationSceneError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SceneParams::parse(s)
+    }
+}
+
+impl TryFrom<&SceneParams> for SceneString {
+    type Error = ValidationSceneError;
+
+    fn try_from(value: &SceneParams) -> Result<Self, Self::Error> {
+        value.to_scene()
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for SceneParams {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        iter.into_iter()
+            .fold(SceneParams::new(), |params, (key, value)| params.insert(key, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_scene() {
+        let params = SceneParams::new().insert("b", "2").insert("a", "1").insert("b", "3");
+        assert_eq!(params.to_scene().unwrap().as_str(), "a=1&b=3");
+        assert_eq!(SceneParams::new().to_scene().unwrap().as_str(), "");
+
+        // 插入顺序不影响结果
+        let reversed: SceneParams = [("a", "1"), ("b", "3")].into_iter().rev().collect();
+        assert_eq!(reversed, params);
+
+        assert!(matches!(
+            SceneParams::new().insert("id", "a&b").to_scene(),
+            Err(ValidationSceneError::ReservedChar('&'))
+        ));
+        assert!(matches!(
+            SceneParams::new().insert("", "1").to_scene(),
+            Err(ValidationSceneError::EmptyKey)
+        ));
+        assert!(matches!(
+            SceneParams::new().insert("name", "张三").to_scene(),
+            Err(ValidationSceneError::InvalidChar('张'))
+        ));
+        assert!(matches!(
+            SceneParams::new().insert("token", "a".repeat(30)).to_scene(),
+            Err(ValidationSceneError::TooLong)
+        ));
+    }
+
+    #[test]
+    fn test_encoded() {
+        let params = SceneParams::new().insert_encoded("n", "张").insert("id", "1");
+        let scene = params.to_scene().unwrap();
+        assert_eq!(scene.as_str(), "id=1&n=*E5*BC*A0");
+
+        let params = SceneParams::parse(&scene).unwrap();
+        assert_eq!(params.get_decoded("n").unwrap().as_deref(), Some("张"));
+        assert_eq!(params.get_decoded("missing").unwrap(), None);
+        assert!(SceneParams::new().insert("n", "*E").get_decoded("n").is_err());
+    }
+
+    #[test]
+    fn test_parse() {
+        let params = SceneParams::parse("uid=42&id=123&flag").unwrap();
+        assert_eq!(params.get("id"), Some("123"));
+        assert_eq!(params.get("uid"), Some("42"));
+        assert_eq!(params.get("flag"), Some(""));
+        assert_eq!(params.len(), 3);
+
+        assert!("=1".parse::<SceneParams>().is_err());
+        assert!(SceneParams::parse("a b=1").is_err());
+    }
+}
@@ -0,0 +1,312 @@
+//! openApi 管理
+//!
+//! 接口报错时微信会在 errmsg 中返回 rid（如 `invalid credential, rid: 6xxx-xxx`），
+//! [`OpenApi::rid_info`] 可以查询该 rid 对应的请求详情，辅助定位问题。
+//! rid 可以通过 [`Error::trace_id`](crate::Error::trace_id) 从错误中取得。
+//!
+//! [`OpenApi::healthcheck`] 获取 access_token 并查询一次接口额度，返回凭据是否有效、
+//! 剩余有效期与接口耗时，可以作为 k8s 等探针的检查项。
+//!
+//! ## 示例
+//!
+//! ```no_run
+//! use wechat_minapp::WechatMinapp;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = WechatMinapp::new("app_id", "secret");
+//!
+//!     if let Err(e) = client.token().await {
+//!         if let Some(rid) = e.trace_id() {
+//!             let info = client.openapi().rid_info(rid).await?;
+//!             println!("{} 耗时 {}ms", info.request_url, info.cost_in_ms);
+//!         }
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::constants::Endpoint;
+use crate::WechatMinapp;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tracing::debug;
+use wechat_core::response::ExtraFields;
+use wechat_core::utils::UnixTimestamp;
+use wechat_core::utils::ResponseExt;
+use wechat_core::{localized, Error, ErrorKind, Result};
+
+pub struct OpenApi {
+    pub client: WechatMinapp,
+}
+
+impl OpenApi {
+    pub fn new(client: impl Into<WechatMinapp>) -> Self {
+        OpenApi {
+            client: client.into(),
+        }
+    }
+
+
+    /// 查询 rid 信息
+    ///
+    /// 只能查询本账号最近 7 天内的 rid，超过 7 天返回 errcode 76001。
+    /// `/sns/` 下的接口（如 code2Session）不支持查询。
+    ///
+    /// [官方文档](https://developers.weixin.qq.com/miniprogram/dev/server/API/openApi-mgnt/api_getridinfo.html)
+    pub async fn rid_info(&self, rid: &str) -> Result<RidInfo> {
+        let rid = rid.trim();
+        if rid.is_empty() {
+            let message = localized!("rid 不能为空", "rid is required");
+            return Err(ErrorKind::InvalidParameter(message).into());
+        }
+
+        let query = serde_json::json!({
+            "access_token": self.client.token().await?
+        });
+        let body = serde_json::json!({ "rid": rid });
+
+        let request = Endpoint::RidInfo.request()
+            .query(query)
+            .body(body)
+            .build()?;
+
+        let response = self.client.core.client.execute(request).await?;
+        debug!("rid info response: {:#?}", response);
+
+        response.to_json::<RidInfoResponse>().map(|response| response.request)
+    }
+
+    wechat_core::endpoint! {
+        POST idempotent api_quota_body(args: ApiQuotaArgs) -> ApiQuota = Endpoint::ApiQuota.url();
+    }
+
+    /// 查询接口调用额度
+    ///
+    /// `cgi_path` 为接口路径，如 `/wxa/getwxacode`。
+    ///
+    /// [官方文档](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/openApi-mgnt/getApiQuota.html)
+    pub async fn api_quota(&self, cgi_path: &str) -> Result<ApiQuota> {
+        let cgi_path = cgi_path.trim();
+        if !cgi_path.starts_with('/') {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "接口路径需要以 / 开头，如 /wxa/getwxacode",
+                "cgi_path must start with '/', e.g. /wxa/getwxacode"
+            ))
+            .into());
+        }
+
+        self.api_quota_body(ApiQuotaArgs {
+            cgi_path: cgi_path.to_string(),
+        })
+        .await
+    }
+
+    /// 健康检查
+    ///
+    /// 获取 access_token 后查询一次额度查询接口自身的额度，不消耗业务接口的调用次数。
+    /// 检查失败不会返回 `Err`，原因记录在 [`HealthCheck::error`] 中。
+    ///
+    /// ```no_run
+    /// use wechat_minapp::WechatMinapp;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = WechatMinapp::new("app_id", "secret");
+    ///     let health = client.healthcheck().await;
+    ///     if !health.is_healthy() {
+    ///         eprintln!("unhealthy: {:?}", health.error);
+    ///         std::process::exit(1);
+    ///     }
+    ///     println!("耗时 {:?}，剩余有效期 {:?}", health.latency, health.expires_in);
+    /// }
+    /// ```
+    pub async fn healthcheck(&self) -> HealthCheck {
+        let started = Instant::now();
+        let result = self.api_quota(HEALTHCHECK_CGI_PATH).await;
+        let latency = started.elapsed();
+
+        let expires_in = self
+            .client
+            .core
+            .token_storage
+            .expires_at()
+            .await
+            .map(|expires_at| (expires_at - Utc::now()).to_std().unwrap_or_default());
+
+        debug!("healthcheck latency: {:?}, result: {:?}", latency, result);
+        HealthCheck {
+            expires_in,
+            latency,
+            error: result.err(),
+        }
+    }
+}
+
+/// 健康检查时查询的接口，查询额度查询接口本身
+const HEALTHCHECK_CGI_PATH: &str = "/cgi-bin/openapi/quota/get";
+
+/// 健康检查结果
+#[derive(Debug)]
+pub struct HealthCheck {
+    /// access_token 剩余有效期，存储不提供过期时间（如固定 access_token）时为 `None`
+    pub expires_in: Option<Duration>,
+    /// 获取 access_token 并调用接口的总耗时
+    pub latency: Duration,
+    /// 检查失败的原因
+    pub error: Option<Error>,
+}
+
+impl HealthCheck {
+    /// 凭据有效且接口调用成功
+    pub fn is_healthy(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ApiQuotaArgs {
+    cgi_path: String,
+}
+
+/// 接口调用额度
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiQuota {
+    /// 每日调用额度
+    pub quota: Quota,
+    /// 普通调用频率限制
+    #[serde(default)]
+    pub rate_limit: Option<RateLimit>,
+    /// 代调用频率限制
+    #[serde(default)]
+    pub component_rate_limit: Option<RateLimit>,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+/// 每日调用额度
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Quota {
+    /// 当天该账号可调用该接口的次数
+    pub daily_limit: i64,
+    /// 当天已经调用的次数
+    pub used: i64,
+    /// 当天剩余调用次数
+    pub remain: i64,
+}
+
+/// 调用频率限制：每 `refresh_second` 秒最多调用 `call_count` 次
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    pub call_count: i64,
+    pub refresh_second: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RidInfoResponse {
+    request: RidInfo,
+}
+
+/// rid 对应的请求详情
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RidInfo {
+    /// 发起请求的时间
+    pub invoke_time: UnixTimestamp,
+    /// 请求耗时（毫秒）
+    pub cost_in_ms: i64,
+    /// 请求的 URL 参数
+    pub request_url: String,
+    /// POST 请求的请求体
+    #[serde(default)]
+    pub request_body: String,
+    /// 接口返回的响应体
+    #[serde(default)]
+    pub response_body: String,
+    /// 发起请求的客户端 IP
+    #[serde(default)]
+    pub client_ip: String,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use std::sync::Arc;
+    use wechat_core::client::DryRunHttpClient;
+
+    fn open_api(response: &str) -> (OpenApi, Arc<DryRunHttpClient>) {
+        let (client, http_client) = fixtures::client(response);
+        (OpenApi::new(client), http_client)
+    }
+
+    #[tokio::test]
+    async fn test_rid_info() {
+        let (openapi, http_client) = open_api(
+            r#"{"errcode":0,"errmsg":"ok","request":{"invoke_time":1635156704,
+            "cost_in_ms":30,"request_url":"access_token=xxx","request_body":"",
+            "response_body":"{\"errcode\":45009}","client_ip":"113.65.0.1"}}"#,
+        );
+        let info = openapi.rid_info(" 61725984-6126f6f9-040f19c4 ").await;
+        let info = info.unwrap();
+        assert_eq!(info.cost_in_ms, 30);
+        assert_eq!(info.invoke_time.raw(), 1635156704);
+        assert_eq!(info.client_ip, "113.65.0.1");
+
+        let requests = http_client.requests();
+        assert!(requests[0].url.starts_with(Endpoint::RidInfo.url()));
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["rid"], "61725984-6126f6f9-040f19c4");
+    }
+
+    #[tokio::test]
+    async fn test_rid_info_errors() {
+        let (openapi, http_client) = open_api(r#"{"errcode":76001,"errmsg":"rid not found"}"#);
+
+        assert!(matches!(
+            openapi.rid_info(" ").await.unwrap_err().kind(),
+            ErrorKind::InvalidParameter(_)
+        ));
+        assert!(http_client.requests().is_empty());
+        assert_eq!(openapi.rid_info("rid").await.unwrap_err().code(), Some(76001));
+    }
+
+    #[tokio::test]
+    async fn test_api_quota() {
+        let (openapi, http_client) = open_api(
+            r#"{"errcode":0,"errmsg":"ok","quota":{"daily_limit":0,"used":0,"remain":0},
+            "rate_limit":{"call_count":100,"refresh_second":60}}"#,
+        );
+
+        let quota = openapi.api_quota("/wxa/getwxacode").await.unwrap();
+        assert_eq!(quota.rate_limit.unwrap().call_count, 100);
+        assert!(quota.component_rate_limit.is_none());
+        let requests = http_client.requests();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["cgi_path"], "/wxa/getwxacode");
+
+        let error = openapi.api_quota("wxa/getwxacode").await.unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidParameter(_)));
+    }
+
+    #[tokio::test]
+    async fn test_healthcheck() {
+        let (openapi, _) = open_api(
+            r#"{"errcode":0,"errmsg":"ok","quota":{"daily_limit":0,"used":0,"remain":0}}"#,
+        );
+        let health = openapi.healthcheck().await;
+        assert!(health.is_healthy());
+        // 固定 access_token 没有过期时间
+        assert!(health.expires_in.is_none());
+
+        let (openapi, _) = open_api(r#"{"errcode":40001,"errmsg":"invalid credential"}"#);
+        let health = openapi.healthcheck().await;
+        assert!(!health.is_healthy());
+        assert_eq!(health.error.unwrap().code(), Some(40001));
+    }
+}
@@ -0,0 +1,134 @@
+//! 批量生成小程序码
+//!
+//! 运营导出带参码时一次要生成成百上千张，[`Qr::unlimited_qr_codes`] 以受控的并发数调用接口，
+//! 可选限制每秒请求数，单个失败不影响其他参数，最终按输入顺序汇总成功与失败的结果。
+
+use super::{Qr, QrCode, UnlimitedQrCodeArgs};
+use crate::batch::{self, BatchOptions, BatchResult};
+use tracing::debug;
+
+impl Qr {
+    /// 批量生成不限制的小程序码
+    ///
+    /// ```no_run
+    /// use wechat_minapp::WechatMinapp;
+    /// use wechat_minapp::qr::{BatchOptions, Qr, UnlimitedQrCodeArgs};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let qr = Qr::new(WechatMinapp::new("app_id", "secret"));
+    ///     let args = (1..=100)
+    ///         .map(|id| {
+    ///             UnlimitedQrCodeArgs::builder()
+    ///                 .page("pages/index/index")
+    ///                 .scene(format!("id={}", id))
+    ///                 .build()
+    ///         })
+    ///         .collect::<Result<Vec<_>, _>>()?;
+    ///
+    ///     // 最多 4 个并发，每秒最多 10 个请求
+    ///     let result = qr.unlimited_qr_codes(args, BatchOptions::new(4).rate_limit(10)).await;
+    ///     for (index, qr_code) in &result.succeeded {
+    ///         qr_code.save_to(format!("qrcode-{}.png", index + 1)).await?;
+    ///     }
+    ///     for (index, error) in &result.failed {
+    ///         eprintln!("第 {} 个小程序码生成失败: {}", index + 1, error);
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn unlimited_qr_codes<I>(
+        &self,
+        args: I,
+        options: impl Into<BatchOptions>,
+    ) -> BatchResult<QrCode>
+    where
+        I: IntoIterator<Item = UnlimitedQrCodeArgs>,
+    {
+        let result = batch::run(args, options.into(), |args| self.unlimited_qr_code(args)).await;
+        debug!(
+            "batch unlimited qr codes: {} succeeded, {} failed",
+            result.succeeded.len(),
+            result.failed.len()
+        );
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use std::time::Duration;
+    use async_trait::async_trait;
+    use http::{Request, Response};
+    use std::sync::Arc;
+    use wechat_core::client::HttpClient;
+    use wechat_core::Result;
+
+    struct StaticClient(&'static [u8]);
+
+    #[async_trait]
+    impl HttpClient for StaticClient {
+        async fn execute(&self, _request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+            Ok(Response::new(self.0.to_vec()))
+        }
+    }
+
+    fn qr(body: &'static [u8]) -> Qr {
+        Qr::new(fixtures::client_with(Arc::new(StaticClient(body))))
+    }
+
+    fn args(count: usize) -> Vec<UnlimitedQrCodeArgs> {
+        (0..count)
+            .map(|id| {
+                UnlimitedQrCodeArgs::builder()
+                    .page("pages/index/index")
+                    .scene(format!("id={}", id))
+                    .build()
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_unlimited_qr_codes() {
+        let qr = qr(b"\x89PNG\r\n\x1a\n");
+        let result = qr.unlimited_qr_codes(args(5), 2).await;
+
+        assert!(result.is_all_ok());
+        assert_eq!(result.len(), 5);
+        let indexes: Vec<usize> = result.succeeded.iter().map(|(index, _)| *index).collect();
+        assert_eq!(indexes, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limit() {
+        let qr = qr(b"\x89PNG\r\n\x1a\n");
+        let start = tokio::time::Instant::now();
+        let result = qr
+            .unlimited_qr_codes(args(5), BatchOptions::new(5).rate_limit(2))
+            .await;
+
+        assert_eq!(result.succeeded.len(), 5);
+        // 首个请求立即发出，其余每 500ms 一个
+        assert!(start.elapsed() >= Duration::from_secs(2));
+
+        // 间隔舍入为 0 时不会 panic
+        let result = qr
+            .unlimited_qr_codes(args(3), BatchOptions::new(3).rate_limit(u32::MAX))
+            .await;
+        assert!(result.is_all_ok());
+    }
+
+    #[tokio::test]
+    async fn test_failed() {
+        let qr = qr(br#"{"errcode":40097,"errmsg":"invalid args"}"#);
+        let result = qr.unlimited_qr_codes(args(3), 8).await;
+
+        assert!(!result.is_all_ok());
+        assert_eq!(result.failed.len(), 3);
+        assert_eq!(result.failed[2].0, 2);
+    }
+}
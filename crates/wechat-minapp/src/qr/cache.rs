@@ -0,0 +1,283 @@
+//! 小程序码缓存
+//!
+//! 参数相同的不限制小程序码生成的图片相同，重复调用只会浪费接口配额。
+//! [`Qr::unlimited_qr_code_cached`] 先从 [`QrCodeCache`] 读取，未命中才调用微信接口并写入缓存。
+//! 默认提供内存实现 [`MemoryQrCodeCache`]，启用 `redis` feature 后可以使用 `RedisQrCodeCache`
+//! 在多实例之间共享。
+
+use super::{Qr, QrCode, UnlimitedQrCodeArgs};
+use async_trait::async_trait;
+use hex::encode;
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+use wechat_core::Result;
+
+/// 定义小程序码缓存读取与写入的行为
+#[async_trait]
+pub trait QrCodeCache: Send + Sync {
+    /// 读取缓存的小程序码
+    async fn get(&self, key: &str) -> Result<Option<QrCode>>;
+    /// 缓存小程序码
+    async fn set(&self, key: &str, qr_code: &QrCode) -> Result<()>;
+}
+
+/// 内存缓存，进程重启后失效，只适合单实例或测试使用
+#[derive(Debug, Default, Clone)]
+pub struct MemoryQrCodeCache {
+    entries: Arc<RwLock<HashMap<String, QrCode>>>,
+}
+
+impl MemoryQrCodeCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl QrCodeCache for MemoryQrCodeCache {
+    async fn get(&self, key: &str) -> Result<Option<QrCode>> {
+        Ok(self.entries.read().await.get(key).cloned())
+    }
+
+    async fn set(&self, key: &str, qr_code: &QrCode) -> Result<()> {
+        self.entries
+            .write()
+            .await
+            .insert(key.to_string(), qr_code.clone());
+        Ok(())
+    }
+}
+
+/// redis 缓存，需要启用 `redis` feature
+///
+/// 小程序码以 hash 保存，`buffer` 为图片数据，`content_type` 为响应的 `Content-Type`。
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use wechat_minapp::qr::RedisQrCodeCache;
+///
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = redis::Client::open("redis://127.0.0.1/")?;
+/// let connection = client.get_multiplexed_async_connection().await?;
+/// let cache = RedisQrCodeCache::new(connection).ttl(Duration::from_secs(30 * 24 * 3600));
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "redis")]
+#[derive(Clone)]
+pub struct RedisQrCodeCache {
+    connection: redis::aio::MultiplexedConnection,
+    prefix: String,
+    ttl: Option<std::time::Duration>,
+}
+
+#[cfg(feature = "redis")]
+impl std::fmt::Debug for RedisQrCodeCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisQrCodeCache")
+            .field("prefix", &self.prefix)
+            .field("ttl", &self.ttl)
+            .finish()
+    }
+}
+
+#[cfg(feature = "redis")]
+impl RedisQrCodeCache {
+    /// 使用默认的 key 前缀 `wechat_minapp:qr_code:` 创建，缓存不过期
+    pub fn new(connection: redis::aio::MultiplexedConnection) -> Self {
+        RedisQrCodeCache {
+            connection,
+            prefix: "wechat_minapp:qr_code:".to_string(),
+            ttl: None,
+        }
+    }
+
+    /// key 前缀
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// 缓存有效期
+    pub fn ttl(mut self, ttl: std::time::Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+}
+
+#[cfg(feature = "redis")]
+pub(super) fn redis_error(error: redis::RedisError) -> wechat_core::Error {
+    wechat_core::ErrorKind::InternalServer(format!("redis: {}", error)).into()
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl QrCodeCache for RedisQrCodeCache {
+    async fn get(&self, key: &str) -> Result<Option<QrCode>> {
+        let mut connection = self.connection.clone();
+        let mut fields: HashMap<String, Vec<u8>> = redis::cmd("HGETALL")
+            .arg(format!("{}{}", self.prefix, key))
+            .query_async(&mut connection)
+            .await
+            .map_err(redis_error)?;
+
+        Ok(fields.remove("buffer").map(|buffer| QrCode {
+            buffer,
+            content_type: fields
+                .remove("content_type")
+                .and_then(|value| String::from_utf8(value).ok()),
+        }))
+    }
+
+    async fn set(&self, key: &str, qr_code: &QrCode) -> Result<()> {
+        let key = format!("{}{}", self.prefix, key);
+        let mut pipe = redis::pipe();
+        pipe.atomic().cmd("DEL").arg(&key).ignore();
+        pipe.cmd("HSET")
+            .arg(&key)
+            .arg("buffer")
+            .arg(&qr_code.buffer)
+            .ignore();
+        if let Some(content_type) = &qr_code.content_type {
+            pipe.cmd("HSET")
+                .arg(&key)
+                .arg("content_type")
+                .arg(content_type)
+                .ignore();
+        }
+        if let Some(ttl) = self.ttl {
+            pipe.cmd("EXPIRE").arg(&key).arg(ttl.as_secs().max(1)).ignore();
+        }
+
+        let mut connection = self.connection.clone();
+        pipe.query_async::<()>(&mut connection)
+            .await
+            .map_err(redis_error)
+    }
+}
+
+/// 缓存 key：appid 与参数 JSON 的 SHA-256，任何参数不同都会得到不同的 key
+fn cache_key(app_id: &str, args: &UnlimitedQrCodeArgs) -> Result<String> {
+    let args = serde_json::to_vec(args)?;
+    Ok(format!("{}:{}", app_id, encode(Sha256::digest(args))))
+}
+
+impl Qr {
+    /// 生成不限制的小程序码，参数相同时直接返回缓存的图片
+    ///
+    /// 读写缓存失败只记录日志，不影响生成小程序码。
+    ///
+    /// ```no_run
+    /// use wechat_minapp::WechatMinapp;
+    /// use wechat_minapp::qr::{MemoryQrCodeCache, UnlimitedQrCodeArgs};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = WechatMinapp::new("app_id", "secret");
+    ///     let cache = MemoryQrCodeCache::new();
+    ///     let args = UnlimitedQrCodeArgs::builder()
+    ///         .page("pages/index/index")
+    ///         .scene("id=1")
+    ///         .build()?;
+    ///
+    ///     let qr_code = client.qr().unlimited_qr_code_cached(args.clone(), &cache).await?;
+    ///     // 第二次直接命中缓存，不再调用微信接口
+    ///     let cached = client.qr().unlimited_qr_code_cached(args, &cache).await?;
+    ///     assert_eq!(qr_code, cached);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn unlimited_qr_code_cached(
+        &self,
+        args: UnlimitedQrCodeArgs,
+        cache: &dyn QrCodeCache,
+    ) -> Result<QrCode> {
+        let key = cache_key(&self.client.app_config().app_id, &args)?;
+
+        match cache.get(&key).await {
+            Ok(Some(qr_code)) => {
+                debug!("unlimited qr code cache hit: {}", key);
+                return Ok(qr_code);
+            }
+            Ok(None) => {}
+            Err(error) => warn!("read qr code cache {} failed: {}", key, error),
+        }
+
+        let qr_code = self.unlimited_qr_code(args).await?;
+        if let Err(error) = cache.set(&key, &qr_code).await {
+            warn!("write qr code cache {} failed: {}", key, error);
+        }
+
+        Ok(qr_code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use http::{Request, Response};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use wechat_core::client::HttpClient;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+    #[derive(Default)]
+    struct PngClient(AtomicUsize);
+
+    #[async_trait]
+    impl HttpClient for PngClient {
+        async fn execute(&self, _request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(Response::new(PNG.to_vec()))
+        }
+    }
+
+    fn qr(http_client: Arc<PngClient>) -> Qr {
+        Qr::new(fixtures::client_with(http_client))
+    }
+
+    fn args(scene: &str) -> UnlimitedQrCodeArgs {
+        UnlimitedQrCodeArgs::builder()
+            .page("pages/index/index")
+            .scene(scene)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_unlimited_qr_code_cached() {
+        let http_client = Arc::new(PngClient::default());
+        let qr = qr(http_client.clone());
+        let cache = MemoryQrCodeCache::new();
+
+        let qr_code = qr.unlimited_qr_code_cached(args("id=1"), &cache).await.unwrap();
+        assert_eq!(qr_code.buffer(), PNG);
+        let cached = qr.unlimited_qr_code_cached(args("id=1"), &cache).await.unwrap();
+        assert_eq!(cached, qr_code);
+        assert_eq!(http_client.0.load(Ordering::SeqCst), 1);
+
+        qr.unlimited_qr_code_cached(args("id=2"), &cache).await.unwrap();
+        assert_eq!(http_client.0.load(Ordering::SeqCst), 2);
+        assert_eq!(cache.entries.read().await.len(), 2);
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let key = cache_key("wx123", &args("id=1")).unwrap();
+        assert!(key.starts_with("wx123:"));
+        assert_eq!(key, cache_key("wx123", &args("id=1")).unwrap());
+        assert_ne!(key, cache_key("wx456", &args("id=1")).unwrap());
+
+        let wider = UnlimitedQrCodeArgs::builder()
+            .page("pages/index/index")
+            .scene("id=1")
+            .width(430)
+            .build()
+            .unwrap();
+        assert_ne!(key, cache_key("wx123", &wider).unwrap());
+    }
+}
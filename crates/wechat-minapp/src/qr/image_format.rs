@@ -0,0 +1,140 @@
+//! 小程序码图片元信息
+//!
+//! 根据文件头推断图片格式，并从 PNG 的 IHDR 块或 JPEG 的 SOF 段读取尺寸，不依赖图片解码库。
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// 小程序码的图片格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+}
+
+impl ImageFormat {
+    /// 根据文件头推断图片格式
+    pub fn detect(buffer: &[u8]) -> Option<ImageFormat> {
+        if buffer.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(ImageFormat::Png)
+        } else if buffer.starts_with(&[0xff, 0xd8, 0xff]) {
+            Some(ImageFormat::Jpeg)
+        } else {
+            None
+        }
+    }
+
+    /// 对应的 MIME 类型，可直接用作 HTTP 响应的 `Content-Type`
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "image/png",
+            ImageFormat::Jpeg => "image/jpeg",
+        }
+    }
+
+    /// 常用的文件扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpg",
+        }
+    }
+
+    /// 读取图片的宽和高（像素）
+    pub(crate) fn dimensions(&self, buffer: &[u8]) -> Option<(u32, u32)> {
+        match self {
+            ImageFormat::Png => png_dimensions(buffer),
+            ImageFormat::Jpeg => jpeg_dimensions(buffer),
+        }
+    }
+}
+
+impl fmt::Display for ImageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.extension())
+    }
+}
+
+fn be_u16(buffer: &[u8], offset: usize) -> Option<u16> {
+    let bytes = buffer.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn be_u32(buffer: &[u8], offset: usize) -> Option<u32> {
+    let bytes = buffer.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// PNG：8 字节签名后紧跟 IHDR 块，宽高位于第 16、20 字节
+fn png_dimensions(buffer: &[u8]) -> Option<(u32, u32)> {
+    if buffer.get(12..16)? != b"IHDR" {
+        return None;
+    }
+
+    Some((be_u32(buffer, 16)?, be_u32(buffer, 20)?))
+}
+
+/// JPEG：逐段查找 SOF 标记（0xC0–0xCF，排除 0xC4、0xC8、0xCC）
+fn jpeg_dimensions(buffer: &[u8]) -> Option<(u32, u32)> {
+    let mut offset = 2;
+
+    loop {
+        while *buffer.get(offset)? != 0xff {
+            offset += 1;
+        }
+        while *buffer.get(offset)? == 0xff {
+            offset += 1;
+        }
+
+        let marker = *buffer.get(offset)?;
+        offset += 1;
+
+        match marker {
+            // 没有长度字段的标记
+            0xd0..=0xd9 | 0x01 => continue,
+            0xc0..=0xcf if !matches!(marker, 0xc4 | 0xc8 | 0xcc) => {
+                let height = be_u16(buffer, offset + 3)?;
+                let width = be_u16(buffer, offset + 5)?;
+                return Some((u32::from(width), u32::from(height)));
+            }
+            _ => offset += usize::from(be_u16(buffer, offset)?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_png() {
+        let mut png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+        png.extend_from_slice(&430u32.to_be_bytes());
+        png.extend_from_slice(&430u32.to_be_bytes());
+
+        let format = ImageFormat::detect(&png).unwrap();
+        assert_eq!(format, ImageFormat::Png);
+        assert_eq!(format.mime_type(), "image/png");
+        assert_eq!(format.dimensions(&png), Some((430, 430)));
+    }
+
+    #[test]
+    fn test_jpeg() {
+        let jpeg = [
+            0xff, 0xd8, // SOI
+            0xff, 0xe0, 0x00, 0x04, 0x00, 0x00, // APP0
+            0xff, 0xc0, 0x00, 0x11, 0x08, 0x01, 0x18, 0x02, 0x80, // SOF0 280x640
+        ];
+
+        let format = ImageFormat::detect(&jpeg).unwrap();
+        assert_eq!(format, ImageFormat::Jpeg);
+        assert_eq!(format.dimensions(&jpeg), Some((640, 280)));
+    }
+
+    #[test]
+    fn test_unknown() {
+        assert_eq!(ImageFormat::detect(b"{\"errcode\":0}"), None);
+        assert_eq!(ImageFormat::Png.dimensions(b"\x89PNG"), None);
+    }
+}
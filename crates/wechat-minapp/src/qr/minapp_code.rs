@@ -46,7 +46,7 @@
 //!
 //! # 参数说明
 //!
-//! - `path`: 小程序页面路径，必填，最大长度 1024 字符
+//! - `path`: 小程序页面路径，必填，最大长度 1024 字符，可以带参数，保留参数 `scancode_time` 会被去掉
 //! - `width`: 小程序码宽度，单位 px，最小 280px，最大 1280px
 //! - `auto_color`: 是否自动配置线条颜色
 //! - `line_color`: 自定义线条颜色，RGB 格式
@@ -58,6 +58,7 @@
 //! - 生成的小程序码永不过期，数量不限
 //! - 接口只能生成已发布的小程序的小程序码
 //! - 支持带参数路径，如 `pages/index/index?param=value`
+//! - 与 [`UnlimitedQrCodeArgs`](super::unlimited_minapp_code::UnlimitedQrCodeArgs) 不同，`path` 不要求去掉参数
 //! - 小程序码大小限制为 128KB，请合理设置 width 参数
 //!
 //! # 示例
@@ -66,7 +67,7 @@
 //!
 //! ```no_run
 //! use wechat_minapp::WechatMinapp;
-//! use wechat_minapp::qr::{QrCodeArgs,Qr, MinappEnvVersion};
+//! use wechat_minapp::qr::{QrCodeArgs,Qr, MinappEnvVersion, Rgb};
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -131,17 +132,24 @@
 //!
 //! 建议在生产环境中妥善处理这些错误。
 
-use super::Qr;
-use wechat_core::utils::{RequestBuilder, ResponseExt};
-use wechat_core::{Result, Error};
-use crate::constants;
-use crate::new_type::PagePath;
+use super::{check_width, ImageFormat, Qr};
+use http::{Request, Response, header::CONTENT_TYPE};
+use wechat_core::utils::{AsyncWrite, check_download, should_check_download};
+use wechat_core::localized;
+use wechat_core::{Result, Error, ErrorKind};
+use crate::constants::Endpoint;
+use crate::new_type::{MinappEnvVersion, PagePath};
+use base64::{engine::general_purpose::STANDARD, Engine};
 use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::str::FromStr;
+use tokio::io::AsyncWriteExt;
 use tracing::debug;
 
 /// 二维码图片数据
 ///
-/// 包含生成的二维码图片的二进制数据，通常是 PNG 格式。
+/// 包含生成的二维码图片的二进制数据（通常是 PNG 格式）、响应的 `Content-Type`，
+/// 并可以从文件头推断图片格式和尺寸。
 ///
 /// # 示例
 ///
@@ -169,9 +177,12 @@ use tracing::debug;
 ///     Ok(())
 /// }
 /// ```
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct QrCode {
     pub buffer: Vec<u8>,
+    /// 微信响应的 `Content-Type`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
 }
 
 impl QrCode {
@@ -185,16 +196,107 @@ impl QrCode {
     pub fn buffer(&self) -> &Vec<u8> {
         &self.buffer
     }
+
+    /// 微信响应的 `Content-Type`
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// 根据文件头推断的图片格式
+    pub fn format(&self) -> Option<ImageFormat> {
+        ImageFormat::detect(&self.buffer)
+    }
+
+    /// 适合作为 HTTP 响应头的 MIME 类型
+    ///
+    /// 优先使用推断出的图片格式，其次是微信响应的 `Content-Type`，都没有时为 `image/png`。
+    pub fn mime_type(&self) -> &str {
+        self.format()
+            .map(|format| format.mime_type())
+            .or(self.content_type())
+            .unwrap_or("image/png")
+    }
+
+    /// 图片的宽和高（像素），无法识别时返回 `None`
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        self.format()?.dimensions(&self.buffer)
+    }
+
+    /// 转换为 base64 data URL，例如 `data:image/png;base64,iVBORw0KGgo...`
+    ///
+    /// 便于直接内嵌到 JSON 响应中交给前端展示（`<image src="...">`）。
+    pub fn to_data_url(&self) -> String {
+        format!("data:{};base64,{}", self.mime_type(), STANDARD.encode(&self.buffer))
+    }
+
+    /// 解码为 [`image::DynamicImage`]，便于叠加海报、添加 logo 等二次合成
+    ///
+    /// 需要启用 `image` feature。
+    #[cfg(feature = "image")]
+    pub fn to_image(&self) -> image::ImageResult<image::DynamicImage> {
+        image::load_from_memory(&self.buffer)
+    }
+
+    /// 将图片写入 `writer`
+    pub async fn write_to<W>(&self, writer: &mut W) -> Result<()>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        writer.write_all(&self.buffer).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+
+    /// 将图片异步写入文件，文件已存在时覆盖
+    ///
+    /// ```no_run
+    /// # async fn run(qr_code: wechat_minapp::qr::QrCode) -> wechat_minapp::Result<()> {
+    /// let file_name = format!("qrcode.{}", qr_code.format().map_or("png", |f| f.extension()));
+    /// qr_code.save_to(file_name).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn save_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        tokio::fs::write(path, &self.buffer).await?;
+        Ok(())
+    }
+
+    /// 从接口响应构造小程序码
+    ///
+    /// 微信出错时会返回 200 状态码和 JSON 错误体（errcode/errmsg），此时按错误码转换为
+    /// 对应的 [`Error`]，而不是把 JSON 当作图片返回。
+    pub(crate) fn from_response(response: Response<Vec<u8>>) -> Result<QrCode> {
+        let is_text = should_check_download(&response);
+        let (parts, buffer) = check_download(response)?.into_parts();
+
+        if is_text {
+            return Err(ErrorKind::InternalServer(
+                String::from_utf8_lossy(&buffer).to_string(),
+            )
+            .into());
+        }
+
+        let content_type = parts
+            .headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+
+        Ok(QrCode {
+            buffer,
+            content_type,
+        })
+    }
 }
 
 /// 二维码生成参数
 ///
 /// 用于配置二维码的生成选项，通过 [`QrCodeArgs::builder()`] 方法创建。
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct QrCodeArgs {
     path: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    width: Option<i16>,
+    width: Option<u16>,
     #[serde(skip_serializing_if = "Option::is_none")]
     auto_color: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -217,7 +319,6 @@ pub struct QrCodeArgs {
 /// let args = QrCodeArgs::builder()
 ///     .path("pages/index/index")
 ///     .width(300)
-///     .with_auto_color()
 ///     .line_color(Rgb::new(255, 0, 0))
 ///     .with_is_hyaline()
 ///     .env_version(MinappEnvVersion::Release)
@@ -227,14 +328,14 @@ pub struct QrCodeArgs {
 #[derive(Debug, Deserialize)]
 pub struct QrCodeArgBuilder {
     path: Option<String>,
-    width: Option<i16>,
+    width: Option<u16>,
     auto_color: Option<bool>,
     line_color: Option<Rgb>,
     is_hyaline: Option<bool>,
     env_version: Option<MinappEnvVersion>,
 }
 
-// RGB 颜色值
+/// RGB 颜色值
 ///
 /// 用于自定义二维码线条颜色。
 ///
@@ -247,12 +348,16 @@ pub struct QrCodeArgBuilder {
 /// let green = Rgb::new(0, 255, 0);    // 绿色
 /// let blue = Rgb::new(0, 0, 255);     // 蓝色
 /// let black = Rgb::new(0, 0, 0);      // 黑色
+///
+/// let orange = Rgb::from_hex("#FF6600").unwrap();
+/// assert_eq!(orange, Rgb::new(255, 102, 0));
+/// assert_eq!(orange.to_hex(), "#FF6600");
 /// ```
-#[derive(Debug, Deserialize, Clone, Serialize)]
+#[derive(Debug, Deserialize, Clone, Copy, Serialize, PartialEq, Eq, Hash)]
 pub struct Rgb {
-    r: i16,
-    g: i16,
-    b: i16,
+    r: u8,
+    g: u8,
+    b: u8,
 }
 
 impl Rgb {
@@ -267,9 +372,69 @@ impl Rgb {
     /// # 返回
     ///
     /// 新的 Rgb 实例
-    pub fn new(r: i16, g: i16, b: i16) -> Self {
+    pub fn new(r: u8, g: u8, b: u8) -> Self {
         Rgb { r, g, b }
     }
+
+    /// 从十六进制颜色创建，支持 `#FF6600`、`FF6600` 和简写的 `#F60`
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        let invalid = || {
+            ErrorKind::InvalidParameter(localized!("无效的十六进制颜色: {}", "invalid hex color: {}", hex))
+        };
+
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid().into());
+        }
+        let component = |s: &str| u8::from_str_radix(s, 16).map_err(|_| invalid());
+
+        match digits.len() {
+            6 => Ok(Rgb::new(
+                component(&digits[0..2])?,
+                component(&digits[2..4])?,
+                component(&digits[4..6])?,
+            )),
+            3 => Ok(Rgb::new(
+                component(&digits[0..1])? * 0x11,
+                component(&digits[1..2])? * 0x11,
+                component(&digits[2..3])? * 0x11,
+            )),
+            _ => Err(invalid().into()),
+        }
+    }
+
+    /// 转换为 `#RRGGBB` 格式的十六进制颜色
+    pub fn to_hex(&self) -> String {
+        format!("#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+    }
+
+    pub fn r(&self) -> u8 {
+        self.r
+    }
+
+    pub fn g(&self) -> u8 {
+        self.g
+    }
+
+    pub fn b(&self) -> u8 {
+        self.b
+    }
+}
+
+impl FromStr for Rgb {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Rgb::from_hex(s)
+    }
+}
+
+impl TryFrom<&str> for Rgb {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        Rgb::from_hex(value)
+    }
 }
 
 impl QrCodeArgs {
@@ -281,7 +446,7 @@ impl QrCodeArgs {
         self.path.clone()
     }
 
-    pub fn width(&self) -> Option<i16> {
+    pub fn width(&self) -> Option<u16> {
         self.width
     }
 
@@ -290,7 +455,7 @@ impl QrCodeArgs {
     }
 
     pub fn line_color(&self) -> Option<Rgb> {
-        self.line_color.clone()
+        self.line_color
     }
 
     pub fn is_hyaline(&self) -> Option<bool> {
@@ -298,7 +463,7 @@ impl QrCodeArgs {
     }
 
     pub fn env_version(&self) -> Option<MinappEnvVersion> {
-        self.env_version.clone()
+        self.env_version
     }
 }
 
@@ -308,30 +473,6 @@ impl Default for QrCodeArgBuilder {
     }
 }
 
-/// 小程序环境版本
-///
-/// 指定二维码生成的环境版本，不同环境版本对应不同的小程序实例。
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum MinappEnvVersion {
-    /// 开发版，用于开发环境
-    Release,
-    /// 体验版，用于测试环境
-    Trial,
-    /// 正式版，用于生产环境
-    Develop,
-}
-
-impl From<MinappEnvVersion> for String {
-    fn from(value: MinappEnvVersion) -> Self {
-        match value {
-            MinappEnvVersion::Develop => "develop".to_string(),
-            MinappEnvVersion::Release => "release".to_string(),
-            MinappEnvVersion::Trial => "trial".to_string(),
-        }
-    }
-}
-
 impl QrCodeArgBuilder {
     pub fn new() -> Self {
         QrCodeArgBuilder {
@@ -349,7 +490,8 @@ impl QrCodeArgBuilder {
         self
     }
 
-    pub fn width(mut self, width: i16) -> Self {
+    /// 小程序码宽度，单位 px，取值范围 280–1280，超出范围时 `build()` 返回错误
+    pub fn width(mut self, width: u16) -> Self {
         self.width = Some(width);
         self
     }
@@ -377,20 +519,26 @@ impl QrCodeArgBuilder {
     pub fn build(self) -> Result<QrCodeArgs> {
         let path = self.path.map_or_else(
             || {
-                Err(Error::InvalidParameter(
-                    "小程序页面路径不能为空".to_string(),
-                ))
+                Err(Error::from(ErrorKind::InvalidParameter(localized!(
+                    "小程序页面路径不能为空",
+                    "page path is required"
+                ))))
             },
             |v| {
-                let valid_path = PagePath::try_from(v)?;
+                // getwxacode 的路径可以带参数，scancode_time 由微信追加，这里直接去掉
+                let valid_path = PagePath::without_reserved(&v)?;
                 Ok(valid_path.to_string())
             },
         )?;
 
+        check_width(self.width)?;
+
         if self.auto_color.is_some() && self.line_color.is_some() {
-            return Err(Error::InvalidParameter(
-                "auto_color 为 true 时，line_color 不能设置".to_string(),
-            ));
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "auto_color 为 true 时，line_color 不能设置",
+                "line_color must not be set when auto_color is true"
+            ))
+            .into());
         }
 
         Ok(QrCodeArgs {
@@ -461,36 +609,203 @@ impl Qr {
     pub async fn qr_code(&self, args: QrCodeArgs) -> Result<QrCode> {
         debug!("get qr code args {:?}", &args);
 
+        let request = self.qr_code_request(args).await?;
+
+        let client = &self.client.core.client;
+
+        let response = client.execute(request).await?;
+
+        debug!("response status: {}, headers: {:?}", response.status(), response.headers());
+
+        QrCode::from_response(response)
+    }
+
+    /// 使用指定的 access_token 获取小程序码，不再从 `TokenStorage` 取值
+    pub async fn qr_code_with_token(
+        &self,
+        args: QrCodeArgs,
+        access_token: impl Into<String>,
+    ) -> Result<QrCode> {
+        Qr::new(self.client.with_token(access_token)).qr_code(args).await
+    }
+
+    /// 获取小程序码并直接写入 `writer`（例如文件），返回写入的字节数
+    ///
+    /// 微信返回错误时不会写入任何数据。
+    ///
+    /// ```no_run
+    /// use wechat_minapp::WechatMinapp;
+    /// use wechat_minapp::qr::{Qr, QrCodeArgs};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let qr = Qr::new(WechatMinapp::new("app_id", "secret"));
+    ///     let args = QrCodeArgs::builder().path("pages/index/index").build()?;
+    ///
+    ///     let mut file = tokio::fs::File::create("qrcode.png").await?;
+    ///     let size = qr.qr_code_to_writer(args, &mut file).await?;
+    ///     println!("写入 {} bytes", size);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn qr_code_to_writer(
+        &self,
+        args: QrCodeArgs,
+        writer: &mut (dyn AsyncWrite + Send + Unpin),
+    ) -> Result<u64> {
+        debug!("get qr code to writer args {:?}", &args);
+
+        let request = self.qr_code_request(args).await?;
+
+        let response = self
+            .client
+            .core
+            .client
+            .execute_streaming(request, writer)
+            .await?;
+
+        Ok(response.into_body())
+    }
+
+    async fn qr_code_request(&self, args: QrCodeArgs) -> Result<Request<Vec<u8>>> {
         let query = serde_json::json!({
             "access_token":self.client.token().await?
         });
 
-        let body = serde_json::to_value(QrCodeArgs {
-            path: args.path,
-            width: args.width,
-            auto_color: args.auto_color,
-            line_color: args.line_color,
-            is_hyaline: args.is_hyaline,
-            env_version: args.env_version,
-        })?;
+        let body = serde_json::to_value(args)?;
 
-        let request = RequestBuilder::new(constants::QR_CODE_ENDPOINT)
+        Endpoint::QrCode.request()
             .query(query)
             .body(body)
-            .build()?;
+            .build()
+    }
+}
 
-        let client = &self.client.core.client;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let response = client.execute(request).await?;
+    fn response(content_type: &str, body: &[u8]) -> Response<Vec<u8>> {
+        Response::builder()
+            .header(CONTENT_TYPE, content_type)
+            .body(body.to_vec())
+            .unwrap()
+    }
 
-        debug!("response: {:#?}", response);
+    #[test]
+    fn test_from_response() {
+        let png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR\x00\x00\x01\xae\x00\x00\x01\xae";
+        let qr_code = QrCode::from_response(response("image/png", png)).unwrap();
+        assert_eq!(qr_code.buffer(), png);
+        assert_eq!(qr_code.content_type(), Some("image/png"));
+        assert_eq!(qr_code.format(), Some(ImageFormat::Png));
+        assert_eq!(qr_code.mime_type(), "image/png");
+        assert_eq!(qr_code.dimensions(), Some((430, 430)));
+
+        let error = QrCode::from_response(response(
+            "application/json; encoding=utf-8",
+            br#"{"errcode":41030,"errmsg":"invalid page"}"#,
+        ))
+        .unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Wechat { code: 41030, .. }));
 
-        let buffer = response.to_raw()?;
-        if buffer.len() > 2048 {
-            return Ok(QrCode { buffer });
-        }
-        Err(Error::InternalServer(
-            String::from_utf8_lossy(&buffer).to_string(),
+        // 部分错误响应不带 JSON content-type
+        let error = QrCode::from_response(response(
+            "image/jpeg",
+            br#"{"errcode":40001,"errmsg":"invalid credential"}"#,
         ))
+        .unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidCredential(_)));
+    }
+
+    #[test]
+    fn test_to_data_url() {
+        let qr_code = QrCode {
+            buffer: b"\x89PNG\r\n\x1a\n".to_vec(),
+            content_type: Some("image/png".to_string()),
+        };
+        assert_eq!(qr_code.to_data_url(), "data:image/png;base64,iVBORw0KGgo=");
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_to_image() {
+        let mut buffer = std::io::Cursor::new(Vec::new());
+        image::RgbImage::new(3, 2)
+            .write_to(&mut buffer, image::ImageFormat::Png)
+            .unwrap();
+        let qr_code = QrCode {
+            buffer: buffer.into_inner(),
+            content_type: None,
+        };
+
+        let image = qr_code.to_image().unwrap();
+        assert_eq!((image.width(), image.height()), (3, 2));
+        assert_eq!(qr_code.dimensions(), Some((3, 2)));
+        assert!(QrCode { buffer: vec![1, 2, 3], content_type: None }.to_image().is_err());
+    }
+
+    #[test]
+    fn test_path_with_query() {
+        let args = QrCodeArgs::builder()
+            .path("pages/detail/detail?id=1&scancode_time=2&from=share")
+            .build()
+            .unwrap();
+        assert_eq!(args.path, "pages/detail/detail?id=1&from=share");
+
+        let args = QrCodeArgs::builder()
+            .path("pages/detail/detail?scancode_time=2")
+            .build()
+            .unwrap();
+        assert_eq!(args.path, "pages/detail/detail");
+
+        // 只有完全相同的参数名才是保留参数
+        let args = QrCodeArgs::builder()
+            .path("pages/detail/detail?my_scancode_time=2")
+            .build()
+            .unwrap();
+        assert_eq!(args.path, "pages/detail/detail?my_scancode_time=2");
+    }
+
+    #[test]
+    fn test_width_range() {
+        let build = |width| QrCodeArgs::builder().path("pages/index/index").width(width).build();
+
+        assert_eq!(build(280).unwrap().width(), Some(280));
+        assert_eq!(build(1280).unwrap().width(), Some(1280));
+        assert!(matches!(build(279).unwrap_err().kind(), ErrorKind::InvalidParameter(_)));
+        assert!(matches!(build(1281).unwrap_err().kind(), ErrorKind::InvalidParameter(_)));
+    }
+
+    #[test]
+    fn test_rgb_from_hex() {
+        assert_eq!(Rgb::from_hex("#FF6600").unwrap(), Rgb::new(255, 102, 0));
+        assert_eq!(Rgb::from_hex("ff6600").unwrap(), Rgb::new(255, 102, 0));
+        assert_eq!(Rgb::from_hex("#F60").unwrap(), Rgb::new(255, 102, 0));
+        assert_eq!("#000000".parse::<Rgb>().unwrap(), Rgb::new(0, 0, 0));
+        assert_eq!(Rgb::try_from("#0000FF").unwrap().to_hex(), "#0000FF");
+
+        for hex in ["", "#FF66", "#GG6600", "#FF66000", "#+F6600", "#中6"] {
+            let error = Rgb::from_hex(hex).unwrap_err();
+            assert!(matches!(error.kind(), ErrorKind::InvalidParameter(_)), "{}", hex);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_to() {
+        let qr_code = QrCode {
+            buffer: b"\x89PNG\r\n\x1a\n".to_vec(),
+            content_type: None,
+        };
+
+        let mut buffer = Vec::new();
+        qr_code.write_to(&mut buffer).await.unwrap();
+        assert_eq!(&buffer, qr_code.buffer());
+
+        let path = std::env::temp_dir().join(format!("wechat_qr_{}.png", std::process::id()));
+        qr_code.save_to(&path).await.unwrap();
+        assert_eq!(&tokio::fs::read(&path).await.unwrap(), qr_code.buffer());
+        tokio::fs::remove_file(&path).await.unwrap();
     }
 }
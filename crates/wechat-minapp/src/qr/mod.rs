@@ -4,22 +4,69 @@
 //!
 //! - [minapp_code] 生成普通小程序码，适用于需要的码数量较少的业务场景。通过该接口生成的小程序码，永久有效，有数量限制。
 //! - [unlimited_minapp_code] 不限制的小程序码,适用于需要的码数量极多的业务场景。通过该接口生成的小程序码，永久有效，数量暂无限制。
+//! - [`SceneMapper`] scene 短码映射，参数超过 32 个字符时存储完整参数、scene 中只放短 id。
+//! - [`QrCodeCache`] 小程序码缓存，参数相同时直接返回缓存的图片，节省接口配额。
 //!
 //!
 //!
+mod batch;
+mod cache;
+mod image_format;
 pub mod minapp_code;
+#[cfg(any(feature = "axum", feature = "actix"))]
+mod response;
+mod scene_store;
 pub mod unlimited_minapp_code;
 
 use crate::WechatMinapp;
-pub use minapp_code::{MinappEnvVersion, QrCode, QrCodeArgs, Rgb};
+pub use crate::batch::{BatchOptions, BatchResult};
+use wechat_core::{ErrorKind, Result};
+use wechat_core::localized;
+#[cfg(feature = "redis")]
+pub use cache::RedisQrCodeCache;
+pub use cache::{MemoryQrCodeCache, QrCodeCache};
+pub use image_format::ImageFormat;
+/// 重新导出 image crate，保证与 [`QrCode::to_image`] 使用相同的版本
+#[cfg(feature = "image")]
+pub use image;
+pub use crate::new_type::MinappEnvVersion;
+pub use minapp_code::{QrCode, QrCodeArgs, Rgb};
+#[cfg(feature = "redis")]
+pub use scene_store::RedisSceneStore;
+pub use scene_store::{MemorySceneStore, SceneMapper, SceneStore};
 pub use unlimited_minapp_code::UnlimitedQrCodeArgs;
 
+/// 小程序码最小宽度（px）
+pub const MIN_WIDTH: u16 = 280;
+/// 小程序码最大宽度（px）
+pub const MAX_WIDTH: u16 = 1280;
+
+/// 校验小程序码宽度是否在 [`MIN_WIDTH`]–[`MAX_WIDTH`] 之间
+pub(crate) fn check_width(width: Option<u16>) -> Result<()> {
+    match width {
+        Some(width) if !(MIN_WIDTH..=MAX_WIDTH).contains(&width) => {
+            Err(ErrorKind::InvalidParameter(localized!(
+                "小程序码宽度必须在 {}–{} px 之间，当前为 {}",
+                "width must be between {} and {} px, got {}",
+                MIN_WIDTH,
+                MAX_WIDTH,
+                width
+            ))
+            .into())
+        }
+        _ => Ok(()),
+    }
+}
+
 pub struct Qr {
     pub client: WechatMinapp,
 }
 
 impl Qr {
-    pub fn new(client: WechatMinapp) -> Self {
-        Qr { client }
+    pub fn new(client: impl Into<WechatMinapp>) -> Self {
+        Qr {
+            client: client.into(),
+        }
     }
+
 }
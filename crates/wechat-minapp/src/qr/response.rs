@@ -0,0 +1,60 @@
+//! 小程序码作为 web 框架的响应
+//!
+//! 启用 `axum` 或 `actix` feature 后，[`QrCode`] 可以直接作为 handler 的返回值，
+//! 响应头 `Content-Type` 取自 [`QrCode::mime_type`]。
+
+use super::QrCode;
+
+#[cfg(feature = "axum")]
+impl axum_core::response::IntoResponse for QrCode {
+    fn into_response(self) -> axum_core::response::Response {
+        let content_type = self.mime_type().to_string();
+        ([(http::header::CONTENT_TYPE, content_type)], self.buffer).into_response()
+    }
+}
+
+#[cfg(feature = "actix")]
+impl actix_web::Responder for QrCode {
+    type Body = actix_web::body::BoxBody;
+
+    fn respond_to(self, _req: &actix_web::HttpRequest) -> actix_web::HttpResponse<Self::Body> {
+        actix_web::HttpResponse::Ok()
+            .content_type(self.mime_type())
+            .body(self.buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn qr_code() -> QrCode {
+        QrCode {
+            buffer: b"\x89PNG\r\n\x1a\n".to_vec(),
+            content_type: Some("image/jpeg".to_string()),
+        }
+    }
+
+    #[cfg(feature = "axum")]
+    #[test]
+    fn test_axum_response() {
+        use axum_core::response::IntoResponse;
+
+        let response = qr_code().into_response();
+        assert_eq!(response.status(), http::StatusCode::OK);
+        assert_eq!(response.headers()[http::header::CONTENT_TYPE], "image/png");
+    }
+
+    #[cfg(feature = "actix")]
+    #[tokio::test]
+    async fn test_actix_response() {
+        use actix_web::Responder;
+
+        let request = actix_web::test::TestRequest::default().to_http_request();
+        let response = qr_code().respond_to(&request);
+        assert_eq!(response.status(), actix_web::http::StatusCode::OK);
+        assert_eq!(response.headers().get("content-type").unwrap(), "image/png");
+        let body = actix_web::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"\x89PNG\r\n\x1a\n");
+    }
+}
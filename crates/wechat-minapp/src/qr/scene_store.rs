@@ -0,0 +1,237 @@
+//! scene 短码映射
+//!
+//! 不限制的小程序码的 scene 最多只能放 32 个字符，业务参数经常放不下。
+//! [`SceneMapper`] 将完整参数保存到 [`SceneStore`] 中，只把短 id 放入 scene，
+//! 扫码进入页面后再用 scene 反查完整参数。默认提供内存实现 [`MemorySceneStore`]，
+//! 启用 `redis` feature 后可以使用 `RedisSceneStore` 在多实例之间共享。
+
+use crate::new_type::SceneString;
+use async_trait::async_trait;
+use hex::encode;
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, fmt, sync::Arc};
+use tokio::sync::RwLock;
+use wechat_core::{ErrorKind, Result};
+
+/// 短 id 的长度（十六进制字符）
+const SCENE_ID_LEN: usize = 20;
+
+/// 定义 scene 短 id 与完整参数读取存储的行为
+#[async_trait]
+pub trait SceneStore: Send + Sync {
+    /// 读取短 id 对应的完整参数
+    async fn get(&self, id: &str) -> Result<Option<String>>;
+    /// 保存短 id 与完整参数的映射
+    async fn set(&self, id: &str, payload: &str) -> Result<()>;
+}
+
+/// 内存存储方式的 scene 映射，进程重启后失效，只适合单实例或测试使用
+#[derive(Debug, Default, Clone)]
+pub struct MemorySceneStore {
+    entries: Arc<RwLock<HashMap<String, String>>>,
+}
+
+impl MemorySceneStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SceneStore for MemorySceneStore {
+    async fn get(&self, id: &str) -> Result<Option<String>> {
+        Ok(self.entries.read().await.get(id).cloned())
+    }
+
+    async fn set(&self, id: &str, payload: &str) -> Result<()> {
+        self.entries
+            .write()
+            .await
+            .insert(id.to_string(), payload.to_string());
+        Ok(())
+    }
+}
+
+/// redis 存储方式的 scene 映射，需要启用 `redis` feature
+///
+/// 小程序码印刷后会长期使用，默认不设置过期时间。
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use wechat_minapp::qr::{RedisSceneStore, SceneMapper};
+///
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = redis::Client::open("redis://127.0.0.1/")?;
+/// let connection = client.get_multiplexed_async_connection().await?;
+/// let mapper = SceneMapper::new(Arc::new(RedisSceneStore::new(connection)));
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "redis")]
+#[derive(Clone)]
+pub struct RedisSceneStore {
+    connection: redis::aio::MultiplexedConnection,
+    prefix: String,
+    ttl: Option<std::time::Duration>,
+}
+
+#[cfg(feature = "redis")]
+impl fmt::Debug for RedisSceneStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedisSceneStore")
+            .field("prefix", &self.prefix)
+            .field("ttl", &self.ttl)
+            .finish()
+    }
+}
+
+#[cfg(feature = "redis")]
+impl RedisSceneStore {
+    /// 使用默认的 key 前缀 `wechat_minapp:scene:` 创建，映射不过期
+    pub fn new(connection: redis::aio::MultiplexedConnection) -> Self {
+        RedisSceneStore {
+            connection,
+            prefix: "wechat_minapp:scene:".to_string(),
+            ttl: None,
+        }
+    }
+
+    /// key 前缀
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// 映射有效期，适用于限时活动的小程序码
+    pub fn ttl(mut self, ttl: std::time::Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl SceneStore for RedisSceneStore {
+    async fn get(&self, id: &str) -> Result<Option<String>> {
+        let mut connection = self.connection.clone();
+        redis::cmd("GET")
+            .arg(format!("{}{}", self.prefix, id))
+            .query_async(&mut connection)
+            .await
+            .map_err(super::cache::redis_error)
+    }
+
+    async fn set(&self, id: &str, payload: &str) -> Result<()> {
+        let mut command = redis::cmd("SET");
+        command.arg(format!("{}{}", self.prefix, id)).arg(payload);
+        if let Some(ttl) = self.ttl {
+            command.arg("EX").arg(ttl.as_secs().max(1));
+        }
+
+        let mut connection = self.connection.clone();
+        command
+            .query_async::<()>(&mut connection)
+            .await
+            .map_err(super::cache::redis_error)
+    }
+}
+
+/// scene 短码映射服务
+///
+/// 短 id 为完整参数 SHA-256 的前 20 个十六进制字符，相同参数总是得到相同的 scene，
+/// 重复生码不会产生新的记录。
+///
+/// # 示例
+///
+/// ```
+/// use std::sync::Arc;
+/// use wechat_minapp::qr::{MemorySceneStore, SceneMapper, UnlimitedQrCodeArgs};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mapper = SceneMapper::new(Arc::new(MemorySceneStore::new()));
+///
+/// let payload = r#"{"activity_id":"2024-spring-sale","channel":"offline-poster","uid":10086}"#;
+/// let scene = mapper.shorten(payload).await?;
+/// let args = UnlimitedQrCodeArgs::builder()
+///     .page("pages/index/index")
+///     .scene(scene.clone())
+///     .build()?;
+///
+/// // 扫码进入页面后，前端把 scene 传回服务端反查
+/// assert_eq!(mapper.resolve(scene.as_str()).await?.as_deref(), Some(payload));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct SceneMapper {
+    store: Arc<dyn SceneStore>,
+}
+
+impl fmt::Debug for SceneMapper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SceneMapper")
+            .field("store", &"Arc<dyn SceneStore>")
+            .finish()
+    }
+}
+
+impl SceneMapper {
+    pub fn new(store: Arc<dyn SceneStore>) -> Self {
+        SceneMapper { store }
+    }
+
+    /// 保存完整参数，返回可以直接放入 scene 的短 id
+    pub async fn shorten(&self, payload: impl Into<String>) -> Result<SceneString> {
+        let payload = payload.into();
+        let id = encode(Sha256::digest(payload.as_bytes()))[..SCENE_ID_LEN].to_string();
+
+        match self.store.get(&id).await? {
+            Some(existing) if existing == payload => {}
+            Some(_) => {
+                return Err(ErrorKind::InternalServer(format!("scene 短 id 冲突: {}", id)).into());
+            }
+            None => self.store.set(&id, &payload).await?,
+        }
+
+        Ok(SceneString::new(&id)?)
+    }
+
+    /// 根据 scene 反查完整参数，未找到时返回 `None`
+    pub async fn resolve(&self, scene: &str) -> Result<Option<String>> {
+        self.store.get(scene).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shorten_and_resolve() {
+        let store = Arc::new(MemorySceneStore::new());
+        let mapper = SceneMapper::new(store.clone());
+        let payload = "activity_id=2024-spring-sale&channel=offline-poster&uid=10086";
+
+        let scene = mapper.shorten(payload).await.unwrap();
+        assert_eq!(scene.len(), SCENE_ID_LEN);
+        assert_eq!(mapper.shorten(payload).await.unwrap(), scene);
+        assert_eq!(store.entries.read().await.len(), 1);
+
+        assert_eq!(mapper.resolve(scene.as_str()).await.unwrap().as_deref(), Some(payload));
+        assert_eq!(mapper.resolve("unknown").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_conflict() {
+        let store = Arc::new(MemorySceneStore::new());
+        let mapper = SceneMapper::new(store.clone());
+
+        let scene = mapper.shorten("a=1").await.unwrap();
+        store.set(scene.as_str(), "b=2").await.unwrap();
+        assert!(matches!(
+            mapper.shorten("a=1").await.unwrap_err().kind(),
+            ErrorKind::InternalServer(_)
+        ));
+    }
+}
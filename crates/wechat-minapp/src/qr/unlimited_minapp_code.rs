@@ -47,11 +47,13 @@
 //!
 //! # 参数说明
 //!
-//! - `path`: 小程序页面路径，必填，最大长度 1024 字符
+//! - `page`: 小程序页面路径，不能携带参数，不填时默认跳主页
+//! - `scene`: 页面参数，必填，最大 32 个可见字符
 //! - `width`: 小程序码宽度，单位 px，最小 280px，最大 1280px
 //! - `auto_color`: 是否自动配置线条颜色
 //! - `line_color`: 自定义线条颜色，RGB 格式
 //! - `is_hyaline`: 是否透明背景
+//! - `check_path`: 是否检查 page 已发布，默认为 true；为 false 时 page 有数量上限（60000 个）
 //! - `env_version`: 环境版本，默认为正式版
 //!
 //! # 示例
@@ -60,7 +62,7 @@
 //!
 //! ```no_run
 //! use wechat_minapp::WechatMinapp;
-//! use wechat_minapp::qr::{UnlimitedQrCodeArgs,Qr, MinappEnvVersion};
+//! use wechat_minapp::qr::{UnlimitedQrCodeArgs,Qr, MinappEnvVersion, Rgb};
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -126,27 +128,30 @@
 //!
 //! 建议在生产环境中妥善处理这些错误。
 
-use super::{MinappEnvVersion, Qr, QrCode, Rgb};
-use wechat_core::utils::{RequestBuilder, ResponseExt};
-use wechat_core::{Result, Error};
-use crate::constants;
+use super::{check_width, MinappEnvVersion, Qr, QrCode, Rgb};
+use wechat_core::utils::AsyncWrite;
+use wechat_core::localized;
+use wechat_core::{Result, Error, ErrorKind};
+use crate::constants::Endpoint;
 use crate::new_type::{NonQueryPagePath, SceneString};
 use http::header::CONTENT_TYPE;
+use http::Request;
 use serde::{Deserialize, Serialize};
 use tracing::debug;
 
 /// 无限制小程序码生成参数
 ///
 /// 用于配置无限制小程序码的生成选项，通过 [`UnlimitedQrCodeArgs::builder()`] 方法创建。
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct UnlimitedQrCodeArgs {
     /// 默认是主页，页面 page，例如 pages/index/index，根路径前不要填加 /，不能携带参数（参数请放在scene字段里），如果不填写这个字段，默认跳主页面。scancode_time为系统保留参数，不允许配置
-    page: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    page: Option<String>,
     /// 最大32个可见字符，只支持数字，大小写英文以及部分特殊字符：!#$&'()*+,/:;=?@-._~，其它字符请自行编码为合法字符（因不支持%，中文无法使用 urlencode 处理，请使用其他编码方式）
     scene: String,
     /// 默认430，二维码的宽度，单位 px，最小 280px，最大 1280px
     #[serde(skip_serializing_if = "Option::is_none")]
-    width: Option<i16>,
+    width: Option<u16>,
     /// 默认是true，检查page 是否存在，为 true 时 page 必须是已经发布的小程序存在的页面（否则报错）；为 false 时允许小程序未发布或者 page 不存在， 但page 有数量上限（60000个）请勿滥用。
     #[serde(skip_serializing_if = "Option::is_none")]
     check_path: Option<bool>,
@@ -175,6 +180,7 @@ pub struct UnlimitedQrCodeArgs {
 ///
 /// let args = UnlimitedQrCodeArgs::builder()
 ///     .page("pages/index/index")
+///     .scene("a=1")
 ///     .width(300)
 ///     .line_color(Rgb::new(255, 0, 0))
 ///     .with_is_hyaline()
@@ -186,7 +192,7 @@ pub struct UnlimitedQrCodeArgs {
 pub struct UnlimitedQrCodeArgsBuilder {
     page: Option<String>,
     scene: Option<String>,
-    width: Option<i16>,
+    width: Option<u16>,
     check_path: Option<bool>,
     auto_color: Option<bool>,
     line_color: Option<Rgb>,
@@ -199,20 +205,25 @@ impl UnlimitedQrCodeArgs {
         UnlimitedQrCodeArgsBuilder::new()
     }
 
-    pub fn page(&self) -> String {
+    /// 页面路径，没有设置时为 `None`，扫码后进入主页
+    pub fn page(&self) -> Option<String> {
         self.page.clone()
     }
 
-    pub fn width(&self) -> Option<i16> {
+    pub fn width(&self) -> Option<u16> {
         self.width
     }
 
+    pub fn check_path(&self) -> Option<bool> {
+        self.check_path
+    }
+
     pub fn auto_color(&self) -> Option<bool> {
         self.auto_color
     }
 
     pub fn line_color(&self) -> Option<Rgb> {
-        self.line_color.clone()
+        self.line_color
     }
 
     pub fn is_hyaline(&self) -> Option<bool> {
@@ -220,7 +231,7 @@ impl UnlimitedQrCodeArgs {
     }
 
     pub fn env_version(&self) -> Option<MinappEnvVersion> {
-        self.env_version.clone()
+        self.env_version
     }
 }
 
@@ -244,6 +255,7 @@ impl UnlimitedQrCodeArgsBuilder {
         }
     }
 
+    /// 扫码后进入的页面，不设置时默认进入主页
     pub fn page(mut self, page: impl Into<String>) -> Self {
         self.page = Some(page.into());
         self
@@ -254,11 +266,21 @@ impl UnlimitedQrCodeArgsBuilder {
         self
     }
 
-    pub fn width(mut self, width: i16) -> Self {
+    /// 小程序码宽度，单位 px，取值范围 280–1280，超出范围时 `build()` 返回错误
+    pub fn width(mut self, width: u16) -> Self {
         self.width = Some(width);
         self
     }
 
+    /// 是否检查 page 是否存在，默认为 true
+    ///
+    /// 为 true 时 page 必须是已经发布的小程序中存在的页面；为未发布的页面或开发中的新页面生码时需要设置为 false。
+    /// 设置为 false 时 page 有数量上限（60000 个），请勿滥用。
+    pub fn check_path(mut self, check_path: bool) -> Self {
+        self.check_path = Some(check_path);
+        self
+    }
+
     pub fn with_auto_color(mut self) -> Self {
         self.auto_color = Some(true);
         self
@@ -280,30 +302,32 @@ impl UnlimitedQrCodeArgsBuilder {
     }
 
     pub fn build(self) -> Result<UnlimitedQrCodeArgs> {
-        let page = self.page.map_or_else(
-            || {
-                Err(Error::InvalidParameter(
-                    "小程序页面路径不能为空".to_string(),
-                ))
-            },
-            |v| {
-                let path = NonQueryPagePath::try_from(v)?;
-                Ok(path.to_string())
-            },
-        )?;
+        let page = self
+            .page
+            .map(|v| NonQueryPagePath::try_from(v).map(|path| path.to_string()))
+            .transpose()?;
 
         let scene = self.scene.map_or_else(
-            || Err(Error::InvalidParameter("scene 不能为空".to_string())),
+            || {
+                Err(Error::from(ErrorKind::InvalidParameter(localized!(
+                    "scene 不能为空",
+                    "scene is required"
+                ))))
+            },
             |v| {
                 let valid_scene = SceneString::try_from(v)?;
                 Ok(valid_scene.to_string())
             },
         )?;
 
+        check_width(self.width)?;
+
         if self.auto_color.is_some() && self.line_color.is_some() {
-            return Err(Error::InvalidParameter(
-                "auto_color 为 true 时，line_color 不能设置".to_string(),
-            ));
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "auto_color 为 true 时，line_color 不能设置",
+                "line_color must not be set when auto_color is true"
+            ))
+            .into());
         }
 
         Ok(UnlimitedQrCodeArgs {
@@ -376,6 +400,93 @@ impl Qr {
     pub async fn unlimited_qr_code(&self, args: UnlimitedQrCodeArgs) -> Result<QrCode> {
         debug!("get unlimited qr code args {:?}", &args);
 
+        let request = self.unlimited_qr_code_request(args).await?;
+
+        let client = &self.client.core.client;
+
+        let response = client.execute(request).await?;
+
+        debug!(
+            "get unlimited qr code response status: {}, headers: {:?}",
+            response.status(),
+            response.headers()
+        );
+
+        QrCode::from_response(response)
+    }
+
+    /// 使用指定的 access_token 获取不限制的小程序码，不再从 `TokenStorage` 取值
+    ///
+    /// ```no_run
+    /// use wechat_minapp::WechatMinapp;
+    /// use wechat_minapp::qr::{Qr, UnlimitedQrCodeArgs};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let qr = Qr::new(WechatMinapp::new("app_id", "secret"));
+    ///     let args = UnlimitedQrCodeArgs::builder()
+    ///         .page("pages/index/index")
+    ///         .scene("a=1")
+    ///         .build()?;
+    ///
+    ///     // access_token 由其他系统下发
+    ///     let qr_code = qr.unlimited_qr_code_with_token(args, "ACCESS_TOKEN").await?;
+    ///     println!("生成的小程序码大小: {} bytes", qr_code.buffer().len());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn unlimited_qr_code_with_token(
+        &self,
+        args: UnlimitedQrCodeArgs,
+        access_token: impl Into<String>,
+    ) -> Result<QrCode> {
+        Qr::new(self.client.with_token(access_token)).unlimited_qr_code(args).await
+    }
+
+    /// 获取不限制的小程序码并直接写入 `writer`（例如文件），返回写入的字节数
+    ///
+    /// 微信返回错误时不会写入任何数据。
+    ///
+    /// ```no_run
+    /// use wechat_minapp::WechatMinapp;
+    /// use wechat_minapp::qr::{Qr, UnlimitedQrCodeArgs};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let qr = Qr::new(WechatMinapp::new("app_id", "secret"));
+    ///     let args = UnlimitedQrCodeArgs::builder()
+    ///         .page("pages/index/index")
+    ///         .scene("a=1")
+    ///         .build()?;
+    ///
+    ///     let mut file = tokio::fs::File::create("qrcode.png").await?;
+    ///     let size = qr.unlimited_qr_code_to_writer(args, &mut file).await?;
+    ///     println!("写入 {} bytes", size);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn unlimited_qr_code_to_writer(
+        &self,
+        args: UnlimitedQrCodeArgs,
+        writer: &mut (dyn AsyncWrite + Send + Unpin),
+    ) -> Result<u64> {
+        debug!("get unlimited qr code to writer args {:?}", &args);
+
+        let request = self.unlimited_qr_code_request(args).await?;
+
+        let response = self
+            .client
+            .core
+            .client
+            .execute_streaming(request, writer)
+            .await?;
+
+        Ok(response.into_body())
+    }
+
+    async fn unlimited_qr_code_request(&self, args: UnlimitedQrCodeArgs) -> Result<Request<Vec<u8>>> {
         let query = serde_json::json!({
             "access_token":self.client.token().await?
         });
@@ -385,34 +496,55 @@ impl Qr {
             CONTENT_TYPE.to_string():"application/json"
         });
 
-        let body = serde_json::to_value(UnlimitedQrCodeArgs {
-            page: args.page,
-            scene: args.scene,
-            check_path: args.check_path,
-            width: args.width,
-            auto_color: args.auto_color,
-            line_color: args.line_color,
-            is_hyaline: args.is_hyaline,
-            env_version: args.env_version,
-        })?;
-
-        let request = RequestBuilder::new(constants::UNLIMITIED_QR_CODE_ENDPOINT)
+        let body = serde_json::to_value(args)?;
+
+        Endpoint::UnlimitedQrCode.request()
             .headers(headers)
             .query(query)
             .body(body)
-            .build()?;
+            .build()
+    }
+}
 
-        let client = &self.client.core.client;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let response = client.execute(request).await?;
+    #[test]
+    fn test_check_path() {
+        let args = UnlimitedQrCodeArgs::builder()
+            .page("pages/new/index")
+            .scene("id=1")
+            .check_path(false)
+            .env_version(MinappEnvVersion::Develop)
+            .build()
+            .unwrap();
 
-        debug!("get unlimited qr code response: {:#?}", response);
-        let buffer = response.to_raw()?;
-        if buffer.len() > 2048 {
-            return Ok(QrCode { buffer });
-        }
-        Err(Error::InternalServer(
-            String::from_utf8_lossy(&buffer).to_string(),
-        ))
+        assert_eq!(args.check_path(), Some(false));
+        let body = serde_json::to_value(&args).unwrap();
+        assert_eq!(body["check_path"], false);
+
+        let args = UnlimitedQrCodeArgs::builder()
+            .page("pages/index/index")
+            .scene("id=1")
+            .build()
+            .unwrap();
+        assert!(serde_json::to_value(&args).unwrap().get("check_path").is_none());
+    }
+
+    #[test]
+    fn test_optional_page() {
+        let args = UnlimitedQrCodeArgs::builder().scene("id=1").build().unwrap();
+        assert_eq!(args.page(), None);
+        let body = serde_json::to_value(&args).unwrap();
+        assert!(body.get("page").is_none());
+        assert_eq!(body["scene"], "id=1");
+
+        // 设置 page 时仍然校验格式
+        let result = UnlimitedQrCodeArgs::builder()
+            .page("pages/index/index?id=1")
+            .scene("id=1")
+            .build();
+        assert!(matches!(result.unwrap_err().kind(), ErrorKind::InvalidParameter(_)));
     }
 }
@@ -0,0 +1,274 @@
+//! 小程序搜索
+//!
+//! 内容型小程序可以通过 [`Search::submit_pages`] 把页面提交给微信搜索收录，
+//! 并使用 [`Search::site_search`]、[`Search::image_search`] 在小程序内搜索已收录的页面。
+//!
+//! ## 示例
+//!
+//! ```no_run
+//! use wechat_minapp::WechatMinapp;
+//! use wechat_minapp::search::{SitePage, SiteSearchArgs};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = WechatMinapp::new("app_id", "secret");
+//!     let search = client.search();
+//!
+//!     search
+//!         .submit_pages(vec![SitePage::new("pages/article/index", "id=1")])
+//!         .await?;
+//!
+//!     let mut args = SiteSearchArgs::new("咖啡");
+//!     loop {
+//!         let result = search.site_search(args.clone()).await?;
+//!         for item in &result.items {
+//!             println!("{} {}", item.title, item.path);
+//!         }
+//!         match result.next_args(&args) {
+//!             Some(next) => args = next,
+//!             None => break,
+//!         }
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::constants::Endpoint;
+use crate::WechatMinapp;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use wechat_core::response::ExtraFields;
+use wechat_core::utils::{Multipart, Part, ResponseExt};
+use wechat_core::{localized, ErrorKind, Result};
+
+pub struct Search {
+    pub client: WechatMinapp,
+}
+
+impl Search {
+    pub fn new(client: impl Into<WechatMinapp>) -> Self {
+        Search {
+            client: client.into(),
+        }
+    }
+
+
+    wechat_core::endpoint! {
+        /// 小程序内部搜索，按关键词搜索本小程序已收录的页面
+        ///
+        /// [官方文档](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/search/siteSearch.html)
+        pub POST idempotent site_search(args: SiteSearchArgs) -> SearchResult =
+            Endpoint::SiteSearch.url();
+
+        POST idempotent submit_page_list(args: SubmitPagesArgs) -> () =
+            Endpoint::SubmitPages.url();
+    }
+
+    /// 提交小程序页面，供微信搜索收录
+    ///
+    /// `pages` 不能为空。
+    ///
+    /// [官方文档](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/search/submitPages.html)
+    pub async fn submit_pages(&self, pages: Vec<SitePage>) -> Result<()> {
+        if pages.is_empty() {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "提交的页面不能为空",
+                "pages must not be empty"
+            ))
+            .into());
+        }
+
+        self.submit_page_list(SubmitPagesArgs { pages }).await
+    }
+
+    /// 本地图片搜索，使用图片搜索本小程序已收录的图片
+    ///
+    /// `image` 为图片内容，`file_name` 的扩展名用于让微信识别图片格式，如 `a.jpg`。
+    ///
+    /// [官方文档](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/search/imageSearch.html)
+    pub async fn image_search(
+        &self,
+        image: impl Into<Vec<u8>>,
+        file_name: impl Into<String>,
+    ) -> Result<SearchResult> {
+        let image = image.into();
+        if image.is_empty() {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "图片内容不能为空",
+                "image must not be empty"
+            ))
+            .into());
+        }
+        debug!("image_search {} bytes", image.len());
+
+        let query = serde_json::json!({
+            "access_token": self.client.token().await?
+        });
+        let form = Multipart::new().part(Part::bytes("img", image).file_name(file_name));
+        let request = Endpoint::ImageSearch
+            .request()
+            .query(query)
+            .build_multipart(form)?;
+
+        let response = self.client.core.client.execute_multipart(request).await?;
+        response.to_json::<SearchResult>()
+    }
+}
+
+/// 站内搜索参数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SiteSearchArgs {
+    keyword: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    next_page_info: String,
+}
+
+impl SiteSearchArgs {
+    /// 搜索 `keyword` 的第一页
+    pub fn new(keyword: impl Into<String>) -> Self {
+        SiteSearchArgs {
+            keyword: keyword.into(),
+            next_page_info: String::new(),
+        }
+    }
+
+    /// 从上一页返回的 `next_page_info` 继续搜索
+    pub fn next_page_info(mut self, next_page_info: impl Into<String>) -> Self {
+        self.next_page_info = next_page_info.into();
+        self
+    }
+
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+}
+
+/// 搜索结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchResult {
+    #[serde(default)]
+    pub items: Vec<SearchItem>,
+    /// 是否还有下一页，1 表示有
+    #[serde(default)]
+    pub has_next_page: i32,
+    /// 下一页的分页信息
+    #[serde(default)]
+    pub next_page_info: String,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+impl SearchResult {
+    /// 是否还有下一页
+    pub fn has_more(&self) -> bool {
+        self.has_next_page == 1 && !self.next_page_info.is_empty()
+    }
+
+    /// 查询下一页的参数，没有下一页时返回 `None`
+    pub fn next_args(&self, args: &SiteSearchArgs) -> Option<SiteSearchArgs> {
+        self.has_more()
+            .then(|| args.clone().next_page_info(self.next_page_info.clone()))
+    }
+}
+
+/// 搜索到的页面
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchItem {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    /// 封面图片地址
+    #[serde(default)]
+    pub image: String,
+    /// 小程序页面路径，含参数
+    #[serde(default)]
+    pub path: String,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+/// 提交收录的页面
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SitePage {
+    /// 页面路径，如 `pages/article/index`
+    pub path: String,
+    /// 页面参数，如 `id=1`，没有参数时为空字符串
+    pub query: String,
+}
+
+impl SitePage {
+    pub fn new(path: impl Into<String>, query: impl Into<String>) -> Self {
+        SitePage {
+            path: path.into(),
+            query: query.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SubmitPagesArgs {
+    pages: Vec<SitePage>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use std::sync::Arc;
+    use wechat_core::client::DryRunHttpClient;
+
+    fn search(response: &str) -> (Search, Arc<DryRunHttpClient>) {
+        let (client, http_client) = fixtures::client(response);
+        (Search::new(client), http_client)
+    }
+
+    #[tokio::test]
+    async fn test_site_search() {
+        let (search, http_client) = search(
+            r#"{"errcode":0,"errmsg":"","items":[{"title":"咖啡","description":"手冲",
+            "image":"https://a/b.jpg","path":"pages/article/index?id=1"}],
+            "has_next_page":1,"next_page_info":"cursor"}"#,
+        );
+        let args = SiteSearchArgs::new("咖啡");
+        let result = search.site_search(args.clone()).await.unwrap();
+
+        assert_eq!(result.items[0].path, "pages/article/index?id=1");
+        let next = result.next_args(&args).unwrap();
+        assert_eq!(serde_json::to_value(&next).unwrap()["next_page_info"], "cursor");
+
+        let requests = http_client.requests();
+        assert_eq!(requests[0].body_text(), r#"{"keyword":"咖啡"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_submit_pages() {
+        let (search, http_client) = search(r#"{"errcode":0,"errmsg":"ok"}"#);
+        search
+            .submit_pages(vec![SitePage::new("pages/article/index", "id=1")])
+            .await
+            .unwrap();
+        assert_eq!(
+            http_client.requests()[0].body_text(),
+            r#"{"pages":[{"path":"pages/article/index","query":"id=1"}]}"#
+        );
+
+        let error = search.submit_pages(Vec::new()).await.unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidParameter(_)));
+    }
+
+    #[tokio::test]
+    async fn test_image_search() {
+        let (search, http_client) = search(r#"{"errcode":0,"errmsg":"","items":[]}"#);
+        let result = search.image_search(vec![0xff, 0xd8], "a.jpg").await.unwrap();
+        assert!(result.items.is_empty());
+        assert!(!result.has_more());
+
+        let requests = http_client.requests();
+        assert!(requests[0].url.starts_with("https://api.weixin.qq.com/wxa/imagesearch?"));
+        assert!(requests[0].body_text().contains("filename=\"a.jpg\""));
+    }
+}
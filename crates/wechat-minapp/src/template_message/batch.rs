@@ -0,0 +1,182 @@
+//! 订阅消息批量发送
+//!
+//! 运营推送时要对大量 openid 逐个调用发送接口，[`TemplateMessage::send_batch`] 以受控的并发数
+//! 与请求频率发送同一条订阅消息，并把未订阅（43101）的用户与其他失败分开汇总。
+
+use super::send_message::{SendMessageArgs, SendMessageArgsBuilder, SendMessageResponse};
+use super::TemplateMessage;
+use crate::batch::{self, BatchOptions, BatchResult};
+use serde_json::Value;
+use tracing::debug;
+use wechat_core::{Error, Result};
+
+/// 用户拒绝接受消息：未订阅该模板或订阅次数已用完
+const USER_REFUSED: i32 = 43101;
+
+/// 订阅消息批量发送的汇总报告，均按输入顺序排列
+#[derive(Debug, Default)]
+pub struct SendBatchReport {
+    /// 发送成功的 openid 与 msgid
+    pub sent: Vec<(String, Option<String>)>,
+    /// 未订阅该模板或订阅次数已用完（43101）的 openid
+    pub refused: Vec<String>,
+    /// 其余发送失败的 openid 与错误
+    pub failed: Vec<(String, Error)>,
+}
+
+impl SendBatchReport {
+    fn new(openids: Vec<String>, result: BatchResult<SendMessageResponse>) -> Self {
+        let mut report = SendBatchReport::default();
+        for (index, response) in result.succeeded {
+            report.sent.push((openids[index].clone(), response.msgid));
+        }
+        for (index, error) in result.failed {
+            let openid = openids[index].clone();
+            if error.code() == Some(USER_REFUSED) {
+                report.refused.push(openid);
+            } else {
+                report.failed.push((openid, error));
+            }
+        }
+        report
+    }
+
+    /// 是否全部发送成功
+    pub fn is_all_sent(&self) -> bool {
+        self.refused.is_empty() && self.failed.is_empty()
+    }
+
+    /// 总数
+    pub fn len(&self) -> usize {
+        self.sent.len() + self.refused.len() + self.failed.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl TemplateMessage {
+    /// 向多个用户发送同一条订阅消息
+    ///
+    /// `template` 设置模板 ID、跳转页面等，不需要设置 `touser`；参数不合法时直接返回错误，
+    /// 不会发起请求。直接传入并发数时不限速、不重试，需要时使用 [`BatchOptions`] 配置。
+    ///
+    /// ```no_run
+    /// use serde_json::json;
+    /// use wechat_minapp::WechatMinapp;
+    /// use wechat_minapp::batch::BatchOptions;
+    /// use wechat_minapp::template_message::{SendMessageArgs, TemplateMessage};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let message = TemplateMessage::new(WechatMinapp::new("app_id", "secret"));
+    ///     let openids = vec!["openid1", "openid2", "openid3"];
+    ///     let template = SendMessageArgs::builder()
+    ///         .template_id("template_id")
+    ///         .page("pages/activity/index");
+    ///     let data = json!({"thing1": {"value": "活动即将开始"}});
+    ///
+    ///     // 最多 8 个并发，每秒最多 50 个请求，系统繁忙时最多重试 2 次
+    ///     let options = BatchOptions::new(8).rate_limit(50).retries(2);
+    ///     let report = message.send_batch(openids, template, data, options).await?;
+    ///     println!("成功 {}，未订阅 {}", report.sent.len(), report.refused.len());
+    ///     for (openid, error) in &report.failed {
+    ///         eprintln!("{} 发送失败: {}", openid, error);
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn send_batch<I>(
+        &self,
+        openids: I,
+        template: SendMessageArgsBuilder,
+        data: impl Into<Value>,
+        options: impl Into<BatchOptions>,
+    ) -> Result<SendBatchReport>
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        // 提前校验模板参数，避免每个 openid 都失败一次
+        let args = template.touser("").data(data).build()?;
+        let openids: Vec<String> = openids.into_iter().map(Into::into).collect();
+
+        let result = batch::run(openids.iter().cloned(), options.into(), |touser| {
+            let args = SendMessageArgs {
+                touser,
+                ..args.clone()
+            };
+            async move { self.send_message(args).await }
+        })
+        .await;
+
+        let report = SendBatchReport::new(openids, result);
+        debug!(
+            "batch send_message: {} sent, {} refused, {} failed",
+            report.sent.len(),
+            report.refused.len(),
+            report.failed.len()
+        );
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use async_trait::async_trait;
+    use http::{Request, Response};
+    use serde_json::json;
+    use std::sync::Arc;
+    use wechat_core::client::HttpClient;
+    use wechat_core::ErrorKind;
+
+    /// 按 touser 返回不同结果
+    struct TouserClient;
+
+    #[async_trait]
+    impl HttpClient for TouserClient {
+        async fn execute(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+            let args: Value = serde_json::from_slice(request.body()).unwrap();
+            let body = match args["touser"].as_str().unwrap() {
+                "refused" => r#"{"errcode":43101,"errmsg":"user refuse to accept the msg"}"#,
+                "invalid" => r#"{"errcode":40003,"errmsg":"invalid openid"}"#,
+                _ => r#"{"errcode":0,"errmsg":"ok","msgid":"1"}"#,
+            };
+            Ok(Response::new(body.as_bytes().to_vec()))
+        }
+    }
+
+    fn message() -> TemplateMessage {
+        TemplateMessage::new(fixtures::client_with(Arc::new(TouserClient)))
+    }
+
+    #[tokio::test]
+    async fn test_send_batch() {
+        let openids = ["a", "refused", "b", "invalid"];
+        let template = SendMessageArgs::builder().template_id("tmpl");
+        let data = json!({"thing1": {"value": "hello"}});
+        let report = message().send_batch(openids, template, data, 2).await.unwrap();
+
+        assert_eq!(report.len(), 4);
+        assert!(!report.is_all_sent());
+        let sent: Vec<&str> = report.sent.iter().map(|(openid, _)| openid.as_str()).collect();
+        assert_eq!(sent, ["a", "b"]);
+        assert_eq!(report.sent[0].1.as_deref(), Some("1"));
+        assert_eq!(report.refused, ["refused"]);
+        assert_eq!(report.failed[0].0, "invalid");
+        assert_eq!(report.failed[0].1.code(), Some(40003));
+    }
+
+    #[tokio::test]
+    async fn test_send_batch_invalid_template() {
+        let data = json!({"thing1": {"value": "hello"}});
+        let result = message()
+            .send_batch(["a"], SendMessageArgs::builder(), data, 1)
+            .await;
+        assert!(matches!(result.unwrap_err().kind(), ErrorKind::InvalidParameter(_)));
+    }
+}
@@ -2,10 +2,13 @@
 //!
 //! ## 功能
 //! - [`send_message`] 发送模板消息
+//! - [`TemplateMessage::send_batch`] 向多个用户批量发送订阅消息
 //!
+mod batch;
 pub mod send_message;
 
 use crate::WechatMinapp;
+pub use batch::SendBatchReport;
 pub use send_message::SendMessageArgs;
 
 pub struct TemplateMessage {
@@ -13,7 +16,10 @@ pub struct TemplateMessage {
 }
 
 impl TemplateMessage {
-    pub fn new(client: WechatMinapp) -> Self {
-        TemplateMessage { client }
+    pub fn new(client: impl Into<WechatMinapp>) -> Self {
+        TemplateMessage {
+            client: client.into(),
+        }
     }
+
 }
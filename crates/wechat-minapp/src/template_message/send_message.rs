@@ -42,9 +42,11 @@
 //! ```
 
 use super::TemplateMessage;
-use wechat_core::utils::{RequestBuilder, ResponseExt};
-use wechat_core::{Result, Error};
-use crate::constants;
+use wechat_core::utils::ResponseExt;
+use wechat_core::localized;
+use wechat_core::response::ExtraFields;
+use wechat_core::{Result, ErrorKind};
+use crate::constants::Endpoint;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tracing::debug;
@@ -52,7 +54,7 @@ use tracing::debug;
 /// 订阅消息
 ///
 /// 用于发送一次性订阅消息的结构
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SendMessageArgs {
     pub touser: String,                    // 接收者openid
     pub template_id: String,               // 模板ID
@@ -63,15 +65,18 @@ pub struct SendMessageArgs {
 }
 
 /// 订阅消息发送响应
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SendMessageResponse {
     pub msgid: Option<String>,  // 消息ID
     pub errcode: Option<i32>,   // 错误码
     pub errmsg: Option<String>, // 错误信息
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    pub extra: ExtraFields,
 }
 
 /// 订阅消息参数构建器
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct SendMessageArgsBuilder {
     touser: Option<String>,
     template_id: Option<String>,
@@ -167,15 +172,21 @@ impl SendMessageArgsBuilder {
     pub fn build(self) -> Result<SendMessageArgs> {
         let touser = self
             .touser
-            .ok_or_else(|| Error::InvalidParameter("接收者openid不能为空".to_string()))?;
+            .ok_or_else(|| {
+                ErrorKind::InvalidParameter(localized!("接收者openid不能为空", "touser is required"))
+            })?;
 
         let template_id = self
             .template_id
-            .ok_or_else(|| Error::InvalidParameter("模板ID不能为空".to_string()))?;
+            .ok_or_else(|| {
+                ErrorKind::InvalidParameter(localized!("模板ID不能为空", "template_id is required"))
+            })?;
 
         let data = self
             .data
-            .ok_or_else(|| Error::InvalidParameter("模板数据不能为空".to_string()))?;
+            .ok_or_else(|| {
+                ErrorKind::InvalidParameter(localized!("模板数据不能为空", "data is required"))
+            })?;
 
         // 验证数据格式
         Self::validate_data(&data)?;
@@ -195,40 +206,50 @@ impl SendMessageArgsBuilder {
         if let Value::Object(map) = data {
             for (key, value) in map {
                 if key.chars().count() > 20 {
-                    return Err(Error::InvalidParameter(format!(
+                    return Err(ErrorKind::InvalidParameter(localized!(
                         "字段名'{}'长度不能超过20个字符",
+                        "field name '{}' must not exceed 20 characters",
                         key
-                    )));
+                    ))
+                    .into());
                 }
 
                 if let Value::Object(item) = value {
                     if let Some(val) = item.get("value") {
                         if let Value::String(s) = val {
                             if s.chars().count() > 50 {
-                                return Err(Error::InvalidParameter(format!(
+                                return Err(ErrorKind::InvalidParameter(localized!(
                                     "字段'{}'的值长度不能超过50个字符",
+                                    "value of field '{}' must not exceed 50 characters",
                                     key
-                                )));
+                                ))
+                                .into());
                             }
                         }
                     } else {
-                        return Err(Error::InvalidParameter(format!(
+                        return Err(ErrorKind::InvalidParameter(localized!(
                             "字段'{}'缺少value属性",
+                            "field '{}' is missing the value property",
                             key
-                        )));
+                        ))
+                        .into());
                     }
                 } else {
-                    return Err(Error::InvalidParameter(format!(
+                    return Err(ErrorKind::InvalidParameter(localized!(
                         "字段'{}'格式不正确，应为{{value: string}}",
+                        "field '{}' must be in the form {{value: string}}",
                         key
-                    )));
+                    ))
+                    .into());
                 }
             }
             Ok(())
         } else {
-            Err(Error::InvalidParameter(
-                "模板数据必须是对象类型".to_string(),
+            Err(ErrorKind::InvalidParameter(localized!(
+                "模板数据必须是对象类型",
+                "data must be a JSON object"
             ))
+            .into())
         }
     }
 }
@@ -282,7 +303,7 @@ impl TemplateMessage {
 
         let body = serde_json::to_value(args)?;
 
-        let request = RequestBuilder::new(constants::TEMPLATE_MESSAGE_SEND_END_POINT)
+        let request = Endpoint::TemplateMessageSend.request()
             .query(query)
             .body(body)
             .build()?;
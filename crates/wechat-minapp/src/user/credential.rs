@@ -1,20 +1,50 @@
-use super::user_info::{UserBuilder, UserInfo};
+#[cfg(feature = "crypto")]
+use super::user_info::{BorrowedStr, Contact, ContactBuilder, UserBuilder, UserInfo};
 use super::User;
-use crate::constants;
-use http::Method;
+use crate::constants::Endpoint;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+#[cfg(feature = "crypto")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "crypto")]
 use serde_json::from_slice;
 use tracing::{debug, instrument};
-use wechat_core::utils::{aes_decrypt, hmac_sha256, RequestBuilder, ResponseExt};
+#[cfg(feature = "crypto")]
+use wechat_core::utils::aes_decrypt;
+use wechat_core::utils::{
+    constant_time_eq, hmac_sha256, sha1_hex, ResponseExt, UnixTimestamp, REDACTED,
+};
+#[cfg(feature = "crypto")]
+use wechat_core::ErrorKind;
+use wechat_core::response::ExtraFields;
 use wechat_core::Result;
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct Credential {
     #[serde(rename = "openid")]
     open_id: String,
     session_key: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// 兼容旧版本保存的 `union_id` 字段
+    #[serde(rename = "unionid", alias = "union_id", skip_serializing_if = "Option::is_none")]
     union_id: Option<String>,
+    /// 用于校验解密数据 watermark 的 appid，不参与序列化
+    #[serde(skip)]
+    app_id: Option<String>,
+    /// 获取 session_key 的时间（Unix 时间戳，秒），随会话一起保存，用于会话过期策略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    issued_at: Option<u64>,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    extra: ExtraFields,
+}
+
+/// 当前 Unix 时间戳（秒）
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
 }
 
 impl Credential {
@@ -30,7 +60,95 @@ impl Credential {
         self.union_id.as_deref()
     }
 
-    /// 解密用户数据，使用的是 AES-128-CBC 算法，数据采用PKCS#7填充。
+    /// 微信新增、SDK 尚未声明的字段
+    pub fn extra(&self) -> &ExtraFields {
+        &self.extra
+    }
+
+    /// 获取 session_key 的时间（Unix 时间戳，秒）
+    ///
+    /// [`User::login`] 和 [`User::reset_session_key`] 返回的 Credential 带有该时间，
+    /// 手动反序列化且未保存该字段时为 `None`。
+    pub fn issued_at(&self) -> Option<u64> {
+        self.issued_at
+    }
+
+    /// 获取 session_key 的时间
+    pub fn issued_time(&self) -> Option<DateTime<Utc>> {
+        self.issued_at
+            .map(|issued_at| UnixTimestamp::new(issued_at as i64).to_datetime())
+    }
+
+    /// 距离获取 session_key 已经过去的时间，获取时间未知时为 `None`
+    pub fn age(&self) -> Option<Duration> {
+        self.issued_at
+            .map(|issued_at| Duration::from_secs(unix_now().saturating_sub(issued_at)))
+    }
+
+    /// 记录获取 session_key 的时间为当前时间
+    pub(crate) fn issued_now(mut self) -> Self {
+        self.issued_at = Some(unix_now());
+        self
+    }
+
+    /// 指定解密时校验 watermark 使用的 appid
+    ///
+    /// [`User::login`] 返回的 Credential 已经带有当前小程序的 appid，
+    /// 从会话存储中反序列化恢复的 Credential 需要重新指定，否则不做校验。
+    pub fn with_app_id(mut self, app_id: impl Into<String>) -> Self {
+        let app_id = app_id.into();
+        self.app_id = (!app_id.is_empty()).then_some(app_id);
+        self
+    }
+
+    /// 解密时不校验 watermark.appid
+    pub fn skip_watermark_check(mut self) -> Self {
+        self.app_id = None;
+        self
+    }
+
+    /// 解密数据的 watermark.appid 必须与当前小程序一致，防止数据串号
+    #[cfg(feature = "crypto")]
+    fn check_watermark(&self, app_id: &str) -> Result<()> {
+        match &self.app_id {
+            Some(expected) if expected != app_id => Err(ErrorKind::WatermarkMismatch {
+                expected: expected.clone(),
+                actual: app_id.to_string(),
+            }
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// 校验 `wx.getUserInfo` 返回的明文数据是否被篡改
+    ///
+    /// 签名为 `sha1(rawData + session_key)`，与前端一同返回的 `signature` 比对，一致时返回 `true`。
+    /// https://developers.weixin.qq.com/miniprogram/dev/framework/open-ability/signature.html
+    /// ```no_run
+    /// use wechat_minapp::WechatMinapp;
+    /// use wechat_minapp::user::User;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let user = User::new(WechatMinapp::new("app_id", "secret"));
+    ///     let credential = user.login("0816abc123def456").await?;
+    ///
+    ///     let (raw_data, signature) = ("raw_data", "signature");
+    ///     if !credential.verify_signature(raw_data, signature) {
+    ///         return Err("用户数据签名校验失败".into());
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn verify_signature(&self, raw_data: &str, signature: &str) -> bool {
+        let expected = sha1_hex(format!("{}{}", raw_data, self.session_key).as_bytes());
+        constant_time_eq(expected.as_bytes(), signature.to_ascii_lowercase().as_bytes())
+    }
+
+    /// 解密用户数据，使用的是 AES-128-CBC 算法，数据采用PKCS#7填充。需要启用 `crypto` feature。
+    ///
+    /// watermark.appid 与当前小程序不一致时返回 [`ErrorKind::WatermarkMismatch`]。
     /// https://developers.weixin.qq.com/miniprogram/dev/framework/open-ability/signature.html
     /// ```no_run
     /// use wechat_minapp::WechatMinapp;
@@ -42,29 +160,110 @@ impl Credential {
     ///     let user = User::new(client);
     ///     let code = "0816abc123def456";
     ///     let credential = user.login(code).await?;
+    ///     let (encrypted_data, iv) = ("encrypted_data", "iv");
     ///     let info = credential.decrypt(&encrypted_data, &iv)?;
-    ///     println!("昵称: {}", info.nickname());
+    ///     println!("昵称: {}", info.nickname().unwrap_or_default());
     ///     println!("性别: {}", info.gender());
-    ///     println!("地区: {}-{}-{}", info.country(), info.province(), info.city());
-    ///     println!("头像: {}", info.avatar());
+    ///     println!("地区: {:?}-{:?}-{:?}", info.country(), info.province(), info.city());
+    ///     println!("头像: {:?}", info.avatar());
     ///     println!("AppID: {}", info.app_id());
     ///     println!("时间戳: {}", info.timestamp());
     ///
     ///     Ok(())
     /// }
     /// ```
+    #[cfg(feature = "crypto")]
     #[instrument(skip(self, encrypted_data, iv))]
     pub fn decrypt(&self, encrypted_data: &str, iv: &str) -> Result<UserInfo> {
-        debug!("encrypted_data: {}", encrypted_data);
-        debug!("iv: {}", iv);
-
         let buffer = aes_decrypt(encrypted_data, &self.session_key, iv)?;
 
         let builder = from_slice::<UserBuilder>(&buffer)?;
 
         debug!("user builder: {:#?}", builder);
 
-        Ok(builder.build())
+        let info = builder.build();
+        self.check_watermark(info.app_id())?;
+        Ok(info)
+    }
+
+    /// 解密任意开放数据并反序列化为 `T`，例如微信运动步数、群信息等，需要启用 `crypto` feature。
+    ///
+    /// 数据中带有 watermark.appid 时同样会校验是否与当前小程序一致。
+    /// ```no_run
+    /// use serde::Deserialize;
+    /// use wechat_minapp::{UnixTimestamp, WechatMinapp};
+    /// use wechat_minapp::user::User;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct StepInfo {
+    ///     timestamp: UnixTimestamp,
+    ///     step: u32,
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// #[serde(rename_all = "camelCase")]
+    /// struct WeRunData {
+    ///     step_info_list: Vec<StepInfo>,
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let user = User::new(WechatMinapp::new("app_id", "secret"));
+    ///     let credential = user.login("0816abc123def456").await?;
+    ///     let (encrypted_data, iv) = ("encrypted_data", "iv");
+    ///     let werun: WeRunData = credential.decrypt_into(encrypted_data, iv)?;
+    ///     for info in werun.step_info_list {
+    ///         println!("{}: {} 步", info.timestamp.to_datetime().date_naive(), info.step);
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "crypto")]
+    #[instrument(skip(self, encrypted_data, iv))]
+    pub fn decrypt_into<T: DeserializeOwned>(&self, encrypted_data: &str, iv: &str) -> Result<T> {
+        let buffer = aes_decrypt(encrypted_data, &self.session_key, iv)?;
+
+        // `T` 由调用方决定，不一定包含 watermark，用 flatten 合并会缓存整个对象且改变 `T` 的反序列化，
+        // 所以单独扫一遍：只借用 watermark.appid、跳过其余字段，比先构建完整的 JSON 值便宜。
+        // 不需要校验时不扫描；解析失败直接返回，不跳过校验。
+        if self.app_id.is_some() {
+            let data = from_slice::<OpenDataWatermark>(&buffer)?;
+            if let Some(app_id) = data.watermark.and_then(|watermark| watermark.appid) {
+                self.check_watermark(&app_id.0)?;
+            }
+        }
+
+        Ok(from_slice(&buffer)?)
+    }
+
+    /// 解密旧版 `wx.getPhoneNumber` 返回的手机号数据，需要启用 `crypto` feature。
+    ///
+    /// 新版前端返回的是 code，请使用 [`User::get_contact`]。
+    /// watermark.appid 与当前小程序不一致时返回 [`ErrorKind::WatermarkMismatch`]。
+    /// ```no_run
+    /// use wechat_minapp::WechatMinapp;
+    /// use wechat_minapp::user::User;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let user = User::new(WechatMinapp::new("app_id", "secret"));
+    ///     let credential = user.login("0816abc123def456").await?;
+    ///     let (encrypted_data, iv) = ("encrypted_data", "iv");
+    ///     let contact = credential.decrypt_contact(encrypted_data, iv)?;
+    ///     println!("手机号: {}", contact.phone_number());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "crypto")]
+    #[instrument(skip(self, encrypted_data, iv))]
+    pub fn decrypt_contact(&self, encrypted_data: &str, iv: &str) -> Result<Contact> {
+        let buffer = aes_decrypt(encrypted_data, &self.session_key, iv)?;
+
+        let contact = from_slice::<ContactBuilder>(&buffer)?.build();
+        self.check_watermark(contact.app_id())?;
+        Ok(contact)
     }
 }
 
@@ -73,8 +272,10 @@ impl std::fmt::Debug for Credential {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Credential")
             .field("open_id", &self.open_id)
-            .field("session_key", &"********")
+            .field("session_key", &REDACTED)
             .field("union_id", &self.union_id)
+            .field("app_id", &self.app_id)
+            .field("issued_at", &self.issued_at)
             .finish()
     }
 }
@@ -87,14 +288,14 @@ impl User {
         let signature = hmac_sha256(b"", session_key)?;
 
         let query = serde_json::json!({
+            "access_token":self.client.token().await?,
             "openid": open_id.to_string(),
             "signature":signature,
             "sig_method": "hmac_sha256".to_string()
         });
 
-        let request = RequestBuilder::new(constants::CHECK_SESSION_KEY_END_POINT)
+        let request = Endpoint::CheckSessionKey.request()
             .query(query)
-            .method(Method::GET)
             .build()?;
 
         let client = &self.client.core.client;
@@ -107,7 +308,7 @@ impl User {
 
     /// 重置用户的 session_key
     /// [官方文档](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/user-login/ResetUserSessionKey.html)
-    #[instrument(skip(self, open_id))]
+    #[instrument(skip(self, session_key, open_id))]
     pub async fn reset_session_key(&self, session_key: &str, open_id: &str) -> Result<Credential> {
         let signature = hmac_sha256(b"", session_key)?;
 
@@ -118,15 +319,137 @@ impl User {
             "sig_method": "hmac_sha256".to_string()
         });
 
-        let request = RequestBuilder::new(constants::RESET_SESSION_KEY_END_POINT)
+        let request = Endpoint::ResetSessionKey.request()
             .query(query)
-            .method(Method::GET)
             .build()?;
 
         let client = &self.client.core.client;
         let response = client.execute(request).await?;
-        debug!("response: {:#?}", &response);
+        debug!("reset session key response status: {}", response.status());
+
+        let credential = response.to_json::<Credential>()?;
+        Ok(credential
+            .with_app_id(self.client.app_config().app_id)
+            .issued_now())
+    }
+}
+
+/// 开放数据中的 watermark，其余字段直接跳过
+#[cfg(feature = "crypto")]
+#[derive(Deserialize)]
+struct OpenDataWatermark<'a> {
+    #[serde(default, borrow)]
+    watermark: Option<WatermarkAppId<'a>>,
+}
+
+#[cfg(feature = "crypto")]
+#[derive(Deserialize)]
+struct WatermarkAppId<'a> {
+    #[serde(default, borrow)]
+    appid: Option<BorrowedStr<'a>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_signature() {
+        let credential: Credential = serde_json::from_value(serde_json::json!({
+            "openid": "oGZUI0egBJY1zhBYw2KhdUfwVJJE",
+            "session_key": "HyVFkGl5F5OQWJZZaNzBBg=="
+        }))
+        .unwrap();
+        let raw_data = r#"{"nickName":"Band","gender":1}"#;
+        let signature = "880cdb5994cb662931a46726252ab9d8942768db";
+
+        assert!(credential.verify_signature(raw_data, signature));
+        assert!(credential.verify_signature(raw_data, &signature.to_uppercase()));
+        assert!(!credential.verify_signature(raw_data, "880cdb5994cb662931a46726252ab9d8942768dc"));
+        assert!(!credential.verify_signature(r#"{"nickName":"Band","gender":2}"#, signature));
+        assert!(!credential.verify_signature(raw_data, ""));
+    }
+
+    #[test]
+    fn test_extra_fields() {
+        let credential: Credential = serde_json::from_value(serde_json::json!({
+            "openid": "o1",
+            "session_key": "MDEyMzQ1Njc4OWFiY2RlZg==",
+            "errcode": 0,
+            "errmsg": "ok",
+            "new_field": 1
+        }))
+        .unwrap();
+        assert_eq!(credential.extra().get_as::<i32>("new_field"), Some(1));
+        assert!(!credential.extra().contains_key("errcode"));
+
+        // 保存到 SessionStorage 后再读取，extra 不丢失
+        let value = serde_json::to_value(&credential).unwrap();
+        assert_eq!(serde_json::from_value::<Credential>(value).unwrap(), credential);
+    }
+
+    #[tokio::test]
+    async fn test_check_session_key() {
+        let (client, http_client) = crate::fixtures::client(r#"{"errcode":0,"errmsg":"ok"}"#);
+        User::new(client)
+            .check_session_key("c2Vzc2lvbl9rZXk=", "o1")
+            .await
+            .unwrap();
+
+        let url = &http_client.requests()[0].url;
+        assert!(url.starts_with(Endpoint::CheckSessionKey.url()));
+        assert!(url.contains(&format!("access_token={}", REDACTED)));
+        let signature = hmac_sha256(b"", "c2Vzc2lvbl9rZXk=").unwrap();
+        assert!(url.contains(&format!("signature={}", signature)));
+        assert!(url.contains("openid=o1"));
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn test_decrypt_contact() {
+        let credential: Credential = serde_json::from_value(serde_json::json!({
+            "openid": "oGZUI0egBJY1zhBYw2KhdUfwVJJE",
+            "session_key": "MDEyMzQ1Njc4OWFiY2RlZg=="
+        }))
+        .unwrap();
+        let iv = "ZmVkY2JhOTg3NjU0MzIxMA==";
+        let encrypted_data = concat!(
+            "/zKksLWsmvxbw65eoGVnlEfdaS+fz/gxmd5B+ovlHqwohotM0F2p+llTp0eCaiaTJG1TUi1u7XQt",
+            "Qnfmgtr006KvIKj0gc38Sx+hvuM7Airkq5wzFDcPelU++4A0sWA1H1Xf4dOLByvExvM+p9IUR+9M",
+            "YPRznpYfSRI+jGFSweM9XeurBhxLgP1UBTYDOZ4/mWF6SY8Unr1V3WQtfwUgCg==",
+        );
+
+        let contact = credential.decrypt_contact(encrypted_data, iv).unwrap();
+        assert_eq!(contact.phone_number(), "+86 13800138000");
+
+        let credential = credential.with_app_id("wx4f4bc4dec97d474b");
+        assert_eq!(credential.decrypt_contact(encrypted_data, iv).unwrap(), contact);
+
+        let credential = credential.with_app_id("wx0000000000000000");
+        assert!(matches!(
+            credential.decrypt_contact(encrypted_data, iv).unwrap_err().kind(),
+            ErrorKind::WatermarkMismatch { .. }
+        ));
+        let credential = credential.skip_watermark_check();
+        assert!(credential.decrypt_contact(encrypted_data, iv).is_ok());
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Phone {
+            pure_phone_number: String,
+        }
+        let phone: Phone = credential.decrypt_into(encrypted_data, iv).unwrap();
+        assert_eq!(phone.pure_phone_number, "13800138000");
 
-        response.to_json::<Credential>()
+        let credential = credential.with_app_id("wx0000000000000000");
+        assert!(matches!(
+            credential.decrypt_into::<serde_json::Value>(encrypted_data, iv).unwrap_err().kind(),
+            ErrorKind::WatermarkMismatch { .. }
+        ));
+        assert_eq!(contact.pure_phone_number(), "13800138000");
+        assert_eq!(contact.country_code(), "86");
+        assert_eq!(contact.app_id(), "wx4f4bc4dec97d474b");
+        assert_eq!(contact.timestamp(), 1477314187);
+        assert_eq!(contact.generated_at().to_rfc3339(), "2016-10-24T13:03:07+00:00");
     }
 }
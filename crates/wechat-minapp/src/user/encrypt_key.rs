@@ -0,0 +1,208 @@
+//! 用户加密 key
+//!
+//! 前端通过 `wx.getUserCryptoManager().getLatestUserKey()` 取得加密 key 加密数据后，
+//! 把数据和 key 的版本号一起传给服务端；服务端调用 [`User::user_encrypt_key`] 查询最近的加密 key，
+//! 再用 [`UserEncryptKeys::decrypt_with_encrypt_key`] 按版本号找到对应的 key 解密。
+
+use super::User;
+use crate::constants::Endpoint;
+#[cfg(feature = "crypto")]
+use base64::{engine::general_purpose::STANDARD, Engine};
+#[cfg(feature = "crypto")]
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+#[cfg(feature = "crypto")]
+use wechat_core::utils::aes_decrypt_raw;
+use wechat_core::utils::{hmac_sha256, ResponseExt, UnixTimestamp, REDACTED};
+#[cfg(feature = "crypto")]
+use wechat_core::{localized, ErrorKind};
+use wechat_core::Result;
+
+/// 一个版本的用户加密 key
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct EncryptKeyInfo {
+    /// 加密 key，base64 编码
+    pub encrypt_key: String,
+    /// key 的版本号
+    pub version: u32,
+    /// 剩余有效时间（秒）
+    pub expire_in: i64,
+    /// 加密 iv，hex 编码
+    pub iv: String,
+    /// 创建时间
+    pub create_time: UnixTimestamp,
+}
+
+impl std::fmt::Debug for EncryptKeyInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptKeyInfo")
+            .field("encrypt_key", &REDACTED)
+            .field("version", &self.version)
+            .field("expire_in", &self.expire_in)
+            .field("iv", &self.iv)
+            .field("create_time", &self.create_time)
+            .finish()
+    }
+}
+
+/// 用户最近三次的加密 key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserEncryptKeys {
+    #[serde(default)]
+    pub key_info_list: Vec<EncryptKeyInfo>,
+}
+
+impl UserEncryptKeys {
+    /// 按版本号查找加密 key
+    pub fn find(&self, version: u32) -> Option<&EncryptKeyInfo> {
+        self.key_info_list.iter().find(|key| key.version == version)
+    }
+
+    /// 最新版本的加密 key
+    pub fn latest(&self) -> Option<&EncryptKeyInfo> {
+        self.key_info_list.iter().max_by_key(|key| key.version)
+    }
+
+    /// 使用指定版本的加密 key 解密数据
+    ///
+    /// - `version`: 前端加密时使用的 key 版本号
+    /// - `iv`: 前端加密时使用的 iv，hex 或 base64 编码，传 `None` 时使用 key 对应的 iv
+    /// - `data`: base64 编码的加密数据
+    ///
+    /// 需要启用 `crypto` feature。
+    #[cfg(feature = "crypto")]
+    pub fn decrypt_with_encrypt_key(
+        &self,
+        version: u32,
+        iv: Option<&str>,
+        data: &str,
+    ) -> Result<Vec<u8>> {
+        let key_info = self.find(version).ok_or_else(|| {
+            ErrorKind::InvalidParameter(localized!(
+                "找不到版本 {} 的加密 key，可能已经过期",
+                "encrypt key of version {} not found, it may have expired",
+                version
+            ))
+        })?;
+
+        let key = STANDARD.decode(key_info.encrypt_key.trim())?;
+        let iv = decode_iv(iv.unwrap_or(&key_info.iv))?;
+        let data = STANDARD.decode(data.trim())?;
+
+        aes_decrypt_raw(&data, &key, &iv)
+    }
+
+    /// 使用指定版本的加密 key 解密 JSON 数据并反序列化
+    #[cfg(feature = "crypto")]
+    pub fn decrypt_into<T: DeserializeOwned>(
+        &self,
+        version: u32,
+        iv: Option<&str>,
+        data: &str,
+    ) -> Result<T> {
+        let plain = self.decrypt_with_encrypt_key(version, iv, data)?;
+        Ok(serde_json::from_slice(&plain)?)
+    }
+}
+
+/// 微信返回的 iv 是 hex 编码，前端也可能以 base64 传递
+#[cfg(feature = "crypto")]
+fn decode_iv(iv: &str) -> Result<Vec<u8>> {
+    let iv = iv.trim();
+    if iv.len() == 32 && iv.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return hex::decode(iv)
+            .map_err(|e| ErrorKind::InvalidParameter(format!("invalid iv: {}", e)).into());
+    }
+    Ok(STANDARD.decode(iv)?)
+}
+
+impl User {
+    /// 获取用户最近三次的加密 key
+    ///
+    /// 使用会话的 session_key 签名，需要在 session_key 有效期内调用。
+    ///
+    /// [官方文档](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/internet/getUserEncryptKey.html)
+    #[instrument(skip(self, session_key, open_id))]
+    pub async fn user_encrypt_key(
+        &self,
+        session_key: &str,
+        open_id: &str,
+    ) -> Result<UserEncryptKeys> {
+        let signature = hmac_sha256(b"", session_key)?;
+
+        let query = serde_json::json!({
+            "access_token": self.client.token().await?,
+            "openid": open_id,
+            "signature": signature,
+            "sig_method": "hmac_sha256",
+        });
+
+        let request = Endpoint::UserEncryptKey.request().query(query).build()?;
+
+        let response = self.client.core.client.execute(request).await?;
+        debug!("user encrypt key response status: {}", response.status());
+
+        response.to_json::<UserEncryptKeys>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    const KEYS: &str = r#"{"errcode":0,"errmsg":"ok","key_info_list":[
+        {"encrypt_key":"VI6BpyrK9XH4i4AIGe86tg==","version":10,"expire_in":3597,
+        "iv":"6003f73ec441c3866a4cc6d7c8fc6fd0","create_time":1616572301},
+        {"encrypt_key":"aAbBcCdDeEfFgGhHiIjJkK==","version":9,"expire_in":0,
+        "iv":"00000000000000000000000000000000","create_time":1616565101}]}"#;
+
+    #[tokio::test]
+    async fn test_user_encrypt_key() {
+        let (client, http_client) = fixtures::client(KEYS);
+        let keys = User::new(client)
+            .user_encrypt_key("c2Vzc2lvbl9rZXk=", "o1")
+            .await
+            .unwrap();
+        assert_eq!(keys.latest().unwrap().version, 10);
+        assert_eq!(keys.find(9).unwrap().create_time.raw(), 1616565101);
+        assert!(!format!("{:?}", keys).contains("VI6BpyrK9XH4i4AIGe86tg=="));
+
+        let url = &http_client.requests()[0].url;
+        assert!(url.starts_with(Endpoint::UserEncryptKey.url()));
+        let signature = hmac_sha256(b"", "c2Vzc2lvbl9rZXk=").unwrap();
+        assert!(url.contains(&format!("signature={}", signature)));
+        assert!(url.contains("openid=o1"));
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn test_decrypt_with_encrypt_key() {
+        let keys: UserEncryptKeys = serde_json::from_str(KEYS).unwrap();
+        let encrypted = "mUM9873YSX2t509n+5ydV1WfBUnJd9YbcM9ewjurCc9kKONKJk/tCNZEKU+yKFM+";
+        let plain = br#"{"step":100,"watermark":{"appid":"wx123"}}"#;
+
+        // iv 可以使用 key 自带的 hex iv，也可以由前端以 hex 或 base64 传入
+        assert_eq!(keys.decrypt_with_encrypt_key(10, None, encrypted).unwrap(), plain);
+        assert_eq!(
+            keys.decrypt_with_encrypt_key(10, Some("YAP3PsRBw4ZqTMbXyPxv0A=="), encrypted)
+                .unwrap(),
+            plain
+        );
+        assert_eq!(
+            keys.decrypt_with_encrypt_key(10, Some("6003f73ec441c3866a4cc6d7c8fc6fd0"), encrypted)
+                .unwrap(),
+            plain
+        );
+
+        let value: serde_json::Value = keys.decrypt_into(10, None, encrypted).unwrap();
+        assert_eq!(value["step"], 100);
+
+        assert!(keys.decrypt_with_encrypt_key(10, None, "not base64!").is_err());
+        assert!(matches!(
+            keys.decrypt_with_encrypt_key(11, None, encrypted).unwrap_err().kind(),
+            ErrorKind::InvalidParameter(_)
+        ));
+    }
+}
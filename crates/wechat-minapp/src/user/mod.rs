@@ -33,11 +33,12 @@
 //!     let user = User::new(client);
 //!     let code = "0816abc123def456";
 //!     let credential = user.login(code).await?;
+//!     let (encrypted_data, iv) = ("encrypted_data", "iv");
 //!     let info = credential.decrypt(&encrypted_data, &iv)?;
-//!     println!("昵称: {}", info.nickname());
+//!     println!("昵称: {}", info.nickname().unwrap_or_default());
 //!     println!("性别: {}", info.gender());
-//!     println!("地区: {}-{}-{}", info.country(), info.province(), info.city());
-//!     println!("头像: {}", info.avatar());
+//!     println!("地区: {:?}-{:?}-{:?}", info.country(), info.province(), info.city());
+//!     println!("头像: {:?}", info.avatar());
 //!     println!("AppID: {}", info.app_id());
 //!     println!("时间戳: {}", info.timestamp());
 //!     
@@ -56,7 +57,6 @@
 //!     let user = User::new(client);
 //!     let code = "0816abc123def456";
 //!     let contact = user.get_contact(code, None).await?;
-//!     let info = credential.decrypt(&encrypted_data, &iv)?;
 //!     println!("用户手机号: {}", contact.phone_number());
 //!     
 //!     Ok(())
@@ -64,15 +64,29 @@
 //! ```
 //!
 mod credential;
+mod encrypt_key;
+mod open_data;
+mod session;
 mod user_info;
 use crate::WechatMinapp;
 
+pub use credential::Credential;
+pub use encrypt_key::{EncryptKeyInfo, UserEncryptKeys};
+#[cfg(feature = "auth")]
+pub(crate) use credential::unix_now;
+pub use open_data::{OpenDataChecker, OpenDataError};
+pub use session::{MemorySessionStorage, SessionManager, SessionPolicy, SessionStorage};
+pub use user_info::{Contact, UserInfo};
+
 pub struct User {
     pub client: WechatMinapp,
 }
 
 impl User {
-    pub fn new(client: WechatMinapp) -> Self {
-        User { client }
+    pub fn new(client: impl Into<WechatMinapp>) -> Self {
+        User {
+            client: client.into(),
+        }
     }
+
 }
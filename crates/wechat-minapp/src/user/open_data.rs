@@ -0,0 +1,228 @@
+//! 开放数据签名校验
+//!
+//! `wx.getUserInfo` 等接口返回的明文 `rawData` 需要用 `sha1(rawData + session_key)` 与
+//! `signature` 比对，防止被篡改。[`OpenDataChecker`] 从 [`SessionStorage`] 读取 session_key
+//! 完成校验，签名不一致时调用 `checkSession` 判断是 session_key 失效还是数据被篡改，
+//! 通过 [`OpenDataError`] 返回明确的失败原因。
+
+use super::{Credential, SessionManager, SessionStorage};
+use crate::WechatMinapp;
+use std::fmt;
+use std::sync::Arc;
+use tracing::debug;
+use wechat_core::{localized, Error, ErrorKind};
+
+/// 开放数据校验失败的原因
+#[derive(Debug)]
+pub enum OpenDataError {
+    /// 没有该用户的会话，需要登录
+    SessionNotFound,
+    /// session_key 已失效或会话已过期，会话已删除，需要重新登录
+    SessionExpired,
+    /// session_key 有效但签名不一致，数据可能被篡改
+    SignatureMismatch,
+    /// 读取会话或调用 `checkSession` 失败
+    Other(Error),
+}
+
+impl OpenDataError {
+    /// 是否需要用户重新登录
+    pub fn requires_relogin(&self) -> bool {
+        matches!(self, OpenDataError::SessionNotFound | OpenDataError::SessionExpired)
+    }
+}
+
+impl fmt::Display for OpenDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpenDataError::SessionNotFound => {
+                f.write_str(&localized!("会话不存在", "session not found"))
+            }
+            OpenDataError::SessionExpired => {
+                f.write_str(&localized!("会话已过期", "session expired"))
+            }
+            OpenDataError::SignatureMismatch => f.write_str(&localized!(
+                "开放数据签名校验失败",
+                "signature of raw data mismatch"
+            )),
+            OpenDataError::Other(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for OpenDataError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            OpenDataError::Other(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// 会话相关的原因转换为 [`ErrorKind::SessionKeyNotExistedOrExpired`]，签名不一致转换为
+/// [`ErrorKind::InvalidSignature`]
+impl From<OpenDataError> for Error {
+    fn from(value: OpenDataError) -> Self {
+        match value {
+            OpenDataError::SessionNotFound | OpenDataError::SessionExpired => {
+                ErrorKind::SessionKeyNotExistedOrExpired(value.to_string()).into()
+            }
+            OpenDataError::SignatureMismatch => {
+                ErrorKind::InvalidSignature(value.to_string()).into()
+            }
+            OpenDataError::Other(error) => error,
+        }
+    }
+}
+
+/// 开放数据签名校验
+///
+/// # 示例
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use wechat_minapp::WechatMinapp;
+/// use wechat_minapp::user::{MemorySessionStorage, OpenDataChecker, OpenDataError};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let client = WechatMinapp::new("app_id", "secret");
+///     let checker = OpenDataChecker::new(client, Arc::new(MemorySessionStorage::new()));
+///
+///     let (open_id, raw_data, signature) = ("openid", "raw_data", "signature");
+///     match checker.check(open_id, raw_data, signature).await {
+///         Ok(_) => println!("数据未被篡改"),
+///         Err(e) if e.requires_relogin() => println!("请重新登录"),
+///         Err(OpenDataError::SignatureMismatch) => println!("数据被篡改"),
+///         Err(e) => return Err(e.into()),
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OpenDataChecker {
+    sessions: SessionManager,
+}
+
+impl OpenDataChecker {
+    pub fn new(client: impl Into<WechatMinapp>, storage: Arc<dyn SessionStorage>) -> Self {
+        OpenDataChecker {
+            sessions: SessionManager::new(client, storage),
+        }
+    }
+
+    /// 校验明文数据的签名，成功时返回使用的会话
+    pub async fn check(
+        &self,
+        open_id: &str,
+        raw_data: &str,
+        signature: &str,
+    ) -> Result<Credential, OpenDataError> {
+        let credential = match self.sessions.find(open_id).await {
+            Ok(Some(credential)) => credential,
+            Ok(None) => return Err(OpenDataError::SessionNotFound),
+            Err(error) if matches!(error.kind(), ErrorKind::SessionKeyNotExistedOrExpired(_)) => {
+                return Err(OpenDataError::SessionExpired)
+            }
+            Err(error) => return Err(OpenDataError::Other(error)),
+        };
+
+        if credential.verify_signature(raw_data, signature) {
+            return Ok(credential);
+        }
+
+        debug!("signature mismatch, checking session of {}", open_id);
+        match self.sessions.check(open_id).await {
+            Ok(_) => Err(OpenDataError::SignatureMismatch),
+            Err(error) if error.requires_relogin() => Err(OpenDataError::SessionExpired),
+            Err(error) => Err(OpenDataError::Other(error)),
+        }
+    }
+}
+
+/// 与 [`SessionManager`] 共用会话存储和过期策略
+impl From<SessionManager> for OpenDataChecker {
+    fn from(sessions: SessionManager) -> Self {
+        OpenDataChecker { sessions }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use crate::user::MemorySessionStorage;
+    use async_trait::async_trait;
+    use http::{Request, Response};
+    use wechat_core::client::HttpClient;
+    use wechat_core::utils::{sha1_hex, Endpoint};
+    use wechat_core::Result;
+
+    const SESSION_KEY: &str = "MDEyMzQ1Njc4OWFiY2RlZg==";
+    const RAW_DATA: &str = r#"{"nickName":"Band"}"#;
+
+    struct CheckSessionClient(&'static str);
+
+    #[async_trait]
+    impl HttpClient for CheckSessionClient {
+        async fn execute(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+            let query = request.uri().query().unwrap_or_default();
+            assert!(query.contains(&format!("access_token={}", fixtures::ACCESS_TOKEN)));
+            let mut response = Response::new(self.0.as_bytes().to_vec());
+            response.extensions_mut().insert(Endpoint(request.uri().path().to_string()));
+            Ok(response)
+        }
+    }
+
+    async fn open_data_checker(
+        check_session: &'static str,
+    ) -> (OpenDataChecker, MemorySessionStorage) {
+        let client = fixtures::client_with(Arc::new(CheckSessionClient(check_session)));
+
+        let storage = MemorySessionStorage::new();
+        let credential: Credential = serde_json::from_value(serde_json::json!({
+            "openid": "o1",
+            "session_key": SESSION_KEY,
+        }))
+        .unwrap();
+        storage.set(&credential).await.unwrap();
+
+        (OpenDataChecker::new(client, Arc::new(storage.clone())), storage)
+    }
+
+    #[tokio::test]
+    async fn test_check() {
+        let (checker, _) = open_data_checker(r#"{"errcode":0,"errmsg":"ok"}"#).await;
+        let signature = sha1_hex(format!("{}{}", RAW_DATA, SESSION_KEY).as_bytes());
+
+        let credential = checker.check("o1", RAW_DATA, &signature).await.unwrap();
+        assert_eq!(credential.open_id(), "o1");
+
+        let error = checker.check("o1", RAW_DATA, "bad").await.unwrap_err();
+        assert!(matches!(error, OpenDataError::SignatureMismatch));
+        assert!(matches!(Error::from(error).kind(), ErrorKind::InvalidSignature(_)));
+
+        let error = checker.check("o2", RAW_DATA, &signature).await.unwrap_err();
+        assert!(matches!(error, OpenDataError::SessionNotFound));
+        assert!(Error::from(error).requires_relogin());
+    }
+
+    #[tokio::test]
+    async fn test_check_expired_session() {
+        let expired = r#"{"errcode":87007,"errmsg":"session_key is not existed or expired"}"#;
+        let (checker, storage) = open_data_checker(expired).await;
+
+        let error = checker.check("o1", RAW_DATA, "bad").await.unwrap_err();
+        assert!(matches!(error, OpenDataError::SessionExpired));
+        assert!(storage.get("o1").await.unwrap().is_none());
+
+        // checkSession 调用失败时返回原始错误
+        let (checker, _) = open_data_checker(r#"{"errcode":-1,"errmsg":"system error"}"#).await;
+        let error = checker.check("o1", RAW_DATA, "bad").await.unwrap_err();
+        match error {
+            OpenDataError::Other(error) => assert!(matches!(error.kind(), ErrorKind::System(_))),
+            error => panic!("unexpected error: {:?}", error),
+        }
+    }
+}
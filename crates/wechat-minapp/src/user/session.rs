@@ -0,0 +1,498 @@
+//! 会话生命周期管理
+//!
+//! 登录后需要保存 session_key 才能解密开放数据，session_key 过期后又要引导用户重新登录。
+//! [`SessionManager`] 把 `login`、`checkSession`、`resetUserSessionKey` 串起来：
+//! 登录后自动保存 [`Credential`] 到 [`SessionStorage`]，校验失败时清理会话并返回
+//! [`Error::requires_relogin`] 为 `true` 的错误。默认提供内存实现 [`MemorySessionStorage`]，
+//! 可参考实现 redis 等存储方式。
+//!
+//! 微信不公开 session_key 的有效期，默认以 `checkSession` 的结果为准，也可以通过
+//! [`SessionPolicy`] 设置会话的最长有效期，以及校验时自动轮换 session_key 的时机。
+
+use super::{Credential, OpenDataChecker, User};
+#[cfg(feature = "crypto")]
+use super::{Contact, UserInfo};
+use crate::WechatMinapp;
+use async_trait::async_trait;
+#[cfg(feature = "crypto")]
+use serde::de::DeserializeOwned;
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tracing::debug;
+use wechat_core::{Error, ErrorKind, Result};
+
+/// 定义会话（Credential）读取存储的行为，以 openid 为键
+#[async_trait]
+pub trait SessionStorage: Send + Sync {
+    /// 读取会话
+    async fn get(&self, open_id: &str) -> Result<Option<Credential>>;
+    /// 保存会话，已存在时覆盖
+    async fn set(&self, credential: &Credential) -> Result<()>;
+    /// 删除会话
+    async fn remove(&self, open_id: &str) -> Result<()>;
+}
+
+/// 内存存储方式的会话存储
+#[derive(Debug, Default, Clone)]
+pub struct MemorySessionStorage {
+    sessions: Arc<RwLock<HashMap<String, Credential>>>,
+}
+
+impl MemorySessionStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SessionStorage for MemorySessionStorage {
+    async fn get(&self, open_id: &str) -> Result<Option<Credential>> {
+        let sessions = self
+            .sessions
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        Ok(sessions.get(open_id).cloned())
+    }
+
+    async fn set(&self, credential: &Credential) -> Result<()> {
+        self.sessions
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(credential.open_id().to_string(), credential.clone());
+        Ok(())
+    }
+
+    async fn remove(&self, open_id: &str) -> Result<()> {
+        self.sessions
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(open_id);
+        Ok(())
+    }
+}
+
+/// 会话过期策略
+///
+/// - [`max_age`](Self::max_age)：获取 session_key 超过该时长的会话视为过期，删除后要求重新登录
+/// - [`refresh_after`](Self::refresh_after)：[`SessionManager::check`] 校验通过、且获取 session_key
+///   超过该时长时，自动调用 `resetUserSessionKey` 轮换 session_key 并保存
+///
+/// 默认两项都不限制。获取时间未知的会话（[`Credential::issued_at`] 为 `None`）不受策略限制。
+///
+/// ```
+/// use std::time::Duration;
+/// use wechat_minapp::user::SessionPolicy;
+///
+/// let policy = SessionPolicy::new()
+///     .max_age(Duration::from_secs(30 * 24 * 3600))
+///     .refresh_after(Duration::from_secs(24 * 3600));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionPolicy {
+    max_age: Option<Duration>,
+    refresh_after: Option<Duration>,
+}
+
+impl SessionPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 会话的最长有效期，`None` 表示不限制
+    pub fn max_age(mut self, max_age: impl Into<Option<Duration>>) -> Self {
+        self.max_age = max_age.into();
+        self
+    }
+
+    /// 校验会话时自动轮换 session_key 的时机，`None` 表示不自动轮换
+    pub fn refresh_after(mut self, refresh_after: impl Into<Option<Duration>>) -> Self {
+        self.refresh_after = refresh_after.into();
+        self
+    }
+
+    /// 会话是否已超过最长有效期
+    pub fn is_expired(&self, credential: &Credential) -> bool {
+        matches!((self.max_age, credential.age()), (Some(max_age), Some(age)) if age > max_age)
+    }
+
+    /// 会话是否需要轮换 session_key
+    pub fn needs_refresh(&self, credential: &Credential) -> bool {
+        matches!(
+            (self.refresh_after, credential.age()),
+            (Some(refresh_after), Some(age)) if age > refresh_after
+        )
+    }
+}
+
+/// 会话管理
+///
+/// # 示例
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use wechat_minapp::WechatMinapp;
+/// use wechat_minapp::user::{MemorySessionStorage, SessionManager};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let client = WechatMinapp::new("app_id", "secret");
+///     let sessions = SessionManager::new(client, Arc::new(MemorySessionStorage::new()));
+///
+///     // 登录接口：保存会话，只把 openid 返回给业务侧
+///     let credential = sessions.login("0816abc123def456").await?;
+///     let open_id = credential.open_id().to_string();
+///
+///     // 解密接口：session_key 失效时清理会话，提示前端重新登录
+///     match sessions.decrypt(&open_id, "encrypted_data", "iv").await {
+///         Ok(info) => println!("昵称: {:?}", info.nickname()),
+///         Err(e) if e.requires_relogin() => println!("请重新登录"),
+///         Err(e) => return Err(e.into()),
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SessionManager {
+    user: Arc<User>,
+    storage: Arc<dyn SessionStorage>,
+    policy: SessionPolicy,
+}
+
+impl fmt::Debug for SessionManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionManager")
+            .field("storage", &"Arc<dyn SessionStorage>")
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl SessionManager {
+    pub fn new(client: impl Into<WechatMinapp>, storage: Arc<dyn SessionStorage>) -> Self {
+        SessionManager {
+            user: Arc::new(User::new(client)),
+            storage,
+            policy: SessionPolicy::default(),
+        }
+    }
+
+    /// 设置会话过期策略
+    pub fn with_policy(mut self, policy: SessionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// 使用 `wx.login` 的 code 登录并保存会话
+    pub async fn login(&self, code: &str) -> Result<Credential> {
+        let credential = self.user.login(code).await?;
+        self.storage.set(&credential).await?;
+        Ok(credential)
+    }
+
+    /// 读取已保存的会话，不存在时返回 [`ErrorKind::SessionKeyNotExistedOrExpired`]
+    ///
+    /// 会话超过 [`SessionPolicy::max_age`] 时删除会话并返回同样的错误。
+    pub async fn credential(&self, open_id: &str) -> Result<Credential> {
+        self.find(open_id).await?.ok_or_else(|| {
+            let message = format!("session of {} not found", open_id);
+            ErrorKind::SessionKeyNotExistedOrExpired(message).into()
+        })
+    }
+
+    /// 读取已保存的会话，不存在时返回 `None`，超过最长有效期时删除会话并返回错误
+    pub(super) async fn find(&self, open_id: &str) -> Result<Option<Credential>> {
+        let Some(credential) = self.storage.get(open_id).await? else {
+            return Ok(None);
+        };
+
+        if self.policy.is_expired(&credential) {
+            debug!("session of {} exceeded max age, removed", open_id);
+            self.storage.remove(open_id).await?;
+            return Err(ErrorKind::SessionKeyNotExistedOrExpired(format!(
+                "session of {} expired",
+                open_id
+            ))
+            .into());
+        }
+
+        Ok(Some(credential.with_app_id(self.user.client.app_config().app_id)))
+    }
+
+    /// 调用 `checkSession` 校验会话，session_key 失效时删除会话并返回错误
+    ///
+    /// 校验通过且会话超过 [`SessionPolicy::refresh_after`] 时自动轮换 session_key，
+    /// 返回轮换后的会话。
+    pub async fn check(&self, open_id: &str) -> Result<Credential> {
+        let credential = self.credential(open_id).await?;
+
+        match self
+            .user
+            .check_session_key(credential.session_key(), open_id)
+            .await
+        {
+            Ok(()) if self.policy.needs_refresh(&credential) => {
+                debug!("session of {} needs refresh, resetting session key", open_id);
+                self.reset_credential(&credential).await
+            }
+            Ok(()) => Ok(credential),
+            Err(error) => {
+                if matches!(
+                    error.kind(),
+                    ErrorKind::SessionKeyNotExistedOrExpired(_) | ErrorKind::InvalidSignature(_)
+                ) {
+                    debug!("session of {} expired, removed", open_id);
+                    self.storage.remove(open_id).await?;
+                }
+                Err(error)
+            }
+        }
+    }
+
+    /// 重置 session_key 并保存新的会话，适用于怀疑 session_key 泄漏的场景
+    pub async fn reset(&self, open_id: &str) -> Result<Credential> {
+        let credential = self.credential(open_id).await?;
+        self.reset_credential(&credential).await
+    }
+
+    async fn reset_credential(&self, credential: &Credential) -> Result<Credential> {
+        let credential = self
+            .user
+            .reset_session_key(credential.session_key(), credential.open_id())
+            .await?;
+        self.storage.set(&credential).await?;
+        Ok(credential)
+    }
+
+    /// 退出登录，删除会话
+    pub async fn logout(&self, open_id: &str) -> Result<()> {
+        self.storage.remove(open_id).await
+    }
+
+    /// 使用已保存的会话校验 `wx.getUserInfo` 等接口返回的明文数据签名
+    ///
+    /// 签名不一致时调用 `checkSession`：session_key 已失效则删除会话并返回需要重新登录的错误，
+    /// 否则返回 [`ErrorKind::InvalidSignature`]。需要区分失败原因时使用 [`OpenDataChecker`]。
+    pub async fn verify_signature(
+        &self,
+        open_id: &str,
+        raw_data: &str,
+        signature: &str,
+    ) -> Result<Credential> {
+        OpenDataChecker::from(self.clone())
+            .check(open_id, raw_data, signature)
+            .await
+            .map_err(Error::from)
+    }
+
+    /// 使用已保存的会话解密用户信息
+    ///
+    /// 解密失败时调用 `checkSession`：session_key 已失效则删除会话并返回需要重新登录的错误，
+    /// 否则返回原始的解密错误。需要启用 `crypto` feature。
+    #[cfg(feature = "crypto")]
+    pub async fn decrypt(&self, open_id: &str, encrypted_data: &str, iv: &str) -> Result<UserInfo> {
+        self.decrypt_with(open_id, |credential| credential.decrypt(encrypted_data, iv)).await
+    }
+
+    /// 使用已保存的会话解密旧版手机号数据，失败时的处理同 [`decrypt`](Self::decrypt)
+    #[cfg(feature = "crypto")]
+    pub async fn decrypt_contact(
+        &self,
+        open_id: &str,
+        encrypted_data: &str,
+        iv: &str,
+    ) -> Result<Contact> {
+        self.decrypt_with(open_id, |credential| credential.decrypt_contact(encrypted_data, iv))
+            .await
+    }
+
+    /// 使用已保存的会话解密任意开放数据，失败时的处理同 [`decrypt`](Self::decrypt)
+    #[cfg(feature = "crypto")]
+    pub async fn decrypt_into<T: DeserializeOwned>(
+        &self,
+        open_id: &str,
+        encrypted_data: &str,
+        iv: &str,
+    ) -> Result<T> {
+        self.decrypt_with(open_id, |credential| credential.decrypt_into(encrypted_data, iv))
+            .await
+    }
+
+    #[cfg(feature = "crypto")]
+    async fn decrypt_with<T>(
+        &self,
+        open_id: &str,
+        decrypt: impl FnOnce(&Credential) -> Result<T>,
+    ) -> Result<T> {
+        let credential = self.credential(open_id).await?;
+
+        match decrypt(&credential) {
+            Ok(value) => Ok(value),
+            // 数据串号与 session_key 无关，不需要校验会话
+            Err(error) if matches!(error.kind(), ErrorKind::WatermarkMismatch { .. }) => Err(error),
+            Err(error) => {
+                debug!("decrypt failed: {}, checking session of {}", error, open_id);
+                self.check(open_id).await?;
+                Err(error)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use http::{Request, Response};
+    use wechat_core::client::HttpClient;
+    use wechat_core::utils::Endpoint;
+
+    /// 按接口路径返回固定响应，与真实客户端一样在响应中附带接口地址
+    struct RouteClient {
+        check_session: &'static str,
+    }
+
+    #[async_trait]
+    impl HttpClient for RouteClient {
+        async fn execute(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+            let body = match request.uri().path() {
+                "/sns/jscode2session" => {
+                    r#"{"openid":"o1","session_key":"MDEyMzQ1Njc4OWFiY2RlZg=="}"#
+                }
+                "/wxa/checksession" => {
+                    let query = request.uri().query().unwrap_or_default();
+                    assert!(query.contains(&format!("access_token={}", fixtures::ACCESS_TOKEN)));
+                    self.check_session
+                }
+                "/wxa/resetusersessionkey" => {
+                    r#"{"openid":"o1","session_key":"bmV3LXNlc3Npb24ta2V5"}"#
+                }
+                path => panic!("unexpected request: {}", path),
+            };
+            let mut response = Response::new(body.as_bytes().to_vec());
+            response.extensions_mut().insert(Endpoint(request.uri().path().to_string()));
+            Ok(response)
+        }
+    }
+
+    fn manager(check_session: &'static str) -> (SessionManager, MemorySessionStorage) {
+        let client = fixtures::client_with(Arc::new(RouteClient { check_session }));
+        let storage = MemorySessionStorage::new();
+        (SessionManager::new(client, Arc::new(storage.clone())), storage)
+    }
+
+    #[tokio::test]
+    async fn test_login_and_check() {
+        let (sessions, storage) = manager(r#"{"errcode":0,"errmsg":"ok"}"#);
+
+        let credential = sessions.login("code").await.unwrap();
+        assert_eq!(credential.open_id(), "o1");
+        assert!(storage.get("o1").await.unwrap().is_some());
+        assert_eq!(sessions.check("o1").await.unwrap().session_key(), credential.session_key());
+
+        sessions.logout("o1").await.unwrap();
+        let error = sessions.credential("o1").await.unwrap_err();
+        assert!(error.requires_relogin());
+    }
+
+    #[tokio::test]
+    async fn test_expired_session_removed() {
+        let (sessions, storage) =
+            manager(r#"{"errcode":87007,"errmsg":"session_key is not existed or expired"}"#);
+
+        sessions.login("code").await.unwrap();
+        let error = sessions.check("o1").await.unwrap_err();
+        assert!(error.requires_relogin());
+        assert_eq!(error.endpoint(), Some("/wxa/checksession"));
+        assert!(storage.get("o1").await.unwrap().is_none());
+    }
+
+    /// 一天前登录的会话
+    fn day_old_credential() -> Credential {
+        let issued_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            - 24 * 3600;
+        serde_json::from_value(serde_json::json!({
+            "openid": "o1",
+            "session_key": "MDEyMzQ1Njc4OWFiY2RlZg==",
+            "issued_at": issued_at,
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_policy_max_age() {
+        let (sessions, storage) = manager(r#"{"errcode":0,"errmsg":"ok"}"#);
+        let policy = SessionPolicy::new().max_age(Duration::from_secs(3600));
+        let sessions = sessions.with_policy(policy);
+
+        // 刚登录的会话未过期
+        let credential = sessions.login("code").await.unwrap();
+        assert!(credential.issued_at().is_some());
+        assert!(sessions.check("o1").await.is_ok());
+
+        storage.set(&day_old_credential()).await.unwrap();
+        let error = sessions.credential("o1").await.unwrap_err();
+        assert!(error.requires_relogin());
+        assert!(storage.get("o1").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_policy_refresh_after() {
+        let (sessions, storage) = manager(r#"{"errcode":0,"errmsg":"ok"}"#);
+        let policy = SessionPolicy::new().refresh_after(Duration::from_secs(3600));
+        let sessions = sessions.with_policy(policy);
+
+        storage.set(&day_old_credential()).await.unwrap();
+        let credential = sessions.check("o1").await.unwrap();
+        assert_eq!(credential.session_key(), "bmV3LXNlc3Npb24ta2V5");
+        assert!(!policy.needs_refresh(&credential));
+
+        let saved = storage.get("o1").await.unwrap().unwrap();
+        assert_eq!(saved.session_key(), "bmV3LXNlc3Npb24ta2V5");
+    }
+
+    #[tokio::test]
+    async fn test_verify_signature() {
+        let (sessions, storage) = manager(r#"{"errcode":0,"errmsg":"ok"}"#);
+        let credential = sessions.login("code").await.unwrap();
+        let raw_data = r#"{"nickName":"Band"}"#;
+        let signature = wechat_core::utils::sha1_hex(
+            format!("{}{}", raw_data, credential.session_key()).as_bytes(),
+        );
+
+        assert!(sessions.verify_signature("o1", raw_data, &signature).await.is_ok());
+        let error = sessions.verify_signature("o1", raw_data, "bad").await.unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidSignature(_)));
+        assert!(storage.get("o1").await.unwrap().is_some());
+
+        // session_key 已失效时删除会话
+        let (sessions, storage) =
+            manager(r#"{"errcode":87007,"errmsg":"session_key is not existed or expired"}"#);
+        sessions.login("code").await.unwrap();
+        let error = sessions.verify_signature("o1", raw_data, "bad").await.unwrap_err();
+        assert!(error.requires_relogin());
+        assert!(storage.get("o1").await.unwrap().is_none());
+    }
+
+    #[cfg(feature = "crypto")]
+    #[tokio::test]
+    async fn test_decrypt_failed_with_expired_session() {
+        let (sessions, storage) =
+            manager(r#"{"errcode":87007,"errmsg":"session_key is not existed or expired"}"#);
+
+        sessions.login("code").await.unwrap();
+        let error = sessions
+            .decrypt("o1", "AAAAAAAAAAAAAAAAAAAAAA==", "ZmVkY2JhOTg3NjU0MzIxMA==")
+            .await
+            .unwrap_err();
+        assert!(error.requires_relogin());
+        assert!(storage.get("o1").await.unwrap().is_none());
+    }
+}
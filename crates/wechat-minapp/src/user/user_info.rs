@@ -1,10 +1,14 @@
 use super::User;
 use super::credential::Credential;
-use wechat_core::utils::{RequestBuilder, ResponseExt};
-use wechat_core::Result;
-use crate::constants;
-use http::Method;
+use wechat_core::utils::ResponseExt;
+use chrono::{DateTime, Utc};
+use wechat_core::response::ExtraFields;
+use wechat_core::utils::UnixTimestamp;
+use wechat_core::{localized, ErrorKind, Result};
+use crate::constants::Endpoint;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "crypto")]
+use std::borrow::Cow;
 use tracing::{debug, instrument};
 
 /// 微信用户基本信息
@@ -24,11 +28,12 @@ use tracing::{debug, instrument};
 ///     let user = User::new(client);
 ///     let code = "0816abc123def456";
 ///     let credential = user.login(code).await?;
+///     let (encrypted_data, iv) = ("encrypted_data", "iv");
 ///     let info = credential.decrypt(&encrypted_data, &iv)?;
-///     println!("昵称: {}", info.nickname());
+///     println!("昵称: {}", info.nickname().unwrap_or_default());
 ///     println!("性别: {}", info.gender());
-///     println!("地区: {}-{}-{}", info.country(), info.province(), info.city());
-///     println!("头像: {}", info.avatar());
+///     println!("地区: {:?}-{:?}-{:?}", info.country(), info.province(), info.city());
+///     println!("头像: {:?}", info.avatar());
 ///     println!("AppID: {}", info.app_id());
 ///     println!("时间戳: {}", info.timestamp());
 ///     
@@ -47,85 +52,176 @@ use tracing::{debug, instrument};
 /// # 字段说明
 ///
 /// - `gender`: 性别，0-未知，1-男性，2-女性
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// - 新版 `wx.getUserProfile` 返回的数据中部分字段可能缺失或为空，对应的方法返回 `None`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct UserInfo {
-    nickname: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nickname: Option<String>,
     gender: u8,
-    country: String,
-    province: String,
-    city: String,
-    avatar: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    country: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    province: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    city: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    avatar: Option<String>,
     watermark: Watermark,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    extra: ExtraFields,
 }
 
 impl UserInfo {
-    pub fn nickname(&self) -> &str {
-        &self.nickname
+    pub fn nickname(&self) -> Option<&str> {
+        self.nickname.as_deref()
     }
 
     pub fn gender(&self) -> u8 {
         self.gender
     }
 
-    pub fn country(&self) -> &str {
-        &self.country
+    /// 显示 country、province、city 所用的语言，如 `zh_CN`
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
     }
 
-    pub fn province(&self) -> &str {
-        &self.province
+    pub fn country(&self) -> Option<&str> {
+        self.country.as_deref()
     }
 
-    pub fn city(&self) -> &str {
-        &self.city
+    pub fn province(&self) -> Option<&str> {
+        self.province.as_deref()
     }
 
-    pub fn avatar(&self) -> &str {
-        &self.avatar
+    pub fn city(&self) -> Option<&str> {
+        self.city.as_deref()
+    }
+
+    pub fn avatar(&self) -> Option<&str> {
+        self.avatar.as_deref()
+    }
+
+    /// 微信新增、SDK 尚未声明的字段
+    pub fn extra(&self) -> &ExtraFields {
+        &self.extra
     }
 
     pub fn app_id(&self) -> &str {
         &self.watermark.app_id
     }
 
+    /// 数据的生成时间（watermark.timestamp 原始值）
     pub fn timestamp(&self) -> u64 {
-        self.watermark.timestamp
+        self.watermark.timestamp.raw() as u64
     }
+
+    /// 数据的生成时间
+    pub fn generated_at(&self) -> DateTime<Utc> {
+        self.watermark.timestamp.to_datetime()
+    }
+}
+
+/// 空字符串视为缺失
+#[cfg(feature = "crypto")]
+fn non_empty(value: Option<BorrowedStr<'_>>) -> Option<String> {
+    value
+        .filter(|value| !value.0.is_empty())
+        .map(|value| value.0.into_owned())
 }
 
+/// 尽量借用的字符串
+///
+/// serde 只对直接声明的 `Cow<'a, str>` 字段借用，`Option<Cow<'a, str>>` 总是分配，
+/// 可选字段需要通过这个包装借用。带转义的字符串无法借用，仍为 owned。
+#[cfg(feature = "crypto")]
+#[derive(Debug, Deserialize)]
+pub(crate) struct BorrowedStr<'a>(#[serde(borrow)] pub(crate) Cow<'a, str>);
+
+/// 解密后的用户信息，字符串尽量借用解密缓冲区，构建 [`UserInfo`] 时再转为 owned
+#[cfg(feature = "crypto")]
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct UserBuilder {
-    #[serde(rename = "nickName")]
-    nickname: String,
+pub(crate) struct UserBuilder<'a> {
+    #[serde(default, borrow, rename = "nickName")]
+    nickname: Option<BorrowedStr<'a>>,
+    #[serde(default)]
     gender: u8,
-    country: String,
-    province: String,
-    city: String,
-    #[serde(rename = "avatarUrl")]
-    avatar: String,
-    watermark: WatermarkBuilder,
+    #[serde(default, borrow)]
+    language: Option<BorrowedStr<'a>>,
+    #[serde(default, borrow)]
+    country: Option<BorrowedStr<'a>>,
+    #[serde(default, borrow)]
+    province: Option<BorrowedStr<'a>>,
+    #[serde(default, borrow)]
+    city: Option<BorrowedStr<'a>>,
+    #[serde(default, borrow, rename = "avatarUrl")]
+    avatar: Option<BorrowedStr<'a>>,
+    #[serde(borrow)]
+    watermark: WatermarkBuilder<'a>,
+    #[serde(flatten)]
+    extra: ExtraFields,
 }
 
-impl UserBuilder {
+#[cfg(feature = "crypto")]
+impl UserBuilder<'_> {
     pub(crate) fn build(self) -> UserInfo {
         UserInfo {
-            nickname: self.nickname,
+            nickname: non_empty(self.nickname),
             gender: self.gender,
-            country: self.country,
-            province: self.province,
-            city: self.city,
-            avatar: self.avatar,
+            language: non_empty(self.language),
+            country: non_empty(self.country),
+            province: non_empty(self.province),
+            city: non_empty(self.city),
+            avatar: non_empty(self.avatar),
             watermark: self.watermark.build(),
+            extra: self.extra,
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// 用户手机号信息
+///
+/// 字段与微信的 camelCase 命名一致，同时兼容旧版本序列化的 snake_case 数据。
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct Contact {
+    #[serde(alias = "phone_number")]
     phone_number: String,
+    #[serde(alias = "pure_phone_number")]
     pure_phone_number: String,
+    #[serde(alias = "country_code", deserialize_with = "string_or_number")]
     country_code: String,
     watermark: Watermark,
+    /// 微信新增、SDK 尚未声明的字段
+    #[serde(flatten)]
+    extra: ExtraFields,
+}
+
+/// getPhoneNumber 接口的响应，手机号信息在 `phone_info` 中
+#[derive(Debug, Deserialize)]
+struct PhoneInfo {
+    phone_info: Contact,
+}
+
+/// 服务端接口返回的 countryCode 是数字，解密数据中是字符串
+fn string_or_number<'de, D>(deserializer: D) -> std::result::Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(u64),
+        String(String),
+    }
+
+    Ok(match Raw::deserialize(deserializer)? {
+        Raw::Number(value) => value.to_string(),
+        Raw::String(value) => value,
+    })
 }
 
 impl Contact {
@@ -141,32 +237,134 @@ impl Contact {
         &self.country_code
     }
 
+    /// 脱敏后的手机号（不含区号），例如 `138****1234`
+    ///
+    /// 8 位及以上保留前 3 位和后 4 位，更短的号码只保留后一半。
+    pub fn masked(&self) -> String {
+        mask_phone(&self.pure_phone_number)
+    }
+
+    /// E.164 格式的手机号，例如 `+8613812341234`，适合标准化存库
+    ///
+    /// 区号或号码包含非数字字符、总长度超过 15 位时返回 [`ErrorKind::InvalidParameter`]。
+    pub fn e164(&self) -> Result<String> {
+        let country_code = self.country_code.trim().trim_start_matches('+');
+        let number = self.pure_phone_number.trim();
+
+        let is_digits =
+            |value: &str| !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit());
+        if !is_digits(country_code) || country_code.len() > 3 || !is_digits(number) {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "手机号格式不正确: +{} {}",
+                "invalid phone number: +{} {}",
+                country_code,
+                mask_phone(number)
+            ))
+            .into());
+        }
+        if country_code.len() + number.len() > 15 {
+            return Err(ErrorKind::InvalidParameter(localized!(
+                "手机号超过 15 位",
+                "phone number exceeds 15 digits"
+            ))
+            .into());
+        }
+
+        Ok(format!("+{}{}", country_code, number))
+    }
+
+    /// 微信新增、SDK 尚未声明的字段
+    pub fn extra(&self) -> &ExtraFields {
+        &self.extra
+    }
+
     pub fn app_id(&self) -> &str {
         &self.watermark.app_id
     }
 
+    /// 数据的生成时间（watermark.timestamp 原始值）
     pub fn timestamp(&self) -> u64 {
-        self.watermark.timestamp
+        self.watermark.timestamp.raw() as u64
+    }
+
+    /// 数据的生成时间
+    pub fn generated_at(&self) -> DateTime<Utc> {
+        self.watermark.timestamp.to_datetime()
+    }
+}
+
+/// 8 位及以上保留前 3 位和后 4 位，更短的只保留后一半
+fn mask_phone(number: &str) -> String {
+    let chars: Vec<char> = number.chars().collect();
+    let (head, tail) = if chars.len() >= 8 {
+        (3, 4)
+    } else {
+        (0, chars.len() / 2)
+    };
+
+    chars
+        .iter()
+        .enumerate()
+        .map(|(index, c)| {
+            if index < head || index >= chars.len() - tail {
+                *c
+            } else {
+                '*'
+            }
+        })
+        .collect()
+}
+
+/// 解密后的手机号信息，字符串尽量借用解密缓冲区
+#[cfg(feature = "crypto")]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ContactBuilder<'a> {
+    #[serde(borrow)]
+    phone_number: Cow<'a, str>,
+    #[serde(borrow)]
+    pure_phone_number: Cow<'a, str>,
+    #[serde(borrow)]
+    country_code: Cow<'a, str>,
+    #[serde(borrow)]
+    watermark: WatermarkBuilder<'a>,
+    #[serde(flatten)]
+    extra: ExtraFields,
+}
+
+#[cfg(feature = "crypto")]
+impl ContactBuilder<'_> {
+    pub(crate) fn build(self) -> Contact {
+        Contact {
+            phone_number: self.phone_number.into_owned(),
+            pure_phone_number: self.pure_phone_number.into_owned(),
+            country_code: self.country_code.into_owned(),
+            watermark: self.watermark.build(),
+            extra: self.extra,
+        }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 struct Watermark {
+    #[serde(rename = "appid", alias = "app_id")]
     app_id: String,
-    timestamp: u64,
+    timestamp: UnixTimestamp,
 }
 
+#[cfg(feature = "crypto")]
 #[derive(Debug, Deserialize, Clone)]
-struct WatermarkBuilder {
-    #[serde(rename = "appid")]
-    app_id: String,
-    timestamp: u64,
+struct WatermarkBuilder<'a> {
+    #[serde(borrow, rename = "appid")]
+    app_id: Cow<'a, str>,
+    timestamp: UnixTimestamp,
 }
 
-impl WatermarkBuilder {
+#[cfg(feature = "crypto")]
+impl WatermarkBuilder<'_> {
     fn build(self) -> Watermark {
         Watermark {
-            app_id: self.app_id,
+            app_id: self.app_id.into_owned(),
             timestamp: self.timestamp,
         }
     }
@@ -188,7 +386,14 @@ impl User {
     /// # 错误
     ///
     /// - 网络错误
-    /// - 微信 API 返回错误
+    /// - 微信 API 返回错误，常见的有：
+    ///   - [`ErrorKind::InvalidCode`](wechat_core::ErrorKind::InvalidCode)（40029）：code 无效
+    ///   - [`ErrorKind::CodeUsed`](wechat_core::ErrorKind::CodeUsed)（40163）：code 已被使用
+    ///   - [`ErrorKind::RateLimitExceeded`](wechat_core::ErrorKind::RateLimitExceeded)（45011）：调用太频繁
+    ///   - [`ErrorKind::CodeBlocked`](wechat_core::ErrorKind::CodeBlocked)（40226）：高风险用户被拦截
+    ///
+    ///   可以用 [`Error::requires_relogin`](wechat_core::Error::requires_relogin)
+    ///   判断是否应该提示用户重新登录。
     /// - 响应解析错误
     ///
     /// # 示例
@@ -215,7 +420,6 @@ impl User {
     /// [微信官方文档 - code2Session](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/user-login/code2Session.html)
     #[instrument(skip(self, code))]
     pub async fn login(&self, code: &str) -> Result<Credential> {
-        debug!("code: {}", code);
         let config = self.client.app_config();
         let query = serde_json::json!({
         "appid": &config.app_id,
@@ -224,17 +428,17 @@ impl User {
         "grant_type": "authorization_code"
         });
 
-        let request = RequestBuilder::new(constants::AUTHENTICATION_END_POINT)
-            .method(Method::GET)
+        let request = Endpoint::Authentication.request()
             .query(query)
             .build()?;
 
         let client = &self.client.core.client;
 
         let response = client.execute(request).await?;
-        debug!("authentication response: {:#?}", &response);
+        debug!("authentication response status: {}", response.status());
 
-        response.to_json::<Credential>()
+        let credential = response.to_json::<Credential>()?;
+        Ok(credential.with_app_id(config.app_id).issued_now())
     }
 
     /// 获取用户手机号信息
@@ -292,8 +496,8 @@ impl User {
     /// # API 文档
     ///
     /// [获取手机号](https://developers.weixin.qq.com/miniprogram/dev/OpenApiDoc/user-info/phone-number/getPhoneNumber.html)
+    #[instrument(skip(self, code))]
     pub async fn get_contact(&self, code: &str, open_id: Option<&str>) -> Result<Contact> {
-        debug!("code: {}, open_id: {:?}", code, open_id);
         let query = serde_json::json!({
             "access_token":self.client.token().await?
         });
@@ -308,7 +512,7 @@ impl User {
                 .insert("openid".to_string(), serde_json::json!(open_id));
         }
 
-        let request = RequestBuilder::new(constants::PHONE_END_POINT)
+        let request = Endpoint::Phone.request()
             .query(query)
             .body(body)
             .build()?;
@@ -316,8 +520,100 @@ impl User {
         let client = &self.client.core.client;
 
         let response = client.execute(request).await?;
-        debug!("authentication response: {:#?}", &response);
+        debug!("get contact response status: {}", response.status());
+
+        Ok(response.to_json::<PhoneInfo>()?.phone_info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact(country_code: &str, pure_phone_number: &str) -> Contact {
+        serde_json::from_value(serde_json::json!({
+            "phoneNumber": format!("+{} {}", country_code, pure_phone_number),
+            "purePhoneNumber": pure_phone_number,
+            "countryCode": country_code,
+            "watermark": {"appid": "wx4f4bc4dec97d474b", "timestamp": 1477314187}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_contact_phone() {
+        let cn = contact("86", "13812341234");
+        assert_eq!(cn.masked(), "138****1234");
+        assert_eq!(cn.e164().unwrap(), "+8613812341234");
+
+        let hk = contact("852", "51234567");
+        assert_eq!(hk.masked(), "512*4567");
+        assert_eq!(hk.e164().unwrap(), "+85251234567");
+        assert_eq!(contact("+1", "2025550123").e164().unwrap(), "+12025550123");
+        assert_eq!(contact("853", "612345").masked(), "***345");
+
+        assert!(matches!(
+            contact("86", "138-1234").e164().unwrap_err().kind(),
+            ErrorKind::InvalidParameter(_)
+        ));
+        assert!(matches!(
+            contact("", "13812341234").e164().unwrap_err().kind(),
+            ErrorKind::InvalidParameter(_)
+        ));
+        let long = contact("852", "1234567890123");
+        assert!(matches!(long.e164().unwrap_err().kind(), ErrorKind::InvalidParameter(_)));
+    }
+
+    #[test]
+    fn test_contact_serde() {
+        let cn = contact("86", "13812341234");
+        let value = serde_json::to_value(&cn).unwrap();
+        assert_eq!(value["purePhoneNumber"], "13812341234");
+        assert_eq!(value["watermark"]["appid"], "wx4f4bc4dec97d474b");
+
+        // 服务端接口返回数字 countryCode
+        let mut number = value.clone();
+        number["countryCode"] = serde_json::json!(86);
+        assert_eq!(serde_json::from_value::<Contact>(number).unwrap(), cn);
+
+        // 旧版本序列化的 snake_case 数据
+        let legacy = serde_json::json!({
+            "phone_number": "+86 13812341234",
+            "pure_phone_number": "13812341234",
+            "country_code": "86",
+            "watermark": {"app_id": "wx4f4bc4dec97d474b", "timestamp": 1477314187}
+        });
+        assert_eq!(serde_json::from_value::<Contact>(legacy).unwrap(), cn);
+    }
 
-        response.to_json::<Contact>()
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn test_user_builder() {
+        let json = br#"{"nickName":"Band","gender":1,"language":"zh_CN","city":"Guangzhou",
+            "province":"Guangdong","country":"CN",
+            "avatarUrl":"https://thirdwx.qlogo.cn/mmopen/vi_32/0/132",
+            "watermark":{"appid":"wx4f4bc4dec97d474b","timestamp":1477314187}}"#;
+        let builder: UserBuilder = serde_json::from_slice(json).unwrap();
+        assert!(matches!(builder.nickname, Some(BorrowedStr(Cow::Borrowed("Band")))));
+        let info = builder.build();
+        assert_eq!(info.nickname(), Some("Band"));
+        assert_eq!(info.language(), Some("zh_CN"));
+        assert_eq!(info.city(), Some("Guangzhou"));
+
+        // 带转义的字符串无法借用，改为 owned
+        let json = br#"{"nickName":"\u5fae\u4fe1","watermark":{"appid":"wx1","timestamp":1}}"#;
+        let builder: UserBuilder = serde_json::from_slice(json).unwrap();
+        assert!(matches!(builder.nickname, Some(BorrowedStr(Cow::Owned(_)))));
+        assert_eq!(builder.build().nickname(), Some("微信"));
+
+        // getUserProfile 新版数据：昵称为空、缺少地区字段
+        let json = br#"{"nickName":"","avatarUrl":"https://thirdwx.qlogo.cn/mmopen/vi_32/0/132",
+            "watermark":{"appid":"wx4f4bc4dec97d474b","timestamp":1477314187}}"#;
+        let builder: UserBuilder = serde_json::from_slice(json).unwrap();
+        let info = builder.build();
+        assert_eq!(info.nickname(), None);
+        assert_eq!(info.gender(), 0);
+        assert_eq!(info.country(), None);
+        assert!(info.avatar().is_some());
     }
 }
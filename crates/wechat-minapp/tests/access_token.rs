@@ -1,4 +1,4 @@
-use dotenv::dotenv;
+use dotenvy::dotenv;
 use std::env;
 use std::sync::Arc;
 use wechat_minapp::{MemoryTokenStorage, StableToken};
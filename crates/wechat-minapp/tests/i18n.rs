@@ -0,0 +1,23 @@
+//! 错误信息语言切换是全局设置，单独放在一个测试进程中
+
+use wechat_minapp::i18n::{self, Language};
+use wechat_minapp::new_type::{PagePath, SceneString};
+use wechat_minapp::Error;
+
+#[test]
+fn test_english_messages() {
+    i18n::set_language(Language::En);
+
+    let error = PagePath::new("").unwrap_err();
+    assert_eq!(error.to_string(), "page path must not be empty");
+    assert_eq!(
+        Error::from(error).to_string(),
+        "invalid parameter: page path must not be empty"
+    );
+
+    let error = SceneString::new("a中").unwrap_err();
+    assert_eq!(error.to_string(), "invalid character: 中");
+
+    i18n::set_language(Language::Zh);
+    assert_eq!(PagePath::new("").unwrap_err().to_string(), "页面路径不能为空");
+}
@@ -1,16 +1,16 @@
-use dotenv::dotenv;
+use dotenvy::dotenv;
 use std::env;
 use wechat_minapp::minapp_security::{Args, MinappSecurity, Scene};
-use wechat_minapp::WechatMinappSDK;
+use wechat_minapp::WechatMinapp;
 
 /// 初始化测试客户端
-fn setup_client() -> WechatMinappSDK {
+fn setup_client() -> WechatMinapp {
     dotenv().ok();
 
     let app_id = env::var("WECHAT_APP_ID").expect("请设置 WECHAT_APP_ID 环境变量");
     let secret = env::var("WECHAT_APP_SECRET").expect("请设置 WECHAT_APP_SECRET 环境变量");
 
-    WechatMinappSDK::new(&app_id, &secret)
+    WechatMinapp::new(&app_id, &secret)
 }
 
 /// 获取测试用的用户openid
@@ -1,4 +1,4 @@
-use dotenv::dotenv;
+use dotenvy::dotenv;
 use std::env;
 use std::sync::Arc;
 use wechat_minapp::qr::{MinappEnvVersion, Qr, QrCodeArgs, Rgb};
@@ -117,7 +117,8 @@ async fn test_qr_code_with_all_parameters() {
 
     assert!(result.is_ok());
     let qr_code = result.unwrap();
-    tokio::fs::write("test_qr_code_with_all_parameters.png", qr_code.buffer())
+    qr_code
+        .save_to("test_qr_code_with_all_parameters.png")
         .await
         .expect("写入文件失败");
     assert!(!qr_code.buffer().is_empty());
@@ -129,14 +130,15 @@ async fn test_qr_code_with_only_width() {
     let qr = Qr::new(client);
     let args = QrCodeArgs::builder()
         .path("pages/index/index")
-        .width(200)
+        .width(280)
         .build()
         .unwrap();
 
     let result = qr.qr_code(args).await;
     assert!(result.is_ok());
     let qr_code = result.unwrap();
-    tokio::fs::write("test_qr_code_with_only_width.png", qr_code.buffer())
+    qr_code
+        .save_to("test_qr_code_with_only_width.png")
         .await
         .expect("写入文件失败");
     assert!(!qr_code.buffer().is_empty());
@@ -155,7 +157,8 @@ async fn test_qr_code_with_only_env_version() {
     let result = qr.qr_code(args).await;
     assert!(result.is_ok());
     let qr_code = result.unwrap();
-    tokio::fs::write("test_qr_code_with_only_env_version.png", qr_code.buffer())
+    qr_code
+        .save_to("test_qr_code_with_only_env_version.png")
         .await
         .expect("写入文件失败");
     assert!(!qr_code.buffer().is_empty());
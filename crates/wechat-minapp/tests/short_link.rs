@@ -1,4 +1,4 @@
-use dotenv::dotenv;
+use dotenvy::dotenv;
 use std::env;
 use std::sync::Arc;
 use wechat_minapp::link::{Link, ShortLinkArgs};
@@ -31,7 +31,7 @@ fn test_short_link_args_build_success() {
 
     assert_eq!(args.path(), "pages/index/index");
     assert!(args.page_title().is_none());
-    assert_eq!(args.is_permanent(), false);
+    assert!(!args.is_permanent());
 }
 
 #[test]
@@ -43,7 +43,7 @@ fn test_short_link_args_build_with_all_fields() {
 
     assert_eq!(args.path(), "pages/detail/detail");
     assert_eq!(args.page_title(), Some("page title".to_string()));
-    assert_eq!(args.is_permanent(), true);
+    assert!(args.is_permanent());
 }
 
 #[test]
@@ -75,6 +75,24 @@ fn test_short_link_args_build_path_boundary() {
     assert!(result.is_ok());
 }
 
+#[test]
+fn test_short_link_args_build_reserved_parameter() {
+    let result = ShortLinkArgs::builder()
+        .path("pages/index/index?scancode_time=1")
+        .build();
+    assert!(result.is_err());
+
+    if let Err(err) = result {
+        assert!(err.to_string().contains("scancode_time"));
+    }
+
+    let args = ShortLinkArgs::builder()
+        .path("pages/index/index?id=1")
+        .build()
+        .expect("带参数的页面路径应该构建成功");
+    assert_eq!(args.path(), "pages/index/index?id=1");
+}
+
 #[tokio::test]
 async fn test_short_link_with_all_parameters() {
     let client = setup_client();
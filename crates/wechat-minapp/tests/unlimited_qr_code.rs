@@ -1,4 +1,4 @@
-use dotenv::dotenv;
+use dotenvy::dotenv;
 use std::env;
 use std::sync::Arc;
 use wechat_minapp::qr::{MinappEnvVersion, Qr, Rgb, UnlimitedQrCodeArgs};
@@ -46,7 +46,7 @@ fn test_unlimited_qr_code_args_build_success() {
         .build()
         .expect("构建应该成功");
 
-    assert_eq!(args.page(), "pages/index/index");
+    assert_eq!(args.page().as_deref(), Some("pages/index/index"));
     assert!(args.width().is_none());
     assert!(args.auto_color().is_none());
     assert!(args.line_color().is_none());
@@ -67,7 +67,7 @@ fn test_unlimited_qr_code_args_build_with_all_fields() {
         .build()
         .expect("构建应该成功");
 
-    assert_eq!(args.page(), "pages/detail/detail");
+    assert_eq!(args.page().as_deref(), Some("pages/detail/detail"));
     assert_eq!(args.width(), Some(400));
     assert!(args.line_color().is_some());
     assert_eq!(args.is_hyaline(), Some(true));
@@ -78,15 +78,13 @@ fn test_unlimited_qr_code_args_build_with_all_fields() {
 }
 
 #[test]
-fn test_unlimited_qr_code_args_build_missing_path() {
-    let result = UnlimitedQrCodeArgs::builder()
+fn test_unlimited_qr_code_args_build_without_path() {
+    // 不设置 page 时默认跳主页
+    let args = UnlimitedQrCodeArgs::builder()
         .scene(format!("i={}", get_test_openid()))
-        .build();
-    assert!(result.is_err());
-
-    if let Err(err) = result {
-        assert!(err.to_string().contains("小程序页面路径不能为空"));
-    }
+        .build()
+        .unwrap();
+    assert_eq!(args.page(), None);
 }
 
 #[test]
@@ -156,11 +154,9 @@ async fn test_unlimited_qr_code_with_all_parameters() {
 
     assert!(result.is_ok());
     let qr_code = result.unwrap();
-    tokio::fs::write(
-        "test_unlimited_qr_code_with_all_parameters.png",
-        qr_code.buffer(),
-    )
-    .await
+    qr_code
+        .save_to("test_unlimited_qr_code_with_all_parameters.png")
+        .await
     .expect("写入文件失败");
     assert!(!qr_code.buffer().is_empty());
 }
@@ -172,18 +168,16 @@ async fn test_unlimited_qr_code_with_only_width() {
     let args = UnlimitedQrCodeArgs::builder()
         .page("pages/index/index")
         .scene(format!("i={}", get_test_openid()))
-        .width(200)
+        .width(280)
         .build()
         .unwrap();
 
     let result = qr.unlimited_qr_code(args).await;
     assert!(result.is_ok());
     let qr_code = result.unwrap();
-    tokio::fs::write(
-        "test_unlimited_qr_code_with_only_width.png",
-        qr_code.buffer(),
-    )
-    .await
+    qr_code
+        .save_to("test_unlimited_qr_code_with_only_width.png")
+        .await
     .expect("写入文件失败");
     assert!(!qr_code.buffer().is_empty());
 }
@@ -202,11 +196,9 @@ async fn test_unlimited_qr_code_with_only_env_version() {
     let result = qr.unlimited_qr_code(args).await;
     assert!(result.is_ok());
     let qr_code = result.unwrap();
-    tokio::fs::write(
-        "test_unlimited_qr_code_with_only_env_version.png",
-        qr_code.buffer(),
-    )
-    .await
+    qr_code
+        .save_to("test_unlimited_qr_code_with_only_env_version.png")
+        .await
     .expect("写入文件失败");
     assert!(!qr_code.buffer().is_empty());
 }
@@ -9,15 +9,35 @@
 //! - 异步支持
 //! - 丰富的接口支持
 //! - HTTP 客户端和接口调用凭据存储读取方式分离
+//!
+//! # Feature
+//!
+//! - `reqwest`（默认开启）：基于 reqwest 的默认 HTTP 客户端（[`WechatMp::new`]）
+//! - `tokio`（默认开启）：依赖 tokio 运行时的后台任务、超时与取消、请求重试
+//! - `log`：没有设置 tracing subscriber 时，内部日志通过 `log` crate 输出
+//! - `simd-json`：使用 simd-json 解析响应体
 
 // 重新导出 core 的内容
+#[cfg(feature = "reqwest")]
+pub use wechat_core::client::ReqwestHttpClient;
+#[cfg(feature = "tokio")]
+pub use wechat_core::{
+    client::{RetryHttpClient, RetryOn, RetryPolicy},
+    utils::{BackgroundTask, CallExt, CancellationToken},
+};
 pub use wechat_core::{
     client::{
-        HttpClient, MemoryTokenStorage, NonStableToken, ReqwestHttpClient, StableToken, TokenType,
-        TokenStorage, WechatCore, AppConfig, AccessToken,
+        CachedHttpClient, ConcurrencyLimitHttpClient, CurlLogHttpClient, DefaultHeadersHttpClient,
+        DryRunHttpClient, HttpClient, MemoryResponseCache, MemoryTokenStorage, NonStableToken,
+        RecordedRequest, RefreshCoordinator, RefreshStats, ResponseCache, StableToken,
+        StaticTokenStorage, TokenStore, TokenType, TokenStorage, WechatCore, AppConfig,
+        AccessToken,
+    },
+    error::{Error, ErrorCode, ErrorKind},
+    utils::{
+        Multipart, Part, RequestBuilder, ResponseExt, MpResponse, Redacted, build_request,
+        parse_query, parse_url,
     },
-    error::{Error, ErrorCode},
-    utils::{RequestBuilder, ResponseExt, MpResponse, build_request, parse_query, parse_url},
     Result,
 };
 
@@ -32,14 +52,33 @@ pub struct WechatMp {
     pub core: WechatCore,
 }
 
+/// 子模块可以直接从客户端的引用创建（例如 `TemplateMessage::new(&client)`），
+/// 内部的 HTTP 客户端和令牌存储通过 `Arc` 共享，不会复制
+impl From<&WechatMp> for WechatMp {
+    fn from(client: &WechatMp) -> Self {
+        client.clone()
+    }
+}
+
 impl WechatMp {
-    /// 使用默认配置创建客户端
+    /// 使用默认配置创建客户端，需要启用 `reqwest` feature
+    #[cfg(feature = "reqwest")]
     pub fn new(app_id: &str, secret: &str) -> Self {
         WechatMp {
             core: WechatCore::new(app_id, secret),
         }
     }
 
+    /// 校验 App ID 与 Secret 的格式后创建客户端，需要启用 `reqwest` feature
+    ///
+    /// 校验规则见 [`AppConfig::validate`]，格式不正确时返回 [`ErrorKind::InvalidParameter`]。
+    #[cfg(feature = "reqwest")]
+    pub fn try_new(app_id: &str, secret: &str) -> Result<Self> {
+        Ok(WechatMp {
+            core: WechatCore::try_new(app_id, secret)?,
+        })
+    }
+
     /// 使用自定义配置创建客户端
     pub fn custom(http_client: Arc<dyn HttpClient>, token_storage: Arc<dyn TokenStorage>) -> Self {
         WechatMp {
@@ -47,6 +86,43 @@ impl WechatMp {
         }
     }
 
+    /// 返回使用指定 access_token 的客户端，适用于 access_token 由其他系统下发的场景
+    ///
+    /// 返回的客户端与原客户端共用 HTTP 客户端，但不再从 `TokenStorage` 取值，也不会自行刷新。
+    /// 各接口模块通过 `new` 传入即可：
+    ///
+    /// ```no_run
+    /// use wechatmp::WechatMp;
+    /// use wechatmp::mp_message::TemplateMessage;
+    ///
+    /// let client = WechatMp::new("app_id", "secret");
+    /// let message = TemplateMessage::new(client.with_token("ACCESS_TOKEN"));
+    /// ```
+    pub fn with_token(&self, access_token: impl Into<String>) -> Self {
+        WechatMp {
+            core: self.core.with_token(access_token),
+        }
+    }
+
+    /// 返回限制最大并发请求数的客户端，超出上限的请求排队等待
+    ///
+    /// 返回的客户端及其克隆共享同一个并发上限，适用于批量生成小程序码等批量任务。
+    pub fn with_concurrency_limit(&self, max_concurrency: usize) -> Self {
+        WechatMp {
+            core: self.core.with_concurrency_limit(max_concurrency),
+        }
+    }
+
+    /// 返回请求失败时按 `policy` 重试的客户端，原客户端不受影响
+    ///
+    /// 适合只对查询类等幂等接口启用重试，发送消息等接口继续使用原客户端。
+    #[cfg(feature = "tokio")]
+    pub fn with_retry(&self, policy: RetryPolicy) -> Self {
+        WechatMp {
+            core: self.core.with_retry(policy),
+        }
+    }
+
     /// 获取 access token
     pub async fn token(&self) -> Result<String> {
         self.core.token().await
@@ -61,4 +137,24 @@ impl WechatMp {
     pub fn client(&self) -> &WechatCore {
         &self.core
     }
+
+    /// 模板消息接口
+    pub fn template_message(&self) -> mp_message::TemplateMessage {
+        mp_message::TemplateMessage::new(self)
+    }
+
+    /// 订阅通知接口
+    pub fn subscribe_notify(&self) -> mp_message::subscribe_notify::SubscribeNotify {
+        mp_message::subscribe_notify::SubscribeNotify::new(self)
+    }
+
+    /// 群发消息接口
+    pub fn mass_message(&self) -> mp_message::mass_message::MassMessage {
+        mp_message::mass_message::MassMessage::new(self)
+    }
+
+    /// 自动回复接口
+    pub fn auto_reply(&self) -> mp_message::autoreply::AutoReply {
+        mp_message::autoreply::AutoReply::new(self)
+    }
 }
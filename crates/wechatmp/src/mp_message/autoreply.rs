@@ -5,10 +5,6 @@
 
 use crate::constants;
 use serde::{Deserialize, Serialize};
-use tracing::debug;
-use wechat_core::utils::{RequestBuilder, ResponseExt};
-use wechat_core::Result;
-use http::Method;
 
 use crate::WechatMp;
 
@@ -19,29 +15,19 @@ pub struct AutoReply {
 }
 
 impl AutoReply {
-    pub fn new(client: WechatMp) -> Self {
-        AutoReply { client }
+    pub fn new(client: impl Into<WechatMp>) -> Self {
+        AutoReply {
+            client: client.into(),
+        }
     }
 
-    /// 获取自动回复规则
-    ///
-    /// 获取公众号当前使用的自动回复规则，包括关注后自动回复、消息自动回复、关键词自动回复
-    pub async fn get_current_autoreply_info(&self) -> Result<AutoReplyInfoResponse> {
-        debug!("get current autoreply info");
 
-        let query = serde_json::json!({
-            "access_token": self.client.token().await?
-        });
-
-        let request = RequestBuilder::new(constants::MP_GET_AUTOREPLY_END_POINT)
-            .method(Method::GET)
-            .query(query)
-            .build()?;
-
-        let client = &self.client.core.client;
-        let response = client.execute(request).await?;
-
-        response.to_json::<AutoReplyInfoResponse>()
+    wechat_core::endpoint! {
+        /// 获取自动回复规则
+        ///
+        /// 获取公众号当前使用的自动回复规则，包括关注后自动回复、消息自动回复、关键词自动回复
+        pub GET get_current_autoreply_info() -> AutoReplyInfoResponse =
+            constants::MP_GET_AUTOREPLY_END_POINT;
     }
 }
 
@@ -50,7 +36,7 @@ impl AutoReply {
 // ========================================
 
 /// 自动回复内容
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct AutoReplyContent {
     #[serde(rename = "type")]
     pub type_: String,
@@ -58,7 +44,7 @@ pub struct AutoReplyContent {
 }
 
 /// 图文消息条目
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct NewsArticle {
     pub title: String,
     pub digest: String,
@@ -70,13 +56,13 @@ pub struct NewsArticle {
 }
 
 /// 图文消息信息
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct NewsInfo {
     pub list: Vec<NewsArticle>,
 }
 
 /// 关键词信息
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct KeywordInfo {
     #[serde(rename = "type")]
     pub type_: String,
@@ -85,7 +71,7 @@ pub struct KeywordInfo {
 }
 
 /// 回复信息
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ReplyInfo {
     #[serde(rename = "type")]
     pub type_: String,
@@ -95,7 +81,7 @@ pub struct ReplyInfo {
 }
 
 /// 关键词自动回复规则
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct KeywordRule {
     pub rule_name: String,
     pub create_time: i64,
@@ -105,13 +91,13 @@ pub struct KeywordRule {
 }
 
 /// 关键词自动回复信息
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct KeywordAutoReplyInfo {
     pub list: Vec<KeywordRule>,
 }
 
 /// 获取自动回复规则响应
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct AutoReplyInfoResponse {
     pub is_add_friend_reply_open: i32,
     pub is_autoreply_open: i32,
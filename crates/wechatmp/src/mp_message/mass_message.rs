@@ -5,10 +5,6 @@
 
 use crate::constants;
 use serde::{Deserialize, Serialize};
-use tracing::debug;
-use wechat_core::utils::{RequestBuilder, ResponseExt};
-use wechat_core::Result;
-use http::Method;
 
 use crate::WechatMp;
 
@@ -19,9 +15,12 @@ pub struct MassMessage {
 }
 
 impl MassMessage {
-    pub fn new(client: WechatMp) -> Self {
-        MassMessage { client }
+    pub fn new(client: impl Into<WechatMp>) -> Self {
+        MassMessage {
+            client: client.into(),
+        }
     }
+
 }
 
 // ========================================
@@ -29,7 +28,7 @@ impl MassMessage {
 // ========================================
 
 /// 微信 API 基础响应
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct MpResponse {
     pub errcode: i32,
     pub errmsg: String,
@@ -40,7 +39,7 @@ pub struct MpResponse {
 // ========================================
 
 /// 群发消息响应
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct MassSendResponse {
     #[serde(rename = "type")]
     pub type_: Option<String>,
@@ -53,7 +52,7 @@ pub struct MassSendResponse {
 // ========================================
 
 /// 查询群发消息发送状态响应
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct MassMsgGetResponse {
     pub msg_id: String,
     pub msg_status: String,
@@ -64,7 +63,7 @@ pub struct MassMsgGetResponse {
 // ========================================
 
 /// 获取群发速度响应
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct GetSpeedResponse {
     pub speed: i32,
     pub realspeed: i32,
@@ -75,7 +74,7 @@ pub struct GetSpeedResponse {
 // ========================================
 
 /// 设置群发速度请求参数
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SetSpeedArgs {
     pub speed: i32,
 }
@@ -85,7 +84,7 @@ pub struct SetSpeedArgs {
 // ========================================
 
 /// 删除群发消息请求参数
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct DeleteMassMsgArgs {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub msg_id: Option<i64>,
@@ -99,7 +98,7 @@ pub struct DeleteMassMsgArgs {
 // ========================================
 
 /// 查询群发消息发送状态请求参数
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct MassMsgGetArgs {
     pub msg_id: String,
 }
@@ -109,7 +108,7 @@ pub struct MassMsgGetArgs {
 // ========================================
 
 /// 图文消息文章
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Article {
     pub title: String,
     pub thumb_media_id: String,
@@ -129,13 +128,13 @@ pub struct Article {
 }
 
 /// 上传图文消息素材请求参数
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct UploadNewsMsgArgs {
     pub articles: Vec<Article>,
 }
 
 /// 上传图文消息素材响应
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct UploadNewsMsgResponse {
     #[serde(rename = "type")]
     pub type_: Option<String>,
@@ -148,200 +147,74 @@ pub struct UploadNewsMsgResponse {
 // ========================================
 
 /// 预览消息响应
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PreviewResponse {
     pub msg_id: Option<i64>,
 }
 
 impl MassMessage {
-    /// 根据标签群发消息
-    ///
-    /// 发送消息体为 `serde_json::Value`，根据 msgtype 不同结构不同：
-    ///
-    /// ```json
-    /// {
-    ///   "filter": {"is_to_all": true, "tag_id": "2"},
-    ///   "msgtype": "text",
-    ///   "text": {"content": "CONTENT"},
-    ///   "clientmsgid": "xxx"
-    /// }
-    /// ```
-    ///
-    /// 支持的 msgtype: mpnews, text, voice, image, mpvideo, wxcard
-    pub async fn send_all(&self, body: serde_json::Value) -> Result<MassSendResponse> {
-        debug!("mass send all body {:?}", &body);
-
-        let query = serde_json::json!({
-            "access_token": self.client.token().await?
-        });
-
-        let request = RequestBuilder::new(constants::MP_MASS_SEND_ALL_END_POINT)
-            .query(query)
-            .body(body)
-            .build()?;
-
-        let client = &self.client.core.client;
-        let response = client.execute(request).await?;
-
-        response.to_json::<MassSendResponse>()
-    }
-
-    /// 根据 OpenID 列表群发消息
-    ///
-    /// 发送体为 `serde_json::Value`，结构与 `send_all` 类似但使用 `touser` 字段
-    pub async fn mass_send(&self, body: serde_json::Value) -> Result<MassSendResponse> {
-        debug!("mass send body {:?}", &body);
-
-        let query = serde_json::json!({
-            "access_token": self.client.token().await?
-        });
-
-        let request = RequestBuilder::new(constants::MP_MASS_SEND_END_POINT)
-            .query(query)
-            .body(body)
-            .build()?;
-
-        let client = &self.client.core.client;
-        let response = client.execute(request).await?;
-
-        response.to_json::<MassSendResponse>()
-    }
-
-    /// 删除群发消息
-    ///
-    /// 群发之后，随时可以通过该接口删除群发
-    pub async fn delete(&self, args: DeleteMassMsgArgs) -> Result<MpResponse> {
-        debug!("delete mass msg args {:?}", &args);
-
-        let query = serde_json::json!({
-            "access_token": self.client.token().await?
-        });
-
-        let body = serde_json::to_value(args)?;
-
-        let request = RequestBuilder::new(constants::MP_MASS_DELETE_END_POINT)
-            .query(query)
-            .body(body)
-            .build()?;
-
-        let client = &self.client.core.client;
-        let response = client.execute(request).await?;
-
-        response.to_json::<MpResponse>()
-    }
-
-    /// 查询群发消息发送状态
-    pub async fn get_status(&self, args: MassMsgGetArgs) -> Result<MassMsgGetResponse> {
-        debug!("get mass msg status args {:?}", &args);
-
-        let query = serde_json::json!({
-            "access_token": self.client.token().await?
-        });
-
-        let body = serde_json::to_value(args)?;
-
-        let request = RequestBuilder::new(constants::MP_MASS_GET_END_POINT)
-            .query(query)
-            .body(body)
-            .build()?;
-
-        let client = &self.client.core.client;
-        let response = client.execute(request).await?;
-
-        response.to_json::<MassMsgGetResponse>()
-    }
-
-    /// 获取群发速度
-    pub async fn get_speed(&self) -> Result<GetSpeedResponse> {
-        debug!("get mass speed");
-
-        let query = serde_json::json!({
-            "access_token": self.client.token().await?
-        });
-
-        let request = RequestBuilder::new(constants::MP_MASS_SPEED_GET_END_POINT)
-            .method(Method::GET)
-            .query(query)
-            .build()?;
-
-        let client = &self.client.core.client;
-        let response = client.execute(request).await?;
-
-        response.to_json::<GetSpeedResponse>()
-    }
-
-    /// 设置群发速度
-    ///
-    /// speed: 0=80w/分钟, 1=60w/分钟, 2=45w/分钟, 3=30w/分钟, 4=10w/分钟
-    pub async fn set_speed(&self, args: SetSpeedArgs) -> Result<MpResponse> {
-        debug!("set mass speed args {:?}", &args);
-
-        let query = serde_json::json!({
-            "access_token": self.client.token().await?
-        });
-
-        let body = serde_json::to_value(args)?;
-
-        let request = RequestBuilder::new(constants::MP_MASS_SPEED_SET_END_POINT)
-            .query(query)
-            .body(body)
-            .build()?;
-
-        let client = &self.client.core.client;
-        let response = client.execute(request).await?;
-
-        response.to_json::<MpResponse>()
-    }
-
-    /// 上传图文消息素材
-    ///
-    /// 用于群发图文消息时上传素材
-    pub async fn upload_news(&self, args: UploadNewsMsgArgs) -> Result<UploadNewsMsgResponse> {
-        debug!("upload news msg args {:?}", &args);
-
-        let query = serde_json::json!({
-            "access_token": self.client.token().await?
-        });
-
-        let body = serde_json::to_value(args)?;
-
-        let request = RequestBuilder::new(constants::MP_MEDIA_UPLOAD_NEWS_END_POINT)
-            .query(query)
-            .body(body)
-            .build()?;
-
-        let client = &self.client.core.client;
-        let response = client.execute(request).await?;
-
-        response.to_json::<UploadNewsMsgResponse>()
-    }
-
-    /// 预览消息
-    ///
-    /// 发送消息给指定用户，在手机端查看消息的样式和排版
-    ///
-    /// `body` 为消息体，根据 msgtype 不同结构不同：
-    ///
-    /// ```json
-    /// {"touser": "OPENID", "msgtype": "text", "text": {"content": "hello"}}
-    /// ```
-    ///
-    /// 支持 msgtype: mpnews, text, voice, music, image, mpvideo, wxcard
-    pub async fn preview(&self, body: serde_json::Value) -> Result<PreviewResponse> {
-        debug!("preview body {:?}", &body);
-
-        let query = serde_json::json!({
-            "access_token": self.client.token().await?
-        });
-
-        let request = RequestBuilder::new(constants::MP_MASS_PREVIEW_END_POINT)
-            .query(query)
-            .body(body)
-            .build()?;
-
-        let client = &self.client.core.client;
-        let response = client.execute(request).await?;
-
-        response.to_json::<PreviewResponse>()
+    wechat_core::endpoint! {
+        /// 根据标签群发消息
+        ///
+        /// 发送消息体为 `serde_json::Value`，根据 msgtype 不同结构不同：
+        ///
+        /// ```json
+        /// {
+        ///   "filter": {"is_to_all": true, "tag_id": "2"},
+        ///   "msgtype": "text",
+        ///   "text": {"content": "CONTENT"},
+        ///   "clientmsgid": "xxx"
+        /// }
+        /// ```
+        ///
+        /// 支持的 msgtype: mpnews, text, voice, image, mpvideo, wxcard
+        pub POST send_all(body: serde_json::Value) -> MassSendResponse =
+            constants::MP_MASS_SEND_ALL_END_POINT;
+
+        /// 根据 OpenID 列表群发消息
+        ///
+        /// 发送体为 `serde_json::Value`，结构与 `send_all` 类似但使用 `touser` 字段
+        pub POST mass_send(body: serde_json::Value) -> MassSendResponse =
+            constants::MP_MASS_SEND_END_POINT;
+
+        /// 删除群发消息
+        ///
+        /// 群发之后，随时可以通过该接口删除群发
+        pub POST delete(args: DeleteMassMsgArgs) -> MpResponse =
+            constants::MP_MASS_DELETE_END_POINT;
+
+        /// 查询群发消息发送状态
+        pub POST idempotent get_status(args: MassMsgGetArgs) -> MassMsgGetResponse =
+            constants::MP_MASS_GET_END_POINT;
+
+        /// 获取群发速度
+        pub GET get_speed() -> GetSpeedResponse =
+            constants::MP_MASS_SPEED_GET_END_POINT;
+
+        /// 设置群发速度
+        ///
+        /// speed: 0=80w/分钟, 1=60w/分钟, 2=45w/分钟, 3=30w/分钟, 4=10w/分钟
+        pub POST set_speed(args: SetSpeedArgs) -> MpResponse =
+            constants::MP_MASS_SPEED_SET_END_POINT;
+
+        /// 上传图文消息素材
+        ///
+        /// 用于群发图文消息时上传素材
+        pub POST upload_news(args: UploadNewsMsgArgs) -> UploadNewsMsgResponse =
+            constants::MP_MEDIA_UPLOAD_NEWS_END_POINT;
+
+        /// 预览消息
+        ///
+        /// 发送消息给指定用户，在手机端查看消息的样式和排版
+        ///
+        /// `body` 为消息体，根据 msgtype 不同结构不同：
+        ///
+        /// ```json
+        /// {"touser": "OPENID", "msgtype": "text", "text": {"content": "hello"}}
+        /// ```
+        ///
+        /// 支持 msgtype: mpnews, text, voice, music, image, mpvideo, wxcard
+        pub POST preview(body: serde_json::Value) -> PreviewResponse =
+            constants::MP_MASS_PREVIEW_END_POINT;
     }
 }
@@ -42,7 +42,10 @@ pub struct TemplateMessage {
 }
 
 impl TemplateMessage {
-    pub fn new(client: WechatMp) -> Self {
-        TemplateMessage { client }
+    pub fn new(client: impl Into<WechatMp>) -> Self {
+        TemplateMessage {
+            client: client.into(),
+        }
     }
+
 }
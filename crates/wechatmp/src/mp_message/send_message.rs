@@ -45,12 +45,12 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tracing::debug;
 use wechat_core::utils::{RequestBuilder, ResponseExt};
-use wechat_core::{Error, Result};
+use wechat_core::{ErrorKind, Result};
 
 /// 订阅消息
 ///
 /// 用于发送一次性订阅消息的结构
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SendMessageArgs {
     pub touser: String,                     // 接收者openid
     pub template_id: String,                // 模板ID
@@ -63,14 +63,14 @@ pub struct SendMessageArgs {
 /// 服务号信息
 ///
 /// 用于指定跳转服务号时的目标
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct MinappProgram {
     pub appid: String,    // 服务号appid
     pub pagepath: String, // 服务号跳转路径
 }
 
 /// 订阅消息发送响应
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SendMessageResponse {
     pub msgid: Option<String>,  // 消息ID
     pub errcode: Option<i32>,   // 错误码
@@ -172,15 +172,15 @@ impl SendMessageArgsBuilder {
     pub fn build(self) -> Result<SendMessageArgs> {
         let touser = self
             .touser
-            .ok_or_else(|| Error::InvalidParameter("接收者openid不能为空".to_string()))?;
+            .ok_or_else(|| ErrorKind::InvalidParameter("接收者openid不能为空".to_string()))?;
 
         let template_id = self
             .template_id
-            .ok_or_else(|| Error::InvalidParameter("模板ID不能为空".to_string()))?;
+            .ok_or_else(|| ErrorKind::InvalidParameter("模板ID不能为空".to_string()))?;
 
         let data = self
             .data
-            .ok_or_else(|| Error::InvalidParameter("模板数据不能为空".to_string()))?;
+            .ok_or_else(|| ErrorKind::InvalidParameter("模板数据不能为空".to_string()))?;
 
         // 验证数据格式
         Self::validate_data(&data)?;
@@ -200,10 +200,11 @@ impl SendMessageArgsBuilder {
         if let Value::Object(map) = data {
             for (key, value) in map {
                 if key.chars().count() > 20 {
-                    return Err(Error::InvalidParameter(format!(
+                    return Err(ErrorKind::InvalidParameter(format!(
                         "字段名'{}'长度不能超过20个字符",
                         key
-                    )));
+                    ))
+                    .into());
                 }
 
                 if let Value::Object(item) = value {
@@ -211,29 +212,33 @@ impl SendMessageArgsBuilder {
                         if let Value::String(s) = val
                             && s.chars().count() > 20
                         {
-                            return Err(Error::InvalidParameter(format!(
+                            return Err(ErrorKind::InvalidParameter(format!(
                                 "字段'{}'的值长度不能超过20个字符",
                                 key
-                            )));
+                            ))
+                            .into());
                         }
                     } else {
-                        return Err(Error::InvalidParameter(format!(
+                        return Err(ErrorKind::InvalidParameter(format!(
                             "字段'{}'缺少value属性",
                             key
-                        )));
+                        ))
+                        .into());
                     }
                 } else {
-                    return Err(Error::InvalidParameter(format!(
+                    return Err(ErrorKind::InvalidParameter(format!(
                         "字段'{}'格式不正确，应为{{value: string}}",
                         key
-                    )));
+                    ))
+                    .into());
                 }
             }
             Ok(())
         } else {
-            Err(Error::InvalidParameter(
+            Err(ErrorKind::InvalidParameter(
                 "模板数据必须是对象类型".to_string(),
-            ))
+            )
+            .into())
         }
     }
 }
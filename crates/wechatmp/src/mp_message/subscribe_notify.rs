@@ -5,10 +5,8 @@
 
 use crate::constants;
 use serde::{Deserialize, Serialize};
-use tracing::debug;
-use wechat_core::utils::{RequestBuilder, ResponseExt};
+use wechat_core::utils::{Paginator, Stream};
 use wechat_core::Result;
-use http::Method;
 
 use crate::WechatMp;
 
@@ -19,9 +17,12 @@ pub struct SubscribeNotify {
 }
 
 impl SubscribeNotify {
-    pub fn new(client: WechatMp) -> Self {
-        SubscribeNotify { client }
+    pub fn new(client: impl Into<WechatMp>) -> Self {
+        SubscribeNotify {
+            client: client.into(),
+        }
     }
+
 }
 
 // ========================================
@@ -29,7 +30,7 @@ impl SubscribeNotify {
 // ========================================
 
 /// 发送订阅通知请求参数
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SendNewSubscribeMsgArgs {
     pub touser: String,
     pub template_id: String,
@@ -45,7 +46,7 @@ pub struct SendNewSubscribeMsgArgs {
 // ========================================
 
 /// 删除模板请求参数
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct DelTemplateArgs {
     #[serde(rename = "priTmplId")]
     pub pri_tmpl_id: String,
@@ -56,7 +57,7 @@ pub struct DelTemplateArgs {
 // ========================================
 
 /// 关键词枚举值
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct KeywordEnumValue {
     #[serde(rename = "keywordCode")]
     pub keyword_code: String,
@@ -65,7 +66,7 @@ pub struct KeywordEnumValue {
 }
 
 /// 模板条目
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TemplateItem {
     #[serde(rename = "priTmplId")]
     pub pri_tmpl_id: String,
@@ -80,7 +81,7 @@ pub struct TemplateItem {
 }
 
 /// 获取已有模板列表响应
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct GetTemplatesResponse {
     pub data: Vec<TemplateItem>,
 }
@@ -90,7 +91,7 @@ pub struct GetTemplatesResponse {
 // ========================================
 
 /// 关键词信息
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct KeywordInfo {
     pub kid: i32,
     pub name: String,
@@ -99,7 +100,7 @@ pub struct KeywordInfo {
 }
 
 /// 获取模板关键词响应
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct GetTemplateKeywordsResponse {
     pub count: i32,
     pub data: Vec<KeywordInfo>,
@@ -110,14 +111,14 @@ pub struct GetTemplateKeywordsResponse {
 // ========================================
 
 /// 类目信息
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct CategoryInfo {
     pub id: i32,
     pub name: String,
 }
 
 /// 获取类目响应
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct GetCategoryResponse {
     pub data: Vec<CategoryInfo>,
 }
@@ -127,7 +128,7 @@ pub struct GetCategoryResponse {
 // ========================================
 
 /// 公共模板标题信息
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PubTemplateTitle {
     pub tid: i32,
     pub title: String,
@@ -138,7 +139,7 @@ pub struct PubTemplateTitle {
 }
 
 /// 获取类目下的公共模板响应
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct GetPubTemplateTitlesResponse {
     pub count: i32,
     pub data: Vec<PubTemplateTitle>,
@@ -149,7 +150,7 @@ pub struct GetPubTemplateTitlesResponse {
 // ========================================
 
 /// 选用模板请求参数
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct AddTemplateArgs {
     pub tid: String,
     #[serde(rename = "kidList")]
@@ -159,7 +160,7 @@ pub struct AddTemplateArgs {
 }
 
 /// 选用模板响应
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct AddTemplateResponse {
     #[serde(rename = "priTmplId")]
     pub pri_tmpl_id: String,
@@ -170,182 +171,105 @@ pub struct AddTemplateResponse {
 // ========================================
 
 /// 微信 API 基础响应
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct MpResponse {
     pub errcode: i32,
     pub errmsg: String,
 }
 
-impl SubscribeNotify {
-    /// 发送订阅通知
-    ///
-    /// 发送订阅通知给用户
-    pub async fn send(&self, args: SendNewSubscribeMsgArgs) -> Result<MpResponse> {
-        debug!("send subscribe notify args {:?}", &args);
-
-        let query = serde_json::json!({
-            "access_token": self.client.token().await?
-        });
-
-        let body = serde_json::to_value(args)?;
-
-        let request = RequestBuilder::new(constants::MP_SUBSCRIBE_SEND_END_POINT)
-            .query(query)
-            .body(body)
-            .build()?;
-
-        let client = &self.client.core.client;
-        let response = client.execute(request).await?;
-
-        response.to_json::<MpResponse>()
-    }
-
-    /// 删除模板
-    ///
-    /// 删除私有模板库中的模板
-    pub async fn del_template(&self, args: DelTemplateArgs) -> Result<MpResponse> {
-        debug!("del template args {:?}", &args);
-
-        let query = serde_json::json!({
-            "access_token": self.client.token().await?
-        });
-
-        let body = serde_json::to_value(args)?;
-
-        let request = RequestBuilder::new(constants::MP_SUBSCRIBE_TEMPLATE_DEL_END_POINT)
-            .query(query)
-            .body(body)
-            .build()?;
-
-        let client = &self.client.core.client;
-        let response = client.execute(request).await?;
-
-        response.to_json::<MpResponse>()
-    }
-
-    /// 获取已有模板列表
-    ///
-    /// 获取当前帐号下的已有模板列表
-    pub async fn get_templates(&self) -> Result<GetTemplatesResponse> {
-        debug!("get templates");
-
-        let query = serde_json::json!({
-            "access_token": self.client.token().await?
-        });
-
-        let request = RequestBuilder::new(constants::MP_SUBSCRIBE_TEMPLATE_LIST_END_POINT)
-            .method(Method::GET)
-            .query(query)
-            .build()?;
+/// 获取类目下的公共模板时每页的最大条数
+const PUB_TEMPLATE_TITLES_PAGE_SIZE: usize = 30;
 
-        let client = &self.client.core.client;
-        let response = client.execute(request).await?;
-
-        response.to_json::<GetTemplatesResponse>()
+impl SubscribeNotify {
+    wechat_core::endpoint! {
+        /// 发送订阅通知
+        ///
+        /// 发送订阅通知给用户
+        pub POST send(args: SendNewSubscribeMsgArgs) -> MpResponse =
+            constants::MP_SUBSCRIBE_SEND_END_POINT;
+
+        /// 删除模板
+        ///
+        /// 删除私有模板库中的模板
+        pub POST del_template(args: DelTemplateArgs) -> MpResponse =
+            constants::MP_SUBSCRIBE_TEMPLATE_DEL_END_POINT;
+
+        /// 获取已有模板列表
+        ///
+        /// 获取当前帐号下的已有模板列表
+        pub GET get_templates() -> GetTemplatesResponse =
+            constants::MP_SUBSCRIBE_TEMPLATE_LIST_END_POINT;
+
+        /// 获取模板关键词
+        ///
+        /// 获取模板标题下的关键词列表
+        ///
+        /// # 参数
+        ///
+        /// - `tid`: 模板标题 id
+        pub GET get_template_keywords(tid: &str) -> GetTemplateKeywordsResponse =
+            constants::MP_SUBSCRIBE_TEMPLATE_KEYWORDS_END_POINT;
+
+        /// 获取类目
+        ///
+        /// 获取小程序、公众号所属类目用于查询公共模板
+        pub GET get_category() -> GetCategoryResponse =
+            constants::MP_SUBSCRIBE_CATEGORY_LIST_END_POINT;
+
+        /// 获取类目下的公共模板
+        ///
+        /// 获取帐号所属类目下的公共模板，可从中选用模板使用
+        ///
+        /// # 参数
+        ///
+        /// - `ids`: 类目 id，多个用逗号隔开
+        /// - `start`: 分页起始位置
+        /// - `limit`: 拉取条数，最大 30
+        pub GET get_pub_template_titles(ids: &str, start: i32, limit: i32)
+            -> GetPubTemplateTitlesResponse = constants::MP_SUBSCRIBE_TEMPLATE_TITLES_END_POINT;
     }
 
-    /// 获取模板关键词
+    /// 逐条拉取类目下的全部公共模板
     ///
-    /// 获取模板标题下的关键词列表
+    /// 基于 [`get_pub_template_titles`](Self::get_pub_template_titles) 自动翻页，每页拉取 30 条
     ///
     /// # 参数
     ///
-    /// - `tid`: 模板标题 id
-    pub async fn get_template_keywords(&self, tid: &str) -> Result<GetTemplateKeywordsResponse> {
-        debug!("get template keywords tid: {}", tid);
-
-        let query = serde_json::json!({
-            "access_token": self.client.token().await?,
-            "tid": tid
-        });
-
-        let request = RequestBuilder::new(constants::MP_SUBSCRIBE_TEMPLATE_KEYWORDS_END_POINT)
-            .method(Method::GET)
-            .query(query)
-            .build()?;
-
-        let client = &self.client.core.client;
-        let response = client.execute(request).await?;
-
-        response.to_json::<GetTemplateKeywordsResponse>()
-    }
-
-    /// 获取类目
-    ///
-    /// 获取小程序、公众号所属类目用于查询公共模板
-    pub async fn get_category(&self) -> Result<GetCategoryResponse> {
-        debug!("get category");
-
-        let query = serde_json::json!({
-            "access_token": self.client.token().await?
-        });
-
-        let request = RequestBuilder::new(constants::MP_SUBSCRIBE_CATEGORY_LIST_END_POINT)
-            .method(Method::GET)
-            .query(query)
-            .build()?;
-
-        let client = &self.client.core.client;
-        let response = client.execute(request).await?;
-
-        response.to_json::<GetCategoryResponse>()
+    /// - `ids`: 类目 id，多个用逗号隔开
+    pub fn pub_template_titles_stream<'a>(
+        &'a self,
+        ids: &'a str,
+    ) -> impl Stream<Item = Result<PubTemplateTitle>> + 'a {
+        Paginator::new(PUB_TEMPLATE_TITLES_PAGE_SIZE, move |start, limit| async move {
+            let response = self
+                .get_pub_template_titles(ids, start as i32, limit as i32)
+                .await?;
+            Ok(response.data)
+        })
+        .into_stream()
     }
 
-    /// 获取类目下的公共模板
-    ///
-    /// 获取帐号所属类目下的公共模板，可从中选用模板使用
+    /// 拉取类目下的全部公共模板
     ///
     /// # 参数
     ///
     /// - `ids`: 类目 id，多个用逗号隔开
-    /// - `start`: 分页起始位置
-    /// - `limit`: 拉取条数，最大 30
-    pub async fn get_pub_template_titles(
-        &self,
-        ids: &str,
-        start: i32,
-        limit: i32,
-    ) -> Result<GetPubTemplateTitlesResponse> {
-        debug!("get pub template titles ids: {}, start: {}, limit: {}", ids, start, limit);
-
-        let query = serde_json::json!({
-            "access_token": self.client.token().await?,
-            "ids": ids,
-            "start": start,
-            "limit": limit
-        });
-
-        let request = RequestBuilder::new(constants::MP_SUBSCRIBE_TEMPLATE_TITLES_END_POINT)
-            .method(Method::GET)
-            .query(query)
-            .build()?;
-
-        let client = &self.client.core.client;
-        let response = client.execute(request).await?;
-
-        response.to_json::<GetPubTemplateTitlesResponse>()
+    pub async fn get_all_pub_template_titles(&self, ids: &str) -> Result<Vec<PubTemplateTitle>> {
+        Paginator::new(PUB_TEMPLATE_TITLES_PAGE_SIZE, |start, limit| async move {
+            let response = self
+                .get_pub_template_titles(ids, start as i32, limit as i32)
+                .await?;
+            Ok(response.data)
+        })
+        .collect_all()
+        .await
     }
 
-    /// 选用模板
-    ///
-    /// 从公共模板库中选用模板到私有模板库
-    pub async fn add_template(&self, args: AddTemplateArgs) -> Result<AddTemplateResponse> {
-        debug!("add template args {:?}", &args);
-
-        let query = serde_json::json!({
-            "access_token": self.client.token().await?
-        });
-
-        let body = serde_json::to_value(args)?;
-
-        let request = RequestBuilder::new(constants::MP_SUBSCRIBE_TEMPLATE_ADD_END_POINT)
-            .query(query)
-            .body(body)
-            .build()?;
-
-        let client = &self.client.core.client;
-        let response = client.execute(request).await?;
-
-        response.to_json::<AddTemplateResponse>()
+    wechat_core::endpoint! {
+        /// 选用模板
+        ///
+        /// 从公共模板库中选用模板到私有模板库
+        pub POST add_template(args: AddTemplateArgs) -> AddTemplateResponse =
+            constants::MP_SUBSCRIBE_TEMPLATE_ADD_END_POINT;
     }
 }
@@ -1,4 +1,4 @@
-use dotenv::dotenv;
+use dotenvy::dotenv;
 use serde_json::json;
 use std::env;
 use std::sync::Arc;
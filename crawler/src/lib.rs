@@ -20,6 +20,12 @@ pub struct ProgressRecord {
     total: usize,                  // 总数量
 }
 
+impl Default for ProgressRecord {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ProgressRecord {
     pub fn new() -> Self {
         Self {
@@ -30,9 +36,9 @@ impl ProgressRecord {
 
     // 从文件加载进度
     pub fn load(progress_file: &str) -> Self {
-        if let Ok(content) = fs::read_to_string(progress_file) {
-            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-                if let Some(urls) = json.get("fetched_urls").and_then(|v| v.as_array()) {
+        if let Ok(content) = fs::read_to_string(progress_file)
+            && let Ok(json) = serde_json::from_str::<serde_json::Value>(&content)
+                && let Some(urls) = json.get("fetched_urls").and_then(|v| v.as_array()) {
                     let mut fetched = HashSet::new();
                     for url in urls {
                         if let Some(s) = url.as_str() {
@@ -45,8 +51,6 @@ impl ProgressRecord {
                         total,
                     };
                 }
-            }
-        }
         Self::new()
     }
 
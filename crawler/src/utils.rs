@@ -340,7 +340,7 @@ pub async fn fetch_all_apis(
     let mut success = 0;
     let mut failed = 0;
 
-    for (_i, api) in apis.iter().enumerate() {
+    for api in apis.iter() {
         // 检查是否已获取
         if progress.is_fetched(&api.url) {
             success += 1;
@@ -357,13 +357,12 @@ pub async fn fetch_all_apis(
             Ok(markdown) => {
                 // 构建保存路径
                 let dir_path = output_dir.join(&api.category);
-                if !dir_path.exists() {
-                    if let Err(e) = fs::create_dir_all(&dir_path) {
+                if !dir_path.exists()
+                    && let Err(e) = fs::create_dir_all(&dir_path) {
                         eprintln!("   ❌ 创建目录失败: {}", e);
                         failed += 1;
                         continue;
                     }
-                }
 
                 // 文件名：清理非法字符
                 let filename = format!("{}.md", api.name)
@@ -403,11 +402,10 @@ pub async fn fetch_all_apis(
         tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
 
         // 每 10 个保存一次进度
-        if (success + failed) % 10 == 0 {
-            if let Err(e) = progress.save(progress_file.to_str().unwrap()) {
+        if (success + failed) % 10 == 0
+            && let Err(e) = progress.save(progress_file.to_str().unwrap()) {
                 eprintln!("⚠️ 保存进度失败: {}", e);
             }
-        }
     }
 
     // 最终保存进度
@@ -3,7 +3,7 @@ use chrono::{DateTime, Utc};
 use serde::Serialize;
 use std::convert::Infallible;
 use thiserror::Error;
-use wechat_minapp::error::Error as WechatMinappError;
+use wechat_minapp::{Error as WechatMinappError, ErrorKind as WechatMinappErrorKind};
 
 #[derive(Error, Debug, Clone)]
 pub enum Error {
@@ -41,9 +41,9 @@ impl From<anyhow::Error> for Error {
 
 impl From<WechatMinappError> for Error {
     fn from(value: WechatMinappError) -> Self {
-        match value {
-            WechatMinappError::MissingCode(e) => Error::InvalidArgument(e),
-            WechatMinappError::InvalidCode(e) => Error::InvalidArgument(e),
+        match value.into_kind() {
+            WechatMinappErrorKind::MissingCode(e) => Error::InvalidArgument(e),
+            WechatMinappErrorKind::InvalidCode(e) => Error::InvalidArgument(e),
             _ => Error::Internal("wechat minapp error".to_string()),
         }
     }